@@ -0,0 +1,57 @@
+//! Measures the frame jitter of the agent thread.
+//!
+//! Usage: `cargo run --release --example latency_test -- [PORT] [FRAMES]`
+//!
+//! Works with real adapters, loopback adapters and virtual ports
+//! *(e.g. `socat -d -d pty,raw,echo=0 pty,raw,echo=0` on Linux)*.
+
+use open_dmx::DMXSerial;
+
+use std::time::{Duration, Instant};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let port = args.next().unwrap_or_else(|| "COM3".to_string());
+    let frames: usize = args.next().map(|n| n.parse()).transpose()?.unwrap_or(1000);
+
+    let mut dmx = DMXSerial::open_sync(&port)?;
+    let packet_time = dmx.get_packet_time();
+
+    println!("Sending {} frames to \"{}\" with a packet time of {:?}...", frames, port, packet_time);
+
+    // The first update also waits for the agent to start
+    dmx.update()?;
+    let mut intervals = Vec::with_capacity(frames);
+    let mut last = Instant::now();
+    for i in 0..frames {
+        dmx.set_channels([(i % 256) as u8; 512]);
+        dmx.update()?;
+        let now = Instant::now();
+        intervals.push(now - last);
+        last = now;
+    }
+
+    // Deviation of every frame from the configured packet time
+    let mut jitter: Vec<Duration> = intervals.iter().map(|interval| interval.abs_diff(packet_time)).collect();
+    jitter.sort();
+    let mean = intervals.iter().sum::<Duration>() / frames.max(1) as u32;
+
+    println!();
+    println!("mean interval: {:>10.3?}", mean);
+    println!("min interval:  {:>10.3?}", intervals.iter().min().copied().unwrap_or_default());
+    println!("max interval:  {:>10.3?}", intervals.iter().max().copied().unwrap_or_default());
+    println!();
+    for p in [50.0, 90.0, 99.0, 99.9, 100.0] {
+        println!("p{:<5} jitter: {:>10.3?}", p, percentile(&jitter, p));
+    }
+    Ok(())
+}
+
+// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,14 @@
+use open_dmx::{DMXSerial, SimulatorOutput, DMX_CHANNELS};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut dmx = DMXSerial::builder("simulator").open_with_transport(SimulatorOutput::terminal())?;
+    //running light
+    let mut position = 0;
+    loop {
+        let mut channels = [0; DMX_CHANNELS];
+        channels[position] = 255;
+        dmx.set_channels(channels);
+        position = (position + 1) % DMX_CHANNELS;
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::builder::{BreakMode, LineSettings};
+
+use serialport::SerialPortType;
+
+use std::path::Path;
+
+/// The USB-to-serial chip of a **DMX adapter**.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterChip {
+    /// FTDI FT232 and related chips *(used by the Enttec Open DMX USB)*
+    Ft232,
+    /// WCH CH340/CH341 and related chips
+    Ch340,
+    /// Silicon Labs CP2102 and related chips
+    Cp2102,
+    /// Prolific PL2303 and related chips
+    Pl2303,
+    /// Any other USB chip
+    Unknown,
+}
+
+impl AdapterChip {
+    /// Classifies the chip by its USB vendor and product id.
+    ///
+    pub fn from_ids(vid: u16, pid: u16) -> AdapterChip {
+        match (vid, pid) {
+            (0x0403, _) => AdapterChip::Ft232,
+            (0x1a86, 0x7523 | 0x5523 | 0x55d4) => AdapterChip::Ch340,
+            (0x10c4, 0xea60 | 0xea70 | 0xea71) => AdapterChip::Cp2102,
+            (0x067b, _) => AdapterChip::Pl2303,
+            _ => AdapterChip::Unknown,
+        }
+    }
+
+    /// Returns `true` if the chip is known to generate unreliable breaks with the break signal of the driver.
+    ///
+    pub fn has_unreliable_break(&self) -> bool {
+        matches!(self, AdapterChip::Ch340 | AdapterChip::Pl2303)
+    }
+
+    /// Returns the [`BreakMode`] which works best with the chip.
+    ///
+    pub fn preferred_break_mode(&self) -> BreakMode {
+        if self.has_unreliable_break() {
+            BreakMode::Baud(LineSettings::BREAK)
+        } else {
+            BreakMode::Signal
+        }
+    }
+}
+
+/// Information about the **DMX adapter** read from the USB descriptor.
+///
+/// See [`DMXSerial::adapter_info`].
+///
+/// [`DMXSerial::adapter_info`]: crate::DMXSerial::adapter_info
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterInfo {
+    pub chip: AdapterChip,
+    pub vid: u16,
+    pub pid: u16,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+impl AdapterInfo {
+    /// Looks up the USB adapter behind the given [`path`].
+    ///
+    /// Returns `None` if the port isn't a USB port or can't be found.
+    ///
+    /// [`path`]: std::path::Path
+    ///
+    pub fn detect<P: AsRef<Path>>(port: P) -> Option<AdapterInfo> {
+        let wanted = canonical(port);
+        serialport::available_ports().ok()?.into_iter()
+            .find(|info| canonical(&info.port_name) == wanted)
+            .and_then(|info| match info.port_type {
+                SerialPortType::UsbPort(usb) => Some(AdapterInfo {
+                    chip: AdapterChip::from_ids(usb.vid, usb.pid),
+                    vid: usb.vid,
+                    pid: usb.pid,
+                    manufacturer: usb.manufacturer,
+                    product: usb.product,
+                    serial_number: usb.serial_number,
+                }),
+                _ => None,
+            })
+    }
+}
+
+// Resolves symlinks like `/dev/serial/by-id/...`, so they match the names of the port list
+pub(crate) fn canonical<P: AsRef<Path>>(port: P) -> String {
+    let port = port.as_ref();
+    std::fs::canonicalize(port)
+        .unwrap_or_else(|_| port.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Reads the latency timer of the FTDI driver, which delays the end of every packet
+#[cfg(target_os = "linux")]
+pub(crate) fn latency_timer(port: &str) -> Option<std::time::Duration> {
+    let path = canonical(port);
+    let name = std::path::Path::new(&path).file_name()?.to_str()?;
+    let millis = std::fs::read_to_string(format!("/sys/class/tty/{}/device/latency_timer", name)).ok()?;
+    millis.trim().parse().ok().map(std::time::Duration::from_millis)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn latency_timer(_port: &str) -> Option<std::time::Duration> {
+    None
+}
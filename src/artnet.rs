@@ -0,0 +1,183 @@
+//! Discovery of **Art-Net** nodes on the network, e.g. for showing the available network outputs next to the serial ports.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use open_dmx::artnet;
+//! use std::time::Duration;
+//!
+//! fn main() {
+//!     for node in artnet::discover(Duration::from_secs(3)).unwrap() {
+//!         println!("{} ({}): {:?}", node.short_name, node.address, node.outputs);
+//!     }
+//! }
+//! ```
+//!
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time;
+
+/// UDP port of Art-Net.
+///
+pub const PORT: u16 = 6454;
+
+const ID: &[u8; 8] = b"Art-Net\0";
+const OP_POLL: u16 = 0x2000;
+const OP_POLL_REPLY: u16 = 0x2100;
+const PROTOCOL_VERSION: u16 = 14;
+
+// Shortest ArtPollReply, up to and including the style and MAC of the oldest protocol versions
+const MIN_POLL_REPLY_SIZE: usize = 207;
+
+/// An Art-Net node which answered a poll.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// IP address of the node.
+    pub address: Ipv4Addr,
+    pub short_name: String,
+    pub long_name: String,
+    /// Port-Addresses *(universes)* the node outputs as DMX.
+    pub outputs: Vec<u16>,
+    /// Port-Addresses *(universes)* the node receives from DMX inputs.
+    pub inputs: Vec<u16>,
+}
+
+/// Finds the Art-Net nodes on the local network by broadcasting an **ArtPoll** and collecting the replies for `timeout`.
+///
+/// Nodes reply to port [`PORT`], so another Art-Net application on the same computer may receive the replies instead.
+/// If the port is in use, the replies of nodes which answer to the port of the poll are still received.
+///
+pub fn discover(timeout: time::Duration) -> io::Result<Vec<Node>> {
+    discover_at(SocketAddr::from((Ipv4Addr::BROADCAST, PORT)), timeout)
+}
+
+/// Does the same as [`discover`], but sends the poll to the given address *(e.g. a directed broadcast like `2.255.255.255` or a single node)*.
+///
+pub fn discover_at(target: SocketAddr, timeout: time::Duration) -> io::Result<Vec<Node>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, PORT)).or_else(|_| UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&encode_poll(), target)?;
+
+    let deadline = time::Instant::now() + timeout;
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut buf = [0; 1024];
+    loop {
+        let remaining = deadline.saturating_duration_since(time::Instant::now());
+        if remaining.is_zero() {
+            return Ok(nodes);
+        }
+        socket.set_read_timeout(Some(remaining))?;
+        let (len, source) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => return Ok(nodes),
+            Err(e) => return Err(e),
+        };
+        let Some(mut node) = decode_poll_reply(&buf[..len]) else {
+            continue;
+        };
+        if node.address.is_unspecified() {
+            if let SocketAddr::V4(source) = source {
+                node.address = *source.ip();
+            }
+        }
+        // Nodes with more than 4 ports send one reply per group of ports
+        match nodes.iter_mut().find(|known| known.address == node.address && known.short_name == node.short_name) {
+            Some(known) => {
+                known.outputs.extend(node.outputs);
+                known.outputs.sort_unstable();
+                known.outputs.dedup();
+                known.inputs.extend(node.inputs);
+                known.inputs.sort_unstable();
+                known.inputs.dedup();
+            },
+            None => nodes.push(node),
+        }
+    }
+}
+
+/// Returns an **ArtPoll** packet.
+///
+pub fn encode_poll() -> Vec<u8> {
+    let mut packet = Vec::with_capacity(14);
+    packet.extend_from_slice(ID);
+    packet.extend_from_slice(&OP_POLL.to_le_bytes());
+    packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    // No flags and the lowest diagnostics priority
+    packet.extend_from_slice(&[0, 0]);
+    packet
+}
+
+/// Reads an **ArtPollReply** packet. Returns `None` if the packet isn't one.
+///
+pub fn decode_poll_reply(packet: &[u8]) -> Option<Node> {
+    if packet.len() < MIN_POLL_REPLY_SIZE || &packet[..8] != ID || u16::from_le_bytes([packet[8], packet[9]]) != OP_POLL_REPLY {
+        return None;
+    }
+    let net = (packet[18] as u16 & 0x7F) << 8;
+    let sub_net = (packet[19] as u16 & 0x0F) << 4;
+    let ports = (u16::from_be_bytes([packet[172], packet[173]]) as usize).min(4);
+    let universes = |kind: u8, switches: &[u8]| -> Vec<u16> {
+        (0..ports)
+            .filter(|port| packet[174 + port] & kind != 0)
+            .map(|port| net | sub_net | (switches[port] as u16 & 0x0F))
+            .collect()
+    };
+    Some(Node {
+        address: Ipv4Addr::new(packet[10], packet[11], packet[12], packet[13]),
+        short_name: text(&packet[26..44]),
+        long_name: text(&packet[44..108]),
+        outputs: universes(0x80, &packet[190..194]),
+        inputs: universes(0x40, &packet[186..190]),
+    })
+}
+
+// Null-terminated ASCII
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|c| *c == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn artnet_nodes_are_discovered() {
+        let node = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = node.local_addr().unwrap();
+        let reply = |bind_index: u8, switches: [u8; 4]| {
+            let mut packet = vec![0; 239];
+            packet[..8].copy_from_slice(b"Art-Net\0");
+            packet[8..10].copy_from_slice(&0x2100u16.to_le_bytes());
+            packet[18] = 1;
+            packet[19] = 2;
+            packet[26..32].copy_from_slice(b"Node 1");
+            packet[44..57].copy_from_slice(b"Test gateway ");
+            packet[173] = 2;
+            packet[174..176].copy_from_slice(&[0x80, 0xC0]);
+            packet[186..190].copy_from_slice(&switches);
+            packet[190..194].copy_from_slice(&switches);
+            packet[211] = bind_index;
+            packet
+        };
+        let responder = std::thread::spawn(move || {
+            let mut buf = [0; 64];
+            let (len, poller) = node.recv_from(&mut buf).unwrap();
+            assert_eq!(&buf[..len], &encode_poll()[..]);
+            node.send_to(b"not art-net", poller).unwrap();
+            node.send_to(&reply(1, [0, 1, 0, 0]), poller).unwrap();
+            node.send_to(&reply(2, [1, 5, 0, 0]), poller).unwrap();
+        });
+
+        let nodes = discover_at(target, Duration::from_millis(300)).unwrap();
+        responder.join().unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].address, std::net::Ipv4Addr::LOCALHOST);
+        assert_eq!((nodes[0].short_name.as_str(), nodes[0].long_name.as_str()), ("Node 1", "Test gateway"));
+        assert_eq!(nodes[0].outputs, [0x120, 0x121, 0x125]);
+        assert_eq!(nodes[0].inputs, [0x121, 0x125]);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::DMX_CHANNELS;
+
+use std::collections::VecDeque;
+use std::time;
+
+/// A recorded write to a channel. See [`DMXSerial::set_audit_depth`].
+///
+/// [`DMXSerial::set_audit_depth`]: crate::DMXSerial::set_audit_depth
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelWrite {
+    /// The written value.
+    pub value: u8,
+    /// When the value was written.
+    pub time: time::SystemTime,
+    /// The origin of the write, if it was made with a tagged function like [`DMXSerial::set_channel_tagged`].
+    ///
+    /// [`DMXSerial::set_channel_tagged`]: crate::DMXSerial::set_channel_tagged
+    pub tag: Option<&'static str>,
+}
+
+// The last writes of every channel
+#[derive(Debug)]
+pub(crate) struct AuditTrail {
+    depth: usize,
+    history: Vec<VecDeque<ChannelWrite>>,
+}
+
+impl AuditTrail {
+    pub fn new(depth: usize) -> AuditTrail {
+        AuditTrail {
+            depth,
+            history: vec![VecDeque::with_capacity(depth); DMX_CHANNELS],
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn record(&mut self, index: usize, value: u8, tag: Option<&'static str>) {
+        let history = &mut self.history[index];
+        if history.len() == self.depth {
+            history.pop_front();
+        }
+        history.push_back(ChannelWrite {
+            value,
+            time: time::SystemTime::now(),
+            tag,
+        });
+    }
+
+    pub fn record_all(&mut self, channels: &[u8; DMX_CHANNELS], tag: Option<&'static str>) {
+        channels.iter().enumerate().for_each(|(index, value)| self.record(index, *value, tag));
+    }
+
+    pub fn history(&self, index: usize) -> Vec<ChannelWrite> {
+        self.history[index].iter().cloned().collect()
+    }
+}
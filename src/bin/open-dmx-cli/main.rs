@@ -0,0 +1,155 @@
+use open_dmx::{check_valid_channel, DMXSerial, Player, SimulatorOutput, DMX_CHANNELS};
+use open_dmx::error::DMXOpenError;
+use open_dmx::recording::RecordingReader;
+
+#[cfg(feature = "tui")]
+mod tui;
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process;
+use std::thread;
+use std::time;
+
+const USAGE: &str = "\
+Sends DMX data until it is stopped with Ctrl+C
+
+Usage:
+    open-dmx-cli [options] set <channel> <value> [<channel> <value>...]
+    open-dmx-cli [options] blast <value>
+    open-dmx-cli [options] replay <file>    (Plays a .dmxrec recording and holds its last frame)
+    open-dmx-cli [options] edit             (Shows all channels and edits them, needs the tui feature)
+    open-dmx-cli [options] monitor          (Not available yet, the interfaces can't receive DMX)
+
+Options:
+    -p, --port <path>     The port of the interface, defaults to $OPEN_DMX_PORT
+    -s, --state <file>    Starts with the values saved in the file and saves them back
+        --simulate        Shows the values in the terminal instead of sending them
+    -h, --help            Shows this message";
+
+#[derive(Debug, Default)]
+struct Options {
+    port: Option<String>,
+    state: Option<String>,
+    simulate: bool,
+    command: Vec<String>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        port: env::var("OPEN_DMX_PORT").ok(),
+        ..Options::default()
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" | "--port" => options.port = Some(args.next().ok_or("Missing path after --port")?),
+            "-s" | "--state" => options.state = Some(args.next().ok_or("Missing file after --state")?),
+            "--simulate" => options.simulate = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            },
+            _ => options.command.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn parse_value(value: &str) -> Result<u8, String> {
+    value.parse().map_err(|_| format!("\"{}\" is not a DMX value (0-255)", value))
+}
+
+fn parse_channel(channel: &str) -> Result<usize, String> {
+    let channel = channel.parse().map_err(|_| format!("\"{}\" is not a DMX channel", channel))?;
+    check_valid_channel(channel).map_err(|e| format!("{}: {}", e, channel))?;
+    Ok(channel)
+}
+
+fn open(options: &Options) -> Result<DMXSerial, String> {
+    let port = match (&options.port, options.simulate) {
+        (_, true) => return DMXSerial::builder("simulator").open_with_transport(SimulatorOutput::terminal()).map_err(|e| e.to_string()),
+        (Some(port), false) => port,
+        (None, false) => return Err("No port given, use --port or set $OPEN_DMX_PORT".to_string()),
+    };
+    let result = match &options.state {
+        Some(state) => DMXSerial::open_with_restore(port, state),
+        None => DMXSerial::open(port),
+    };
+    result.map_err(|e| format!("Failed to open \"{}\": {}", port, DMXOpenError::from(e)))
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let (command, args) = options.command.split_first().ok_or(USAGE)?;
+    let mut channels: Vec<(usize, u8)> = Vec::new();
+    match command.as_str() {
+        "set" => {
+            if args.is_empty() || args.len() % 2 != 0 {
+                return Err("Expected pairs of <channel> <value>".to_string());
+            }
+            for pair in args.chunks(2) {
+                channels.push((parse_channel(&pair[0])?, parse_value(&pair[1])?));
+            }
+        },
+        "blast" => {
+            let [value] = args else {
+                return Err("Expected a single <value>".to_string());
+            };
+            let value = parse_value(value)?;
+            channels.extend((1..=DMX_CHANNELS).map(|channel| (channel, value)));
+        },
+        "replay" => {
+            let [path] = args else {
+                return Err("Expected a single <file>".to_string());
+            };
+            let file = File::open(path).map_err(|e| format!("Failed to open \"{}\": {}", path, e))?;
+            let reader = RecordingReader::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+            let mut dmx = open(&options)?;
+            let mut player = Player::new(reader);
+            player.play();
+            while player.poll(&mut dmx).map_err(|e| e.to_string())? {
+                dmx.check_agent().map_err(|e| e.to_string())?;
+                thread::sleep(dmx.get_packet_time());
+            }
+            return send_until_stopped(dmx, options.simulate);
+        },
+        // Monitoring needs a receive path, but the agent only transmits and no transport reads DMX back
+        "monitor" => return Err("Monitoring isn't available yet, open_dmx can only send DMX".to_string()),
+        #[cfg(feature = "tui")]
+        "edit" => {
+            if options.simulate {
+                return Err("The simulator can't be shown while editing".to_string());
+            }
+            let mut dmx = open(&options)?;
+            return tui::run(&mut dmx).map_err(|e| e.to_string());
+        },
+        _ => return Err(format!("Unknown command \"{}\"\n\n{}", command, USAGE)),
+    }
+
+    let mut dmx = open(&options)?;
+    for (channel, value) in channels {
+        // Channels are validated while parsing
+        let _ = dmx.set_channel(channel, value);
+    }
+    send_until_stopped(dmx, options.simulate)
+}
+
+// Keeps the interface sending until the process is stopped
+fn send_until_stopped(dmx: DMXSerial, simulate: bool) -> Result<(), String> {
+    if !simulate {
+        eprintln!("Sending on \"{}\", press Ctrl+C to stop", dmx.name());
+    }
+    loop {
+        thread::sleep(time::Duration::from_secs(1));
+        dmx.check_agent().map_err(|e| e.to_string())?;
+    }
+}
+
+fn main() {
+    let result = parse_options().and_then(run);
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
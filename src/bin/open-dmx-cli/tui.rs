@@ -0,0 +1,103 @@
+use open_dmx::{DMXSerial, DMX_CHANNELS};
+
+use crossterm::cursor;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{execute, queue};
+
+use std::io::{self, Write};
+use std::time;
+
+const COLUMNS: usize = 16;
+const ROWS: usize = DMX_CHANNELS / COLUMNS;
+// Lines above and below the grid
+const HEADER: u16 = 2;
+const FOOTER: u16 = 2;
+
+const HELP: &str = "arrows: move | +/-: 1 | PgUp/PgDn: 10 | f: full | 0: zero | q: quit";
+
+// Restores the terminal, even if the editor returns early
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<RawMode> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Shows all channels in a grid and edits the selected one
+pub fn run(dmx: &mut DMXSerial) -> io::Result<()> {
+    let _raw_mode = RawMode::enable()?;
+    let mut selected = 0;
+    // First visible row, if the terminal is too small for the whole grid
+    let mut scroll = 0;
+    loop {
+        let (_, height) = terminal::size()?;
+        let visible = (height.saturating_sub(HEADER + FOOTER) as usize).clamp(1, ROWS);
+        let row = selected / COLUMNS;
+        scroll = scroll.clamp(row.saturating_sub(visible - 1), row);
+        draw(dmx, selected, scroll, visible)?;
+
+        // Redrawn regularly, so the values stay live
+        if !event::poll(time::Duration::from_millis(50))? {
+            dmx.check_agent().map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))?;
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        let value = dmx.get_channels()[selected] as i16;
+        let change = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Left => { selected = selected.saturating_sub(1); None },
+            KeyCode::Right => { selected = (selected + 1).min(DMX_CHANNELS - 1); None },
+            KeyCode::Up => { selected = selected.saturating_sub(COLUMNS); None },
+            KeyCode::Down => { selected = (selected + COLUMNS).min(DMX_CHANNELS - 1); None },
+            KeyCode::Char('+') => Some(value + 1),
+            KeyCode::Char('-') => Some(value - 1),
+            KeyCode::PageUp => Some(value + 10),
+            KeyCode::PageDown => Some(value - 10),
+            KeyCode::Char('f') => Some(255),
+            KeyCode::Char('0') => Some(0),
+            _ => None,
+        };
+        if let Some(value) = change {
+            // The selection is always a valid channel
+            let _ = dmx.set_channel(selected + 1, value.clamp(0, 255) as u8);
+        }
+    }
+}
+
+fn draw(dmx: &DMXSerial, selected: usize, scroll: usize, visible: usize) -> io::Result<()> {
+    let channels = dmx.get_channels();
+    let mut stdout = io::stdout();
+    queue!(
+        stdout,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(ClearType::CurrentLine),
+        Print(format!("{} | channel {:>3} = {:>3}", dmx.name(), selected + 1, channels[selected])),
+    )?;
+    for (line, row) in (scroll..scroll + visible).enumerate() {
+        queue!(stdout, cursor::MoveTo(0, HEADER + line as u16), Print(format!("{:>3} |", row * COLUMNS + 1)))?;
+        for (index, value) in channels.iter().enumerate().skip(row * COLUMNS).take(COLUMNS) {
+            let attribute = if index == selected { Attribute::Reverse } else { Attribute::Reset };
+            queue!(stdout, Print(" "), SetAttribute(attribute), Print(format!("{:>3}", value)), SetAttribute(Attribute::Reset))?;
+        }
+    }
+    // Lines are overwritten instead of clearing the screen, which would flicker
+    queue!(stdout, cursor::MoveTo(0, HEADER + visible as u16 + 1), Print(HELP), terminal::Clear(ClearType::FromCursorDown))?;
+    stdout.flush()
+}
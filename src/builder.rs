@@ -0,0 +1,469 @@
+use crate::DMXSerial;
+use crate::clock::Clock;
+use crate::transport::Transport;
+#[cfg(feature = "thread_priority")]
+use crate::priority::{AgentPriority, PriorityFailure};
+
+use serialport::{DataBits, Parity, StopBits};
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time;
+
+/// The low-level settings of the [SerialPort] line.
+///
+/// [SerialPort]: serialport::SerialPort
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSettings {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub stop_bits: StopBits,
+    pub parity: Parity,
+}
+
+impl LineSettings {
+    /// The settings used for sending **DMX data**. *(250000 baud, 8N2)*
+    ///
+    pub const DMX: LineSettings = LineSettings {
+        baud_rate: crate::codec::BAUD_RATE,
+        data_bits: DataBits::Eight,
+        stop_bits: StopBits::Two,
+        parity: Parity::None,
+    };
+
+    /// The settings used for generating a break with [`BreakMode::Baud`]. *(57600 baud, 7N1)*
+    ///
+    /// Sending a single `0` byte results in a break of roughly 139 µs followed by a 17 µs mark after break.
+    ///
+    pub const BREAK: LineSettings = LineSettings {
+        baud_rate: 57_600,
+        data_bits: DataBits::Seven,
+        stop_bits: StopBits::One,
+        parity: Parity::None,
+    };
+}
+
+impl Default for LineSettings {
+    fn default() -> Self {
+        LineSettings::DMX
+    }
+}
+
+/// The way the **break** in front of every **DMX packet** is generated.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BreakMode {
+    /// Uses the break signal of the [SerialPort] driver *(`TIOCSBRK` on Unix, `SetCommBreak` on Windows)*. *(default)*
+    ///
+    /// The break is held for the [break time](DMXSerialBuilder::break_time), which is more precise than the break byte of [`BreakMode::Baud`].
+    ///
+    /// [SerialPort]: serialport::SerialPort
+    #[default]
+    Signal,
+    /// Switches the line to the given [`LineSettings`] and sends a single `0` byte.
+    ///
+    /// Useful for adapters which don't support the break signal. See [`LineSettings::BREAK`].
+    Baud(LineSettings),
+}
+
+/// The way the RS-485 transceiver is switched into transmit mode while sending.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectionControl {
+    /// The adapter switches the direction by itself. *(default)*
+    #[default]
+    None,
+    /// Drives the given GPIO pin high while sending and low afterwards. *(Linux only)*
+    ///
+    /// Meant for transceivers whose DE/RE pins are wired to a GPIO, like most RS-485 HATs for the Raspberry Pi.
+    Gpio(u32),
+    /// Asserts the RTS line while sending. With `inverted` the line is cleared while sending instead.
+    Rts { inverted: bool },
+    /// Asserts the DTR line while sending. With `inverted` the line is cleared while sending instead.
+    Dtr { inverted: bool },
+}
+
+/// Margins around switching the transceiver with a [`DirectionControl`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DirectionTiming {
+    /// Time between enabling transmit mode and the start of the **break**.
+    pub lead: time::Duration,
+    /// Time between the last byte leaving the port and switching back to receive mode.
+    pub lag: time::Duration,
+}
+
+/// A builder for configuring a [DMXSerial] before opening it.
+///
+/// # Example
+///
+/// Using the baud rate trick for generating breaks:
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, BreakMode, LineSettings};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::builder("COM3")
+///         .line_settings(LineSettings { stop_bits: open_dmx::StopBits::One, ..LineSettings::DMX })
+///         .break_mode(BreakMode::Baud(LineSettings::BREAK))
+///         .open()
+///         .unwrap();
+///     dmx.set_channels([255; 512]);
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct DMXSerialBuilder {
+    pub(crate) port: PathBuf,
+    pub(crate) sync: bool,
+    pub(crate) packet_time: time::Duration,
+    pub(crate) line_settings: LineSettings,
+    pub(crate) exclusive: bool,
+    // None if it should be chosen from the detected adapter
+    pub(crate) break_mode: Option<BreakMode>,
+    pub(crate) break_time: time::Duration,
+    pub(crate) direction: DirectionControl,
+    pub(crate) direction_timing: DirectionTiming,
+    #[cfg(feature = "thread_priority")]
+    pub(crate) priority: AgentPriority,
+    #[cfg(feature = "thread_priority")]
+    pub(crate) priority_failure: PriorityFailure,
+    #[cfg(feature = "cpu_affinity")]
+    pub(crate) cpu_affinity: Option<usize>,
+    // File the set values are saved to and the interval
+    pub(crate) persist: Option<(PathBuf, time::Duration)>,
+    // File the set values are restored from on opening
+    pub(crate) restore: Option<PathBuf>,
+    pub(crate) clock: Arc<dyn Clock>,
+    // False if the packets are sent as fast as possible, e.g. for offline rendering
+    pub(crate) paced: bool,
+    // How the transport is opened again by `DMXSerial::reopen`
+    pub(crate) source: TransportSource,
+}
+
+// Opens a new transport for every reopen
+pub(crate) type TransportFactory = Arc<dyn Fn() -> serialport::Result<Box<dyn Transport>> + Send + Sync>;
+
+#[derive(Clone)]
+pub(crate) enum TransportSource {
+    // The serial port at the path
+    Path,
+    Factory(TransportFactory),
+    // A transport which was handed over once and can't be reopened
+    Fixed,
+}
+
+impl fmt::Debug for TransportSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransportSource::Path => write!(f, "Path"),
+            TransportSource::Factory(_) => write!(f, "Factory(..)"),
+            TransportSource::Fixed => write!(f, "Fixed"),
+        }
+    }
+}
+
+impl DMXSerialBuilder {
+    /// Creates a new [DMXSerialBuilder] for the given [`path`] with the default settings.
+    ///
+    /// The path is checked when the port is opened. On Windows, `COM10` and above are opened as `\\.\COM10`.
+    ///
+    /// [`path`]: std::path::Path
+    ///
+    pub fn new<P: AsRef<Path>>(port: P) -> DMXSerialBuilder {
+        DMXSerialBuilder {
+            port: port.as_ref().to_path_buf(),
+            sync: false,
+            packet_time: crate::codec::DEFAULT_PACKET_TIME,
+            line_settings: LineSettings::DMX,
+            exclusive: true,
+            break_mode: None,
+            break_time: crate::codec::BREAK_TIME,
+            direction: DirectionControl::None,
+            direction_timing: DirectionTiming::default(),
+            #[cfg(feature = "thread_priority")]
+            priority: AgentPriority::Max,
+            #[cfg(feature = "thread_priority")]
+            priority_failure: PriorityFailure::Log,
+            #[cfg(feature = "cpu_affinity")]
+            cpu_affinity: None,
+            persist: None,
+            restore: None,
+            clock: crate::clock::system(),
+            paced: true,
+            source: TransportSource::Path,
+        }
+    }
+
+    /// Creates a new [DMXSerialBuilder] for the native UART of a Raspberry Pi *(`/dev/serial0`)*.
+    ///
+    /// The transceiver is switched with the given GPIO pin *(BCM numbering)* and the break is generated by the UART itself.
+    ///
+    /// The PL011 UART has to be mapped to `/dev/serial0` *(e.g. with `dtoverlay=disable-bt`)*, since the mini UART can't generate a proper break.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use open_dmx::DMXSerialBuilder;
+    ///
+    /// fn main() {
+    ///     let mut dmx = DMXSerialBuilder::raspberry_pi(18).open().unwrap();
+    ///     dmx.set_channels([255; 512]);
+    /// }
+    /// ```
+    ///
+    pub fn raspberry_pi(de_pin: u32) -> DMXSerialBuilder {
+        DMXSerialBuilder::new("/dev/serial0")
+            .break_mode(BreakMode::Signal)
+            .direction_control(DirectionControl::Gpio(de_pin))
+    }
+
+    /// Opens the [DMXSerial] in **sync mode**. See [`DMXSerial::open_sync`].
+    ///
+    pub fn sync(mut self) -> DMXSerialBuilder {
+        self.sync = true;
+        self
+    }
+
+    /// Sets the minimum [`Duration`] between two **DMX packets**. See [`DMXSerial::set_packet_time`].
+    ///
+    /// [`Duration`]: time::Duration
+    ///
+    pub fn packet_time(mut self, time: time::Duration) -> DMXSerialBuilder {
+        self.packet_time = time;
+        self
+    }
+
+    /// Sets the [`LineSettings`] used while sending **DMX data**.
+    ///
+    /// # Default
+    ///
+    /// - [`LineSettings::DMX`]
+    ///
+    pub fn line_settings(mut self, settings: LineSettings) -> DMXSerialBuilder {
+        self.line_settings = settings;
+        self
+    }
+
+    /// Sets whether the port is opened exclusively, so no other process can open it at the same time. *(Unix only, ports are always exclusive on Windows)*
+    ///
+    /// Opening it non-exclusively allows monitoring tools to sniff the same device.
+    ///
+    /// # Default
+    ///
+    /// - `true`
+    ///
+    pub fn exclusive(mut self, exclusive: bool) -> DMXSerialBuilder {
+        self.exclusive = exclusive;
+        self
+    }
+
+    /// Sets the [`BreakMode`] used for generating the **break**.
+    ///
+    /// # Default
+    ///
+    /// - The [preferred mode] of the detected adapter chip, otherwise [`BreakMode::Signal`]
+    ///
+    /// [preferred mode]: crate::AdapterChip::preferred_break_mode
+    ///
+    pub fn break_mode(mut self, mode: BreakMode) -> DMXSerialBuilder {
+        self.break_mode = Some(mode);
+        self
+    }
+
+    /// Sets how long the break signal is held with [`BreakMode::Signal`].
+    ///
+    /// The end of the break is waited for by spinning, so it isn't stretched by the scheduler.
+    ///
+    /// # Default
+    ///
+    /// - 136 µs *(see [`codec::BREAK_TIME`](crate::codec::BREAK_TIME))*
+    ///
+    pub fn break_time(mut self, time: time::Duration) -> DMXSerialBuilder {
+        self.break_time = time;
+        self
+    }
+
+    /// Sets the [`DirectionControl`] used for switching the transceiver into transmit mode.
+    ///
+    /// # Default
+    ///
+    /// - [`DirectionControl::None`]
+    ///
+    pub fn direction_control(mut self, direction: DirectionControl) -> DMXSerialBuilder {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the [`DirectionTiming`] used around switching the transceiver.
+    ///
+    /// Some adapters need a few microseconds until the driver is enabled, otherwise the start of the **break** gets cut off.
+    ///
+    /// # Default
+    ///
+    /// - No margins
+    ///
+    pub fn direction_timing(mut self, timing: DirectionTiming) -> DMXSerialBuilder {
+        self.direction_timing = timing;
+        self
+    }
+
+    /// Sets the [`AgentPriority`] of the thread which writes to the [SerialPort].
+    ///
+    /// [SerialPort]: serialport::SerialPort
+    ///
+    /// # Default
+    ///
+    /// - [`AgentPriority::Max`]
+    ///
+    #[cfg(feature = "thread_priority")]
+    pub fn thread_priority(mut self, priority: AgentPriority) -> DMXSerialBuilder {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets what happens if the [`AgentPriority`] can't be set.
+    ///
+    /// # Default
+    ///
+    /// - [`PriorityFailure::Log`]
+    ///
+    #[cfg(feature = "thread_priority")]
+    pub fn on_priority_failure(mut self, failure: PriorityFailure) -> DMXSerialBuilder {
+        self.priority_failure = failure;
+        self
+    }
+
+    /// Pins the thread which writes to the [SerialPort] to the CPU core with the given id.
+    ///
+    /// Keeping the thread on one core reduces the scheduling jitter on busy systems.
+    /// Opening fails if the thread can't be pinned.
+    ///
+    /// [SerialPort]: serialport::SerialPort
+    ///
+    /// # Default
+    ///
+    /// - Not pinned
+    ///
+    #[cfg(feature = "cpu_affinity")]
+    pub fn cpu_affinity(mut self, core_id: usize) -> DMXSerialBuilder {
+        self.cpu_affinity = Some(core_id);
+        self
+    }
+
+    /// Saves the last transmitted universe to the given file in the given interval.
+    ///
+    /// The saved values are the ones on the wire, after merges, crossfades, transforms, parked channels and the safety stages.
+    /// The file is only written if the values changed, so flash storage isn't worn out. See [`DMXSerial::open_with_restore`].
+    ///
+    /// # Default
+    ///
+    /// - Not saved
+    ///
+    pub fn persist_state<P: Into<PathBuf>>(mut self, path: P, interval: time::Duration) -> DMXSerialBuilder {
+        self.persist = Some((path.into(), interval));
+        self
+    }
+
+    /// Restores the channel values from a file written with [`DMXSerialBuilder::persist_state`] before the first packet is sent.
+    ///
+    /// The saved output becomes the set channel values, so stages added after opening the interface apply to it again.
+    /// If the file doesn't exist, all channels start at `0`. An unreadable file is logged and ignored, so the interface still opens.
+    ///
+    /// # Default
+    ///
+    /// - Not restored
+    ///
+    pub fn restore_state<P: Into<PathBuf>>(mut self, path: P) -> DMXSerialBuilder {
+        self.restore = Some(path.into());
+        self
+    }
+
+    /// Sets the [`Clock`] of the fades, keyframes, the idle policy, the render callback and the keep-alive timestamps, e.g. a [`ManualClock`] for tests.
+    ///
+    /// The packets are still paced in real time.
+    ///
+    /// [`ManualClock`]: crate::ManualClock
+    ///
+    /// # Default
+    ///
+    /// - [`SystemClock`](crate::SystemClock)
+    ///
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> DMXSerialBuilder {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Opens the [DMXSerial] with the configured settings.
+    ///
+    pub fn open(self) -> Result<DMXSerial, serialport::Error> {
+        DMXSerial::from_builder(self)
+    }
+
+    /// Opens the [DMXSerial] with the configured settings on a custom [`Transport`] instead of the [SerialPort].
+    ///
+    /// The path only serves as the [name] of the interface and no adapter detection takes place.
+    /// The transport can't be opened again, so [`DMXSerial::reopen`] fails. Use [`DMXSerialBuilder::open_with_transport_factory`] for interfaces which should be reopened.
+    ///
+    /// [SerialPort]: serialport::SerialPort
+    /// [name]: DMXSerial::name
+    ///
+    pub fn open_with_transport<T: Transport + 'static>(mut self, transport: T) -> Result<DMXSerial, serialport::Error> {
+        self.source = TransportSource::Fixed;
+        DMXSerial::from_transport(self, Box::new(transport))
+    }
+
+    /// Opens the [DMXSerial] with the configured settings on the custom [`Transport`] created by the `factory`.
+    ///
+    /// The factory is called again on every [`DMXSerial::reopen`], so network targets, widgets and simulators can be reconnected *(e.g. by a [`DMXFailover`](crate::DMXFailover))*.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use open_dmx::{DMXSerial, SimulatorOutput};
+    ///
+    /// fn main() {
+    ///     // Connects to the viewer again once it was restarted
+    ///     let mut dmx = DMXSerial::builder("viewer")
+    ///         .open_with_transport_factory(|| Ok(SimulatorOutput::tcp("127.0.0.1:9000")?))
+    ///         .unwrap();
+    ///     if dmx.check_agent().is_err() {
+    ///         dmx.reopen().unwrap();
+    ///     }
+    /// }
+    /// ```
+    ///
+    pub fn open_with_transport_factory<T, F>(mut self, factory: F) -> Result<DMXSerial, serialport::Error>
+    where
+        T: Transport + 'static,
+        F: Fn() -> serialport::Result<T> + Send + Sync + 'static,
+    {
+        let factory: TransportFactory = Arc::new(move || Ok(Box::new(factory()?) as Box<dyn Transport>));
+        let transport = factory()?;
+        self.source = TransportSource::Factory(factory);
+        DMXSerial::from_transport(self, transport)
+    }
+
+    /// Opens the [DMXSerial] with the configured settings on a [SerialPort] which was already opened. See [`DMXSerial::from_port`].
+    ///
+    /// The port is switched to the configured [`LineSettings`], all other settings are kept.
+    ///
+    /// [SerialPort]: serialport::SerialPort
+    ///
+    pub fn open_with_port(self, port: Box<dyn serialport::SerialPort>) -> Result<DMXSerial, serialport::Error> {
+        DMXSerial::from_serial_port(self, port)
+    }
+
+    /// Opens the [DMXSerial] with the configured settings and a [`SerialPortBuilder`]. See [`DMXSerial::open_with`].
+    ///
+    /// The path of the [`SerialPortBuilder`] is used instead of the path given to this builder.
+    /// Its [`LineSettings`] are replaced by the configured ones, all other settings are kept.
+    ///
+    /// [`SerialPortBuilder`]: serialport::SerialPortBuilder
+    ///
+    pub fn open_with(self, builder: serialport::SerialPortBuilder) -> Result<DMXSerial, serialport::Error> {
+        DMXSerial::from_serial_builder(self, builder)
+    }
+}
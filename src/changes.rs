@@ -0,0 +1,63 @@
+use crate::DMX_CHANNELS;
+
+use std::sync::{mpsc, Mutex};
+
+/// A changed channel value, sent to the receivers of [`DMXSerial::subscribe_changes`].
+///
+/// [`DMXSerial::subscribe_changes`]: crate::DMXSerial::subscribe_changes
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelChange {
+    /// Increases by one with every change of the interface, so gaps and the order are easy to detect.
+    pub sequence: u64,
+    /// The changed channel *(1-512)*.
+    pub channel: usize,
+    pub old: u8,
+    pub new: u8,
+    /// The origin of the write, if it was made with a tagged function like [`DMXSerial::set_channel_tagged`].
+    ///
+    /// [`DMXSerial::set_channel_tagged`]: crate::DMXSerial::set_channel_tagged
+    pub source: Option<&'static str>,
+}
+
+// Receivers of the channel changes, shared by all handles of an interface
+#[derive(Debug, Default)]
+pub(crate) struct ChangeSubscribers {
+    // The sequence number of the next change is kept behind the same lock, so the events stay in order
+    inner: Mutex<(u64, Vec<mpsc::Sender<ChannelChange>>)>,
+}
+
+impl ChangeSubscribers {
+    pub fn subscribe(&self) -> mpsc::Receiver<ChannelChange> {
+        let (tx, rx) = mpsc::channel();
+        // Mutex can be unwrapped here
+        self.inner.lock().unwrap().1.push(tx);
+        rx
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // Mutex can be unwrapped here
+        self.inner.lock().unwrap().1.is_empty()
+    }
+
+    pub fn notify(&self, old: &[u8; DMX_CHANNELS], new: &[u8; DMX_CHANNELS], source: Option<&'static str>) {
+        // Mutex can be unwrapped here
+        let mut inner = self.inner.lock().unwrap();
+        let (sequence, senders) = &mut *inner;
+        for (index, (old, new)) in old.iter().zip(new.iter()).enumerate() {
+            if old == new {
+                continue;
+            }
+            let change = ChannelChange {
+                sequence: *sequence,
+                channel: index + 1,
+                old: *old,
+                new: *new,
+                source,
+            };
+            *sequence += 1;
+            // Dropped receivers are removed
+            senders.retain(|sender| sender.send(change).is_ok());
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+/// The source of the current time for fades, keyframes, the idle policy, the keep-alive deadline and playback.
+///
+/// The [`SystemClock`] is used by default. A [`ManualClock`] makes this timing logic deterministic in tests.
+/// The pacing of the packets always follows the real time.
+///
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    ///
+    fn now(&self) -> time::Instant;
+}
+
+/// A [`Clock`] which follows the real time.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> time::Instant {
+        time::Instant::now()
+    }
+}
+
+/// A [`Clock`] which only moves when it is advanced, for testing timing logic without sleeping.
+///
+/// Clones share the same time, so a clone given to a [DMXSerial] can be advanced by the test.
+///
+/// [DMXSerial]: crate::DMXSerial
+///
+/// # Example
+///
+/// ```
+/// use open_dmx::{DMXSerial, ManualClock};
+/// use open_dmx::sim::Rack;
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let clock = ManualClock::new();
+///     let mut dmx = DMXSerial::builder("rack")
+///         .sync()
+///         .clock(clock.clone())
+///         .open_with_transport(Rack::new().output())
+///         .unwrap();
+///     dmx.disable_output(Duration::from_secs(2));
+///     clock.advance(Duration::from_secs(1));
+///     assert_eq!(dmx.output_level(), 0.5);
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<time::Instant>>,
+}
+
+impl ManualClock {
+    /// Creates a [ManualClock] which starts at the current real time.
+    ///
+    pub fn new() -> ManualClock {
+        ManualClock { now: Arc::new(Mutex::new(time::Instant::now())) }
+    }
+
+    /// Moves the time forward.
+    ///
+    pub fn advance(&self, duration: time::Duration) {
+        *self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> ManualClock {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> time::Instant {
+        *self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub(crate) fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
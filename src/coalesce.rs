@@ -0,0 +1,63 @@
+use crate::thread::ChannelBuffer;
+use crate::DMX_CHANNELS;
+
+use std::sync::{mpsc, Arc, Mutex};
+
+// A write which is queued in coalescing mode
+#[derive(Debug)]
+pub(crate) enum ChannelUpdate {
+    Single(usize, u8),
+    All(Box<[u8; DMX_CHANNELS]>),
+}
+
+// Queue of channel writes, which are applied all at once before the next frame
+#[derive(Debug, Clone)]
+pub(crate) struct WriteQueue {
+    tx: mpsc::Sender<ChannelUpdate>,
+    rx: Arc<Mutex<mpsc::Receiver<ChannelUpdate>>>,
+}
+
+impl WriteQueue {
+    pub fn new() -> WriteQueue {
+        let (tx, rx) = mpsc::channel();
+        WriteQueue {
+            tx,
+            rx: Arc::new(Mutex::new(rx)),
+        }
+    }
+
+    pub fn push(&self, update: ChannelUpdate) {
+        // The receiver lives as long as the queue
+        let _ = self.tx.send(update);
+    }
+
+    pub fn receiver(&self) -> PendingWrites {
+        PendingWrites {
+            rx: self.rx.clone(),
+        }
+    }
+}
+
+// The receiving side of the queue, shared by the agent and the interface
+#[derive(Debug, Clone)]
+pub(crate) struct PendingWrites {
+    rx: Arc<Mutex<mpsc::Receiver<ChannelUpdate>>>,
+}
+
+impl PendingWrites {
+    // Applies all queued writes in a single write to the buffer
+    pub fn apply(&self, channels: &ChannelBuffer) {
+        // Mutex can be unwrapped here
+        let rx = self.rx.lock().unwrap();
+        let Ok(first) = rx.try_recv() else {
+            return;
+        };
+        let channels = channels.write();
+        for update in std::iter::once(first).chain(rx.try_iter()) {
+            match update {
+                ChannelUpdate::Single(index, value) => channels.set(index, value),
+                ChannelUpdate::All(values) => channels.set_all(&values),
+            }
+        }
+    }
+}
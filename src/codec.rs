@@ -0,0 +1,120 @@
+//! The byte-level format and timing of the **DMX packets** sent by this crate.
+//!
+//! Useful for other crates which handle the same data *(e.g. network gateways, tests or analyzers)*.
+//!
+//! A packet starts with a **break**, followed by the **mark after break** and the **frame**.
+//! The frame consists of the start code and up to [`DMX_CHANNELS`] slots, each sent as one byte.
+//!
+//! # Example
+//!
+//! ```
+//! use open_dmx::codec::{decode_frame, encode_frame, START_CODE_DMX};
+//!
+//! let frame = encode_frame(START_CODE_DMX, &[255, 0, 128]).unwrap();
+//! assert_eq!(frame, [0, 255, 0, 128]);
+//! assert_eq!(decode_frame(&frame).unwrap(), (START_CODE_DMX, &[255, 0, 128][..]));
+//! ```
+//!
+
+use crate::error::DMXFrameError;
+use crate::DMX_CHANNELS;
+
+use std::time;
+
+/// Start code of regular **DMX data**.
+///
+pub const START_CODE_DMX: u8 = 0x00;
+
+/// Start code of **RDM** packets.
+///
+pub const START_CODE_RDM: u8 = 0xCC;
+
+/// Start code of text packets.
+///
+pub const START_CODE_TEXT: u8 = 0x17;
+
+/// Start code of system information packets.
+///
+pub const START_CODE_SYSTEM_INFORMATION: u8 = 0xCF;
+
+/// Baud rate of the frame.
+///
+pub const BAUD_RATE: u32 = 250_000;
+
+/// Time to send a single slot *(start bit, 8 data bits and 2 stop bits at 250000 baud)*.
+///
+pub const SLOT_TIME: time::Duration = time::Duration::from_micros(44);
+
+/// Length of the break sent by this crate with the break signal.
+///
+pub const BREAK_TIME: time::Duration = time::Duration::from_micros(136);
+
+/// Shortest break a transmitter may send.
+///
+pub const MIN_BREAK_TIME: time::Duration = time::Duration::from_micros(92);
+
+/// Shortest mark after break a transmitter may send.
+///
+pub const MIN_MARK_AFTER_BREAK: time::Duration = time::Duration::from_micros(12);
+
+/// Shortest time between the start of two packets.
+///
+pub const MIN_PACKET_TIME: time::Duration = time::Duration::from_micros(1204);
+
+/// Time between the start of two packets used by default *(about 44 packets per second)*.
+///
+pub const DEFAULT_PACKET_TIME: time::Duration = time::Duration::from_micros(22_700);
+
+/// Builds a frame from the start code and the slots.
+///
+/// # Errors
+///
+/// Returns [`DMXFrameError::TooLong`] if there are more than [`DMX_CHANNELS`] slots.
+///
+pub fn encode_frame(start_code: u8, slots: &[u8]) -> Result<Vec<u8>, DMXFrameError> {
+    if slots.len() > DMX_CHANNELS {
+        return Err(DMXFrameError::TooLong);
+    }
+    let mut frame = Vec::with_capacity(slots.len() + 1);
+    frame.push(start_code);
+    frame.extend_from_slice(slots);
+    Ok(frame)
+}
+
+/// Splits a frame into the start code and the slots.
+///
+/// # Errors
+///
+/// - [`DMXFrameError::Empty`] if the frame doesn't contain a start code.
+///
+/// - [`DMXFrameError::TooLong`] if there are more than [`DMX_CHANNELS`] slots.
+///
+pub fn decode_frame(frame: &[u8]) -> Result<(u8, &[u8]), DMXFrameError> {
+    match frame.split_first() {
+        None => Err(DMXFrameError::Empty),
+        Some((_, slots)) if slots.len() > DMX_CHANNELS => Err(DMXFrameError::TooLong),
+        Some((start_code, slots)) => Ok((*start_code, slots)),
+    }
+}
+
+/// Returns the time on the line of a packet with the given number of slots, with the break of this crate and the shortest mark after break.
+///
+pub fn packet_duration(slots: usize) -> time::Duration {
+    BREAK_TIME + MIN_MARK_AFTER_BREAK + SLOT_TIME * (slots as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DMX_CHANNELS;
+    use std::time::Duration;
+
+    #[test]
+    fn codec_round_trips_frames() {
+        let frame = encode_frame(START_CODE_RDM, &[1, 2, 3]).unwrap();
+        assert_eq!(decode_frame(&frame).unwrap(), (START_CODE_RDM, &[1, 2, 3][..]));
+        assert!(encode_frame(START_CODE_DMX, &[0; DMX_CHANNELS + 1]).is_err());
+        assert!(decode_frame(&[]).is_err());
+        assert_eq!(packet_duration(DMX_CHANNELS), Duration::from_micros(136 + 12 + 44 * 513));
+    }
+}
@@ -0,0 +1,289 @@
+/// How colors are mixed in fades and gradients.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Every channel fades linearly on its own. Fades between saturated colors pass through darker, muddy colors.
+    #[default]
+    Rgb,
+    /// Hue, saturation and value fade linearly, the hue takes the shorter way around the color circle *(e.g. red over yellow to green)*.
+    Hsv,
+}
+
+/// An RGB color, e.g. for [`Fixture::set_color`](crate::Fixture::set_color) or the pixel effects.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const WHITE: Color = Color::new(255, 255, 255);
+
+    /// Creates a [Color] from its red, green and blue values.
+    ///
+    pub const fn new(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+
+    /// Creates a [Color] from the `hue` *(in degrees)*, the `saturation` and the `value` *(`0.0` to `1.0`)*.
+    ///
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (red, green, blue) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let offset = value - chroma;
+        let channel = |component: f32| ((component + offset) * 255.0).round() as u8;
+        Color::new(channel(red), channel(green), channel(blue))
+    }
+
+    /// Creates the [Color] of white light with the given correlated color temperature, e.g. `3200` K for tungsten or `5600` K for daylight.
+    ///
+    /// The temperature is clamped to `1000..=40000` K. The result is an approximation of the black body color, normalized to full brightness.
+    ///
+    pub fn from_temperature(kelvin: u32) -> Color {
+        let temperature = kelvin.clamp(1_000, 40_000) as f32 / 100.0;
+        let red = if temperature <= 66.0 {
+            255.0
+        } else {
+            329.698_73 * (temperature - 60.0).powf(-0.133_204_76)
+        };
+        let green = if temperature <= 66.0 {
+            99.470_8 * temperature.ln() - 161.119_57
+        } else {
+            288.122_17 * (temperature - 60.0).powf(-0.075_514_85)
+        };
+        let blue = if temperature >= 66.0 {
+            255.0
+        } else if temperature <= 19.0 {
+            0.0
+        } else {
+            138.517_73 * (temperature - 10.0).ln() - 305.044_8
+        };
+        let channel = |component: f32| component.clamp(0.0, 255.0).round() as u8;
+        Color::new(channel(red), channel(green), channel(blue))
+    }
+
+    /// Splits the color into the red, green and blue parts and a white part, for fixtures with a white emitter.
+    ///
+    /// The white part is the amount all three channels have in common, it's removed from them.
+    ///
+    pub fn to_rgbw(&self) -> (Color, u8) {
+        let white = self.red.min(self.green).min(self.blue);
+        (Color::new(self.red - white, self.green - white, self.blue - white), white)
+    }
+
+    /// Scales every channel by the one of the `gains` *(`255` = unchanged)*, e.g. for the white balance of a fixture.
+    ///
+    pub fn scale(&self, gains: Color) -> Color {
+        let channel = |value: u8, gain: u8| (value as u16 * gain as u16 / 255) as u8;
+        Color::new(channel(self.red, gains.red), channel(self.green, gains.green), channel(self.blue, gains.blue))
+    }
+
+    /// Returns the hue *(in degrees)*, the saturation and the value *(`0.0` to `1.0`)* of the color.
+    ///
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (red, green, blue) = (self.red as f32, self.green as f32, self.blue as f32);
+        let max = red.max(green).max(blue);
+        let delta = max - red.min(green).min(blue);
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / delta)
+        } else if max == green {
+            60.0 * ((blue - red) / delta + 2.0)
+        } else {
+            60.0 * ((red - green) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+        (hue.rem_euclid(360.0), saturation, max / 255.0)
+    }
+
+    /// Mixes the color with another one in the given [`ColorSpace`]. A `progress` of `0.0` returns this color, `1.0` the other one.
+    ///
+    pub fn mix(&self, other: Color, progress: f32, space: ColorSpace) -> Color {
+        match space {
+            ColorSpace::Rgb => self.lerp(other, progress),
+            ColorSpace::Hsv => {
+                let progress = progress.clamp(0.0, 1.0);
+                let (mut from_hue, mut from_saturation, from_value) = self.to_hsv();
+                let (mut to_hue, mut to_saturation, to_value) = other.to_hsv();
+                // Grays have no hue and black has no saturation either, they take the ones of the other color
+                if from_saturation == 0.0 {
+                    from_hue = to_hue;
+                }
+                if to_saturation == 0.0 {
+                    to_hue = from_hue;
+                }
+                if from_value == 0.0 {
+                    from_saturation = to_saturation;
+                }
+                if to_value == 0.0 {
+                    to_saturation = from_saturation;
+                }
+                let turn = (to_hue - from_hue + 540.0).rem_euclid(360.0) - 180.0;
+                Color::from_hsv(
+                    from_hue + turn * progress,
+                    from_saturation + (to_saturation - from_saturation) * progress,
+                    from_value + (to_value - from_value) * progress,
+                )
+            },
+        }
+    }
+
+    /// Mixes the color with another one, linearly per channel. A `progress` of `0.0` returns this color, `1.0` the other one.
+    ///
+    pub fn lerp(&self, other: Color, progress: f32) -> Color {
+        let progress = progress.clamp(0.0, 1.0);
+        let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * progress).round() as u8;
+        Color::new(channel(self.red, other.red), channel(self.green, other.green), channel(self.blue, other.blue))
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([red, green, blue]: [u8; 3]) -> Color {
+        Color::new(red, green, blue)
+    }
+}
+
+impl From<Color> for [u8; 3] {
+    fn from(color: Color) -> [u8; 3] {
+        [color.red, color.green, color.blue]
+    }
+}
+
+/// Named colors, like the color palettes of lighting consoles.
+///
+/// The colors keep the order they were added in, e.g. for the palette of a [`PixelEffect`](crate::PixelEffect).
+///
+/// # Example
+///
+/// ```
+/// use open_dmx::{Color, ColorPalette};
+///
+/// fn main() {
+///     let mut palette = ColorPalette::new();
+///     palette.set("Congo", Color::new(40, 0, 255));
+///     palette.set("Amber", Color::new(255, 126, 0));
+///     assert_eq!(palette.get("Amber"), Some(Color::new(255, 126, 0)));
+///     assert_eq!(palette.names(), ["Congo", "Amber"]);
+/// }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ColorPalette {
+    entries: Vec<(String, Color)>,
+}
+
+impl ColorPalette {
+    /// Creates an empty [ColorPalette].
+    ///
+    pub fn new() -> ColorPalette {
+        ColorPalette::default()
+    }
+
+    /// Stores a color under the name. A color with the same name is replaced in its place.
+    ///
+    pub fn set(&mut self, name: &str, color: Color) {
+        match self.entries.iter_mut().find(|(entry, _)| entry == name) {
+            Some((_, stored)) => *stored = color,
+            None => self.entries.push((name.to_string(), color)),
+        }
+    }
+
+    /// Returns the color with the name.
+    ///
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.entries.iter().find(|(entry, _)| entry == name).map(|(_, color)| *color)
+    }
+
+    /// Removes the color with the name and returns it.
+    ///
+    pub fn remove(&mut self, name: &str) -> Option<Color> {
+        let index = self.entries.iter().position(|(entry, _)| entry == name)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Returns the names of the colors.
+    ///
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Returns the colors.
+    ///
+    pub fn colors(&self) -> Vec<Color> {
+        self.entries.iter().map(|(_, color)| *color).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Attribute, Crossfade, Crossfader, Easing, Fixture, OfflineRenderer, PixelEffect, PixelPattern, DMX_CHANNELS};
+    use std::time::Duration;
+
+    #[test]
+    fn colors_fade_through_hsv() {
+        let (red, green) = (Color::new(255, 0, 0), Color::new(0, 255, 0));
+        assert_eq!(red.mix(green, 0.5, ColorSpace::Rgb), Color::new(128, 128, 0));
+        assert_eq!(red.mix(green, 0.5, ColorSpace::Hsv), Color::new(255, 255, 0));
+        // Black takes the hue of the other color instead of passing through red
+        assert_eq!(Color::BLACK.mix(Color::new(0, 0, 200), 0.5, ColorSpace::Hsv), Color::new(0, 0, 100));
+        assert_eq!(Color::new(255, 128, 0).to_hsv().0.round(), 30.0);
+
+        let par = Fixture::new("Par", 1, vec![Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+        let mut from = [0; DMX_CHANNELS];
+        from[..4].copy_from_slice(&[100, 255, 0, 0]);
+        let mut to = [0; DMX_CHANNELS];
+        to[..4].copy_from_slice(&[200, 0, 255, 0]);
+        let mut fader = Crossfader::new(from, to, &Crossfade::new(Duration::from_secs(1), Easing::Linear).hsv_fixture(&par));
+        fader.set_position(0.5);
+        assert_eq!(fader.universe()[..4], [150, 255, 255, 0]);
+
+        let mut palette = ColorPalette::new();
+        palette.set("Red", red);
+        palette.set("Green", green);
+        palette.set("Red", Color::new(200, 0, 0));
+        assert_eq!(palette.colors(), [Color::new(200, 0, 0), green]);
+        assert_eq!(palette.remove("Red"), Some(Color::new(200, 0, 0)));
+        assert_eq!(palette.names(), ["Green"]);
+        let strip: Vec<Fixture> = (0..4).map(|i| Fixture::new("Pixel", 1 + i * 3, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap()).collect();
+        let gradient = PixelEffect::new(&strip, PixelPattern::Gradient).palette(vec![red, Color::new(0, 0, 255)]).color_space(ColorSpace::Hsv);
+        assert_eq!(gradient.color(1, Duration::ZERO), Color::new(255, 0, 255));
+    }
+
+    #[test]
+    fn fixture_colors_are_white_balanced() {
+        assert_eq!(Color::from_temperature(6600), Color::WHITE);
+        let tungsten = Color::from_temperature(3200);
+        assert_eq!(tungsten.red, 255);
+        assert!(tungsten.green > tungsten.blue && tungsten.blue > 0);
+        assert!(Color::from_temperature(10_000).blue > Color::from_temperature(10_000).red);
+        assert_eq!(Color::new(255, 200, 100).to_rgbw(), (Color::new(155, 100, 0), 100));
+
+        let mut renderer = OfflineRenderer::new().unwrap();
+        let dmx = renderer.dmx();
+        let mut par = Fixture::new("Par", 1, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+        let mut wash = Fixture::new("Wash", 4, vec![Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue, Attribute::White]).unwrap();
+        par.set_white_balance(Color::new(255, 255, 128));
+        assert_eq!(par.white_balance(), Color::new(255, 255, 128));
+        par.set_color(dmx, Color::WHITE).unwrap();
+        wash.set_color(dmx, Color::new(200, 255, 100)).unwrap();
+        assert_eq!(dmx.get_channels()[..9], [255, 255, 128, 0, 100, 155, 0, 100, 0]);
+        wash.set_white_balance(Color::new(255, 128, 255));
+        wash.set_color(dmx, Color::WHITE).unwrap();
+        assert_eq!(dmx.get_channels()[4..8], [127, 0, 127, 128]);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::error::ConfigError;
+use crate::transform::{Curve, TransformId};
+use crate::{check_valid_channel, DMXSerial, DMXSerialBuilder, DMX_CHANNELS};
+
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time;
+
+/// The settings of the interface in a [`Config`].
+///
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InterfaceConfig {
+    /// The path of the port. Only used when opening, see [`Config::builder`].
+    pub port: Option<String>,
+    /// Opens the interface in **sync mode**.
+    pub sync: Option<bool>,
+    /// The [packet time] in microseconds.
+    ///
+    /// [packet time]: DMXSerial::set_packet_time
+    pub packet_time_us: Option<u64>,
+}
+
+/// A gamma curve in a [`Config`], applied to the given channels or to all channels if none are given.
+///
+/// Channels can be given as numbers or as names from the patch.
+///
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CurveConfig {
+    pub gamma: f32,
+    #[serde(default)]
+    pub channels: Vec<String>,
+}
+
+/// The definitions of an installation, loaded from a TOML or JSON file.
+///
+/// The patch maps names to channels. Scenes and curves can refer to channels by these names or by their number.
+///
+/// # Example
+///
+/// ```toml
+/// [interface]
+/// port = "/dev/ttyUSB0"
+/// packet_time_us = 25000
+///
+/// [patch]
+/// stage_left = 1
+/// stage_right = 2
+///
+/// [[curves]]
+/// gamma = 2.2
+/// channels = ["stage_left", "stage_right"]
+///
+/// [scenes.evening]
+/// stage_left = 255
+/// 3 = 128
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub interface: InterfaceConfig,
+    pub patch: BTreeMap<String, usize>,
+    pub curves: Vec<CurveConfig>,
+    pub scenes: BTreeMap<String, BTreeMap<String, u8>>,
+}
+
+impl Config {
+    /// Loads a [Config] from a file. Files ending in `.json` are read as JSON, everything else as TOML.
+    ///
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json")) {
+            Config::from_json(&content)
+        } else {
+            Config::from_toml(&content)
+        }
+    }
+
+    /// Parses a [Config] from TOML.
+    ///
+    pub fn from_toml(content: &str) -> Result<Config, ConfigError> {
+        let config: Config = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses a [Config] from JSON.
+    ///
+    pub fn from_json(content: &str) -> Result<Config, ConfigError> {
+        let config: Config = serde_json::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Checks that all channels in the patch, the curves and the scenes exist and that the curves are valid
+    fn validate(&self) -> Result<(), ConfigError> {
+        for channel in self.patch.values() {
+            check_valid_channel(*channel)?;
+        }
+        if let Some(curve) = self.curves.iter().find(|curve| Curve::gamma(curve.gamma).is_none()) {
+            return Err(ConfigError::InvalidGamma(curve.gamma));
+        }
+        let referenced = self.curves.iter().flat_map(|curve| curve.channels.iter())
+            .chain(self.scenes.values().flat_map(|scene| scene.keys()));
+        for name in referenced {
+            self.channel(name)?;
+        }
+        Ok(())
+    }
+
+    /// Resolves a channel name from the patch or a channel number.
+    ///
+    pub fn channel(&self, name: &str) -> Result<usize, ConfigError> {
+        let channel = match self.patch.get(name) {
+            Some(channel) => *channel,
+            None => name.parse().map_err(|_| ConfigError::UnknownChannel(name.to_string()))?,
+        };
+        check_valid_channel(channel)?;
+        Ok(channel)
+    }
+
+    /// Returns the channel values of a scene, or `None` if there is no scene with the given name.
+    ///
+    /// Channels which aren't part of the scene are `0`.
+    ///
+    pub fn scene(&self, name: &str) -> Option<[u8; DMX_CHANNELS]> {
+        let scene = self.scenes.get(name)?;
+        let mut channels = [0; DMX_CHANNELS];
+        for (channel, value) in scene {
+            // Validated when loading
+            if let Ok(channel) = self.channel(channel) {
+                channels[channel - 1] = *value;
+            }
+        }
+        Some(channels)
+    }
+
+    /// Returns a [`DMXSerialBuilder`] with the interface settings, or `None` if no port is configured.
+    ///
+    pub fn builder(&self) -> Option<DMXSerialBuilder> {
+        let mut builder = DMXSerialBuilder::new(self.interface.port.as_ref()?);
+        if self.interface.sync == Some(true) {
+            builder = builder.sync();
+        }
+        if let Some(micros) = self.interface.packet_time_us {
+            builder = builder.packet_time(time::Duration::from_micros(micros));
+        }
+        Some(builder)
+    }
+
+    // Combines all curves into a lookup table per channel
+    fn curve_tables(&self) -> Option<Vec<[u8; 256]>> {
+        if self.curves.is_empty() {
+            return None;
+        }
+        let mut tables = vec![std::array::from_fn(|value| value as u8); DMX_CHANNELS];
+        for curve in &self.curves {
+            let channels: Vec<usize> = if curve.channels.is_empty() {
+                (1..=DMX_CHANNELS).collect()
+            } else {
+                // Validated when loading
+                curve.channels.iter().filter_map(|name| self.channel(name).ok()).collect()
+            };
+            // Validated when loading
+            let Some(gamma) = Curve::gamma(curve.gamma) else {
+                continue;
+            };
+            for channel in channels {
+                tables[channel - 1].iter_mut().for_each(|mapped| *mapped = gamma.table()[*mapped as usize]);
+            }
+        }
+        Some(tables)
+    }
+}
+
+/// Watches a [`Config`] file and applies changes to a running [DMXSerial].
+///
+/// The file is checked for changes on every [`ConfigWatcher::poll`]. The sync mode, the [packet time], the curves and the [active scene] are applied live.
+/// If the sync mode or the packet time are removed from the file, the interface returns to async mode and the default packet time.
+/// The port is only used when opening.
+///
+/// [active scene]: ConfigWatcher::set_scene
+///
+/// [packet time]: DMXSerial::set_packet_time
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::ConfigWatcher;
+///
+/// fn main() {
+///     let mut watcher = ConfigWatcher::new("install.toml").unwrap();
+///     let mut dmx = watcher.open().unwrap();
+///     loop {
+///         if let Err(e) = watcher.poll(&mut dmx) {
+///             eprintln!("Keeping the previous configuration: {}", e);
+///         }
+///         std::thread::sleep(std::time::Duration::from_secs(1));
+///     }
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<time::SystemTime>,
+    config: Config,
+    // Transform of the curves, which is replaced on every change
+    curves: Option<TransformId>,
+    // Scene which is applied again if it changes
+    scene: Option<String>,
+}
+
+impl ConfigWatcher {
+    /// Loads the [`Config`] from the given file and starts watching it.
+    ///
+    pub fn new<P: Into<PathBuf>>(path: P) -> Result<ConfigWatcher, ConfigError> {
+        let path = path.into();
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        let config = Config::load(&path)?;
+        Ok(ConfigWatcher {
+            path,
+            modified,
+            config,
+            curves: None,
+            scene: None,
+        })
+    }
+
+    /// Returns the current [`Config`].
+    ///
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Opens the [DMXSerial] configured in the interface settings and applies the [`Config`] to it.
+    ///
+    pub fn open(&mut self) -> Result<DMXSerial, ConfigError> {
+        let builder = self.config.builder().ok_or(ConfigError::MissingPort)?;
+        let mut dmx = builder.open()?;
+        self.apply(&mut dmx);
+        Ok(dmx)
+    }
+
+    /// Applies the current [`Config`] to the given [DMXSerial].
+    ///
+    pub fn apply(&mut self, dmx: &mut DMXSerial) {
+        match self.config.interface.sync {
+            Some(true) => dmx.set_sync(),
+            Some(false) => dmx.set_async(),
+            None => {},
+        }
+        if let Some(micros) = self.config.interface.packet_time_us {
+            dmx.set_packet_time(time::Duration::from_micros(micros));
+        }
+        if let Some(id) = self.curves.take() {
+            dmx.remove_transform(id);
+        }
+        if let Some(tables) = self.config.curve_tables() {
+            self.curves = Some(dmx.add_transform(Box::new(move |channels: &mut [u8; DMX_CHANNELS]| {
+                channels.iter_mut().zip(tables.iter()).for_each(|(value, table)| *value = table[*value as usize]);
+            })));
+        }
+    }
+
+    /// Sets the channels of the given DMXSerial to a scene of the [`Config`] and keeps it active.
+    ///
+    /// If the active scene is changed in the file, the new values are set on the next [`ConfigWatcher::poll`]. Other channel changes are kept until then.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::UnknownScene`] if there is no scene with the given name.
+    ///
+    pub fn set_scene(&mut self, dmx: &mut DMXSerial, name: &str) -> Result<(), ConfigError> {
+        let channels = self.config.scene(name).ok_or_else(|| ConfigError::UnknownScene(name.to_string()))?;
+        dmx.set_channels(channels);
+        self.scene = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Returns the name of the active scene, see [`ConfigWatcher::set_scene`].
+    ///
+    /// The scene stops being active when it's removed from the file.
+    ///
+    pub fn scene(&self) -> Option<&str> {
+        self.scene.as_deref()
+    }
+
+    /// Reloads the file if it changed since the last poll and applies it to the given [DMXSerial].
+    ///
+    /// Returns `true` if the [`Config`] was reloaded. If the changed file is invalid, the previous [`Config`] stays active.
+    ///
+    pub fn poll(&mut self, dmx: &mut DMXSerial) -> Result<bool, ConfigError> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        if self.modified == Some(modified) {
+            return Ok(false);
+        }
+        // Remembered before parsing, so an invalid file is only reported once
+        self.modified = Some(modified);
+        let previous = std::mem::replace(&mut self.config, Config::load(&self.path)?);
+        self.apply(dmx);
+
+        // Removed settings return to the defaults of the builder
+        if previous.interface.sync.is_some() && self.config.interface.sync.is_none() {
+            dmx.set_async();
+        }
+        if previous.interface.packet_time_us.is_some() && self.config.interface.packet_time_us.is_none() {
+            dmx.set_packet_time(crate::codec::DEFAULT_PACKET_TIME);
+        }
+        if let Some(name) = self.scene.take() {
+            let channels = self.config.scene(&name);
+            if channels != previous.scene(&name) {
+                if let Some(channels) = channels {
+                    dmx.set_channels(channels);
+                }
+            }
+            self.scene = channels.map(|_| name);
+        }
+        Ok(true)
+    }
+}
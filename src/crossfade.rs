@@ -0,0 +1,317 @@
+use crate::color::{Color, ColorSpace};
+use crate::fixture::{Attribute, Fixture};
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::time;
+
+/// How a fade progresses over its time.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    /// Starts slow and speeds up.
+    EaseIn,
+    /// Starts fast and slows down.
+    EaseOut,
+    /// Starts and ends slow *(S-curve)*.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the linear `progress` *(`0.0` to `1.0`)* to the eased progress.
+    ///
+    pub fn apply(&self, progress: f32) -> f32 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => progress,
+            Easing::EaseIn => progress * progress,
+            Easing::EaseOut => 1.0 - (1.0 - progress) * (1.0 - progress),
+            Easing::EaseInOut => progress * progress * (3.0 - 2.0 * progress),
+        }
+    }
+}
+
+/// The settings of a crossfade. See [`DMXSerial::crossfade_with`].
+///
+/// Channels going up and channels going down have their own fade and delay times, like the split fades of lighting consoles *(e.g. lights coming on faster than the others go out)*.
+/// The delays are counted from the start of the crossfade.
+///
+/// [`DMXSerial::crossfade_with`]: crate::DMXSerial::crossfade_with
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossfade {
+    /// Fade time of the channels which go up.
+    pub fade_up: time::Duration,
+    /// Fade time of the channels which go down.
+    pub fade_down: time::Duration,
+    /// Wait before the channels which go up start fading.
+    pub delay_up: time::Duration,
+    /// Wait before the channels which go down start fading.
+    pub delay_down: time::Duration,
+    pub easing: Easing,
+    /// Channels which jump to their new value at the start instead of fading *(e.g. gobo wheels or macros)*.
+    pub snapped: Vec<usize>,
+    /// The red, green and blue channels of fixtures whose color fades in [HSV](ColorSpace::Hsv) instead of per channel.
+    pub hsv: Vec<[usize; 3]>,
+}
+
+impl Crossfade {
+    /// Creates a [Crossfade] which fades all channels over the same `duration`, without delays.
+    ///
+    pub fn new(duration: time::Duration, easing: Easing) -> Crossfade {
+        Crossfade::split(duration, duration, easing)
+    }
+
+    /// Creates a [Crossfade] with different fade times for the channels going up and down, without delays.
+    ///
+    pub fn split(fade_up: time::Duration, fade_down: time::Duration, easing: Easing) -> Crossfade {
+        Crossfade {
+            fade_up,
+            fade_down,
+            delay_up: time::Duration::ZERO,
+            delay_down: time::Duration::ZERO,
+            easing,
+            snapped: Vec::new(),
+            hsv: Vec::new(),
+        }
+    }
+
+    /// Sets the delays of the channels going up and down.
+    ///
+    pub fn delays(mut self, delay_up: time::Duration, delay_down: time::Duration) -> Crossfade {
+        self.delay_up = delay_up;
+        self.delay_down = delay_down;
+        self
+    }
+
+    /// Snaps the channels of the fixture whose [`Attribute`](crate::Attribute) doesn't fade *(see [`Attribute::fades`](crate::Attribute::fades))*, the theatrical crossfade.
+    ///
+    pub fn snap_fixture(mut self, fixture: &Fixture) -> Crossfade {
+        self.snapped.extend(fixture.attributes().iter().enumerate()
+            .filter(|(_, attribute)| !attribute.fades())
+            .map(|(offset, _)| fixture.address() + offset));
+        self
+    }
+
+    /// Fades the color of the fixture in [HSV](ColorSpace::Hsv), so it doesn't pass through muddy colors. Does nothing if the fixture lacks a red, green or blue channel.
+    ///
+    pub fn hsv_fixture(mut self, fixture: &Fixture) -> Crossfade {
+        if let (Some(red), Some(green), Some(blue)) = (fixture.channel(Attribute::Red), fixture.channel(Attribute::Green), fixture.channel(Attribute::Blue)) {
+            self.hsv.push([red, green, blue]);
+        }
+        self
+    }
+
+    /// Returns the time until all channels reached their target, including the delays.
+    ///
+    pub fn total_time(&self) -> time::Duration {
+        (self.delay_up + self.fade_up).max(self.delay_down + self.fade_down)
+    }
+
+    // Progress of the channels going up and down after the given time
+    fn progress(&self, elapsed: time::Duration) -> (f32, f32) {
+        let progress = |delay: time::Duration, fade: time::Duration| match elapsed.checked_sub(delay) {
+            None => 0.0,
+            Some(_) if fade.is_zero() => 1.0,
+            Some(faded) => self.easing.apply(faded.as_secs_f32() / fade.as_secs_f32()),
+        };
+        (progress(self.delay_up, self.fade_up), progress(self.delay_down, self.fade_down))
+    }
+
+    fn snapped_channels(&self) -> [bool; DMX_CHANNELS] {
+        let mut snapped = [false; DMX_CHANNELS];
+        self.snapped.iter()
+            .filter(|channel| (1..=DMX_CHANNELS).contains(channel))
+            .for_each(|channel| snapped[channel - 1] = true);
+        snapped
+    }
+}
+
+// Blends the channels from the start values towards their target, snapped channels are left at the target
+fn blend(from: &[u8; DMX_CHANNELS], to: &mut [u8; DMX_CHANNELS], (up, down): (f32, f32), snapped: &[bool; DMX_CHANNELS], hsv: &[[usize; 3]]) {
+    let target = *to;
+    to.iter_mut().zip(from.iter()).zip(snapped.iter())
+        .filter(|(_, snapped)| !**snapped)
+        .for_each(|((value, from), _)| {
+            let progress = if *value >= *from { up } else { down };
+            *value = (*from as f32 + (*value as f32 - *from as f32) * progress).round() as u8;
+        });
+    for channels in hsv {
+        if channels.iter().any(|channel| !(1..=DMX_CHANNELS).contains(channel) || snapped[channel - 1]) {
+            continue;
+        }
+        let color = |values: &[u8; DMX_CHANNELS]| Color::new(values[channels[0] - 1], values[channels[1] - 1], values[channels[2] - 1]);
+        let (start, end) = (color(from), color(&target));
+        // Colors getting brighter follow the up times
+        let progress = if end.to_hsv().2 >= start.to_hsv().2 { up } else { down };
+        let mixed = start.mix(end, progress, ColorSpace::Hsv);
+        for (channel, value) in channels.iter().zip([mixed.red, mixed.green, mixed.blue]) {
+            to[channel - 1] = value;
+        }
+    }
+}
+
+// A crossfade in progress, applied by the agent in place of the set values
+#[derive(Debug, Clone)]
+pub(crate) struct ActiveCrossfade {
+    from: [u8; DMX_CHANNELS],
+    start: time::Instant,
+    crossfade: Crossfade,
+    snapped: [bool; DMX_CHANNELS],
+}
+
+impl ActiveCrossfade {
+    pub fn new(from: [u8; DMX_CHANNELS], crossfade: &Crossfade, start: time::Instant) -> ActiveCrossfade {
+        ActiveCrossfade {
+            from,
+            start,
+            crossfade: crossfade.clone(),
+            snapped: crossfade.snapped_channels(),
+        }
+    }
+
+    pub fn is_finished(&self, now: time::Instant) -> bool {
+        now.saturating_duration_since(self.start) >= self.crossfade.total_time()
+    }
+
+    // Blends from the start values to the target (the set values). Returns false once the crossfade is finished
+    pub fn apply(&self, now: time::Instant, channels: &mut [u8; DMX_CHANNELS]) -> bool {
+        if self.is_finished(now) {
+            return false;
+        }
+        blend(&self.from, channels, self.crossfade.progress(now.saturating_duration_since(self.start)), &self.snapped, &self.crossfade.hsv);
+        true
+    }
+}
+
+/// A crossfade which follows a manual **fader position** *(e.g. from a physical fader)* instead of the time.
+///
+/// The position `0.0` shows the start look and `1.0` the target look. In between, the position stands for the share of the [total time](Crossfade::total_time) of the [`Crossfade`], so split fade times and delays apply like in a timed crossfade.
+/// Without any time, all channels follow the position directly.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Crossfade, Crossfader, DMXSerial, Easing};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let fade = Crossfade::new(Duration::from_secs(5), Easing::Linear);
+///     let mut fader = Crossfader::new(dmx.get_channels(), [255; 512], &fade);
+///     fader.set_position(0.25); // e.g. read from a MIDI controller
+///     fader.apply(&mut dmx);
+/// }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossfader {
+    from: [u8; DMX_CHANNELS],
+    to: [u8; DMX_CHANNELS],
+    crossfade: Crossfade,
+    snapped: [bool; DMX_CHANNELS],
+    position: f32,
+}
+
+impl Crossfader {
+    /// Creates a [Crossfader] at the position `0.0`, which fades from the look `from` to the look `to` with the times of the [`Crossfade`].
+    ///
+    pub fn new(from: [u8; DMX_CHANNELS], to: [u8; DMX_CHANNELS], crossfade: &Crossfade) -> Crossfader {
+        Crossfader {
+            from,
+            to,
+            crossfade: crossfade.clone(),
+            snapped: crossfade.snapped_channels(),
+            position: 0.0,
+        }
+    }
+
+    /// Sets the fader position, clamped to `0.0..=1.0`.
+    ///
+    pub fn set_position(&mut self, position: f32) {
+        self.position = if position.is_nan() { 0.0 } else { position.clamp(0.0, 1.0) };
+    }
+
+    /// Returns the fader position.
+    ///
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
+    /// Returns `true` once the fader reached the target look.
+    ///
+    pub fn is_complete(&self) -> bool {
+        self.position >= 1.0
+    }
+
+    /// Returns the blended look at the current position. Snapped channels jump to the target as soon as the fader leaves `0.0`.
+    ///
+    pub fn universe(&self) -> [u8; DMX_CHANNELS] {
+        if self.position <= 0.0 {
+            return self.from;
+        }
+        let total = self.crossfade.total_time();
+        let progress = if total.is_zero() {
+            (self.position, self.position)
+        } else {
+            self.crossfade.progress(total.mul_f32(self.position))
+        };
+        let mut channels = self.to;
+        blend(&self.from, &mut channels, progress, &self.snapped, &self.crossfade.hsv);
+        channels
+    }
+
+    /// Sets the blended look on the [DMXSerial]. A running timed crossfade is stopped.
+    ///
+    pub fn apply(&self, dmx: &mut DMXSerial) {
+        dmx.crossfade_to(self.universe(), time::Duration::ZERO, Easing::Linear);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cue, CueStack, Easing, OfflineRenderer};
+    use std::time::Duration;
+
+    #[test]
+    fn crossfaders_follow_the_fader_position() {
+        let mut renderer = OfflineRenderer::new().unwrap();
+        let dmx = renderer.dmx();
+        let mut stack = CueStack::new();
+        stack.add(Cue::new("Preset", [100; DMX_CHANNELS], Crossfade::new(Duration::ZERO, Easing::Linear)));
+        let mut next = [100; DMX_CHANNELS];
+        next[0] = 200;
+        next[1] = 0;
+        next[2] = 50;
+        // Up in 2 s, down in 1 s after a 1 s delay, channel 3 snaps
+        let mut crossfade = Crossfade::split(Duration::from_secs(2), Duration::from_secs(1), Easing::Linear)
+            .delays(Duration::ZERO, Duration::from_secs(1));
+        crossfade.snapped.push(3);
+        stack.add(Cue::new("Next", next, crossfade));
+        stack.go(dmx);
+
+        let mut fader = stack.crossfader(dmx).unwrap();
+        assert_eq!(fader.universe()[..3], [100, 100, 100]);
+        fader.set_position(0.25);
+        assert_eq!(fader.universe()[..3], [125, 100, 50]);
+        fader.set_position(0.75);
+        assert_eq!(fader.universe()[..3], [175, 50, 50]);
+        fader.apply(dmx);
+        assert_eq!(dmx.get_channel(2).unwrap(), 50);
+        // Faders can be moved back
+        fader.set_position(0.5);
+        assert_eq!(fader.universe()[..3], [150, 100, 50]);
+        fader.set_position(2.0);
+        assert!(fader.is_complete());
+        assert_eq!(fader.universe(), next);
+        assert_eq!(stack.go(dmx), Some(1));
+        assert!(stack.crossfader(dmx).is_none());
+
+        let mut direct = Crossfader::new([0; DMX_CHANNELS], [255; DMX_CHANNELS], &Crossfade::new(Duration::ZERO, Easing::Linear));
+        direct.set_position(0.5);
+        assert_eq!(direct.universe()[0], 128);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::clock::Clock;
+use crate::crossfade::{Crossfade, Crossfader};
+use crate::preset::PresetPool;
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::fmt;
+use std::sync::{mpsc, Arc};
+use std::time;
+
+/// A look of a [`CueStack`] and how it is reached.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub name: String,
+    pub channels: [u8; DMX_CHANNELS],
+    /// The transition from the previous look.
+    pub crossfade: Crossfade,
+    /// Starts the next cue on its own, this long after this cue was started *(auto-follow)*. With `None`, the stack waits for [`CueStack::go`].
+    pub follow: Option<time::Duration>,
+    /// Names of the [presets](crate::PresetPool) which are written over the channels when the cue starts, in order.
+    pub presets: Vec<String>,
+    /// Index of the cue which comes next instead of the following one. Linking back to an earlier cue makes a loop.
+    pub link: Option<usize>,
+}
+
+impl Cue {
+    /// Creates a [Cue] which waits for [`CueStack::go`] and continues with the following cue.
+    ///
+    pub fn new(name: &str, channels: [u8; DMX_CHANNELS], crossfade: Crossfade) -> Cue {
+        Cue {
+            name: name.to_string(),
+            channels,
+            crossfade,
+            presets: Vec::new(),
+            follow: None,
+            link: None,
+        }
+    }
+
+    /// Sets the follow time. See [`Cue::follow`].
+    ///
+    pub fn follow(mut self, after: time::Duration) -> Cue {
+        self.follow = Some(after);
+        self
+    }
+
+    /// Adds a preset which is written over the channels. See [`Cue::presets`].
+    ///
+    pub fn preset(mut self, name: &str) -> Cue {
+        self.presets.push(name.to_string());
+        self
+    }
+
+    /// Sets the cue which comes next. See [`Cue::link`].
+    ///
+    pub fn link(mut self, index: usize) -> Cue {
+        self.link = Some(index);
+        self
+    }
+}
+
+/// A transition of a [`CueStack`], e.g. for showing the playback position in a UI.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CueEvent {
+    /// The cue with the index was started, by [`CueStack::go`], [`CueStack::go_to`] or its follow time.
+    Started(usize),
+    /// The crossfade of the cue with the index is complete.
+    Completed(usize),
+    /// [`CueStack::go`] was called after the last cue, which has no link.
+    Ended,
+}
+
+/// A list of [`Cue`]s which are played one after another with their crossfades, like the main playback of a lighting console.
+///
+/// The stack doesn't run on its own: [`CueStack::poll`] starts the cues whose follow time is due and reports completed crossfades. It should be called regularly *(e.g. once per packet)*.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Crossfade, Cue, CueStack, DMXSerial, Easing};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let fade = Crossfade::new(Duration::from_secs(2), Easing::Linear);
+///     let mut stack = CueStack::new();
+///     stack.add(Cue::new("Preset", [50; 512], fade.clone()));
+///     // Chase between two looks until another cue is started
+///     stack.add(Cue::new("Red", [255; 512], fade.clone()).follow(Duration::from_secs(5)));
+///     stack.add(Cue::new("Blue", [128; 512], fade).follow(Duration::from_secs(5)).link(1));
+///     let events = stack.events();
+///     stack.go(&mut dmx);
+///     loop {
+///         stack.poll(&mut dmx);
+///         while let Ok(event) = events.try_recv() {
+///             println!("{:?}", event);
+///         }
+///         std::thread::sleep(Duration::from_millis(20));
+///     }
+/// }
+/// ```
+///
+pub struct CueStack {
+    cues: Vec<Cue>,
+    current: Option<usize>,
+    // When the current cue was started
+    started: time::Instant,
+    completed: bool,
+    // The follow time of the current cue reached the end of the stack
+    ended: bool,
+    presets: PresetPool,
+    clock: Arc<dyn Clock>,
+    events: Vec<mpsc::Sender<CueEvent>>,
+}
+
+impl CueStack {
+    /// Creates an empty [CueStack].
+    ///
+    pub fn new() -> CueStack {
+        CueStack::with_clock(crate::SystemClock)
+    }
+
+    /// Creates an empty [CueStack] which follows the given [`Clock`].
+    ///
+    pub fn with_clock<C: Clock + 'static>(clock: C) -> CueStack {
+        let clock: Arc<dyn Clock> = Arc::new(clock);
+        CueStack {
+            cues: Vec::new(),
+            current: None,
+            started: clock.now(),
+            completed: true,
+            ended: false,
+            presets: PresetPool::new(),
+            clock,
+            events: Vec::new(),
+        }
+    }
+
+    /// Adds a cue at the end. Returns its index.
+    ///
+    pub fn add(&mut self, cue: Cue) -> usize {
+        self.cues.push(cue);
+        self.cues.len() - 1
+    }
+
+    /// Returns the cues.
+    ///
+    pub fn cues(&self) -> &[Cue] {
+        &self.cues
+    }
+
+    /// Returns the [`PresetPool`] the cues refer to.
+    ///
+    pub fn presets(&self) -> &PresetPool {
+        &self.presets
+    }
+
+    /// Replaces the [`PresetPool`], e.g. with one which is shared by several stacks.
+    ///
+    pub fn set_presets(&mut self, presets: PresetPool) {
+        self.presets = presets;
+    }
+
+    /// Returns the look of the cue with the given index, with its presets written over the channels. Returns `None` if there is no such cue.
+    ///
+    pub fn look(&self, index: usize) -> Option<[u8; DMX_CHANNELS]> {
+        let cue = self.cues.get(index)?;
+        let mut channels = cue.channels;
+        self.presets.apply(&cue.presets, &mut channels);
+        Some(channels)
+    }
+
+    /// Returns the index of the current cue, or `None` before the first [`CueStack::go`].
+    ///
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Returns the index of the cue which [`CueStack::go`] starts, or `None` at the end of the stack.
+    ///
+    pub fn next(&self) -> Option<usize> {
+        let next = match self.current {
+            None => 0,
+            Some(current) => self.cues[current].link.unwrap_or(current + 1),
+        };
+        (next < self.cues.len()).then_some(next)
+    }
+
+    /// Returns a [`Crossfader`] from the current look of the [DMXSerial] to the next cue *(see [`CueStack::next`])*, with the times of the next cue. Returns `None` at the end of the stack.
+    ///
+    /// Once the fader is [complete](Crossfader::is_complete), [`CueStack::go`] makes the next cue the current one without a visible fade.
+    ///
+    pub fn crossfader(&self, dmx: &DMXSerial) -> Option<Crossfader> {
+        let next = self.next()?;
+        Some(Crossfader::new(dmx.get_channels(), self.look(next)?, &self.cues[next].crossfade))
+    }
+
+    /// Returns a [`Receiver`] for the [`CueEvent`]s of this [CueStack].
+    ///
+    /// [`Receiver`]: std::sync::mpsc::Receiver
+    ///
+    pub fn events(&mut self) -> mpsc::Receiver<CueEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.events.push(tx);
+        rx
+    }
+
+    /// Starts the next cue *(see [`CueStack::next`])*. Returns its index, or `None` at the end of the stack.
+    ///
+    pub fn go(&mut self, dmx: &mut DMXSerial) -> Option<usize> {
+        match self.next() {
+            Some(next) => {
+                self.start(next, self.clock.now(), dmx);
+                Some(next)
+            },
+            None => {
+                self.notify(CueEvent::Ended);
+                None
+            },
+        }
+    }
+
+    /// Starts the cue with the given index. Returns `false` if there is none.
+    ///
+    pub fn go_to(&mut self, index: usize, dmx: &mut DMXSerial) -> bool {
+        if index >= self.cues.len() {
+            return false;
+        }
+        self.start(index, self.clock.now(), dmx);
+        true
+    }
+
+    /// Reports the completed crossfade of the current cue and starts the next cue once its follow time is due.
+    ///
+    pub fn poll(&mut self, dmx: &mut DMXSerial) {
+        let Some(current) = self.current else {
+            return;
+        };
+        let now = self.clock.now();
+        let (fade_time, follow) = (self.cues[current].crossfade.total_time(), self.cues[current].follow);
+        if !self.completed && now.saturating_duration_since(self.started) >= fade_time {
+            self.completed = true;
+            self.notify(CueEvent::Completed(current));
+        }
+        let Some(follow) = follow else {
+            return;
+        };
+        if self.ended || now.saturating_duration_since(self.started) < follow {
+            return;
+        }
+        match self.next() {
+            // Counted from the due time, so loops don't drift with the poll interval
+            Some(next) => self.start(next, self.started + follow, dmx),
+            None => {
+                self.ended = true;
+                self.notify(CueEvent::Ended);
+            },
+        }
+    }
+
+    fn start(&mut self, index: usize, at: time::Instant, dmx: &mut DMXSerial) {
+        // The presets are looked up now, so changed presets take effect
+        if let Some(look) = self.look(index) {
+            dmx.crossfade_with(look, &self.cues[index].crossfade);
+        }
+        self.current = Some(index);
+        self.started = at;
+        self.completed = false;
+        self.ended = false;
+        self.notify(CueEvent::Started(index));
+    }
+
+    fn notify(&mut self, event: CueEvent) {
+        // Receivers which got dropped are removed
+        self.events.retain(|tx| tx.send(event).is_ok());
+    }
+}
+
+impl Default for CueStack {
+    fn default() -> CueStack {
+        CueStack::new()
+    }
+}
+
+impl fmt::Debug for CueStack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CueStack")
+            .field("cues", &self.cues.len())
+            .field("current", &self.current)
+            .field("completed", &self.completed)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cue, CueEvent, Easing, ManualClock, OfflineRenderer};
+    use std::time::Duration;
+
+    #[test]
+    fn cues_follow_link_and_loop() {
+        let clock = ManualClock::new();
+        let mut renderer = OfflineRenderer::new().unwrap();
+        let dmx = renderer.dmx();
+        let fade = Crossfade::new(Duration::from_secs(1), Easing::Linear);
+        let mut stack = CueStack::with_clock(clock.clone());
+        stack.add(Cue::new("Preset", [10; DMX_CHANNELS], fade.clone()));
+        stack.add(Cue::new("A", [20; DMX_CHANNELS], fade.clone()).follow(Duration::from_secs(3)));
+        stack.add(Cue::new("B", [30; DMX_CHANNELS], fade.clone()).follow(Duration::from_secs(2)).link(1));
+        stack.add(Cue::new("Never", [40; DMX_CHANNELS], fade));
+        let events = stack.events();
+
+        assert_eq!(stack.go(dmx), Some(0));
+        stack.poll(dmx);
+        assert_eq!(stack.next(), Some(1));
+        // Without a follow time, the stack waits
+        clock.advance(Duration::from_secs(10));
+        stack.poll(dmx);
+        assert_eq!(stack.current(), Some(0));
+        stack.go(dmx);
+        for _ in 0..7 {
+            clock.advance(Duration::from_secs(1));
+            stack.poll(dmx);
+        }
+        // A at 0 s, B at 3 s, A at 5 s and completed at 6 s
+        assert_eq!(stack.current(), Some(1));
+        assert_eq!(dmx.get_channel(1).unwrap(), 20);
+        let received: Vec<CueEvent> = events.try_iter().collect();
+        assert_eq!(received, [
+            CueEvent::Started(0), CueEvent::Completed(0), CueEvent::Started(1), CueEvent::Completed(1),
+            CueEvent::Started(2), CueEvent::Completed(2), CueEvent::Started(1), CueEvent::Completed(1),
+        ]);
+
+        assert!(stack.go_to(3, dmx));
+        assert_eq!(stack.go(dmx), None);
+        assert_eq!(events.try_iter().last(), Some(CueEvent::Ended));
+        assert!(!stack.go_to(4, dmx));
+    }
+}
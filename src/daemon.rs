@@ -0,0 +1,210 @@
+use crate::merge::DMXWriter;
+use crate::{check_valid_channel, DMXSerial, DMX_CHANNELS};
+
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+// Opcodes of the protocol. Every message is an opcode followed by a fixed or announced number of bytes, all numbers are big endian
+const SET_CHANNEL: u8 = 0x01; // channel: u16, value: u8
+const SET_CHANNELS: u8 = 0x02; // first channel: u16, count: u16, values: [u8; count]
+const RELEASE_ALL: u8 = 0x03;
+const SET_PRIORITY: u8 = 0x04; // priority: u8
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Shares one [DMXSerial] with several applications on the same machine over a Unix socket. *(Unix only)*
+///
+/// Every connected [`DaemonClient`] gets its own [`DMXWriter`], so the values of all clients are merged with the [merge policy] of the interface.
+/// The values of a client are released as soon as it disconnects.
+///
+/// [merge policy]: DMXSerial::set_merge_policy
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXDaemon, DMXSerial};
+///
+/// fn main() {
+///     let dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let daemon = DMXDaemon::bind(dmx, "/run/open_dmx.sock").unwrap();
+///     daemon.run().unwrap();
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct DMXDaemon {
+    dmx: Arc<Mutex<DMXSerial>>,
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl DMXDaemon {
+    /// Binds the socket at the given path. A leftover socket file is replaced.
+    ///
+    pub fn bind<P: AsRef<Path>>(dmx: DMXSerial, path: P) -> io::Result<DMXDaemon> {
+        let path = path.as_ref();
+        // A socket file left over from a crashed daemon would make binding fail
+        if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            fs::remove_file(path)?;
+        }
+        Ok(DMXDaemon {
+            dmx: Arc::new(Mutex::new(dmx)),
+            listener: UnixListener::bind(path)?,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Gives access to the shared [DMXSerial] *(e.g. for changing the merge policy)*.
+    ///
+    pub fn interface(&self) -> MutexGuard<'_, DMXSerial> {
+        // Mutex can be unwrapped here
+        self.dmx.lock().unwrap()
+    }
+
+    /// Accepts clients until the socket fails. Every client is served on its own thread.
+    ///
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let writer = self.interface().writer(0);
+            let _ = thread::spawn(move || {
+                if let Err(e) = serve(stream, &writer) {
+                    eprintln!("Disconnected DMX daemon client: \"{}\"", e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DMXDaemon {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Applies the messages of a client to its writer until it disconnects
+fn serve(stream: UnixStream, writer: &DMXWriter) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut opcode = [0];
+    loop {
+        match reader.read_exact(&mut opcode) {
+            Ok(()) => {},
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        match opcode[0] {
+            SET_CHANNEL => {
+                let mut message = [0; 3];
+                reader.read_exact(&mut message)?;
+                let channel = u16::from_be_bytes([message[0], message[1]]) as usize;
+                writer.set_channel(channel, message[2]).map_err(|e| invalid_data(format!("{}: {}", e, channel)))?;
+            },
+            SET_CHANNELS => {
+                let mut header = [0; 4];
+                reader.read_exact(&mut header)?;
+                let first = u16::from_be_bytes([header[0], header[1]]) as usize;
+                let count = u16::from_be_bytes([header[2], header[3]]) as usize;
+                if count == 0 || check_valid_channel(first).is_err() || first + count - 1 > DMX_CHANNELS {
+                    return Err(invalid_data(format!("{} channels starting at {} are out of range", count, first)));
+                }
+                let mut values = vec![0; count];
+                reader.read_exact(&mut values)?;
+                writer.set_channel_range(first, &values);
+            },
+            RELEASE_ALL => writer.release_all(),
+            SET_PRIORITY => {
+                let mut priority = [0];
+                reader.read_exact(&mut priority)?;
+                writer.set_priority(priority[0]);
+            },
+            opcode => return Err(invalid_data(format!("unknown opcode {:#04x}", opcode))),
+        }
+    }
+}
+
+/// Connects to a [`DMXDaemon`] to share its interface with other applications. *(Unix only)*
+///
+/// The values of the client are released when it's dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::DaemonClient;
+///
+/// fn main() {
+///     let mut client = DaemonClient::connect("/run/open_dmx.sock").unwrap();
+///     client.set_channel(1, 255).unwrap();
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct DaemonClient {
+    stream: BufWriter<UnixStream>,
+}
+
+impl DaemonClient {
+    /// Connects to the socket of a [`DMXDaemon`].
+    ///
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<DaemonClient> {
+        Ok(DaemonClient {
+            stream: BufWriter::new(UnixStream::connect(path)?),
+        })
+    }
+
+    /// Sets the specified [`channel`] to the given [`value`].
+    ///
+    /// [`channel`]: usize
+    /// [`value`]: u8
+    ///
+    pub fn set_channel(&mut self, channel: usize, value: u8) -> io::Result<()> {
+        check_valid_channel(channel).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let [high, low] = (channel as u16).to_be_bytes();
+        self.send(&[SET_CHANNEL, high, low, value])
+    }
+
+    /// Sets all channels to the given values.
+    ///
+    pub fn set_channels(&mut self, channels: [u8; DMX_CHANNELS]) -> io::Result<()> {
+        self.set_channel_range(1, &channels)
+    }
+
+    /// Sets the channels starting at `first` to the given values.
+    ///
+    pub fn set_channel_range(&mut self, first: usize, values: &[u8]) -> io::Result<()> {
+        if values.is_empty() || check_valid_channel(first).is_err() || first + values.len() - 1 > DMX_CHANNELS {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "channels are out of range"));
+        }
+        let mut message = vec![SET_CHANNELS];
+        message.extend_from_slice(&(first as u16).to_be_bytes());
+        message.extend_from_slice(&(values.len() as u16).to_be_bytes());
+        message.extend_from_slice(values);
+        self.send(&message)
+    }
+
+    /// Releases all channels of this client.
+    ///
+    pub fn release_all(&mut self) -> io::Result<()> {
+        self.send(&[RELEASE_ALL])
+    }
+
+    /// Sets the priority of this client, used with [`MergePolicy::Priority`]. Clients start with priority `0`.
+    ///
+    /// [`MergePolicy::Priority`]: crate::MergePolicy::Priority
+    ///
+    pub fn set_priority(&mut self, priority: u8) -> io::Result<()> {
+        self.send(&[SET_PRIORITY, priority])
+    }
+
+    fn send(&mut self, message: &[u8]) -> io::Result<()> {
+        self.stream.write_all(message)?;
+        self.stream.flush()
+    }
+}
@@ -1,21 +1,46 @@
 
-#[cfg(feature = "thread_priority")]
-use thread_priority;
-
 use crate::thread::*;
 use crate::check_valid_channel;
-use crate::error::{DMXDisconnectionError, DMXChannelValidityError};
+use crate::error::{DMXDisconnectionError, DMXChannelValidityError, DMXFrameError};
 use crate::DMX_CHANNELS;
+use crate::adapter::AdapterInfo;
+#[cfg(feature = "thread_priority")]
+use crate::priority::PriorityFailure;
+use crate::builder::{BreakMode, DirectionControl, DirectionTiming, DMXSerialBuilder, LineSettings, TransportSource};
 
-use serialport::SerialPort;
+use crate::transport::{SerialTransport, Transport};
+use crate::rfc2217::Rfc2217Port;
+use crate::transform::{FrameTransform, TransformId, TransformStage};
+use crate::audit::{AuditTrail, ChannelWrite};
+use crate::changes::{ChangeSubscribers, ChannelChange};
+use crate::coalesce::{ChannelUpdate, PendingWrites, WriteQueue};
+use crate::idle::{IdlePolicy, IdleTracker};
+use crate::merge::{DMXWriter, MergePolicy, Merger, WriterRef};
+use crate::keyframe::Keyframes;
+use crate::crossfade::{ActiveCrossfade, Crossfade, Easing};
+use crate::ramp::OutputRamp;
+use crate::render::{FrameContext, Renderer};
+use crate::safety::{SafetyInterlock, SlewLimit, StrobeGuard};
+use crate::selftest::PacketTiming;
+use crate::clock::Clock;
+#[cfg(unix)]
+use crate::ipc::SocketListener;
 
 use std::time;
-use std::io::Write;
+use std::io::{self, Write};
+use std::path::Path;
 use std::thread;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, Weak};
+
+// Interval in which the state is saved by `open_with_restore`
+const PERSIST_INTERVAL: time::Duration = time::Duration::from_secs(5);
 
-// Sleep duration between sending the break and the data
-const TIME_BREAK_TO_DATA: time::Duration = time::Duration::new(0, 136_000);
+// The clock can't wake the agent, so the keep-alive deadline is checked at least this often
+const KEEP_ALIVE_POLL: time::Duration = time::Duration::from_millis(10);
+
+// Receivers of every transmitted frame and the time it was sent at
+type FrameSubscribers = ArcRwLock<Vec<mpsc::Sender<(time::Instant, [u8; DMX_CHANNELS])>>>;
 
 /// A [DMX-Interface] which writes to the [SerialPort] independently from the main thread.
 /// 
@@ -30,15 +55,108 @@ pub struct DMXSerial {
     
     name: String,
     // Array of DMX-Values which are written to the Serial-Port
-    channels: ArcRwLock<[u8; DMX_CHANNELS]>,
+    channels: Arc<ChannelBuffer>,
     // Connection to the Agent-Thread, if this is dropped the Agent-Thread will stop
-    agent: AgentCommunication::<()>,
+    agent: mpsc::Sender<AgentCommand>,
+    // Dropped by the Agent-Thread when it stops
+    agent_running: Weak<()>,
 
     // Mode
     is_sync: ArcRwLock<bool>,
 
     min_time_break_to_break: ArcRwLock<time::Duration>,
 
+    // Frames which are interleaved with the regular packets
+    periodic_frames: ArcRwLock<Vec<PeriodicFrame>>,
+    next_periodic_id: Arc<AtomicU64>,
+
+    // Additional sources which are merged with the channel values
+    writers: ArcRwLock<Vec<WriterRef>>,
+    merge_policy: ArcRwLock<MergePolicy>,
+    // Sockets on which other processes send channel values
+    #[cfg(unix)]
+    sockets: Vec<SocketListener>,
+
+    // Stages which modify the channel values before they are sent
+    transforms: ArcRwLock<Vec<TransformStage>>,
+    next_transform_id: Arc<AtomicU64>,
+
+    // Channels which are locked at a fixed value, applied after the transforms
+    parked: ArcRwLock<[Option<u8>; DMX_CHANNELS]>,
+
+    // Limits how fast the channels change, applied after the parked channels
+    slew_limit: ArcRwLock<Option<SlewLimit>>,
+
+    // Holds the output while the rig flashes too fast, applied after the slew limit
+    strobe_guard: ArcRwLock<Option<StrobeGuard>>,
+
+    // Forces the protected channels to 0 unless they're armed, applied last
+    interlock: ArcRwLock<Option<SafetyInterlock>>,
+
+    // Highest sent value of every channel, only recorded in peak hold mode
+    peak_hold: ArcRwLock<bool>,
+    peaks: ArcRwLock<[u8; DMX_CHANNELS]>,
+
+    // Set values of the last packet and the number of packets the output stayed the same
+    last_sent: Arc<ChannelBuffer>,
+    unchanged_frames: ArcRwLock<u64>,
+    // Output of the last packet after all stages, which is what gets persisted
+    transmitted: Arc<ChannelBuffer>,
+    // Receivers of every transmitted frame, e.g. a recorder
+    frame_subscribers: FrameSubscribers,
+
+    // Shorter packets for higher refresh rates, with regular full frames
+    truncation: ArcRwLock<Option<Truncation>>,
+
+    // Fades to a look after a while without changes
+    idle_policy: ArcRwLock<Option<IdlePolicy>>,
+    is_idle: ArcRwLock<bool>,
+
+    // Timestamped universes, which are interpolated in place of the set values
+    keyframes: ArcRwLock<Keyframes>,
+
+    // Fades from the previous values to the set values
+    crossfade: ArcRwLock<Option<ActiveCrossfade>>,
+
+    // Generates the channel values right before every packet
+    renderer: Arc<Mutex<Option<Renderer>>>,
+
+    // Ramps the output on and off while the agent keeps running
+    output_ramp: ArcRwLock<OutputRamp>,
+
+    // Longest pause between two packets in sync mode, before a keep-alive packet is sent
+    max_frame_interval: ArcRwLock<Option<time::Duration>>,
+    keep_alive_subscribers: ArcRwLock<Vec<mpsc::Sender<time::Instant>>>,
+
+    // Last writes of every channel, if enabled
+    audit: Option<AuditTrail>,
+    // Receivers of every changed channel value
+    change_subscribers: Arc<ChangeSubscribers>,
+
+    // Writes which are applied by the agent before the next packet, only used in coalescing mode
+    write_queue: WriteQueue,
+    pending_writes: PendingWrites,
+    // The written values in coalescing mode, since the channel buffer lags behind. Shared, so all handles see the same values
+    staged: Arc<Mutex<Option<[u8; DMX_CHANNELS]>>>,
+
+    // Settings of the Serial-Port, kept for reopening
+    options: DMXSerialBuilder,
+    break_mode: BreakMode,
+
+    // USB adapter behind the port, if it could be detected
+    adapter: Option<AdapterInfo>,
+
+    // Errors which stopped the agent
+    port_errors: ArcRwLock<Vec<String>>,
+
+    // Break and packet timing measured by the agent, used by the self-test
+    packet_timing: ArcRwLock<PacketTiming>,
+
+    // Counted over reopens for the health report
+    reconnects: u64,
+    previous_frames: u64,
+    previous_errors: usize,
+
 }
 
 impl DMXSerial {
@@ -46,11 +164,13 @@ impl DMXSerial {
     /// 
     /// The [`path`] should look something like this:
     /// 
-    /// - **Windows**: `COM3`
+    /// - **Windows**: `COM3` *(`COM10` and above are opened as `\\.\COM10`)*
     /// - **Linux**: `/dev/ttyUSB0`
+    /// - **Remote** *(RFC 2217, see [`Rfc2217Port`])*: `rfc2217://10.0.0.5:7000`
     /// 
     /// [DMX-Interface]: DMXSerial
-    /// [`path`]: std::str
+    /// [`path`]: std::path::Path
+    /// [`Rfc2217Port`]: crate::Rfc2217Port
     /// 
     /// <br>
     /// 
@@ -69,7 +189,7 @@ impl DMXSerial {
     /// 
     /// Basic usage:
     /// 
-    /// ```
+    /// ```no_run
     /// use open_dmx::DMXSerial;
     /// 
     /// fn main() {
@@ -79,50 +199,391 @@ impl DMXSerial {
     /// }
     /// ```
     /// 
-    pub fn open(port: &str) -> Result<DMXSerial, serialport::Error> {
+    pub fn open<P: AsRef<Path>>(port: P) -> Result<DMXSerial, serialport::Error> {
+        DMXSerial::builder(port).open()
+    }
+
+    /// Returns a [`DMXSerialBuilder`] for configuring the [DMX-Interface] on the given [`path`] before opening it.
+    /// 
+    /// [DMX-Interface]: DMXSerial
+    /// [`path`]: std::path::Path
+    /// 
+    /// # Example
+    /// 
+    /// Basic usage:
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// use std::time::Duration;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::builder("COM3")
+    ///         .packet_time(Duration::from_millis(30))
+    ///         .open()
+    ///         .unwrap();
+    ///     dmx.set_channels([255; 512]);
+    /// }
+    /// ```
+    /// 
+    pub fn builder<P: AsRef<Path>>(port: P) -> DMXSerialBuilder {
+        DMXSerialBuilder::new(port)
+    }
+
+    /// Opens a new [DMX-Interface] on a [SerialPort] which was already opened, e.g. with special ioctls or on a PTY.
+    /// 
+    /// The port is switched to [`LineSettings::DMX`], all other settings *(e.g. flow control or exclusivity)* are kept.
+    /// Use [`DMXSerialBuilder::open_with_port`] for other settings and [`DMXSerialBuilder::open_with_transport`] for connections which aren't a [SerialPort].
+    /// 
+    /// The name of the port is used for the adapter detection and for [reopening](DMXSerial::reopen).
+    /// 
+    /// [DMX-Interface]: DMXSerial
+    /// [SerialPort]: serialport::SerialPort
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// 
+    /// fn main() {
+    ///     let port = serialport::new("/dev/ttyUSB0", 250_000).open().unwrap();
+    ///     let mut dmx = DMXSerial::from_port(port).unwrap();
+    ///     dmx.set_channels([255; 512]);
+    /// }
+    /// ```
+    /// 
+    pub fn from_port(port: Box<dyn serialport::SerialPort>) -> Result<DMXSerial, serialport::Error> {
+        let name = port.name().unwrap_or_else(|| "custom".to_string());
+        DMXSerial::builder(name).open_with_port(port)
+    }
+
+    pub(crate) fn from_builder(options: DMXSerialBuilder) -> Result<DMXSerial, serialport::Error> {
+        if let Some(address) = crate::rfc2217::remote_address(&options.port) {
+            let transport = Rfc2217Port::connect(address, options.line_settings)?;
+            let break_mode = options.break_mode.unwrap_or_default();
+            return DMXSerial::start(options, Box::new(transport), break_mode, None);
+        }
+        let adapter = AdapterInfo::detect(&options.port);
+        let break_mode = DMXSerial::select_break_mode(&options, &adapter);
+        let transport = SerialTransport::open(&options)?;
+        DMXSerial::start(options, Box::new(transport), break_mode, adapter)
+    }
+
+    /// Opens a new [DMX-Interface] with a [`SerialPortBuilder`], so the port can be configured before the agent takes over *(e.g. flow control, exclusivity or timeouts)*.
+    /// 
+    /// The [`LineSettings`] of the builder are replaced by [`LineSettings::DMX`]. Use [`DMXSerialBuilder::open_with`] for other settings.
+    /// 
+    /// [DMX-Interface]: DMXSerial
+    /// [`SerialPortBuilder`]: serialport::SerialPortBuilder
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// 
+    /// fn main() {
+    ///     let builder = serialport::new("/dev/ttyUSB0", 250_000).exclusive(false);
+    ///     let mut dmx = DMXSerial::open_with(builder).unwrap();
+    ///     dmx.set_channels([255; 512]);
+    /// }
+    /// ```
+    /// 
+    pub fn open_with(builder: serialport::SerialPortBuilder) -> Result<DMXSerial, serialport::Error> {
+        DMXSerialBuilder::new("custom").open_with(builder)
+    }
+
+    pub(crate) fn from_serial_builder(mut options: DMXSerialBuilder, builder: serialport::SerialPortBuilder) -> Result<DMXSerial, serialport::Error> {
+        let transport = SerialTransport::from_builder(builder, options.line_settings)?;
+        if let Some(name) = transport.name() {
+            options.port = name.into();
+        }
+        let adapter = AdapterInfo::detect(&options.port);
+        let break_mode = DMXSerial::select_break_mode(&options, &adapter);
+        DMXSerial::start(options, Box::new(transport), break_mode, adapter)
+    }
+
+    pub(crate) fn from_serial_port(options: DMXSerialBuilder, port: Box<dyn serialport::SerialPort>) -> Result<DMXSerial, serialport::Error> {
+        let adapter = AdapterInfo::detect(&options.port);
+        let break_mode = DMXSerial::select_break_mode(&options, &adapter);
+        let transport = SerialTransport::from_port(port, options.line_settings)?;
+        DMXSerial::start(options, Box::new(transport), break_mode, adapter)
+    }
+
+    // Uses the configured break mode or the one which works best with the adapter
+    fn select_break_mode(options: &DMXSerialBuilder, adapter: &Option<AdapterInfo>) -> BreakMode {
+        match (options.break_mode, adapter) {
+            (Some(mode), Some(info)) if mode == BreakMode::Signal && info.chip.has_unreliable_break() => {
+                eprintln!("The {:?} adapter on \"{}\" is known to generate unreliable breaks. Consider using BreakMode::Baud", info.chip, options.port.display());
+                mode
+            },
+            (Some(mode), _) => mode,
+            (None, Some(info)) => info.chip.preferred_break_mode(),
+            (None, None) => BreakMode::Signal,
+        }
+    }
+
+    pub(crate) fn from_transport(options: DMXSerialBuilder, transport: Box<dyn Transport>) -> Result<DMXSerial, serialport::Error> {
+        let break_mode = options.break_mode.unwrap_or_default();
+        DMXSerial::start(options, transport, break_mode, None)
+    }
+
+    fn start(options: DMXSerialBuilder, transport: Box<dyn Transport>, break_mode: BreakMode, adapter: Option<AdapterInfo>) -> Result<DMXSerial, serialport::Error> {
 
-        let (handler, agent_rx) = mpsc::sync_channel(0);
         let (agent_tx, handler_rec) = mpsc::channel();
 
+        let write_queue = WriteQueue::new();
+        let pending_writes = write_queue.receiver();
+        let agent_writes = write_queue.receiver();
+
+        let running = Arc::new(());
+
         // channel default created here!
         let dmx = DMXSerial {
-            name: port.to_string(),
-            channels: ArcRwLock::new([0; DMX_CHANNELS]),
-            agent: AgentCommunication::new(agent_tx, agent_rx),
-            is_sync: ArcRwLock::new(false),
-            min_time_break_to_break: ArcRwLock::new(time::Duration::from_micros(22_700))};
-
-        let mut agent = DMXSerialAgent::open(&port, dmx.min_time_break_to_break.read_only())?;
-        let channel_view = dmx.channels.read_only();
+            name: options.port.to_string_lossy().into_owned(),
+            channels: ChannelBuffer::new(),
+            agent: agent_tx,
+            agent_running: Arc::downgrade(&running),
+            is_sync: ArcRwLock::new(options.sync),
+            min_time_break_to_break: ArcRwLock::new(options.packet_time),
+            periodic_frames: ArcRwLock::new(Vec::new()),
+            next_periodic_id: Arc::new(AtomicU64::new(0)),
+            writers: ArcRwLock::new(Vec::new()),
+            merge_policy: ArcRwLock::new(MergePolicy::default()),
+            #[cfg(unix)]
+            sockets: Vec::new(),
+            transforms: ArcRwLock::new(Vec::new()),
+            next_transform_id: Arc::new(AtomicU64::new(0)),
+            parked: ArcRwLock::new([None; DMX_CHANNELS]),
+            slew_limit: ArcRwLock::new(None),
+            strobe_guard: ArcRwLock::new(None),
+            interlock: ArcRwLock::new(None),
+            peak_hold: ArcRwLock::new(false),
+            peaks: ArcRwLock::new([0; DMX_CHANNELS]),
+            last_sent: ChannelBuffer::new(),
+            unchanged_frames: ArcRwLock::new(0),
+            transmitted: ChannelBuffer::new(),
+            frame_subscribers: ArcRwLock::new(Vec::new()),
+            truncation: ArcRwLock::new(None),
+            idle_policy: ArcRwLock::new(None),
+            is_idle: ArcRwLock::new(false),
+            keyframes: ArcRwLock::new(Keyframes::default()),
+            crossfade: ArcRwLock::new(None),
+            renderer: Arc::new(Mutex::new(None)),
+            output_ramp: ArcRwLock::new(OutputRamp::new(options.clock.now())),
+            max_frame_interval: ArcRwLock::new(None),
+            keep_alive_subscribers: ArcRwLock::new(Vec::new()),
+            audit: None,
+            change_subscribers: Arc::new(ChangeSubscribers::default()),
+            write_queue,
+            pending_writes,
+            staged: Arc::new(Mutex::new(None)),
+            options: options.clone(),
+            break_mode,
+            adapter,
+            port_errors: ArcRwLock::new(Vec::new()),
+            packet_timing: ArcRwLock::new(PacketTiming::default()),
+            reconnects: 0,
+            previous_frames: 0,
+            previous_errors: 0};
+
+        if let Some(path) = &options.restore {
+            match crate::persist::load(path) {
+                Ok(channels) => {
+                    dmx.channels.write().set_all(&channels);
+                    // Until the first packet, the restored output is still the last one
+                    dmx.transmitted.write().set_all(&channels);
+                },
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+                Err(e) => eprintln!("Failed to restore the DMX state from \"{}\": \"{}\". Starting with all channels at 0...", path.display(), e),
+            }
+        }
+        if let Some((path, interval)) = &options.persist {
+            crate::persist::spawn(path.clone(), *interval, Arc::downgrade(&dmx.transmitted));
+        }
+
+        let mut agent = DMXSerialAgent::open(&options, transport, break_mode, &dmx)?;
         let is_sync_view = dmx.is_sync.read_only();
+        let periodic_view = dmx.periodic_frames.read_only();
+        let writers_view = dmx.writers.read_only();
+        let merge_policy_view = dmx.merge_policy.read_only();
+        let mut merger = Merger::new();
+        let transform_view = dmx.transforms.read_only();
+        let parked_view = dmx.parked.read_only();
+        let slew_limit_view = dmx.slew_limit.read_only();
+        let peak_hold_view = dmx.peak_hold.read_only();
+        let peaks = dmx.peaks.clone();
+        let last_sent = Arc::clone(&dmx.last_sent);
+        let unchanged_frames = dmx.unchanged_frames.clone();
+        let mut last_output: Option<[u8; DMX_CHANNELS]> = None;
+        let truncation_view = dmx.truncation.read_only();
+        let port_errors = dmx.port_errors.clone();
+        #[cfg(feature = "metrics")]
+        let port_name = options.port.to_string_lossy().into_owned();
+        let idle_policy_view = dmx.idle_policy.read_only();
+        let is_idle = dmx.is_idle.clone();
+        let clock = options.clock.clone();
+        let mut idle_tracker = IdleTracker::new(clock.now());
+        let keyframes = dmx.keyframes.clone();
+        let crossfade = dmx.crossfade.clone();
+        let renderer = dmx.renderer.clone();
+        let packet_time_view = dmx.min_time_break_to_break.read_only();
+        let output_ramp_view = dmx.output_ramp.read_only();
+        let max_frame_interval_view = dmx.max_frame_interval.read_only();
+        let keep_alive_subscribers = dmx.keep_alive_subscribers.clone();
+        let mut last_packet = clock.now();
+        // Writable, since pending writes are applied by the agent
+        let channel_buffer = Arc::clone(&dmx.channels);
+        #[cfg(feature = "thread_priority")]
+        let (priority, priority_failure) = (options.priority, options.priority_failure.clone());
+        #[cfg(feature = "cpu_affinity")]
+        let cpu_affinity = options.cpu_affinity;
+        // Reports back if the agent could be started
+        let (started, started_rec) = mpsc::sync_channel::<Result<(), String>>(1);
         let _ = thread::spawn(move || {
+                let _running = running;
                 #[cfg(feature = "thread_priority")]
-                thread_priority::set_current_thread_priority(thread_priority::ThreadPriority::Max).unwrap_or_else(|e| {
-                    eprintln!("Failed to set thread priority: \"{:?}\". Continuing anyways...", e)
-                });
+                if let Err(e) = crate::priority::apply(priority) {
+                    match priority_failure {
+                        PriorityFailure::Ignore => {},
+                        PriorityFailure::Log => eprintln!("Failed to set thread priority: \"{}\". Continuing anyways...", e),
+                        PriorityFailure::Error => {
+                            let _ = started.send(Err(format!("Failed to set thread priority: {}", e)));
+                            return;
+                        },
+                        PriorityFailure::Callback(callback) => callback(&e),
+                    }
+                }
+                #[cfg(feature = "cpu_affinity")]
+                if let Some(id) = cpu_affinity {
+                    if !core_affinity::set_for_current(core_affinity::CoreId { id }) {
+                        let _ = started.send(Err(format!("Failed to pin the agent thread to CPU core {}", id)));
+                        return;
+                    }
+                }
+                let _ = started.send(Ok(()));
+                let mut packet_count: u64 = 0;
                 loop {
                     // This can be unwrapped since the values can't be dropped while the thread is running
-                    if is_sync_view.read().unwrap().clone() {
-                        if handler_rec.recv().is_err() {
+                    let command = if *is_sync_view.read().unwrap() {
+                        let max_frame_interval = *max_frame_interval_view.read().unwrap();
+                        let received = match max_frame_interval {
+                            Some(interval) => handler_rec.recv_timeout(interval.saturating_sub(clock.now().saturating_duration_since(last_packet)).min(KEEP_ALIVE_POLL)),
+                            None => handler_rec.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+                        };
+                        match received {
+                            Ok(command) => command,
+                            // The application missed the deadline, so the last values are sent again
+                            Err(mpsc::RecvTimeoutError::Timeout) => {
+                                if clock.now().saturating_duration_since(last_packet) < max_frame_interval.unwrap_or_default() {
+                                    continue;
+                                }
+                                keep_alive_subscribers.write().unwrap().retain(|subscriber| subscriber.send(clock.now()).is_ok());
+                                // Nobody requested it, so nobody waits for it
+                                AgentCommand::Update(None)
+                            },
                             // If the channel is dropped by the other side, the thread will stop
-                            break;
+                            Err(mpsc::RecvTimeoutError::Disconnected) => break,
                         }
-                    }
+                    } else {
+                        // Queued frames take the place of a single regular packet
+                        match handler_rec.try_recv() {
+                            Ok(command) => command,
+                            Err(mpsc::TryRecvError::Empty) => AgentCommand::Update(None),
+                            // If the channel is dropped by the other side, the thread will stop
+                            Err(mpsc::TryRecvError::Disconnected) => break,
+                        }
+                    };
 
-                    let channels = channel_view.read().unwrap().clone();
+                    // Notified once the packet has been sent
+                    let confirmation;
+                    let result = match command {
+                        AgentCommand::Update(sent) => {
+                            confirmation = sent;
+                            last_packet = clock.now();
+                            agent_writes.apply(&channel_buffer);
+                            let mut channels = channel_buffer.load();
+                            let now = clock.now();
+                            {
+                                let mut crossfade = crossfade.write().unwrap();
+                                if crossfade.as_ref().is_some_and(|fade| !fade.apply(now, &mut channels)) {
+                                    *crossfade = None;
+                                }
+                            }
+                            keyframes.write().unwrap().apply(now, &mut channels);
+                            if let Some(renderer) = renderer.lock().unwrap().as_mut() {
+                                renderer.render(*packet_time_view.read().unwrap(), &mut channels, now);
+                            }
+                            last_sent.write().set_all(&channels);
+                            merger.apply(*merge_policy_view.read().unwrap(), &writers_view.read().unwrap(), &mut channels);
+                            let values = channels;
+                            transform_view.read().unwrap().iter().for_each(|stage| stage.transform.apply(&mut channels));
+                            *is_idle.write().unwrap() = idle_tracker.apply(idle_policy_view.read().unwrap().as_ref(), &values, &mut channels, now);
+                            output_ramp_view.read().unwrap().apply(&mut channels, now);
+                            channels.iter_mut().zip(parked_view.read().unwrap().iter())
+                                .for_each(|(value, parked)| if let Some(parked) = parked { *value = *parked });
+                            if let Some(slew_limit) = slew_limit_view.read().unwrap().as_ref() {
+                                slew_limit.apply(&mut channels, now);
+                            }
+                            packet_count = packet_count.wrapping_add(1);
+                            let slots = match *truncation_view.read().unwrap() {
+                                Some(truncation) if truncation.full_frame_interval == 0 || !packet_count.is_multiple_of(truncation.full_frame_interval as u64) => truncation.last_channel,
+                                _ => DMX_CHANNELS,
+                            };
+                            // The strobe guard and the interlock are applied while sending, so the peaks and the activity see the values which were actually sent
+                            let result = agent.send_dmx_packet(&mut channels, slots);
+                            if *peak_hold_view.read().unwrap() {
+                                peaks.write().unwrap().iter_mut().zip(channels.iter()).for_each(|(peak, value)| *peak = (*peak).max(*value));
+                            }
+                            {
+                                let mut unchanged = unchanged_frames.write().unwrap();
+                                *unchanged = if last_output == Some(channels) { unchanged.saturating_add(1) } else { 0 };
+                            }
+                            #[cfg(feature = "metrics")]
+                            if let Some(last) = &last_output {
+                                crate::telemetry::channels_changed(&port_name, last.iter().zip(channels.iter()).filter(|(last, value)| last != value).count());
+                            }
+                            last_output = Some(channels);
+                            // Collect the due frames first, so the lock isn't held while sending
+                            let due: Vec<Vec<u8>> = periodic_view.read().unwrap().iter()
+                                .filter(|periodic| packet_count.is_multiple_of(periodic.interval))
+                                .map(|periodic| periodic.frame.clone())
+                                .collect();
+                            result.and_then(|_| due.iter().try_for_each(|frame| agent.send_frame(frame)))
+                        },
+                        AgentCommand::Frame(frame, sent) => {
+                            confirmation = Some(sent);
+                            last_packet = clock.now();
+                            agent.send_frame(&frame)
+                        },
+                        AgentCommand::ReadLineSettings(settings) => {
+                            let _ = settings.send(agent.port.read_line_settings());
+                            continue;
+                        },
+                        AgentCommand::Probe(done) => {
+                            confirmation = Some(done);
+                            agent.port.probe()
+                        },
+                    };
 
                     // If an error occurs, the thread will stop
-                    if let Err(_) = agent.send_dmx_packet(channels) {
+                    if let Err(e) = result {
+                        port_errors.write().unwrap().push(e.to_string());
+                        #[cfg(feature = "metrics")]
+                        crate::telemetry::port_error(&port_name);
+                        // Stopped before the waiting handle is released, so it sees the agent as stopped
+                        drop(_running);
                         break;
                     }
-
-                    //If the channel is dropped by the other side, the thread will stop
-                    if let Err(mpsc::TrySendError::Disconnected(_)) = handler.try_send(()) {
-                        break;
+                    if let Some(sent) = confirmation {
+                        let _ = sent.send(());
                     }
+
                 }
         });
-        Ok(dmx)
+        match started_rec.recv() {
+            Ok(Ok(())) => Ok(dmx),
+            Ok(Err(e)) => Err(serialport::Error::new(serialport::ErrorKind::Unknown, e)),
+            Err(_) => Err(serialport::Error::new(serialport::ErrorKind::Unknown, "DMX agent thread stopped unexpectedly")),
+        }
     }
 
     /// Does the same as [`DMXSerial::open`] but sets the [DMXSerial] to **sync mode**.
@@ -131,7 +592,7 @@ impl DMXSerial {
     /// 
     /// Basic strobe effect:
     /// 
-    /// ```
+    /// ```no_run
     /// use open_dmx::DMXSerial;
     /// fn main() {
     ///     let mut dmx = DMXSerial::open_sync("COM3").unwrap();
@@ -143,33 +604,215 @@ impl DMXSerial {
     ///         dmx.update();
     ///     }
     /// }
-    pub fn open_sync(port: &str) -> Result<DMXSerial, serialport::Error> {
+    pub fn open_sync<P: AsRef<Path>>(port: P) -> Result<DMXSerial, serialport::Error> {
         let mut dmx = DMXSerial::open(port)?;
         dmx.set_sync();
         Ok(dmx)
     }
 
+    /// Does the same as [`DMXSerial::open`], but restores the channel values from the given file and saves the transmitted universe there every 5 seconds.
+    ///
+    /// After a power cycle the rig comes back in its previous state instead of a blackout.
+    /// Use [`DMXSerialBuilder::persist_state`] and [`DMXSerialBuilder::restore_state`] for other intervals.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    ///
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open_with_restore("/dev/ttyUSB0", "/var/lib/dmx/state").unwrap();
+    ///     dmx.set_channel(1, 255).unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn open_with_restore<P: AsRef<Path>, Q: AsRef<Path>>(port: P, path: Q) -> Result<DMXSerial, serialport::Error> {
+        let path = path.as_ref();
+        DMXSerial::builder(port)
+            .restore_state(path)
+            .persist_state(path, PERSIST_INTERVAL)
+            .open()
+    }
+
     /// Reopens the [DMXSerial] on the same [`path`].
     /// 
-    /// It keeps the current [`channel`] values.
-    ///
-    /// [`path`]: std::str
+    /// It keeps
+    /// - the current [`channel`] values, the mode and the [packet time]
+    /// - the periodic frames, the writers, the merge policy and the sockets
+    /// - the transforms, the parked channels and the slew limit
+    /// - the strobe guard and the safety interlock
+    /// - the peaks, the truncation and the idle policy
+    /// - the keyframes, the crossfade, the render callback and the output ramp
+    /// - the max frame interval, the audit trail and the coalescing mode
+    /// - all settings of the [`DMXSerialBuilder`]
+    /// 
+    /// The [SerialPort] at the path is opened again. Interfaces opened with [`DMXSerialBuilder::open_with_transport_factory`] get a new transport from the factory instead.
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error of the kind [`InvalidInput`](serialport::ErrorKind::InvalidInput) if the interface was opened with [`DMXSerialBuilder::open_with_transport`], since that transport can't be opened again.
+    /// 
+    /// [SerialPort]: serialport::SerialPort
+    /// [`path`]: std::path::Path
     /// [`channel`]: usize
+    /// [packet time]: DMXSerial::set_packet_time
     ///
     pub fn reopen(&mut self) -> Result<(), serialport::Error> {
         let channels = self.get_channels();
-        let new_dmx = DMXSerial::open(&self.name)?;
+        let builder = DMXSerialBuilder {
+            sync: self.is_sync(),
+            packet_time: self.get_packet_time(),
+            ..self.options.clone()
+        };
+        let mut new_dmx = match &builder.source {
+            TransportSource::Path => builder.open()?,
+            TransportSource::Factory(factory) => {
+                let transport = factory()?;
+                DMXSerial::from_transport(builder, transport)?
+            },
+            TransportSource::Fixed => return Err(serialport::Error::new(
+                serialport::ErrorKind::InvalidInput,
+                "the interface was opened with a single transport and can't be reopened",
+            )),
+        };
+        // The new agent reads from the new buffers, so the frames and transforms are moved over
+        // RwLock can be unwrapped here
+        *new_dmx.periodic_frames.write().unwrap() = std::mem::take(&mut *self.periodic_frames.write().unwrap());
+        *new_dmx.transforms.write().unwrap() = std::mem::take(&mut *self.transforms.write().unwrap());
+        *new_dmx.writers.write().unwrap() = std::mem::take(&mut *self.writers.write().unwrap());
+        *new_dmx.merge_policy.write().unwrap() = self.merge_policy();
+        #[cfg(unix)]
+        {
+            new_dmx.sockets = std::mem::take(&mut self.sockets);
+        }
+        *new_dmx.parked.write().unwrap() = *self.parked.read().unwrap();
+        *new_dmx.slew_limit.write().unwrap() = self.slew_limit();
+        *new_dmx.strobe_guard.write().unwrap() = self.strobe_guard();
+        *new_dmx.interlock.write().unwrap() = self.interlock();
+        *new_dmx.peak_hold.write().unwrap() = self.is_peak_hold();
+        *new_dmx.peaks.write().unwrap() = self.peaks();
+        *new_dmx.idle_policy.write().unwrap() = self.idle_policy();
+        *new_dmx.truncation.write().unwrap() = self.truncation();
+        *new_dmx.keyframes.write().unwrap() = std::mem::take(&mut *self.keyframes.write().unwrap());
+        *new_dmx.crossfade.write().unwrap() = self.crossfade.write().unwrap().take();
+        *new_dmx.renderer.lock().unwrap() = self.renderer.lock().unwrap().take();
+        *new_dmx.output_ramp.write().unwrap() = *self.output_ramp.read().unwrap();
+        *new_dmx.max_frame_interval.write().unwrap() = self.max_frame_interval();
+        *new_dmx.keep_alive_subscribers.write().unwrap() = std::mem::take(&mut *self.keep_alive_subscribers.write().unwrap());
+        *new_dmx.frame_subscribers.write().unwrap() = std::mem::take(&mut *self.frame_subscribers.write().unwrap());
+        new_dmx.next_periodic_id = self.next_periodic_id.clone();
+        new_dmx.next_transform_id = self.next_transform_id.clone();
+        new_dmx.reconnects = self.reconnects + 1;
+        new_dmx.previous_frames = self.previous_frames + self.packet_timing().frames;
+        // RwLock can be unwrapped here
+        new_dmx.previous_errors = self.previous_errors + self.port_errors.read().unwrap().len();
+        let audit = self.audit.take();
+        let change_subscribers = self.change_subscribers.clone();
+        let coalescing = self.is_coalescing();
         *self = new_dmx;
         self.set_channels(channels);
+        // Restored afterwards, since restoring the channels isn't a write
+        self.audit = audit;
+        self.change_subscribers = change_subscribers;
+        self.set_coalescing(coalescing);
+        #[cfg(feature = "metrics")]
+        crate::telemetry::reconnected(&self.name);
         Ok(())
     }
+
+    /// Returns a second handle to the same [DMXSerial], e.g. for splitting ownership between a control and a monitoring thread.
+    /// 
+    /// Both handles share the [`channel`] values, the mode, the [packet time], the periodic frames, the writers, the transforms, the parked channels, the peaks and all other state of the agent.
+    /// The agent keeps running until every handle is dropped.
+    /// 
+    /// The audit trail and the sockets stay with this handle. [`DMXSerial::reopen`] only reopens the handle it is called on.
+    /// If several handles call [`DMXSerial::update`] at the same time, each returns once its own packet has been sent.
+    /// 
+    /// # Errors
+    /// 
+    /// Returns a [`DMXDisconnectionError`] if the agent has already stopped.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// use std::thread;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let monitor = dmx.try_clone().unwrap();
+    ///     thread::spawn(move || {
+    ///         while monitor.check_agent().is_ok() {
+    ///             println!("Channel 1: {}", monitor.get_channel(1).unwrap());
+    ///             thread::sleep(std::time::Duration::from_secs(1));
+    ///         }
+    ///     });
+    ///     dmx.set_channel(1, 255).unwrap();
+    /// }
+    /// ```
+    /// 
+    /// [`channel`]: usize
+    /// [packet time]: DMXSerial::set_packet_time
+    ///
+    pub fn try_clone(&self) -> Result<DMXSerial, DMXDisconnectionError> {
+        self.check_agent()?;
+        Ok(DMXSerial {
+            name: self.name.clone(),
+            channels: self.channels.clone(),
+            agent: self.agent.clone(),
+            agent_running: self.agent_running.clone(),
+            is_sync: self.is_sync.clone(),
+            min_time_break_to_break: self.min_time_break_to_break.clone(),
+            periodic_frames: self.periodic_frames.clone(),
+            next_periodic_id: self.next_periodic_id.clone(),
+            writers: self.writers.clone(),
+            merge_policy: self.merge_policy.clone(),
+            #[cfg(unix)]
+            sockets: Vec::new(),
+            transforms: self.transforms.clone(),
+            next_transform_id: self.next_transform_id.clone(),
+            parked: self.parked.clone(),
+            slew_limit: self.slew_limit.clone(),
+            strobe_guard: self.strobe_guard.clone(),
+            interlock: self.interlock.clone(),
+            peak_hold: self.peak_hold.clone(),
+            peaks: self.peaks.clone(),
+            last_sent: self.last_sent.clone(),
+            transmitted: self.transmitted.clone(),
+            frame_subscribers: self.frame_subscribers.clone(),
+            unchanged_frames: self.unchanged_frames.clone(),
+            truncation: self.truncation.clone(),
+            idle_policy: self.idle_policy.clone(),
+            is_idle: self.is_idle.clone(),
+            keyframes: self.keyframes.clone(),
+            crossfade: self.crossfade.clone(),
+            renderer: self.renderer.clone(),
+            output_ramp: self.output_ramp.clone(),
+            max_frame_interval: self.max_frame_interval.clone(),
+            keep_alive_subscribers: self.keep_alive_subscribers.clone(),
+            audit: None,
+            change_subscribers: self.change_subscribers.clone(),
+            write_queue: self.write_queue.clone(),
+            pending_writes: self.pending_writes.clone(),
+            staged: self.staged.clone(),
+            options: self.options.clone(),
+            break_mode: self.break_mode,
+            adapter: self.adapter.clone(),
+            port_errors: self.port_errors.clone(),
+            packet_timing: self.packet_timing.clone(),
+            reconnects: self.reconnects,
+            previous_frames: self.previous_frames,
+            previous_errors: self.previous_errors,
+        })
+    }
+
     /// Gets the name of the Path on which the [DMXSerial] is opened.
     /// 
     ///  # Example
     /// 
     /// Basic usage:
     /// 
-    /// ```
+    /// ```no_run
     /// # use open_dmx::DMXSerial;
     /// # fn main() {
     /// let mut dmx = DMXSerial::open("COM3").unwrap();
@@ -181,6 +824,79 @@ impl DMXSerial {
         &self.name
     }
 
+    /// Returns the [`path`] on which the [DMXSerial] is opened, exactly as it was given.
+    /// 
+    /// [`DMXSerial::name`] returns the same path as a string, where invalid unicode is replaced.
+    /// 
+    /// [`path`]: std::path::Path
+    /// 
+    pub fn path(&self) -> &Path {
+        &self.options.port
+    }
+
+    /// Returns the [`LineSettings`] used while sending **DMX data**.
+    /// 
+    pub fn line_settings(&self) -> LineSettings {
+        self.options.line_settings
+    }
+
+    /// Reads back the [`LineSettings`] which are actually applied by the driver and the errors which stopped the interface.
+    /// 
+    /// Some drivers silently accept 250000 baud, but keep another rate. Compare the result with the requested settings to detect this.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// # let dmx = DMXSerial::open("COM3").unwrap();
+    /// let diagnostics = dmx.port_diagnostics();
+    /// if !diagnostics.is_applied() {
+    ///     eprintln!("{:?} were requested, but the driver applied {:?}", diagnostics.requested, diagnostics.applied);
+    /// }
+    /// # }
+    /// ```
+    /// 
+    pub fn port_diagnostics(&self) -> PortDiagnostics {
+        let (settings, settings_rec) = mpsc::sync_channel(1);
+        let applied = self.agent.send(AgentCommand::ReadLineSettings(settings))
+            .map_err(|_| DMXDisconnectionError.to_string())
+            .and_then(|_| settings_rec.recv().map_err(|_| DMXDisconnectionError.to_string()))
+            .and_then(|result| result.map_err(|e| e.to_string()));
+        PortDiagnostics {
+            requested: self.options.line_settings,
+            applied,
+            // RwLock can be unwrapped here
+            errors: self.port_errors.read().unwrap().clone(),
+        }
+    }
+
+    /// Returns the [`BreakMode`] used for generating the **break**.
+    /// 
+    pub fn break_mode(&self) -> BreakMode {
+        self.break_mode
+    }
+
+    /// Returns the [`AdapterInfo`] of the USB adapter behind the port.
+    /// 
+    /// Returns `None` if the port isn't a USB port or the adapter couldn't be detected.
+    /// 
+    pub fn adapter_info(&self) -> Option<&AdapterInfo> {
+        self.adapter.as_ref()
+    }
+
+    /// Returns the [`DirectionControl`] used for switching the transceiver into transmit mode.
+    /// 
+    pub fn direction_control(&self) -> DirectionControl {
+        self.options.direction
+    }
+
+    /// Returns the [`DirectionTiming`] used around switching the transceiver.
+    /// 
+    pub fn direction_timing(&self) -> DirectionTiming {
+        self.options.direction_timing
+    }
+
     /// Sets the specified [`channel`] to the given [`value`].
     /// 
     /// [`channel`]: usize
@@ -190,7 +906,7 @@ impl DMXSerial {
     /// 
     /// Basic usage:
     /// 
-    /// ```
+    /// ```no_run
     /// # use open_dmx::DMXSerial;
     /// # fn main() {
     /// # let mut dmx = DMXSerial::open("COM3").unwrap();
@@ -200,12 +916,57 @@ impl DMXSerial {
     /// 
     pub fn set_channel(&mut self, channel: usize, value: u8) -> Result<(), DMXChannelValidityError> {
         check_valid_channel(channel)?;
-        // RwLock can be unwrapped here
-        let mut channels = self.channels.write().unwrap();
-        channels[channel - 1] = value;
+        self.write_channel(channel - 1, value, None);
+        Ok(())
+    }
+
+    /// Does the same as [`DMXSerial::set_channel`], but records the write with an origin `tag` in the [audit trail].
+    /// 
+    /// [audit trail]: DMXSerial::set_audit_depth
+    /// 
+    pub fn set_channel_tagged(&mut self, channel: usize, value: u8, tag: &'static str) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        self.write_channel(channel - 1, value, Some(tag));
         Ok(())
     }
 
+    fn write_channel(&mut self, index: usize, value: u8, tag: Option<&'static str>) {
+        if !self.change_subscribers.is_empty() {
+            let old = self.get_channels();
+            let mut new = old;
+            new[index] = value;
+            self.change_subscribers.notify(&old, &new, tag);
+        }
+        // Mutex can be unwrapped here
+        match self.staged.lock().unwrap().as_mut() {
+            Some(staged) => {
+                staged[index] = value;
+                self.write_queue.push(ChannelUpdate::Single(index, value));
+            },
+            None => self.channels.write().set(index, value),
+        }
+        if let Some(audit) = &mut self.audit {
+            audit.record(index, value, tag);
+        }
+    }
+
+    fn write_channels(&mut self, channels: [u8; DMX_CHANNELS], tag: Option<&'static str>) {
+        if !self.change_subscribers.is_empty() {
+            self.change_subscribers.notify(&self.get_channels(), &channels, tag);
+        }
+        // Mutex can be unwrapped here
+        match self.staged.lock().unwrap().as_mut() {
+            Some(staged) => {
+                *staged = channels;
+                self.write_queue.push(ChannelUpdate::All(Box::new(channels)));
+            },
+            None => self.channels.write().set_all(&channels),
+        }
+        if let Some(audit) = &mut self.audit {
+            audit.record_all(&channels, tag);
+        }
+    }
+
     /// Sets all channels to the given [`value`] via a array of size [`DMX_CHANNELS`].
     /// 
     /// [`value`]: u8
@@ -214,7 +975,7 @@ impl DMXSerial {
     /// 
     /// Checkerboard effect:
     /// 
-    /// ```
+    /// ```no_run
     /// # use open_dmx::{DMXSerial, DMX_CHANNELS};
     /// # fn main() {
     ///    let mut dmx = DMXSerial::open("COM3").unwrap();
@@ -225,8 +986,15 @@ impl DMXSerial {
     /// ```
     /// 
     pub fn set_channels(&mut self, channels: [u8; DMX_CHANNELS]) {
-        // RwLock can be unwrapped here
-        *self.channels.write().unwrap() = channels;
+        self.write_channels(channels, None);
+    }
+
+    /// Does the same as [`DMXSerial::set_channels`], but records the writes with an origin `tag` in the [audit trail].
+    /// 
+    /// [audit trail]: DMXSerial::set_audit_depth
+    /// 
+    pub fn set_channels_tagged(&mut self, channels: [u8; DMX_CHANNELS], tag: &'static str) {
+        self.write_channels(channels, Some(tag));
     }
 
     /// Tries to get the [`value`] of the specified [`channel`].
@@ -240,7 +1008,7 @@ impl DMXSerial {
     /// 
     /// Basic usage:
     /// 
-    /// ```
+    /// ```no_run
     /// # use open_dmx::DMXSerial;
     /// # fn main() {
     /// # let mut dmx = DMXSerial::open("COM3").unwrap();
@@ -251,9 +1019,7 @@ impl DMXSerial {
     /// 
     pub fn get_channel(&self, channel: usize) -> Result<u8, DMXChannelValidityError> {
         check_valid_channel(channel)?;
-        // RwLock can be unwrapped here
-        let channels = self.channels.read().unwrap();
-        Ok(channels[channel - 1])
+        Ok(self.get_channels()[channel - 1])
     }
 
     /// Returns the [`value`] of all channels via a array of size [`DMX_CHANNELS`].
@@ -264,92 +1030,989 @@ impl DMXSerial {
     /// 
     /// Basic usage:
     /// 
-    /// ```
+    /// ```no_run
     /// # use open_dmx::{DMXSerial, DMX_CHANNELS};
     /// # fn main() {
     /// # let mut dmx = DMXSerial::open("COM3").unwrap();
-    /// dmx.set_channels([255; DMX_CHANNELS]).unwrap();
+    /// dmx.set_channels([255; DMX_CHANNELS]);
     /// assert_eq!(dmx.get_channels(), [255; DMX_CHANNELS]);
     /// # }
     /// 
     pub fn get_channels(&self) -> [u8; DMX_CHANNELS] {
-        // RwLock can be unwrapped here
-        self.channels.read().unwrap().clone()
+        // Mutex can be unwrapped here
+        if let Some(staged) = *self.staged.lock().unwrap() {
+            return staged;
+        }
+        self.channels.load()
     }
 
-    /// Resets all channels to `0`.
-    ///     
+    /// Creates a new [`DMXWriter`] with the given priority, whose values are merged with the channel values before every packet.
+    /// 
+    /// The merged values are only sent, [`DMXSerial::get_channels`] still returns the values set on the [DMXSerial]. See [`MergePolicy`].
+    /// 
     /// # Example
     /// 
-    /// Basic usage:
+    /// Two parts of an application sharing the interface:
     /// 
-    /// ```
-    /// # use open_dmx::{DMXSerial, DMX_CHANNELS};
+    /// ```no_run
+    /// # use open_dmx::{DMXSerial, MergePolicy};
     /// # fn main() {
     /// # let mut dmx = DMXSerial::open("COM3").unwrap();
-    /// dmx.set_channels([255; DMX_CHANNELS]).unwrap();
-    /// assert_eq!(dmx.get_channels(), [255; DMX_CHANNELS]);
-    /// dmx.reset_channels();
-    /// assert_eq!(dmx.get_channels(), [0; DMX_CHANNELS]);
+    /// dmx.set_merge_policy(MergePolicy::Priority);
+    /// let effects = dmx.writer(50);
+    /// let override_panel = dmx.writer(100);
+    /// effects.set_channel(1, 128).unwrap();
+    /// override_panel.set_channel(1, 255).unwrap(); // wins until it's released
+    /// override_panel.release_channel(1).unwrap();
     /// # }
     /// ```
     /// 
-    pub fn reset_channels(&mut self) {
+    pub fn writer(&mut self, priority: u8) -> DMXWriter {
+        let writer = DMXWriter::new(priority);
         // RwLock can be unwrapped here
-        self.channels.write().unwrap().fill(0);
+        let mut writers = self.writers.write().unwrap();
+        writers.retain(|writer| writer.is_alive());
+        writers.push(writer.downgrade());
+        writer
     }
 
-    fn wait_for_update(&self) -> Result<(), DMXDisconnectionError> {
-        self.agent.rx.recv().map_err(|_| DMXDisconnectionError)?;
-        Ok(())
-    }
-    
-    /// Updates the DMX data.
+    /// Listens for channel values from other processes on a Unix socket at the given path. *(Unix only)*
     /// 
-    /// Returns after the data has been sent.
+    /// The received values are merged like a [`DMXWriter`] with the given priority. They're sent with a [`UniverseSender`].
+    /// This keeps the real-time output running in its own process, even if the process controlling it crashes.
     /// 
-    /// Works both in **sync** and **async** mode.
+    /// A leftover socket file at the path is replaced. The socket is closed and removed once the [DMXSerial] is dropped.
     /// 
-    /// # Example
+    /// [`UniverseSender`]: crate::UniverseSender
     /// 
-    /// [Basic Usage]
+    /// # Example
     /// 
-    /// [Basic Usage]: #example-1
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    /// dmx.listen_on_socket("/run/open_dmx.sock", 100).unwrap();
+    /// # }
+    /// ```
     /// 
-    pub fn update(&mut self) -> Result<(), DMXDisconnectionError> {
-        self.update_async()?;
-        self.wait_for_update().map_err(|_| DMXDisconnectionError)?;
+    #[cfg(unix)]
+    pub fn listen_on_socket<P: AsRef<Path>>(&mut self, path: P, priority: u8) -> io::Result<()> {
+        let writer = self.writer(priority);
+        self.sockets.push(SocketListener::bind(path.as_ref(), writer)?);
         Ok(())
     }
 
-    /// Updates the DMX data but returns immediately.
+    /// Sets the [`MergePolicy`] for combining the [`DMXWriter`]s.
     /// 
-    /// Useless in **async** mode.
+    /// # Default
     /// 
-    pub fn update_async(&self) -> Result<(), DMXDisconnectionError> {
-        self.agent.tx.send(()).map_err(|_| DMXDisconnectionError)?;
-        Ok(())
-    }
-
-    /// Sets the DMX mode to **sync**.
+    /// - [`MergePolicy::Htp`]
     /// 
-    pub fn set_sync(&mut self) {
+    pub fn set_merge_policy(&mut self, policy: MergePolicy) {
         // RwLock can be unwrapped here
-        *self.is_sync.write().unwrap() = true;
+        *self.merge_policy.write().unwrap() = policy;
     }
 
-    /// Sets the DMX mode to **async**.
-    ///     
-    pub fn set_async(&mut self) {
+    /// Returns the current [`MergePolicy`].
+    /// 
+    pub fn merge_policy(&self) -> MergePolicy {
         // RwLock can be unwrapped here
-        *self.is_sync.write().unwrap() = false;
+        *self.merge_policy.read().unwrap()
     }
 
-    /// Returns `true` if the DMX mode is **sync**.
-    ///     
+    /// Sets the [`Truncation`], which only sends the channels up to a last channel. `None` always sends all channels *(default)*.
+    /// 
+    /// Shorter packets take less time, so installations with only a few channels can be refreshed more often.
+    /// The [packet time] has to be lowered accordingly. Full frames are still sent in the given interval, so devices with higher addresses aren't left without data.
+    /// 
+    /// [packet time]: DMXSerial::set_packet_time
+    /// 
+    /// # Example
+    /// 
+    /// A 40 channel dimmer at roughly 90 Hz:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::{DMXSerial, Truncation};
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// dmx.set_truncation(Some(Truncation { last_channel: 40, full_frame_interval: 100 })).unwrap();
+    /// dmx.set_packet_time(Duration::from_millis(11));
+    /// # }
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// Returns a [`DMXChannelValidityError`] if the last channel is not valid.
+    /// 
+    pub fn set_truncation(&mut self, truncation: Option<Truncation>) -> Result<(), DMXChannelValidityError> {
+        if let Some(truncation) = &truncation {
+            check_valid_channel(truncation.last_channel)?;
+        }
+        // RwLock can be unwrapped here
+        *self.truncation.write().unwrap() = truncation;
+        Ok(())
+    }
+
+    /// Returns the current [`Truncation`].
+    /// 
+    pub fn truncation(&self) -> Option<Truncation> {
+        // RwLock can be unwrapped here
+        *self.truncation.read().unwrap()
+    }
+
+    /// Sets the [`IdlePolicy`], which fades the output to a look after a while without channel changes. `None` disables it *(default)*.
+    /// 
+    /// The next change restores the output immediately. Parked channels keep their value.
+    /// In **sync mode**, the fade only progresses with every [`DMXSerial::update()`].
+    /// 
+    /// # Example
+    /// 
+    /// Blackout after 10 minutes:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::{DMXSerial, IdlePolicy};
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// dmx.set_idle_policy(Some(IdlePolicy::blackout(Duration::from_secs(600), Duration::from_secs(5))));
+    /// # }
+    /// ```
+    /// 
+    pub fn set_idle_policy(&mut self, policy: Option<IdlePolicy>) {
+        // RwLock can be unwrapped here
+        *self.idle_policy.write().unwrap() = policy;
+    }
+
+    /// Returns the current [`IdlePolicy`].
+    /// 
+    pub fn idle_policy(&self) -> Option<IdlePolicy> {
+        // RwLock can be unwrapped here
+        self.idle_policy.read().unwrap().clone()
+    }
+
+    /// Returns `true` if the output is fading or has faded to the look of the [`IdlePolicy`].
+    /// 
+    pub fn is_idle(&self) -> bool {
+        // RwLock can be unwrapped here
+        *self.is_idle.read().unwrap()
+    }
+
+    /// Submits a `universe`, which should be reached at the given time. The agent interpolates the channels between the keyframes.
+    /// 
+    /// Useful for content at another rate than the packets *(e.g. 30 Hz from the network, while the packets are sent at 44 Hz)*.
+    /// Submit the keyframes with a small delay *(e.g. one network frame)*, so the next keyframe is already known when the previous one is reached.
+    /// 
+    /// The keyframes replace the set values as soon as the first one is due, the last one is held until [`DMXSerial::clear_keyframes`] is called.
+    /// The merging, the [transforms] and the parked channels still apply.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::{DMXSerial, DMX_CHANNELS};
+    /// use std::time::{Duration, Instant};
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let delay = Duration::from_millis(33);
+    ///     loop {
+    ///         let universe = [255; DMX_CHANNELS]; // e.g. received from the network
+    ///         dmx.submit_keyframe(universe, Instant::now() + delay);
+    ///         std::thread::sleep(delay);
+    ///     }
+    /// }
+    /// ```
+    /// 
+    /// [transforms]: DMXSerial::add_transform
+    /// 
+    pub fn submit_keyframe(&mut self, universe: [u8; DMX_CHANNELS], at: time::Instant) {
+        // RwLock can be unwrapped here
+        self.keyframes.write().unwrap().insert(universe, at);
+    }
+
+    /// Removes all keyframes, so the set values are sent again. See [`DMXSerial::submit_keyframe`].
+    /// 
+    pub fn clear_keyframes(&mut self) {
+        // RwLock can be unwrapped here
+        self.keyframes.write().unwrap().clear();
+    }
+
+    /// Fades from the current output to the `target` over the `duration` with the given [`Easing`]. The `target` becomes the set values right away.
+    /// 
+    /// The fade is calculated by the agent, so it runs smoothly at the packet rate. See [`DMXSerial::crossfade_with`] for snapping some channels.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::{DMXSerial, Easing};
+    /// use std::time::Duration;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     dmx.crossfade_to([255; 512], Duration::from_secs(3), Easing::EaseInOut);
+    /// }
+    /// ```
+    /// 
+    pub fn crossfade_to(&mut self, target: [u8; DMX_CHANNELS], duration: time::Duration, easing: Easing) {
+        self.crossfade_with(target, &Crossfade::new(duration, easing));
+    }
+
+    /// Does the same as [`DMXSerial::crossfade_to`] with the given [`Crossfade`] settings, e.g. for split fade times or for snapping the channels which shouldn't fade.
+    /// 
+    /// An unfinished crossfade is replaced, starting from its current level. Changes of the set values during the fade are faded to as well.
+    /// The merging, the [transforms] and the parked channels still apply, keyframes take precedence.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::{Attribute, Crossfade, DMXSerial, Easing, Fixture};
+    /// use std::time::Duration;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let spot = Fixture::new("Spot", 1, vec![Attribute::Dimmer, Attribute::Other]).unwrap();
+    ///     // The dimmer fades, the gobo wheel snaps
+    ///     let crossfade = Crossfade::new(Duration::from_secs(3), Easing::Linear).snap_fixture(&spot);
+    ///     dmx.crossfade_with([255; 512], &crossfade);
+    /// }
+    /// ```
+    /// 
+    /// [transforms]: DMXSerial::add_transform
+    /// 
+    pub fn crossfade_with(&mut self, target: [u8; DMX_CHANNELS], crossfade: &Crossfade) {
+        let now = self.options.clock.now();
+        let mut from = self.get_channels();
+        // RwLock can be unwrapped here
+        if let Some(active) = self.crossfade.read().unwrap().as_ref() {
+            active.apply(now, &mut from);
+        }
+        *self.crossfade.write().unwrap() = (!crossfade.total_time().is_zero()).then(|| ActiveCrossfade::new(from, crossfade, now));
+        self.set_channels(target);
+    }
+
+    /// Returns `true` while a crossfade is running. See [`DMXSerial::crossfade_to`].
+    /// 
+    pub fn is_crossfading(&self) -> bool {
+        // RwLock can be unwrapped here
+        self.crossfade.read().unwrap().as_ref().is_some_and(|fade| !fade.is_finished(self.options.clock.now()))
+    }
+
+    /// Returns the number of keyframes which are still needed for the interpolation.
+    /// 
+    pub fn pending_keyframes(&self) -> usize {
+        // RwLock can be unwrapped here
+        self.keyframes.read().unwrap().len()
+    }
+
+    /// Sets a callback which renders the channel values right before every packet, and the frame rate the packets are sent with.
+    /// 
+    /// The callback runs on the agent thread, so generative content *(e.g. effects or pixel mapping)* is computed exactly once per frame.
+    /// It gets the [`FrameContext`] with the timing of the frame and the set values, which it can change. The result is sent like set values, so the merging, the [transforms] and the parked channels still apply.
+    /// 
+    /// The `fps` are clamped to the range the **DMX** timing allows and replace the [packet time]. Only one callback can be set, a new one replaces the old one.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     dmx.set_render_callback(40, |frame, channels| {
+    ///         // A slow pulse on channel 1
+    ///         let phase = (frame.elapsed.as_secs_f32() * std::f32::consts::PI).sin();
+    ///         channels[0] = (127.5 + 127.5 * phase) as u8;
+    ///     });
+    /// }
+    /// ```
+    /// 
+    /// [transforms]: DMXSerial::add_transform
+    /// [packet time]: DMXSerial::set_packet_time
+    /// 
+    pub fn set_render_callback<F>(&mut self, fps: u32, callback: F)
+    where
+        F: FnMut(&FrameContext, &mut [u8; DMX_CHANNELS]) + Send + 'static,
+    {
+        let packet_time = (time::Duration::from_secs(1) / fps.max(1)).max(crate::codec::MIN_PACKET_TIME);
+        self.set_packet_time(packet_time);
+        // Mutex can be unwrapped here
+        *self.renderer.lock().unwrap() = Some(Renderer::new(Box::new(callback)));
+    }
+
+    /// Removes the render callback. The channel values stay as they are, the packet time is kept.
+    /// 
+    pub fn clear_render_callback(&mut self) {
+        // Mutex can be unwrapped here
+        *self.renderer.lock().unwrap() = None;
+    }
+
+    /// Ramps the output up to the set values over the `fade` time. The output is enabled by default.
+    /// 
+    /// Together with [`DMXSerial::disable_output`], this avoids hard jumps of the lights when an application takes or releases control.
+    /// The agent keeps running while the output is disabled. Parked channels are not affected.
+    /// An unfinished ramp is reversed from its current level.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// use std::time::Duration;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     dmx.disable_output(Duration::ZERO);
+    ///     dmx.set_channels([255; 512]);
+    ///     dmx.enable_output(Duration::from_secs(2));
+    /// }
+    /// ```
+    /// 
+    pub fn enable_output(&mut self, fade: time::Duration) {
+        // RwLock can be unwrapped here
+        self.output_ramp.write().unwrap().start(1.0, fade, self.options.clock.now());
+    }
+
+    /// Ramps the output down to `0` over the `fade` time. See [`DMXSerial::enable_output`].
+    /// 
+    pub fn disable_output(&mut self, fade: time::Duration) {
+        // RwLock can be unwrapped here
+        self.output_ramp.write().unwrap().start(0.0, fade, self.options.clock.now());
+    }
+
+    /// Returns `true` if the output is enabled or ramping up.
+    /// 
+    pub fn is_output_enabled(&self) -> bool {
+        // RwLock can be unwrapped here
+        self.output_ramp.read().unwrap().target() > 0.0
+    }
+
+    /// Returns the current level of the output ramp *(`0.0` = disabled, `1.0` = enabled)*.
+    /// 
+    pub fn output_level(&self) -> f32 {
+        // RwLock can be unwrapped here
+        self.output_ramp.read().unwrap().level(self.options.clock.now())
+    }
+
+    /// Sets the longest pause between two packets in **sync mode**. `None` disables it *(default)*.
+    /// 
+    /// Some receivers *(e.g. wireless transmitters)* stop their output if no packet arrives for a while.
+    /// If the application doesn't call [`DMXSerial::update`] in time, the agent sends the last values again as a keep-alive packet.
+    /// Every keep-alive packet is reported to the receivers of [`DMXSerial::subscribe_keep_alives`].
+    /// The deadline follows the [clock](DMXSerialBuilder::clock) of the interface.
+    /// 
+    /// Takes effect after the next packet.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// use std::time::Duration;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open_sync("/dev/ttyUSB0").unwrap();
+    ///     dmx.set_max_frame_interval(Some(Duration::from_millis(800)));
+    ///     dmx.update().unwrap();
+    /// }
+    /// ```
+    /// 
+    pub fn set_max_frame_interval(&mut self, interval: Option<time::Duration>) {
+        // RwLock can be unwrapped here
+        *self.max_frame_interval.write().unwrap() = interval;
+    }
+
+    /// Returns the longest pause between two packets in **sync mode**. See [`DMXSerial::set_max_frame_interval`].
+    /// 
+    pub fn max_frame_interval(&self) -> Option<time::Duration> {
+        // RwLock can be unwrapped here
+        *self.max_frame_interval.read().unwrap()
+    }
+
+    /// Returns a [`Receiver`] of the times at which keep-alive packets were sent. See [`DMXSerial::set_max_frame_interval`].
+    /// 
+    /// [`Receiver`]: mpsc::Receiver
+    /// 
+    pub fn subscribe_keep_alives(&self) -> mpsc::Receiver<time::Instant> {
+        let (tx, rx) = mpsc::channel();
+        // RwLock can be unwrapped here
+        self.keep_alive_subscribers.write().unwrap().push(tx);
+        rx
+    }
+
+    /// Returns a [`Receiver`] of every frame with the null start code after it was sent, together with the time it was sent at.
+    /// 
+    /// The frames contain the values as they were transmitted, after all stages like [parked channels](DMXSerial::park_channel) or the [strobe guard](DMXSerial::set_strobe_guard).
+    /// A [`Recorder`](crate::recording::Recorder) writes them to a recording.
+    /// 
+    /// [`Receiver`]: mpsc::Receiver
+    /// 
+    pub fn subscribe_frames(&self) -> mpsc::Receiver<(time::Instant, [u8; DMX_CHANNELS])> {
+        let (tx, rx) = mpsc::channel();
+        // RwLock can be unwrapped here
+        self.frame_subscribers.write().unwrap().push(tx);
+        rx
+    }
+
+    /// Returns `true` if the channel values have been changed since the last packet was sent.
+    /// 
+    /// # Example
+    /// 
+    /// Basic usage:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open_sync("COM3").unwrap();
+    /// dmx.set_channel(1, 255).unwrap();
+    /// assert!(dmx.is_dirty());
+    /// dmx.update().unwrap();
+    /// assert!(!dmx.is_dirty());
+    /// # }
+    /// ```
+    /// 
+    pub fn is_dirty(&self) -> bool {
+        self.get_channels() != self.last_sent.load()
+    }
+
+    /// Returns how many packets in a row have been sent with the same output.
+    /// 
+    /// It's `0` right after the output changed. The sent values are compared, so changes made by [transforms] and [parked] channels count as well.
+    /// This can be used for decisions based on the output activity, like dimming or powering down fixtures.
+    /// 
+    /// [transforms]: DMXSerial::add_transform
+    /// [parked]: DMXSerial::park_channel
+    /// 
+    pub fn frames_since_last_change(&self) -> u64 {
+        // RwLock can be unwrapped here
+        *self.unchanged_frames.read().unwrap()
+    }
+
+    /// Enables or disables the **coalescing** mode.
+    /// 
+    /// By default every write goes straight to the channel buffer, which the agent thread copies for every packet.
+    /// When a GUI sends thousands of writes per second, the agent may have to retry the copy many times, since it never copies a half finished write.
+    /// 
+    /// In coalescing mode, writes are queued and the agent applies all pending writes at once before the next packet.
+    /// The getters return the written values right away, but a write reaches the port up to one [packet time] later than without coalescing,
+    /// since it can't be picked up in the middle of building a packet.
+    /// 
+    /// Disabling the mode applies all pending writes immediately. The mode and the written values are shared with the [clones](DMXSerial::try_clone).
+    /// 
+    /// [packet time]: DMXSerial::set_packet_time
+    /// 
+    pub fn set_coalescing(&mut self, enabled: bool) {
+        // Mutex can be unwrapped here
+        let mut staged = self.staged.lock().unwrap();
+        if enabled == staged.is_some() {
+            return;
+        }
+        if enabled {
+            *staged = Some(self.channels.load());
+        } else {
+            self.pending_writes.apply(&self.channels);
+            *staged = None;
+        }
+    }
+
+    /// Returns `true` if the **coalescing** mode is enabled.
+    /// 
+    pub fn is_coalescing(&self) -> bool {
+        // Mutex can be unwrapped here
+        self.staged.lock().unwrap().is_some()
+    }
+
+    /// Resets all channels to `0`.
+    ///     
+    /// # Example
+    /// 
+    /// Basic usage:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::{DMXSerial, DMX_CHANNELS};
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// dmx.set_channels([255; DMX_CHANNELS]);
+    /// assert_eq!(dmx.get_channels(), [255; DMX_CHANNELS]);
+    /// dmx.reset_channels();
+    /// assert_eq!(dmx.get_channels(), [0; DMX_CHANNELS]);
+    /// # }
+    /// ```
+    /// 
+    pub fn reset_channels(&mut self) {
+        self.write_channels([0; DMX_CHANNELS], None);
+    }
+
+    /// Sets how many writes are recorded per channel in the **audit trail**. `0` disables it *(default)*.
+    /// 
+    /// The audit trail helps with finding out which part of a larger application set a channel.
+    /// Writes made with the tagged functions *(e.g. [`DMXSerial::set_channel_tagged`])* are recorded with their origin.
+    /// 
+    /// Changing the depth clears the recorded writes.
+    /// 
+    /// # Example
+    /// 
+    /// Basic usage:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// dmx.set_audit_depth(10);
+    /// dmx.set_channel_tagged(37, 255, "ui").unwrap();
+    /// 
+    /// for write in dmx.channel_history(37).unwrap() {
+    ///     println!("{:?}: {} by {:?}", write.time, write.value, write.tag);
+    /// }
+    /// # }
+    /// ```
+    /// 
+    pub fn set_audit_depth(&mut self, depth: usize) {
+        self.audit = (depth > 0).then(|| AuditTrail::new(depth));
+    }
+
+    /// Returns how many writes are recorded per channel in the **audit trail**.
+    /// 
+    pub fn audit_depth(&self) -> usize {
+        self.audit.as_ref().map_or(0, |audit| audit.depth())
+    }
+
+    /// Returns the recorded writes of the specified [`channel`], oldest first.
+    /// 
+    /// Empty if the **audit trail** is disabled. See [`DMXSerial::set_audit_depth`].
+    /// 
+    /// [`channel`]: usize
+    /// 
+    pub fn channel_history(&self, channel: usize) -> Result<Vec<ChannelWrite>, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        Ok(self.audit.as_ref().map_or_else(Vec::new, |audit| audit.history(channel - 1)))
+    }
+
+    /// Returns a [`Receiver`] of every changed channel value, so UIs and loggers can react to changes instead of comparing the channels.
+    /// 
+    /// Only writes which change a value are sent, in the order they were made by all handles of the interface.
+    /// Writes made with the tagged functions *(e.g. [`DMXSerial::set_channel_tagged`])* carry their origin as the `source`.
+    /// The values sent by the agent *(e.g. after the [transforms])* are not included. Dropping the [`Receiver`] ends the subscription.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// 
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let changes = dmx.subscribe_changes();
+    ///     dmx.set_channel_tagged(1, 255, "ui").unwrap();
+    ///     for change in changes.try_iter() {
+    ///         println!("{}: {} -> {} by {:?}", change.channel, change.old, change.new, change.source);
+    ///     }
+    /// }
+    /// ```
+    /// 
+    /// [`Receiver`]: mpsc::Receiver
+    /// [transforms]: DMXSerial::add_transform
+    /// 
+    pub fn subscribe_changes(&self) -> mpsc::Receiver<ChannelChange> {
+        self.change_subscribers.subscribe()
+    }
+
+    /// Parks the specified [`channel`] at the given [`value`].
+    /// 
+    /// A parked channel is always sent with its parked value, regardless of the set values and the [transforms], until it's unparked.
+    /// This is useful for keeping e.g. house lights or smoke machines at a fixed level.
+    /// 
+    /// [`channel`]: usize
+    /// [`value`]: u8
+    /// [transforms]: DMXSerial::add_transform
+    /// 
+    /// # Example
+    /// 
+    /// Basic usage:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// dmx.park_channel(512, 255).unwrap(); // house lights stay on
+    /// dmx.set_channels([0; 512]);
+    /// dmx.unpark_channel(512).unwrap();
+    /// # }
+    /// ```
+    /// 
+    pub fn park_channel(&mut self, channel: usize, value: u8) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        // RwLock can be unwrapped here
+        self.parked.write().unwrap()[channel - 1] = Some(value);
+        Ok(())
+    }
+
+    /// Unparks the specified [`channel`], so it's sent with its set value again.
+    /// 
+    /// [`channel`]: usize
+    /// 
+    pub fn unpark_channel(&mut self, channel: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        // RwLock can be unwrapped here
+        self.parked.write().unwrap()[channel - 1] = None;
+        Ok(())
+    }
+
+    /// Unparks all channels.
+    /// 
+    pub fn unpark_all(&mut self) {
+        // RwLock can be unwrapped here
+        self.parked.write().unwrap().fill(None);
+    }
+
+    /// Returns the parked [`value`] of the specified [`channel`], or `None` if it isn't parked.
+    /// 
+    /// [`channel`]: usize
+    /// [`value`]: u8
+    /// 
+    pub fn parked_value(&self, channel: usize) -> Result<Option<u8>, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        // RwLock can be unwrapped here
+        Ok(self.parked.read().unwrap()[channel - 1])
+    }
+
+    /// Sets the [`SlewLimit`], which limits how fast the channels may change. Replaces a previous limit.
+    /// 
+    /// It's applied to every packet after the [transforms] and the parked channels.
+    /// 
+    /// [transforms]: DMXSerial::add_transform
+    /// 
+    pub fn set_slew_limit(&mut self, limit: SlewLimit) {
+        // RwLock can be unwrapped here
+        *self.slew_limit.write().unwrap() = Some(limit);
+    }
+
+    /// Removes the [`SlewLimit`].
+    /// 
+    pub fn clear_slew_limit(&mut self) {
+        // RwLock can be unwrapped here
+        *self.slew_limit.write().unwrap() = None;
+    }
+
+    /// Returns the [`SlewLimit`], if one is set.
+    /// 
+    pub fn slew_limit(&self) -> Option<SlewLimit> {
+        // RwLock can be unwrapped here
+        self.slew_limit.read().unwrap().clone()
+    }
+
+    /// Sets the [`StrobeGuard`], which holds the output while the rig flashes faster than allowed. Replaces a previous guard.
+    /// 
+    /// It's applied to every packet after the [`SlewLimit`] and to raw frames with the null start code.
+    /// 
+    pub fn set_strobe_guard(&mut self, guard: StrobeGuard) {
+        guard.reset();
+        // RwLock can be unwrapped here
+        *self.strobe_guard.write().unwrap() = Some(guard);
+    }
+
+    /// Removes the [`StrobeGuard`].
+    /// 
+    pub fn clear_strobe_guard(&mut self) {
+        // RwLock can be unwrapped here
+        *self.strobe_guard.write().unwrap() = None;
+    }
+
+    /// Returns the [`StrobeGuard`], if one is set.
+    /// 
+    pub fn strobe_guard(&self) -> Option<StrobeGuard> {
+        // RwLock can be unwrapped here
+        self.strobe_guard.read().unwrap().clone()
+    }
+
+    /// Sets the [`SafetyInterlock`], which forces the protected channels to `0` unless they're armed. Replaces a previous interlock.
+    /// 
+    /// It's applied to every packet after the [transforms] and the parked channels, so nothing else can bypass it.
+    /// Raw frames with the null start code *(from [`DMXSerial::send_raw_frame`], periodic frames and the [`DmxFrameWriter`](crate::DmxFrameWriter))* pass it as well.
+    /// 
+    /// [transforms]: DMXSerial::add_transform
+    /// 
+    pub fn set_interlock(&mut self, interlock: SafetyInterlock) {
+        // RwLock can be unwrapped here
+        *self.interlock.write().unwrap() = Some(interlock);
+    }
+
+    /// Removes the [`SafetyInterlock`].
+    /// 
+    pub fn clear_interlock(&mut self) {
+        // RwLock can be unwrapped here
+        *self.interlock.write().unwrap() = None;
+    }
+
+    /// Returns the [`SafetyInterlock`], if one is set.
+    /// 
+    pub fn interlock(&self) -> Option<SafetyInterlock> {
+        // RwLock can be unwrapped here
+        self.interlock.read().unwrap().clone()
+    }
+
+    /// Enables or disables the **peak hold** mode.
+    /// 
+    /// In peak hold mode, the highest value which has been sent on every channel is recorded.
+    /// It's meant for diagnostics, like checking which channels a show uses or finding stray writes.
+    /// 
+    /// # Example
+    /// 
+    /// Basic usage:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// dmx.set_peak_hold(true);
+    /// // ... run the show
+    /// let unused = dmx.peaks().iter().filter(|peak| **peak == 0).count();
+    /// println!("{} channels were never used", unused);
+    /// # }
+    /// ```
+    /// 
+    pub fn set_peak_hold(&mut self, enabled: bool) {
+        // RwLock can be unwrapped here
+        *self.peak_hold.write().unwrap() = enabled;
+    }
+
+    /// Returns `true` if the **peak hold** mode is enabled.
+    /// 
+    pub fn is_peak_hold(&self) -> bool {
+        // RwLock can be unwrapped here
+        *self.peak_hold.read().unwrap()
+    }
+
+    /// Returns the highest sent [`value`] of every channel since the last [reset].
+    /// 
+    /// Only packets sent in **peak hold** mode are recorded. See [`DMXSerial::set_peak_hold`].
+    /// 
+    /// [`value`]: u8
+    /// [reset]: DMXSerial::reset_peaks
+    /// 
+    pub fn peaks(&self) -> [u8; DMX_CHANNELS] {
+        // RwLock can be unwrapped here
+        *self.peaks.read().unwrap()
+    }
+
+    /// Resets the recorded peaks to `0`.
+    /// 
+    pub fn reset_peaks(&mut self) {
+        // RwLock can be unwrapped here
+        self.peaks.write().unwrap().fill(0);
+    }
+
+    /// Updates the DMX data.
+    /// 
+    /// Returns after the data has been sent.
+    /// 
+    /// Works both in **sync** and **async** mode.
+    /// 
+    /// # Example
+    /// 
+    /// [Basic Usage]
+    /// 
+    /// [Basic Usage]: #example-1
+    /// 
+    pub fn update(&mut self) -> Result<(), DMXDisconnectionError> {
+        // Every update gets its own confirmation, so it can't return on the one of another packet
+        let (sent, sent_rec) = mpsc::sync_channel(1);
+        self.agent.send(AgentCommand::Update(Some(sent))).map_err(|_| DMXDisconnectionError)?;
+        sent_rec.recv().map_err(|_| DMXDisconnectionError)?;
+        Ok(())
+    }
+
+    /// Updates the DMX data but returns immediately.
+    /// 
+    /// Useless in **async** mode.
+    /// 
+    pub fn update_async(&self) -> Result<(), DMXDisconnectionError> {
+        self.agent.send(AgentCommand::Update(None)).map_err(|_| DMXDisconnectionError)?;
+        Ok(())
+    }
+
+    /// Sends the current channel values the given number of times and stops the output afterwards.
+    /// 
+    /// Meant for testing fixtures *(e.g. measuring how long they hold the last values)*. Returns once the last packet has been sent.
+    /// The interface stays in **sync mode** afterwards, so nothing is sent until the next [`DMXSerial::update`].
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    /// dmx.set_channels([255; 512]);
+    /// dmx.send_burst(10).unwrap();
+    /// // No more packets are sent from here on
+    /// # }
+    /// ```
+    /// 
+    pub fn send_burst(&mut self, frames: usize) -> Result<(), DMXDisconnectionError> {
+        self.set_sync();
+        for _ in 0..frames {
+            self.update()?;
+        }
+        Ok(())
+    }
+
+    /// Sends a single raw **DMX frame** in between the regular packets.
+    /// 
+    /// The `frame` consists of the start code followed by up to [`DMX_CHANNELS`] slots.
+    /// This allows sending packets with alternate start codes *(e.g. RDM, SIP or text packets)*.
+    /// 
+    /// Returns after the frame has been sent. In **sync** mode the frame is sent without an additional [`DMXSerial::update()`].
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an [`DMXFrameError`] if the frame is empty, too long or the port got disconnected.
+    /// 
+    /// # Example
+    /// 
+    /// Sending a text packet:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// # let dmx = DMXSerial::open("COM3").unwrap();
+    /// let mut frame = vec![0x17]; // ASCII text start code
+    /// frame.extend_from_slice(b"Hello DMX");
+    /// dmx.send_raw_frame(&frame).unwrap();
+    /// # }
+    /// ```
+    /// 
+    pub fn send_raw_frame(&self, frame: &[u8]) -> Result<(), DMXFrameError> {
+        crate::codec::decode_frame(frame)?;
+        self.send_frame(frame.to_vec())?;
+        Ok(())
+    }
+
+    /// Registers a raw **DMX frame** which is sent after every `interval` regular packets.
+    /// 
+    /// The `frame` consists of the start code followed by up to [`DMX_CHANNELS`] slots *(e.g. an E1.11 SIP or a text packet)*.
+    /// Each periodic frame takes up one [packet time], so the timing of the regular packets stays the same.
+    /// 
+    /// Returns a [`PeriodicFrameId`] which can be used to remove the frame again.
+    /// 
+    /// [packet time]: DMXSerial::set_packet_time
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an [`DMXFrameError`] if the frame is empty, too long or the `interval` is `0`.
+    /// 
+    /// # Example
+    /// 
+    /// Sending a text packet every 100 packets:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// let mut frame = vec![0x17]; // ASCII text start code
+    /// frame.extend_from_slice(b"Hello DMX");
+    /// let id = dmx.add_periodic_frame(&frame, 100).unwrap();
+    /// // ...
+    /// dmx.remove_periodic_frame(id);
+    /// # }
+    /// ```
+    /// 
+    pub fn add_periodic_frame(&mut self, frame: &[u8], interval: usize) -> Result<PeriodicFrameId, DMXFrameError> {
+        if frame.is_empty() {
+            return Err(DMXFrameError::Empty);
+        }
+        if frame.len() > DMX_CHANNELS + 1 {
+            return Err(DMXFrameError::TooLong);
+        }
+        if interval == 0 {
+            return Err(DMXFrameError::ZeroInterval);
+        }
+        let id = PeriodicFrameId(self.next_periodic_id.fetch_add(1, Ordering::Relaxed));
+        // RwLock can be unwrapped here
+        self.periodic_frames.write().unwrap().push(PeriodicFrame {
+            id,
+            frame: frame.to_vec(),
+            interval: interval as u64,
+        });
+        Ok(id)
+    }
+
+    /// Removes a periodic frame registered with [`DMXSerial::add_periodic_frame()`].
+    /// 
+    /// Returns `false` if there was no frame with the given [`PeriodicFrameId`].
+    /// 
+    pub fn remove_periodic_frame(&mut self, id: PeriodicFrameId) -> bool {
+        // RwLock can be unwrapped here
+        let mut periodic_frames = self.periodic_frames.write().unwrap();
+        let len = periodic_frames.len();
+        periodic_frames.retain(|periodic| periodic.id != id);
+        periodic_frames.len() != len
+    }
+
+    /// Adds a [`FrameTransform`] to the end of the transmit pipeline.
+    /// 
+    /// The transforms modify the channel values of every regular packet before it's sent, in the order they were added.
+    /// Raw frames aren't affected.
+    /// 
+    /// Returns a [`TransformId`] which can be used to remove the transform again.
+    /// 
+    /// # Example
+    /// 
+    /// Master dimmer with a dimmer curve:
+    /// 
+    /// ```no_run
+    /// # use open_dmx::{DMXSerial, MasterDimmer, Curve};
+    /// # fn main() {
+    /// # let mut dmx = DMXSerial::open("COM3").unwrap();
+    /// let master = MasterDimmer::all_channels(255);
+    /// dmx.add_transform(Box::new(master.clone()));
+    /// dmx.add_transform(Box::new(Curve::gamma(2.2).unwrap()));
+    /// 
+    /// master.set_level(128); // all channels at half
+    /// # }
+    /// ```
+    /// 
+    pub fn add_transform(&mut self, transform: Box<dyn FrameTransform>) -> TransformId {
+        let id = TransformId(self.next_transform_id.fetch_add(1, Ordering::Relaxed));
+        // RwLock can be unwrapped here
+        self.transforms.write().unwrap().push(TransformStage { id, transform });
+        id
+    }
+
+    /// Removes a transform added with [`DMXSerial::add_transform()`].
+    /// 
+    /// Returns `false` if there was no transform with the given [`TransformId`].
+    /// 
+    pub fn remove_transform(&mut self, id: TransformId) -> bool {
+        // RwLock can be unwrapped here
+        let mut transforms = self.transforms.write().unwrap();
+        let len = transforms.len();
+        transforms.retain(|stage| stage.id != id);
+        transforms.len() != len
+    }
+
+    // Queues a complete frame (start code + slots) and returns once the agent has sent it
+    pub(crate) fn send_frame(&self, frame: Vec<u8>) -> Result<(), DMXDisconnectionError> {
+        let (sent, sent_rec) = mpsc::sync_channel(1);
+        self.agent.send(AgentCommand::Frame(frame, sent)).map_err(|_| DMXDisconnectionError)?;
+        sent_rec.recv().map_err(|_| DMXDisconnectionError)?;
+        Ok(())
+    }
+
+    /// Sets the DMX mode to **sync**.
+    /// 
+    pub fn set_sync(&mut self) {
+        // RwLock can be unwrapped here
+        *self.is_sync.write().unwrap() = true;
+    }
+
+    /// Sets the DMX mode to **async**.
+    ///     
+    pub fn set_async(&mut self) {
+        // RwLock can be unwrapped here
+        *self.is_sync.write().unwrap() = false;
+    }
+
+    /// Returns `true` if the DMX mode is **sync**.
+    ///     
     pub fn is_sync(&self) -> bool {
         // RwLock can be unwrapped here
-        self.is_sync.read().unwrap().clone()
+        *self.is_sync.read().unwrap()
     }
 
     /// Returns `true` if the DMX mode is **async**.
@@ -370,6 +2033,9 @@ impl DMXSerial {
     /// 
     /// Some devices may require a longer time between two **packets**.
     /// 
+    /// In **async** mode, the packets are sent on a fixed schedule, so a packet which started late is followed by a shorter pause and the average rate stays the same.
+    /// If a packet is late by more than the packet time, the schedule starts over instead of catching up.
+    /// 
     /// See the [DMX512-Standard] for timing.
     /// 
     /// [DMX512-Standard]: https://www.erwinrol.com/page/articles/dmx512/
@@ -384,7 +2050,7 @@ impl DMXSerial {
     /// 
     pub fn get_packet_time(&self) -> time::Duration {
         // RwLock can be unwrapped here
-        self.min_time_break_to_break.read().unwrap().clone()
+        *self.min_time_break_to_break.read().unwrap()
     }
 
     /// Checks if the [`DMXSerial`] device is still connected.
@@ -393,71 +2059,442 @@ impl DMXSerial {
     /// 
     /// Basic usage:
     /// 
-    /// ```
+    /// ```no_run
     /// # use open_dmx::DMXSerial;
     /// # fn main() {
     /// # let mut dmx = DMXSerial::open("COM3").unwrap();
     /// assert!(dmx.check_agent().is_ok()); // If not, the device got disconnected
     /// # }
     pub fn check_agent(&self) -> Result<(), DMXDisconnectionError> {
-        if let Err(mpsc::TryRecvError::Disconnected) = self.agent.rx.try_recv() {
+        if self.agent_running.strong_count() == 0 {
             return Err(DMXDisconnectionError);
         }
         Ok(())
     }
+
+    /// Returns `false` if the interface was opened with [`DMXSerialBuilder::open_with_transport`], so [`DMXSerial::reopen`] can't open it again.
+    /// 
+    pub fn can_reopen(&self) -> bool {
+        !matches!(self.options.source, TransportSource::Fixed)
+    }
+
+    // Lets the agent check that the transport is still connected without sending anything, it stops if it isn't
+    pub(crate) fn probe(&self) -> Result<(), DMXDisconnectionError> {
+        let (done, done_rec) = mpsc::sync_channel(1);
+        self.agent.send(AgentCommand::Probe(done)).map_err(|_| DMXDisconnectionError)?;
+        done_rec.recv().map_err(|_| DMXDisconnectionError)
+    }
+
+    /// Returns a [`WeakDMXHandle`], which can check if the interface is alive and read the channels without keeping the agent running.
+    /// 
+    /// Useful for monitoring UIs, which must not prevent the shutdown of the interface.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use open_dmx::DMXSerial;
+    /// 
+    /// fn main() {
+    ///     let dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let weak = dmx.downgrade();
+    ///     assert!(weak.is_alive());
+    ///     drop(dmx);
+    ///     assert!(weak.get_channels().is_none());
+    /// }
+    /// ```
+    /// 
+    pub fn downgrade(&self) -> WeakDMXHandle {
+        WeakDMXHandle {
+            name: self.name.clone(),
+            channels: Arc::downgrade(&self.channels),
+            agent_running: self.agent_running.clone(),
+        }
+    }
+
+    pub(crate) fn packet_timing(&self) -> PacketTiming {
+        // RwLock can be unwrapped here
+        *self.packet_timing.read().unwrap()
+    }
+
+    pub(crate) fn reset_packet_timing(&self) {
+        // RwLock can be unwrapped here
+        self.packet_timing.write().unwrap().reset();
+    }
+
+    /// Combines the state of the interface into a single report, e.g. for the health endpoint of a supervisor.
+    /// 
+    /// The frames and errors are counted since the interface was opened, including all [reopens].
+    /// 
+    /// [reopens]: DMXSerial::reopen
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// # let dmx = DMXSerial::open("COM3").unwrap();
+    /// let health = dmx.health();
+    /// if !health.is_healthy(Duration::from_secs(1)) {
+    ///     eprintln!("{:?}", health);
+    /// }
+    /// # }
+    /// ```
+    /// 
+    pub fn health(&self) -> HealthReport {
+        let timing = self.packet_timing();
+        HealthReport {
+            is_alive: self.check_agent().is_ok(),
+            last_frame_age: timing.last_frame.map(|last_frame| last_frame.elapsed()),
+            frames_sent: self.previous_frames + timing.frames,
+            // RwLock can be unwrapped here
+            errors: self.previous_errors + self.port_errors.read().unwrap().len(),
+            reconnects: self.reconnects,
+        }
+    }
 }
 
-#[derive(Debug)]
-struct AgentCommunication<T> {
-    pub tx: mpsc::Sender<T>,
-    pub rx: mpsc::Receiver<T>,
+/// A handle to a [DMXSerial], which doesn't keep the interface alive. Returned by [`DMXSerial::downgrade`].
+/// 
+#[derive(Debug, Clone)]
+pub struct WeakDMXHandle {
+    name: String,
+    channels: Weak<ChannelBuffer>,
+    agent_running: Weak<()>,
 }
 
-impl<T> AgentCommunication<T> {
-    pub fn new(tx: mpsc::Sender<T>, rx: mpsc::Receiver<T>) -> AgentCommunication<T> {
-        AgentCommunication {
-            tx,
-            rx,
-        }
+impl WeakDMXHandle {
+    /// Gets the name of the Path on which the [DMXSerial] was opened.
+    /// 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` while the agent of the [DMXSerial] is running.
+    /// 
+    /// It stops once every handle of the interface is dropped or the port got disconnected.
+    /// 
+    pub fn is_alive(&self) -> bool {
+        self.agent_running.strong_count() > 0
+    }
+
+    /// Returns the values of all channels, or `None` if the [DMXSerial] has been dropped.
+    /// 
+    pub fn get_channels(&self) -> Option<[u8; DMX_CHANNELS]> {
+        self.channels.upgrade().map(|channels| channels.load())
+    }
+
+    /// Returns the value of the specified [`channel`], or `None` if the [DMXSerial] has been dropped.
+    /// 
+    /// [`channel`]: usize
+    /// 
+    pub fn get_channel(&self, channel: usize) -> Result<Option<u8>, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        Ok(self.get_channels().map(|channels| channels[channel - 1]))
+    }
+}
+
+/// The state of a [DMXSerial], returned by [`DMXSerial::health`].
+/// 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// `false` if the interface stopped and has to be [reopened](DMXSerial::reopen).
+    pub is_alive: bool,
+    /// Time since the last frame started, `None` if nothing was sent yet.
+    pub last_frame_age: Option<time::Duration>,
+    /// Number of frames sent since the interface was opened, including all [reopens](DMXSerial::reopen).
+    pub frames_sent: u64,
+    /// Number of errors which stopped the interface.
+    pub errors: usize,
+    /// Number of successful [reopens](DMXSerial::reopen).
+    pub reconnects: u64,
+}
+
+impl HealthReport {
+    /// Returns `true` if the interface is alive and the last frame isn't older than `max_frame_age`.
+    /// 
+    /// In **sync mode**, frames are only sent on [updates](DMXSerial::update), so the age depends on the application.
+    /// 
+    pub fn is_healthy(&self, max_frame_age: time::Duration) -> bool {
+        self.is_alive && self.last_frame_age.is_some_and(|age| age <= max_frame_age)
+    }
+}
+
+/// The state of the port, returned by [`DMXSerial::port_diagnostics`].
+/// 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortDiagnostics {
+    /// The [`LineSettings`] used while sending **DMX data**.
+    pub requested: LineSettings,
+    /// The [`LineSettings`] read back from the driver, or why they couldn't be read.
+    pub applied: Result<LineSettings, String>,
+    /// Errors reported by the driver, which stopped the interface.
+    pub errors: Vec<String>,
+}
+
+impl PortDiagnostics {
+    /// Returns `true` if the driver applied the requested settings.
+    /// 
+    pub fn is_applied(&self) -> bool {
+        self.applied.as_ref().is_ok_and(|applied| *applied == self.requested)
     }
 }
 
+/// Sends shorter packets with only the channels up to `last_channel`. See [`DMXSerial::set_truncation`].
+/// 
+/// Every `full_frame_interval` packets, a full frame with all channels is sent. With an interval of `0`, only short packets are sent.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncation {
+    pub last_channel: usize,
+    pub full_frame_interval: usize,
+}
+
+/// Identifies a frame registered with [`DMXSerial::add_periodic_frame()`].
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PeriodicFrameId(u64);
+
+#[derive(Debug)]
+struct PeriodicFrame {
+    id: PeriodicFrameId,
+    frame: Vec<u8>,
+    interval: u64,
+}
+
+#[derive(Debug)]
+enum AgentCommand {
+    // Send the current channel values and notify the sender afterwards, if there is one
+    Update(Option<mpsc::SyncSender<()>>),
+    // Send the given frame once and notify the sender afterwards
+    Frame(Vec<u8>, mpsc::SyncSender<()>),
+    // Read back the settings applied by the driver
+    ReadLineSettings(mpsc::SyncSender<serialport::Result<LineSettings>>),
+    // Check the connection of the transport and notify the sender if it's still there
+    Probe(mpsc::SyncSender<()>),
+}
+
 struct DMXSerialAgent {
-    port: Box<dyn SerialPort>,
+    port: Box<dyn Transport>,
     min_b2b: ReadOnly<time::Duration>,
+    is_sync: ReadOnly<bool>,
+    line_settings: LineSettings,
+    break_mode: BreakMode,
+    break_time: time::Duration,
+    direction: DirectionControl,
+    direction_timing: DirectionTiming,
+    // Pin which switches the transceiver into transmit mode
+    #[cfg(target_os = "linux")]
+    de_pin: Option<crate::gpio::GpioPin>,
+    timing: ArcRwLock<PacketTiming>,
+    // Applied to every frame with the null start code, whichever way it was sent
+    strobe_guard: ReadOnly<Option<StrobeGuard>>,
+    interlock: ReadOnly<Option<SafetyInterlock>>,
+    clock: Arc<dyn Clock>,
+    // Slots of the last frame with the null start code, receivers keep them beyond the end of a shorter frame
+    last_slots: [u8; DMX_CHANNELS],
+    // Shared copy of the slots once they were sent
+    transmitted: Arc<ChannelBuffer>,
+    frame_subscribers: FrameSubscribers,
+    // Start of the next packet on the schedule
+    next_deadline: Option<time::Instant>,
+    paced: bool,
+    // Number of the last sent frame, recorded in the spans
+    #[cfg(feature = "tracing")]
+    sequence: u64,
+    #[cfg(feature = "metrics")]
+    port_name: String,
 }
 
 impl DMXSerialAgent {
 
-    pub fn open (port: &str, min_b2b: ReadOnly<time::Duration>) -> Result<DMXSerialAgent, serialport::Error> {
-        let port = serialport::new(port, 250000)
-        .data_bits(serialport::DataBits::Eight)
-        .stop_bits(serialport::StopBits::Two)
-        .parity(serialport::Parity::None)
-        .flow_control(serialport::FlowControl::None)
-        .open()?;
-        let dmx = DMXSerialAgent {
+    // Reads the settings it follows from the interface
+    pub fn open (options: &DMXSerialBuilder, port: Box<dyn Transport>, break_mode: BreakMode, interface: &DMXSerial) -> Result<DMXSerialAgent, serialport::Error> {
+        #[cfg(target_os = "linux")]
+        let de_pin = match options.direction {
+            DirectionControl::Gpio(pin) => Some(crate::gpio::GpioPin::open(pin)?),
+            _ => None,
+        };
+        #[cfg(not(target_os = "linux"))]
+        if let DirectionControl::Gpio(_) = options.direction {
+            return Err(serialport::Error::new(
+                serialport::ErrorKind::InvalidInput,
+                "GPIO direction control is only supported on Linux",
+            ));
+        }
+
+        let mut dmx = DMXSerialAgent {
             port,
-            min_b2b,
+            min_b2b: interface.min_time_break_to_break.read_only(),
+            is_sync: interface.is_sync.read_only(),
+            line_settings: options.line_settings,
+            break_mode,
+            break_time: options.break_time,
+            direction: options.direction,
+            direction_timing: options.direction_timing,
+            #[cfg(target_os = "linux")]
+            de_pin,
+            timing: interface.packet_timing.clone(),
+            strobe_guard: interface.strobe_guard.read_only(),
+            interlock: interface.interlock.read_only(),
+            clock: options.clock.clone(),
+            last_slots: [0; DMX_CHANNELS],
+            transmitted: Arc::clone(&interface.transmitted),
+            frame_subscribers: interface.frame_subscribers.clone(),
+            next_deadline: None,
+            paced: options.paced,
+            #[cfg(feature = "tracing")]
+            sequence: 0,
+            #[cfg(feature = "metrics")]
+            port_name: options.port.to_string_lossy().into_owned(),
         };
+        // Start out in receive mode
+        dmx.set_direction(false)?;
         Ok(dmx)
     }
 
+    fn send_break(&mut self) -> serialport::Result<()> {
+        match self.break_mode {
+            BreakMode::Signal => {
+                let start = time::Instant::now();
+                self.port.set_break()?;
+                if self.paced {
+                    precise_sleep(self.break_time);
+                }
+                self.port.clear_break()?;
+                // RwLock can be unwrapped here
+                self.timing.write().unwrap().record_break(start.elapsed());
+            },
+            BreakMode::Baud(settings) => {
+                self.port.apply_line_settings(settings)?;
+                self.send_data(&[0])?;
+                // Wait until the break byte has left the port before switching back
+                self.port.flush()?;
+                self.port.apply_line_settings(self.line_settings)?;
+            },
+        }
+        Ok(())
+    }
+    fn set_direction(&mut self, transmit: bool) -> serialport::Result<()> {
+        match self.direction {
+            DirectionControl::None => {},
+            #[cfg(target_os = "linux")]
+            DirectionControl::Gpio(_) => {
+                if let Some(pin) = &mut self.de_pin {
+                    pin.set(transmit)?;
+                }
+            },
+            #[cfg(not(target_os = "linux"))]
+            DirectionControl::Gpio(_) => {},
+            DirectionControl::Rts { inverted } => self.port.write_request_to_send(transmit != inverted)?,
+            DirectionControl::Dtr { inverted } => self.port.write_data_terminal_ready(transmit != inverted)?,
+        }
+        Ok(())
+    }
+
+    fn set_transmit(&mut self, transmit: bool) -> serialport::Result<()> {
+        if self.direction == DirectionControl::None {
+            return Ok(());
+        }
+        if transmit {
+            self.set_direction(true)?;
+            thread::sleep(self.direction_timing.lead);
+        } else {
+            // Wait until the last byte has left the port before releasing the line
+            self.port.flush()?;
+            thread::sleep(self.direction_timing.lag);
+            self.set_direction(false)?;
+        }
+        Ok(())
+    }
+
     fn send_data(&mut self, data: &[u8]) -> serialport::Result<()> {
-        self.port.write(data)?;
+        self.port.write_all(data)?;
         Ok(())
     }
     
-    pub fn send_dmx_packet(&mut self, channels: [u8; DMX_CHANNELS]) -> serialport::Result<()> {
-        let start = time::Instant::now();
-        self.port.set_break()?;
-        thread::sleep(TIME_BREAK_TO_DATA);
-        self.port.clear_break()?;
+    // Sends the first `slots` channels, the safety stages are applied to all of them
+    pub fn send_dmx_packet(&mut self, channels: &mut [u8; DMX_CHANNELS], slots: usize) -> serialport::Result<()> {
+        self.apply_safety(channels);
         let mut prefixed_data = [0; 513];// 1 start byte + 512 channels
-        prefixed_data[1..].copy_from_slice(&channels);
-        self.send_data(&prefixed_data)?;
+        prefixed_data[1..=slots].copy_from_slice(&channels[..slots]);
+        self.transmit(&prefixed_data[..=slots])?;
+        self.transmitted.write().set_all(&self.last_slots);
+        // RwLock can be unwrapped here
+        if !self.frame_subscribers.read().unwrap().is_empty() {
+            let now = self.clock.now();
+            let slots = self.last_slots;
+            self.frame_subscribers.write().unwrap().retain(|subscriber| subscriber.send((now, slots)).is_ok());
+        }
+        Ok(())
+    }
 
-        thread::sleep(self.min_b2b.read().unwrap().saturating_sub(start.elapsed()));
+    // Sends a raw frame, frames with the null start code pass the safety stages like regular packets
+    pub fn send_frame(&mut self, frame: &[u8]) -> serialport::Result<()> {
+        if frame.first() != Some(&crate::codec::START_CODE_DMX) {
+            return self.transmit(frame);
+        }
+        let slots = frame.len() - 1;
+        let mut channels = self.last_slots;
+        channels[..slots].copy_from_slice(&frame[1..]);
+        self.send_dmx_packet(&mut channels, slots)
+    }
+
+    fn apply_safety(&mut self, channels: &mut [u8; DMX_CHANNELS]) {
+        // RwLock can be unwrapped here
+        if let Some(strobe_guard) = self.strobe_guard.read().unwrap().as_ref() {
+            strobe_guard.apply(channels, self.clock.now());
+        }
+        if let Some(interlock) = self.interlock.read().unwrap().as_ref() {
+            interlock.apply(channels);
+        }
+        self.last_slots = *channels;
+    }
+
+    fn transmit(&mut self, frame: &[u8]) -> serialport::Result<()> {
+        let start = time::Instant::now();
+        // RwLock can be unwrapped here
+        let _interval = self.timing.write().unwrap().record_start(start);
+        #[cfg(feature = "metrics")]
+        crate::telemetry::frame_sent(&self.port_name, frame.len(), _interval);
+        #[cfg(feature = "tracing")]
+        let span = {
+            self.sequence = self.sequence.wrapping_add(1);
+            tracing::trace_span!("dmx_frame", seq = self.sequence, bytes = frame.len(), break_us = tracing::field::Empty, frame_us = tracing::field::Empty)
+        };
+        #[cfg(feature = "tracing")]
+        let entered = span.enter();
+        if self.port.is_framed() {
+            self.send_data(frame)?;
+        } else {
+            self.set_transmit(true)?;
+            #[cfg(feature = "tracing")]
+            let break_start = time::Instant::now();
+            self.send_break()?;
+            #[cfg(feature = "tracing")]
+            span.record("break_us", break_start.elapsed().as_micros() as u64);
+            self.send_data(frame)?;
+            self.set_transmit(false)?;
+        }
+        #[cfg(feature = "tracing")]
+        {
+            span.record("frame_us", start.elapsed().as_micros() as u64);
+            // The wait for the next packet isn't part of the frame
+            drop(entered);
+        }
+
+        // Scheduled from the last deadline instead of the start of this frame, so long frames don't add up to drift.
+        // In sync mode the packets are sent on demand, so the packet time only serves as the minimum pause
+        let packet_time = *self.min_b2b.read().unwrap();
+        let deadline = match self.next_deadline {
+            // Fell behind by more than a packet, so the schedule starts over instead of catching up with a burst
+            Some(deadline) if start <= deadline + packet_time => deadline + packet_time,
+            _ => start + packet_time,
+        };
+        self.next_deadline = (!*self.is_sync.read().unwrap()).then_some(deadline);
+        if self.paced {
+            thread::sleep(deadline.saturating_duration_since(time::Instant::now()));
+        }
 
         Ok(())
     }
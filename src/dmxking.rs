@@ -0,0 +1,198 @@
+use crate::builder::LineSettings;
+use crate::transport::Transport;
+use crate::widget::{self, LABEL_SEND_DMX};
+use crate::DMX_CHANNELS;
+
+use serialport::{SerialPort, SerialPortType};
+
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+// USB IDs of the DMXKing widgets with the extended API (ultraDMX Pro and its successors)
+const VENDOR_ID: u16 = 0x16D0;
+const PRODUCT_ID: u16 = 0x0833;
+
+// Ignored by the virtual serial port
+const BAUD_RATE: u32 = 115_200;
+
+// Sends a frame to one output port of the extended API: 100 for port A and 101 for port B
+const LABEL_SEND_DMX_PORT: u8 = 100;
+
+/// A **DMXKing** widget with several DMX outputs *(e.g. the ultraDMX Pro)*, connected over one USB port.
+///
+/// The widget speaks the *Enttec DMX USB Pro* API with an extra message for each output port. Every port gets its own [`DmxKingOutput`], which is used as the [`Transport`] of one [DMXSerial], so each port sends its own universe.
+///
+/// [DMXSerial]: crate::DMXSerial
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, DmxKingWidget};
+///
+/// fn main() {
+///     let widget = DmxKingWidget::find().unwrap();
+///     let mut universe_a = DMXSerial::builder("ultraDMX A")
+///         .open_with_transport(widget.output(0).unwrap())
+///         .unwrap();
+///     let mut universe_b = DMXSerial::builder("ultraDMX B")
+///         .open_with_transport(widget.output(1).unwrap())
+///         .unwrap();
+///     universe_a.set_channels([255; 512]);
+///     universe_b.set_channels([128; 512]);
+/// }
+/// ```
+///
+#[derive(Clone)]
+pub struct DmxKingWidget {
+    port: Arc<Mutex<Box<dyn SerialPort>>>,
+}
+
+impl DmxKingWidget {
+    /// Highest number of output ports which can be addressed.
+    ///
+    /// The extended API of the ultraDMX Pro only defines the messages of its two outputs *(port A and B)*.
+    ///
+    pub const MAX_PORTS: u8 = 2;
+
+    /// Opens the first connected DMXKing widget, detected by its USB vendor and product id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of the kind [`io::ErrorKind::NotFound`] if there is no DMXKing widget.
+    ///
+    pub fn find() -> io::Result<DmxKingWidget> {
+        let port = serialport::available_ports()?.into_iter()
+            .find(|info| matches!(&info.port_type, SerialPortType::UsbPort(usb) if usb.vid == VENDOR_ID && usb.pid == PRODUCT_ID))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no DMXKing widget found"))?;
+        DmxKingWidget::open(&port.port_name)
+    }
+
+    /// Opens the DMXKing widget at the given serial port *(e.g. `/dev/ttyUSB0` or `COM3`)*.
+    ///
+    pub fn open(port: &str) -> io::Result<DmxKingWidget> {
+        let port = serialport::new(crate::port_path::normalize(std::path::Path::new(port))?, BAUD_RATE)
+            .flow_control(serialport::FlowControl::None)
+            .open()?;
+        Ok(DmxKingWidget::from_port(port))
+    }
+
+    /// Uses an already opened serial port.
+    ///
+    pub fn from_port(port: Box<dyn SerialPort>) -> DmxKingWidget {
+        DmxKingWidget { port: Arc::new(Mutex::new(port)) }
+    }
+
+    /// Returns the [`Transport`] of the given output port, counted from `0` *(port A of the ultraDMX Pro)*.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of the kind [`io::ErrorKind::InvalidInput`] if the port is not below [`DmxKingWidget::MAX_PORTS`].
+    ///
+    pub fn output(&self, port: u8) -> io::Result<DmxKingOutput> {
+        if port >= DmxKingWidget::MAX_PORTS {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("DMXKing widgets have at most {} ports", DmxKingWidget::MAX_PORTS)));
+        }
+        Ok(DmxKingOutput {
+            widget: self.clone(),
+            label: LABEL_SEND_DMX_PORT + port,
+        })
+    }
+
+    /// Returns a [`Transport`] which sends the same frames on all output ports, using the standard message of the Enttec API.
+    ///
+    pub fn all_outputs(&self) -> DmxKingOutput {
+        DmxKingOutput {
+            widget: self.clone(),
+            label: LABEL_SEND_DMX,
+        }
+    }
+}
+
+impl fmt::Debug for DmxKingWidget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.port.lock().map(|port| port.name()).unwrap_or_default();
+        f.debug_struct("DmxKingWidget").field("port", &name).finish()
+    }
+}
+
+/// The [`Transport`] of one output port of a [`DmxKingWidget`].
+///
+#[derive(Debug)]
+pub struct DmxKingOutput {
+    widget: DmxKingWidget,
+    label: u8,
+}
+
+impl DmxKingOutput {
+    /// Returns the output port, counted from `0`, or `None` if the frames are sent on all ports.
+    ///
+    pub fn port(&self) -> Option<u8> {
+        self.label.checked_sub(LABEL_SEND_DMX_PORT)
+    }
+}
+
+// Message of a frame (start code + channels) with the label of the output. `None` if it isn't a valid frame
+fn encode_frame(label: u8, frame: &[u8]) -> Option<Vec<u8>> {
+    (!frame.is_empty() && frame.len() <= DMX_CHANNELS + 1).then(|| widget::encode_message(label, frame))
+}
+
+impl io::Write for DmxKingOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The agent writes every frame at once
+        if let Some(message) = encode_frame(self.label, buf) {
+            // The messages of the ports must not be interleaved
+            let mut port = self.widget.port.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            port.write_all(&message)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.widget.port.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).flush()
+    }
+}
+
+impl Transport for DmxKingOutput {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_sent_with_the_label_of_the_port() {
+        // Start delimiter, label, length (LSB first), the frame and the end delimiter
+        assert_eq!(encode_frame(LABEL_SEND_DMX_PORT, &[0, 10, 20]).unwrap(), [0x7E, 100, 3, 0, 0, 10, 20, 0xE7]);
+        assert_eq!(encode_frame(LABEL_SEND_DMX_PORT + 1, &[0, 10]).unwrap()[1], 101);
+        assert_eq!(encode_frame(LABEL_SEND_DMX, &[0]).unwrap(), [0x7E, 6, 1, 0, 0, 0xE7]);
+
+        let message = encode_frame(LABEL_SEND_DMX_PORT, &[0; DMX_CHANNELS + 1]).unwrap();
+        assert_eq!(message[2..4], [0x01, 0x02]);
+        assert!(encode_frame(LABEL_SEND_DMX_PORT, &[]).is_none());
+        assert!(encode_frame(LABEL_SEND_DMX_PORT, &[0; DMX_CHANNELS + 2]).is_none());
+    }
+}
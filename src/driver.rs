@@ -0,0 +1,198 @@
+use crate::check_valid_channel;
+use crate::clock::Clock;
+use crate::codec::{BREAK_TIME, DEFAULT_PACKET_TIME};
+use crate::error::DMXChannelValidityError;
+use crate::DMX_CHANNELS;
+
+use std::sync::Arc;
+use std::time;
+
+/// The next step of a [`DMXDriver`], which has to be carried out by the caller.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action<'a> {
+    /// Sends the break signal for [`DMXDriver::break_time`] *(e.g. with [`Transport::set_break`] and [`Transport::clear_break`])*.
+    ///
+    /// [`Transport::set_break`]: crate::Transport::set_break
+    /// [`Transport::clear_break`]: crate::Transport::clear_break
+    SendBreak,
+    /// Writes the frame, starting with the start code.
+    SendData(&'a [u8]),
+    /// Nothing to do for the given [`Duration`](time::Duration). Calling [`DMXDriver::next_action`] earlier returns the remaining time.
+    Sleep(time::Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DriverState {
+    Break,
+    Data,
+}
+
+/// Sends **DMX data** from your own event loop instead of the thread of a [DMXSerial].
+///
+/// The driver doesn't do any I/O. Every call of [`DMXDriver::next_action`] returns the next [`Action`], which has to be carried out before calling it again.
+/// Useful for embedded ports or applications which already own a loop.
+///
+/// [DMXSerial]: crate::DMXSerial
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Action, DMXDriver};
+/// use std::thread;
+///
+/// fn main() {
+///     let mut driver = DMXDriver::new();
+///     driver.set_channel(1, 255).unwrap();
+///     loop {
+///         match driver.next_action() {
+///             Action::SendBreak => { /* hold the line low */ },
+///             Action::SendData(frame) => { /* write the frame */ },
+///             Action::Sleep(duration) => thread::sleep(duration),
+///         }
+///     }
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct DMXDriver {
+    channels: [u8; DMX_CHANNELS],
+    // Start code and channels of the packet which is being sent
+    frame: [u8; DMX_CHANNELS + 1],
+    packet_time: time::Duration,
+    state: DriverState,
+    packet_start: Option<time::Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl DMXDriver {
+    /// Creates a driver with all channels at `0` and the default packet time of 22.7 ms.
+    ///
+    pub fn new() -> DMXDriver {
+        DMXDriver::with_clock(crate::SystemClock)
+    }
+
+    /// Creates a driver like [`DMXDriver::new`], which takes the time from the given [`Clock`].
+    ///
+    /// With a [`ManualClock`](crate::ManualClock), the packet timing can be stepped through without sleeping.
+    ///
+    pub fn with_clock<C: Clock + 'static>(clock: C) -> DMXDriver {
+        DMXDriver {
+            channels: [0; DMX_CHANNELS],
+            frame: [0; DMX_CHANNELS + 1],
+            packet_time: DEFAULT_PACKET_TIME,
+            state: DriverState::Break,
+            packet_start: None,
+            clock: Arc::new(clock),
+        }
+    }
+
+    /// Returns the next [`Action`]. Changed channels are sent with the next packet.
+    ///
+    pub fn next_action(&mut self) -> Action<'_> {
+        match self.state {
+            DriverState::Break => {
+                let now = self.clock.now();
+                if let Some(start) = self.packet_start {
+                    let remaining = self.packet_time.saturating_sub(now.saturating_duration_since(start));
+                    if !remaining.is_zero() {
+                        return Action::Sleep(remaining);
+                    }
+                }
+                self.packet_start = Some(now);
+                self.state = DriverState::Data;
+                Action::SendBreak
+            },
+            DriverState::Data => {
+                // Copied here, so changes while the break is sent still make it into the packet
+                self.frame[1..].copy_from_slice(&self.channels);
+                self.state = DriverState::Break;
+                Action::SendData(&self.frame)
+            },
+        }
+    }
+
+    /// Sets the specified [`channel`] to the given [`value`].
+    ///
+    /// [`channel`]: usize
+    /// [`value`]: u8
+    ///
+    pub fn set_channel(&mut self, channel: usize, value: u8) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        self.channels[channel - 1] = value;
+        Ok(())
+    }
+
+    /// Sets all channels to the given values.
+    ///
+    pub fn set_channels(&mut self, channels: [u8; DMX_CHANNELS]) {
+        self.channels = channels;
+    }
+
+    /// Returns the value of the specified [`channel`].
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn get_channel(&self, channel: usize) -> Result<u8, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        Ok(self.channels[channel - 1])
+    }
+
+    /// Returns the values of all channels.
+    ///
+    pub fn get_channels(&self) -> [u8; DMX_CHANNELS] {
+        self.channels
+    }
+
+    /// Sets the minimum [`Duration`](time::Duration) between the start of two packets. See [`DMXSerial::set_packet_time`].
+    ///
+    /// [`DMXSerial::set_packet_time`]: crate::DMXSerial::set_packet_time
+    ///
+    pub fn set_packet_time(&mut self, time: time::Duration) {
+        self.packet_time = time;
+    }
+
+    /// Returns the minimum [`Duration`](time::Duration) between the start of two packets.
+    ///
+    pub fn packet_time(&self) -> time::Duration {
+        self.packet_time
+    }
+
+    /// Returns how long the break signal should be held, the same time the [DMXSerial] uses.
+    ///
+    /// [DMXSerial]: crate::DMXSerial
+    ///
+    pub fn break_time(&self) -> time::Duration {
+        BREAK_TIME
+    }
+}
+
+impl Default for DMXDriver {
+    fn default() -> Self {
+        DMXDriver::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use std::time::Duration;
+
+    #[test]
+    fn driver_steps_through_packets() {
+        let clock = ManualClock::new();
+        let mut driver = DMXDriver::with_clock(clock.clone());
+        driver.set_packet_time(Duration::from_millis(5));
+        driver.set_channel(1, 255).unwrap();
+        assert_eq!(driver.next_action(), Action::SendBreak);
+        driver.set_channel(2, 128).unwrap();
+        match driver.next_action() {
+            Action::SendData(frame) => assert_eq!(&frame[..3], &[0, 255, 128]),
+            action => panic!("expected data, got {:?}", action),
+        }
+        assert_eq!(driver.next_action(), Action::Sleep(Duration::from_millis(5)));
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(driver.next_action(), Action::SendBreak);
+    }
+}
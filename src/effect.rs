@@ -0,0 +1,596 @@
+use crate::color::{Color, ColorSpace};
+use crate::fixture::{Attribute, Fixture};
+use crate::render::FrameContext;
+use crate::DMX_CHANNELS;
+
+use std::collections::hash_map::RandomState;
+use std::f32::consts::TAU;
+use std::hash::{BuildHasher, Hasher};
+use std::time;
+
+/// Generative content which renders the channels of every frame, e.g. from the [render callback](crate::DMXSerial::set_render_callback).
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, DMXSerial, Effect, Fixture, Oscillator, Waveform};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let pars: Vec<Fixture> = (0..10)
+///         .map(|i| Fixture::new(&format!("Par {}", i + 1), 1 + i * 4, vec![Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap())
+///         .collect();
+///     // A wave running through the pars instead of all of them flashing together
+///     let mut wave = Oscillator::new(&pars, Attribute::Dimmer, Waveform::Sine, Duration::from_secs(2)).fan(1.0);
+///     dmx.set_render_callback(40, move |frame, channels| wave.render(frame, channels));
+/// }
+/// ```
+///
+pub trait Effect: Send {
+    /// Renders the effect into the channel values of the frame.
+    ///
+    fn render(&mut self, frame: &FrameContext, channels: &mut [u8; DMX_CHANNELS]);
+}
+
+/// The shape of an [`Oscillator`] over one period.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Waveform {
+    /// A smooth wave, starting at the low value.
+    #[default]
+    Sine,
+    /// Rises and falls linearly.
+    Triangle,
+    /// High for the first half of the period, low for the second half.
+    Square,
+    /// Rises linearly, then drops.
+    SawUp,
+    /// Jumps up, then falls linearly.
+    SawDown,
+}
+
+impl Waveform {
+    /// Returns the level *(`0.0` to `1.0`)* at the `phase` *(`0.0` to `1.0`)* of the period.
+    ///
+    pub fn level(&self, phase: f32) -> f32 {
+        let phase = phase.rem_euclid(1.0);
+        match self {
+            Waveform::Sine => 0.5 - 0.5 * (phase * TAU).cos(),
+            Waveform::Triangle => 1.0 - (2.0 * phase - 1.0).abs(),
+            Waveform::Square => if phase < 0.5 { 1.0 } else { 0.0 },
+            Waveform::SawUp => phase,
+            Waveform::SawDown => 1.0 - phase,
+        }
+    }
+}
+
+/// A periodic [`Effect`] on one [`Attribute`] of a group of fixtures.
+///
+/// The phase of every fixture is offset by its position in the group:
+/// - **fan** spreads the phases over the group, so the effect runs through it as a wave.
+/// - **spread** lets neighbouring fixtures share a phase, so the wave moves in blocks.
+/// - **wings** split the group into mirrored parts, e.g. two wings make the wave run from both ends to the middle.
+///
+/// Fixtures without the attribute are skipped. The value is written over the set value of the channel.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Oscillator {
+    pub fixtures: Vec<Fixture>,
+    pub attribute: Attribute,
+    pub waveform: Waveform,
+    /// Time of one cycle. With `0`, the effect stands still at its start.
+    pub period: time::Duration,
+    /// Value at the level `0.0` of the waveform.
+    pub low: u8,
+    /// Value at the level `1.0` of the waveform.
+    pub high: u8,
+    /// Phase offset from the first to the last block of a wing, in cycles *(`1.0` spreads one full cycle over the wing)*. `0.0` makes all fixtures move together.
+    pub fan: f32,
+    /// Number of neighbouring fixtures which share the same phase.
+    pub spread: usize,
+    /// Number of mirrored parts of the group.
+    pub wings: usize,
+}
+
+impl Oscillator {
+    /// Creates an [Oscillator] over the full range, with all fixtures moving together.
+    ///
+    pub fn new(fixtures: &[Fixture], attribute: Attribute, waveform: Waveform, period: time::Duration) -> Oscillator {
+        Oscillator {
+            fixtures: fixtures.to_vec(),
+            attribute,
+            waveform,
+            period,
+            low: 0,
+            high: 255,
+            fan: 0.0,
+            spread: 1,
+            wings: 1,
+        }
+    }
+
+    /// Sets the values at the low and the high point of the waveform.
+    ///
+    pub fn range(mut self, low: u8, high: u8) -> Oscillator {
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    /// Sets the fan. See [`Oscillator::fan`].
+    ///
+    pub fn fan(mut self, fan: f32) -> Oscillator {
+        self.fan = fan;
+        self
+    }
+
+    /// Sets the spread. See [`Oscillator::spread`].
+    ///
+    pub fn spread(mut self, spread: usize) -> Oscillator {
+        self.spread = spread;
+        self
+    }
+
+    /// Sets the wings. See [`Oscillator::wings`].
+    ///
+    pub fn wings(mut self, wings: usize) -> Oscillator {
+        self.wings = wings;
+        self
+    }
+
+    /// Returns the phase offset *(in cycles)* of the fixture at the `index` of the group.
+    ///
+    pub fn phase_offset(&self, index: usize) -> f32 {
+        let count = self.fixtures.len().max(1);
+        let wing_size = count.div_ceil(self.wings.clamp(1, count));
+        let mut position = index % wing_size;
+        // Every second wing runs backwards
+        if (index / wing_size) % 2 == 1 {
+            position = wing_size - 1 - position;
+        }
+        let spread = self.spread.max(1);
+        self.fan * (position / spread) as f32 / wing_size.div_ceil(spread) as f32
+    }
+
+    /// Returns the value of the fixture at the `index` of the group, the given time after the start of the effect.
+    ///
+    pub fn value(&self, index: usize, elapsed: time::Duration) -> u8 {
+        let cycles = if self.period.is_zero() { 0.0 } else { elapsed.as_secs_f32() / self.period.as_secs_f32() };
+        let level = self.waveform.level(cycles + self.phase_offset(index));
+        (self.low as f32 + (self.high as f32 - self.low as f32) * level).round() as u8
+    }
+}
+
+impl Effect for Oscillator {
+    fn render(&mut self, frame: &FrameContext, channels: &mut [u8; DMX_CHANNELS]) {
+        for (index, fixture) in self.fixtures.iter().enumerate() {
+            if let Some(channel) = fixture.channel(self.attribute) {
+                channels[channel - 1] = self.value(index, frame.elapsed);
+            }
+        }
+    }
+}
+
+/// A stochastic [`Effect`] which gives every fixture of a group a new random level at a fixed rate, e.g. for flickering flames.
+///
+/// After each jump, the level decays linearly to the low value over the decay time *(`0` holds the level until the next jump)*.
+///
+#[derive(Debug, Clone)]
+pub struct RandomLevels {
+    pub fixtures: Vec<Fixture>,
+    pub attribute: Attribute,
+    /// Time between the jumps to new levels.
+    pub interval: time::Duration,
+    pub decay: time::Duration,
+    pub low: u8,
+    pub high: u8,
+    rng: Rng,
+    // Number of the interval the levels were chosen in
+    step: Option<u64>,
+    levels: Vec<u8>,
+}
+
+impl RandomLevels {
+    /// Creates a [RandomLevels] effect over the full range, without decay.
+    ///
+    pub fn new(fixtures: &[Fixture], attribute: Attribute, interval: time::Duration) -> RandomLevels {
+        RandomLevels {
+            fixtures: fixtures.to_vec(),
+            attribute,
+            interval,
+            decay: time::Duration::ZERO,
+            low: 0,
+            high: 255,
+            rng: Rng::new(),
+            step: None,
+            levels: Vec::new(),
+        }
+    }
+
+    /// Sets the decay time. See [`RandomLevels`].
+    ///
+    pub fn decay(mut self, decay: time::Duration) -> RandomLevels {
+        self.decay = decay;
+        self
+    }
+
+    /// Sets the range of the random levels.
+    ///
+    pub fn range(mut self, low: u8, high: u8) -> RandomLevels {
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    /// Makes the random sequence repeatable, e.g. for offline rendering or tests.
+    ///
+    pub fn seed(mut self, seed: u64) -> RandomLevels {
+        self.rng = Rng::seeded(seed);
+        self
+    }
+}
+
+impl Effect for RandomLevels {
+    fn render(&mut self, frame: &FrameContext, channels: &mut [u8; DMX_CHANNELS]) {
+        let interval = self.interval.max(time::Duration::from_millis(1));
+        let step = (frame.elapsed.as_nanos() / interval.as_nanos()) as u64;
+        let since = time::Duration::from_nanos((frame.elapsed.as_nanos() % interval.as_nanos()) as u64);
+        if self.step != Some(step) || self.levels.len() != self.fixtures.len() {
+            let (low, high) = (self.low, self.high);
+            let rng = &mut self.rng;
+            self.levels = self.fixtures.iter().map(|_| rng.between(low, high)).collect();
+            self.step = Some(step);
+        }
+        for (fixture, level) in self.fixtures.iter().zip(self.levels.iter()) {
+            if let Some(channel) = fixture.channel(self.attribute) {
+                channels[channel - 1] = decayed(*level, self.low, since, self.decay);
+            }
+        }
+    }
+}
+
+/// A stochastic [`Effect`] which flashes random fixtures of a group to the high value, e.g. for sparkling or twinkling stars.
+///
+/// Each fixture flashes on average `rate` times per second. A flash decays linearly to the low value over the decay time, a long decay gives a soft twinkle.
+///
+#[derive(Debug, Clone)]
+pub struct Sparkle {
+    pub fixtures: Vec<Fixture>,
+    pub attribute: Attribute,
+    /// Average flashes per second of every fixture.
+    pub rate: f32,
+    pub decay: time::Duration,
+    pub low: u8,
+    pub high: u8,
+    rng: Rng,
+    // Time of the last flash of every fixture
+    flashes: Vec<Option<time::Duration>>,
+}
+
+impl Sparkle {
+    /// Creates a [Sparkle] effect over the full range, with short flashes.
+    ///
+    pub fn new(fixtures: &[Fixture], attribute: Attribute, rate: f32) -> Sparkle {
+        Sparkle {
+            fixtures: fixtures.to_vec(),
+            attribute,
+            rate,
+            decay: time::Duration::from_millis(100),
+            low: 0,
+            high: 255,
+            rng: Rng::new(),
+            flashes: Vec::new(),
+        }
+    }
+
+    /// Sets the decay time of the flashes.
+    ///
+    pub fn decay(mut self, decay: time::Duration) -> Sparkle {
+        self.decay = decay;
+        self
+    }
+
+    /// Sets the values between flashes and at the start of a flash.
+    ///
+    pub fn range(mut self, low: u8, high: u8) -> Sparkle {
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    /// Makes the random sequence repeatable, e.g. for offline rendering or tests.
+    ///
+    pub fn seed(mut self, seed: u64) -> Sparkle {
+        self.rng = Rng::seeded(seed);
+        self
+    }
+}
+
+impl Effect for Sparkle {
+    fn render(&mut self, frame: &FrameContext, channels: &mut [u8; DMX_CHANNELS]) {
+        self.flashes.resize(self.fixtures.len(), None);
+        // Chance of at least one flash since the previous frame
+        let chance = 1.0 - (-self.rate.max(0.0) * frame.delta.as_secs_f32()).exp();
+        for (fixture, flash) in self.fixtures.iter().zip(self.flashes.iter_mut()) {
+            if self.rng.next() < chance {
+                *flash = Some(frame.elapsed);
+            }
+            if let Some(channel) = fixture.channel(self.attribute) {
+                channels[channel - 1] = match flash {
+                    Some(start) => decayed(self.high, self.low, frame.elapsed.saturating_sub(*start), self.decay),
+                    None => self.low,
+                };
+            }
+        }
+    }
+}
+
+/// The pattern of a [`PixelEffect`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelPattern {
+    /// The colors of the palette blend into each other along the strip.
+    Gradient,
+    /// The full hue circle along the strip, the palette isn't used.
+    Rainbow,
+    /// A block of `width` lit pixels runs along the strip, the others are black. Every pass takes the next color of the palette.
+    Chase { width: usize },
+}
+
+/// The direction the pattern of a [`PixelEffect`] moves in.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// From the first to the last pixel.
+    #[default]
+    Forward,
+    /// From the last to the first pixel.
+    Backward,
+}
+
+/// A color [`Effect`] for RGB pixel strips, where every pixel is a [`Fixture`] with red, green and blue channels.
+///
+/// The pixels are used in the given order. Their color channels are written, other channels keep their set values.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, Color, DMXSerial, Effect, Fixture, PixelEffect, PixelPattern};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let strip: Vec<Fixture> = (0..170)
+///         .map(|i| Fixture::new(&format!("Pixel {}", i + 1), 1 + i * 3, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap())
+///         .collect();
+///     let mut gradient = PixelEffect::new(&strip, PixelPattern::Gradient)
+///         .palette(vec![Color::new(255, 0, 0), Color::new(0, 0, 255)])
+///         .speed(0.25);
+///     dmx.set_render_callback(40, move |frame, channels| gradient.render(frame, channels));
+/// }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelEffect {
+    pub pixels: Vec<Fixture>,
+    pub pattern: PixelPattern,
+    /// The colors of the gradient and the chase.
+    pub palette: Vec<Color>,
+    /// Passes of the pattern along the strip per second. With `0.0`, the pattern stands still.
+    pub speed: f32,
+    pub direction: Direction,
+    /// How the colors of the gradient blend into each other.
+    pub color_space: ColorSpace,
+}
+
+impl PixelEffect {
+    /// Creates a standing [PixelEffect] with a white palette.
+    ///
+    pub fn new(pixels: &[Fixture], pattern: PixelPattern) -> PixelEffect {
+        PixelEffect {
+            pixels: pixels.to_vec(),
+            pattern,
+            palette: vec![Color::WHITE],
+            speed: 0.0,
+            direction: Direction::Forward,
+            color_space: ColorSpace::Rgb,
+        }
+    }
+
+    /// Sets the palette. See [`PixelEffect::palette`].
+    ///
+    pub fn palette(mut self, palette: Vec<Color>) -> PixelEffect {
+        self.palette = palette;
+        self
+    }
+
+    /// Sets the speed. See [`PixelEffect::speed`].
+    ///
+    pub fn speed(mut self, speed: f32) -> PixelEffect {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the direction.
+    ///
+    pub fn direction(mut self, direction: Direction) -> PixelEffect {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the color space of the gradient.
+    ///
+    pub fn color_space(mut self, color_space: ColorSpace) -> PixelEffect {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Returns the color of the pixel at the `index` of the strip, the given time after the start of the effect.
+    ///
+    pub fn color(&self, index: usize, elapsed: time::Duration) -> Color {
+        let count = self.pixels.len().max(1);
+        let passes = elapsed.as_secs_f64() * self.speed as f64;
+        // Position of the pixel in the pattern, which moves along the strip
+        let shift = match self.direction {
+            Direction::Forward => -passes,
+            Direction::Backward => passes,
+        };
+        let position = (index as f64 / count as f64 + shift).rem_euclid(1.0) as f32;
+        match self.pattern {
+            PixelPattern::Gradient => {
+                let Some(first) = self.palette.first() else {
+                    return Color::BLACK;
+                };
+                // The last color blends back into the first one, so moving gradients have no seam
+                let scaled = position * self.palette.len() as f32;
+                let from = scaled as usize % self.palette.len();
+                let to = self.palette.get(from + 1).unwrap_or(first);
+                self.palette[from].mix(*to, scaled.fract(), self.color_space)
+            },
+            PixelPattern::Rainbow => Color::from_hsv(position * 360.0, 1.0, 1.0),
+            PixelPattern::Chase { width } => {
+                if self.palette.is_empty() {
+                    return Color::BLACK;
+                }
+                let lead = passes.rem_euclid(1.0) * count as f64;
+                let index = match self.direction {
+                    Direction::Forward => index,
+                    Direction::Backward => count - 1 - index.min(count - 1),
+                };
+                let behind = (lead as usize + count - index % count) % count;
+                if behind < width {
+                    self.palette[passes.floor() as usize % self.palette.len()]
+                } else {
+                    Color::BLACK
+                }
+            },
+        }
+    }
+}
+
+impl Effect for PixelEffect {
+    fn render(&mut self, frame: &FrameContext, channels: &mut [u8; DMX_CHANNELS]) {
+        for (index, pixel) in self.pixels.iter().enumerate() {
+            pixel.write_color(channels, self.color(index, frame.elapsed));
+        }
+    }
+}
+
+// Fades linearly from the level to the low value over the decay time, a decay of 0 holds the level
+fn decayed(level: u8, low: u8, since: time::Duration, decay: time::Duration) -> u8 {
+    if decay.is_zero() {
+        return level;
+    }
+    let progress = (since.as_secs_f32() / decay.as_secs_f32()).min(1.0);
+    (level as f32 + (low as f32 - level as f32) * progress).round() as u8
+}
+
+// A small xorshift generator, the effects don't need more than that
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Rng {
+        Rng::seeded(RandomState::new().build_hasher().finish())
+    }
+
+    fn seeded(seed: u64) -> Rng {
+        // The state must not be 0
+        Rng((seed ^ 0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    // A value in 0.0..1.0
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn between(&mut self, low: u8, high: u8) -> u8 {
+        (low as f32 + (high as f32 - low as f32) * self.next()).round() as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Direction, OfflineRenderer};
+    use std::time::Duration;
+
+    #[test]
+    fn group_effects_fan_through_the_fixtures() {
+        let pars: Vec<Fixture> = (0..4).map(|i| Fixture::new("Par", 1 + i * 2, vec![Attribute::Dimmer, Attribute::Red]).unwrap()).collect();
+        let period = Duration::from_secs(4);
+        let wave = Oscillator::new(&pars, Attribute::Dimmer, Waveform::SawUp, period).range(0, 200).fan(1.0);
+        assert_eq!((0..4).map(|i| wave.phase_offset(i)).collect::<Vec<_>>(), [0.0, 0.25, 0.5, 0.75]);
+        let blocks = wave.clone().spread(2);
+        assert_eq!((0..4).map(|i| blocks.phase_offset(i)).collect::<Vec<_>>(), [0.0, 0.0, 0.5, 0.5]);
+        let wings = wave.clone().wings(2);
+        assert_eq!((0..4).map(|i| wings.phase_offset(i)).collect::<Vec<_>>(), [0.0, 0.5, 0.5, 0.0]);
+        assert_eq!(Waveform::Square.level(0.25), 1.0);
+        assert_eq!(Waveform::Sine.level(0.5), 1.0);
+
+        let mut renderer = OfflineRenderer::new().unwrap();
+        let mut effect = wave;
+        renderer.dmx().set_render_callback(40, move |frame, channels| effect.render(frame, channels));
+        assert_eq!(renderer.render_frame(Duration::ZERO).unwrap().channels()[..8], [0, 0, 50, 0, 100, 0, 150, 0]);
+        assert_eq!(renderer.render_frame(Duration::from_secs(1)).unwrap().channels()[..8], [50, 0, 100, 0, 150, 0, 0, 0]);
+    }
+
+    #[test]
+    fn random_effects_are_repeatable_with_a_seed() {
+        let stars: Vec<Fixture> = (0..8).map(|i| Fixture::new("Star", 1 + i, vec![Attribute::Dimmer]).unwrap()).collect();
+        let render = |mut effect: Box<dyn Effect>| {
+            let mut renderer = OfflineRenderer::new().unwrap();
+            renderer.dmx().set_render_callback(40, move |frame, channels| effect.render(frame, channels));
+            let frames = renderer.render(Duration::from_secs(1), Duration::from_millis(50)).unwrap();
+            frames.iter().map(|universe| universe.channels()[..8].to_vec()).collect::<Vec<_>>()
+        };
+
+        let flicker = || RandomLevels::new(&stars, Attribute::Dimmer, Duration::from_millis(200)).range(100, 200).decay(Duration::from_millis(100)).seed(7);
+        let frames = render(Box::new(flicker()));
+        assert_eq!(frames, render(Box::new(flicker())));
+        assert!(frames.iter().flatten().all(|value| (100..=200).contains(value)));
+        // New levels every 4 frames, decayed to the low value after 2
+        assert_ne!(frames[0], frames[4]);
+        assert_eq!(frames[2], [100; 8]);
+
+        let sparkle = || Sparkle::new(&stars, Attribute::Dimmer, 2.0).range(10, 250).decay(Duration::from_millis(100)).seed(3);
+        let frames = render(Box::new(sparkle()));
+        assert_eq!(frames, render(Box::new(sparkle())));
+        // No flashes before the first interval, then some fixtures flash
+        assert_eq!(frames[0], [10; 8]);
+        assert!(frames.iter().flatten().any(|value| *value == 250));
+        assert!(frames.iter().flatten().all(|value| [10, 130, 250].contains(value)));
+    }
+
+    #[test]
+    fn pixel_effects_move_along_the_strip() {
+        let strip: Vec<Fixture> = (0..4).map(|i| Fixture::new("Pixel", 1 + i * 3, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap()).collect();
+        let (red, blue) = (Color::new(255, 0, 0), Color::new(0, 0, 255));
+        let second = Duration::from_secs(1);
+
+        let gradient = PixelEffect::new(&strip, PixelPattern::Gradient).palette(vec![red, blue]).speed(0.25);
+        assert_eq!((0..4).map(|i| gradient.color(i, Duration::ZERO)).collect::<Vec<_>>(), [red, red.lerp(blue, 0.5), blue, blue.lerp(red, 0.5)]);
+        // A quarter pass moves the pattern by one pixel
+        assert_eq!(gradient.color(1, second), red);
+        assert_eq!(gradient.clone().direction(Direction::Backward).color(1, second), blue);
+
+        let rainbow = PixelEffect::new(&strip, PixelPattern::Rainbow);
+        assert_eq!(rainbow.color(0, Duration::ZERO), red);
+        assert_eq!(rainbow.color(2, Duration::ZERO), Color::new(0, 255, 255));
+        assert_eq!(Color::from_hsv(240.0, 1.0, 0.5), Color::new(0, 0, 128));
+
+        let chase = PixelEffect::new(&strip, PixelPattern::Chase { width: 1 }).palette(vec![red, blue]).speed(0.25);
+        let lit = |elapsed: Duration| (0..4).filter(|i| chase.color(*i, elapsed) != Color::BLACK).collect::<Vec<_>>();
+        assert_eq!(lit(Duration::ZERO), [0]);
+        assert_eq!(lit(second * 3), [3]);
+        assert_eq!(chase.color(0, second * 4), blue);
+
+        let mut renderer = OfflineRenderer::new().unwrap();
+        let dmx = renderer.dmx();
+        strip[1].set_color(dmx, blue).unwrap();
+        assert_eq!(dmx.get_channels()[3..6], [0, 0, 255]);
+    }
+}
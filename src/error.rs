@@ -47,4 +47,357 @@ impl std::error::Error for DMXChannelValidityError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         None
     }
-}
\ No newline at end of file
+}
+
+/// Error for when a raw **DMX frame** could not be sent.
+/// 
+/// - [`DMXFrameError::Empty`] if the frame doesn't contain a start code.
+/// 
+/// - [`DMXFrameError::TooLong`] if the frame contains more than [`DMX_CHANNELS`] slots after the start code.
+/// 
+/// - [`DMXFrameError::ZeroInterval`] if a periodic frame should be sent every `0` packets.
+/// 
+/// - [`DMXFrameError::Disconnected`] if the [DMXSerial] port is disconnected.
+/// 
+/// [`DMX_CHANNELS`]: crate::DMX_CHANNELS
+/// [DMXSerial]: crate::DMXSerial
+/// 
+#[derive(Debug)]
+pub enum DMXFrameError {
+    Empty,
+    TooLong,
+    ZeroInterval,
+    Disconnected(DMXDisconnectionError),
+}
+
+impl std::fmt::Display for DMXFrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DMXFrameError::Empty => write!(f, "DMX frame is missing a start code"),
+            DMXFrameError::TooLong => write!(f, "DMX frame contains too many slots"),
+            DMXFrameError::ZeroInterval => write!(f, "DMX frame interval must not be zero"),
+            DMXFrameError::Disconnected(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DMXFrameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DMXFrameError::Disconnected(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DMXDisconnectionError> for DMXFrameError {
+    fn from(e: DMXDisconnectionError) -> Self {
+        DMXFrameError::Disconnected(e)
+    }
+}
+
+/// Error for when a [Config] could not be loaded or applied.
+/// 
+/// - [`ConfigError::Io`] if the file could not be read.
+/// 
+/// - [`ConfigError::Toml`] or [`ConfigError::Json`] if the file could not be parsed.
+/// 
+/// - [`ConfigError::UnknownChannel`] if a name is neither in the patch nor a channel number.
+/// 
+/// - [`ConfigError::InvalidChannel`] if a channel is outside of the valid range.
+/// 
+/// - [`ConfigError::InvalidGamma`] if the gamma of a curve isn't a finite number above `0`.
+/// 
+/// - [`ConfigError::UnknownScene`] if a scene should be applied which isn't in the [Config].
+/// 
+/// - [`ConfigError::MissingPort`] if the interface should be opened without a configured port.
+/// 
+/// - [`ConfigError::Serial`] if the interface could not be opened.
+/// 
+/// [Config]: crate::Config
+/// 
+#[cfg(feature = "config")]
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    UnknownChannel(String),
+    InvalidChannel(DMXChannelValidityError),
+    InvalidGamma(f32),
+    UnknownScene(String),
+    MissingPort,
+    Serial(serialport::Error),
+}
+
+#[cfg(feature = "config")]
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "Config could not be read: {}", e),
+            ConfigError::Toml(e) => write!(f, "Config is not valid TOML: {}", e),
+            ConfigError::Json(e) => write!(f, "Config is not valid JSON: {}", e),
+            ConfigError::UnknownChannel(name) => write!(f, "Config refers to the unknown channel \"{}\"", name),
+            ConfigError::InvalidChannel(e) => write!(f, "{}", e),
+            ConfigError::InvalidGamma(gamma) => write!(f, "Config contains the invalid gamma {}", gamma),
+            ConfigError::UnknownScene(name) => write!(f, "Config doesn't contain the scene \"{}\"", name),
+            ConfigError::MissingPort => write!(f, "Config doesn't contain a port"),
+            ConfigError::Serial(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::Toml(e) => Some(e),
+            ConfigError::Json(e) => Some(e),
+            ConfigError::InvalidChannel(e) => Some(e),
+            ConfigError::Serial(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Toml(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Json(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<DMXChannelValidityError> for ConfigError {
+    fn from(e: DMXChannelValidityError) -> Self {
+        ConfigError::InvalidChannel(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<serialport::Error> for ConfigError {
+    fn from(e: serialport::Error) -> Self {
+        ConfigError::Serial(e)
+    }
+}
+
+/// Error for when a [DMXSerial] port could not be opened, classified so applications can show what to do about it.
+/// 
+/// Created from the [`serialport::Error`] returned by the open functions.
+/// 
+/// - [`DMXOpenError::PermissionDenied`] if the user isn't allowed to access the port.
+/// 
+/// - [`DMXOpenError::NotFound`] if there is no port with the given name.
+/// 
+/// - [`DMXOpenError::Busy`] if another process holds the port.
+/// 
+/// - [`DMXOpenError::UnsupportedBaud`] if the driver doesn't support the baud rate of the [`LineSettings`].
+/// 
+/// - [`DMXOpenError::Other`] for everything else.
+/// 
+/// [DMXSerial]: crate::DMXSerial
+/// [`LineSettings`]: crate::LineSettings
+/// 
+/// # Example
+/// 
+/// ```no_run
+/// use open_dmx::DMXSerial;
+/// use open_dmx::error::DMXOpenError;
+/// 
+/// fn main() {
+///     match DMXSerial::open("/dev/ttyUSB0").map_err(DMXOpenError::from) {
+///         Ok(dmx) => { /* ... */ },
+///         Err(DMXOpenError::NotFound(_)) => eprintln!("Is the adapter plugged in?"),
+///         Err(e) => eprintln!("{}", e),
+///     }
+/// }
+/// ```
+/// 
+#[derive(Debug)]
+pub enum DMXOpenError {
+    PermissionDenied(serialport::Error),
+    NotFound(serialport::Error),
+    Busy(serialport::Error),
+    UnsupportedBaud(serialport::Error),
+    Other(serialport::Error),
+}
+
+impl std::fmt::Display for DMXOpenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DMXOpenError::PermissionDenied(_) if cfg!(target_os = "linux") => write!(f, "Permission denied on the DMX port. Add the user to the `dialout` group (or `uucp` on some distributions) or allow the adapter with a udev rule, then log in again"),
+            DMXOpenError::PermissionDenied(_) => write!(f, "Permission denied on the DMX port"),
+            DMXOpenError::NotFound(_) => write!(f, "DMX port not found. Check the name and if the adapter is plugged in"),
+            DMXOpenError::Busy(_) => write!(f, "DMX port is used by another process"),
+            DMXOpenError::UnsupportedBaud(e) => write!(f, "{}", e),
+            DMXOpenError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DMXOpenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DMXOpenError::PermissionDenied(e)
+            | DMXOpenError::NotFound(e)
+            | DMXOpenError::Busy(e)
+            | DMXOpenError::UnsupportedBaud(e)
+            | DMXOpenError::Other(e) => Some(e),
+        }
+    }
+}
+
+impl From<serialport::Error> for DMXOpenError {
+    fn from(e: serialport::Error) -> Self {
+        use serialport::ErrorKind;
+        match e.kind() {
+            ErrorKind::Io(std::io::ErrorKind::PermissionDenied) => DMXOpenError::PermissionDenied(e),
+            ErrorKind::Io(std::io::ErrorKind::NotFound) => DMXOpenError::NotFound(e),
+            // Used for `EBUSY` on Unix and for ports held by another process on Windows, see `missing_port`
+            ErrorKind::NoDevice => DMXOpenError::Busy(e),
+            // Rejected baud rates are reported with the rate in the description
+            ErrorKind::InvalidInput if e.description.contains("baud") => DMXOpenError::UnsupportedBaud(e),
+            _ => DMXOpenError::Other(e),
+        }
+    }
+}
+
+// Windows reports missing ports (`ERROR_FILE_NOT_FOUND`) and ports held by another process (`ERROR_ACCESS_DENIED`) both as `NoDevice`.
+// Ports the system doesn't list are missing, the others are in use
+#[cfg(any(windows, test))]
+pub(crate) fn missing_port(e: serialport::Error, listed: bool) -> serialport::Error {
+    if e.kind() == serialport::ErrorKind::NoDevice && !listed {
+        return serialport::Error::new(serialport::ErrorKind::Io(std::io::ErrorKind::NotFound), e.description);
+    }
+    e
+}
+
+/// Error for when a [Fixture] could not be added to a [Patch].
+/// 
+/// - [`PatchError::AddressConflict`] if the channels of the fixture overlap with the channels of other fixtures. Lists the names of the conflicting fixtures.
+/// 
+/// - [`PatchError::NoSpace`] if there are no free channels left for the fixture.
+/// 
+/// - [`PatchError::InvalidCsv`] if a line of an imported patch list could not be read *(counted from `1`)*.
+/// 
+/// - [`PatchError::UnknownMode`] if the channels of a mode in an imported patch list are unknown.
+/// 
+/// [Fixture]: crate::Fixture
+/// [Patch]: crate::Patch
+/// 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    AddressConflict { fixture: String, conflicts: Vec<String> },
+    NoSpace { fixture: String },
+    InvalidCsv { line: usize, message: String },
+    UnknownMode { line: usize, mode: String },
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PatchError::AddressConflict { fixture, conflicts } => write!(f, "\"{}\" overlaps with {}", fixture, conflicts.iter().map(|name| format!("\"{}\"", name)).collect::<Vec<_>>().join(", ")),
+            PatchError::NoSpace { fixture } => write!(f, "No free channels left for \"{}\"", fixture),
+            PatchError::InvalidCsv { line, message } => write!(f, "Invalid patch list in line {}: {}", line, message),
+            PatchError::UnknownMode { line, mode } => write!(f, "Unknown mode \"{}\" in line {}", mode, line),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// Error for when a recording could not be read or converted.
+/// 
+/// - [`RecordingError::Io`] if reading or writing the data failed.
+/// 
+/// - [`RecordingError::NotARecording`] if the data isn't in the `.dmxrec` format.
+/// 
+/// - [`RecordingError::UnsupportedVersion`] if the recording uses another format version or unknown features.
+/// 
+/// - [`RecordingError::Compressed`] if the recording is compressed, but the `zstd` feature isn't enabled.
+/// 
+/// - [`RecordingError::Corrupt`] if the recording is damaged or incomplete. Describes the problem.
+/// 
+/// - [`RecordingError::InvalidCsv`] if a line of a CSV file could not be read *(counted from `1`)*.
+/// 
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(std::io::Error),
+    NotARecording,
+    UnsupportedVersion(u16),
+    Compressed,
+    Corrupt(&'static str),
+    InvalidCsv { line: usize, message: String },
+}
+
+impl std::fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RecordingError::Io(e) => write!(f, "Recording could not be read or written: {}", e),
+            RecordingError::NotARecording => write!(f, "Data is not a DMX recording"),
+            RecordingError::UnsupportedVersion(version) => write!(f, "Recording format version {} is not supported", version),
+            RecordingError::Compressed => write!(f, "Recording is compressed, which requires the `zstd` feature"),
+            RecordingError::Corrupt(problem) => write!(f, "Recording is corrupt: {}", problem),
+            RecordingError::InvalidCsv { line, message } => write!(f, "Invalid CSV in line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordingError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(e: std::io::Error) -> Self {
+        RecordingError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn open_errors_are_classified() {
+        let error = |kind, description| DMXOpenError::from(serialport::Error::new(kind, description));
+        assert!(matches!(error(serialport::ErrorKind::Io(io::ErrorKind::PermissionDenied), "denied"), DMXOpenError::PermissionDenied(_)));
+        assert!(matches!(error(serialport::ErrorKind::Io(io::ErrorKind::NotFound), "missing"), DMXOpenError::NotFound(_)));
+        assert!(matches!(error(serialport::ErrorKind::NoDevice, "busy"), DMXOpenError::Busy(_)));
+        assert!(matches!(error(serialport::ErrorKind::InvalidInput, "250000 baud is not supported by the serial driver"), DMXOpenError::UnsupportedBaud(_)));
+        assert!(matches!(error(serialport::ErrorKind::Unknown, "other"), DMXOpenError::Other(_)));
+    }
+
+    #[test]
+    fn missing_windows_ports_are_not_reported_as_busy() {
+        let no_device = || serialport::Error::new(serialport::ErrorKind::NoDevice, "windows error");
+        assert!(matches!(DMXOpenError::from(missing_port(no_device(), false)), DMXOpenError::NotFound(_)));
+        assert!(matches!(DMXOpenError::from(missing_port(no_device(), true)), DMXOpenError::Busy(_)));
+        let denied = serialport::Error::new(serialport::ErrorKind::Io(io::ErrorKind::PermissionDenied), "denied");
+        assert!(matches!(DMXOpenError::from(missing_port(denied, false)), DMXOpenError::PermissionDenied(_)));
+    }
+}
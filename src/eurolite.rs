@@ -0,0 +1,148 @@
+use crate::builder::LineSettings;
+use crate::transport::Transport;
+use crate::widget::{self, LABEL_SEND_DMX};
+use crate::DMX_CHANNELS;
+
+use serialport::{SerialPort, SerialPortType};
+
+use std::fmt;
+use std::io;
+
+// USB IDs of the (first) USB-DMX512 PRO, which shows up as a CDC serial port
+const VENDOR_ID: u16 = 0x04D8;
+const PRODUCT_ID: u16 = 0xFA63;
+
+// Ignored by the CDC port, the interface sends at 250000 baud on its own
+const BAUD_RATE: u32 = 115_200;
+
+/// A [`Transport`] for **Eurolite USB-DMX512 PRO** interfaces, which send the frames on their own and receive them as messages of the *Enttec DMX USB Pro* API.
+///
+/// Unlike the Enttec interface, the Eurolite drops frames with less than [`DMX_CHANNELS`] channels, so shorter frames are filled up with zeros.
+/// The break settings are ignored and the [packet time] only sets how often the frames are sent to the interface.
+///
+/// [packet time]: crate::DMXSerial::set_packet_time
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, EuroliteProOutput};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::builder("eurolite")
+///         .open_with_transport(EuroliteProOutput::find().unwrap())
+///         .unwrap();
+///     dmx.set_channels([255; 512]);
+/// }
+/// ```
+///
+pub struct EuroliteProOutput {
+    port: Box<dyn SerialPort>,
+}
+
+impl EuroliteProOutput {
+    /// Opens the first connected USB-DMX512 PRO, detected by its USB vendor and product id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of the kind [`io::ErrorKind::NotFound`] if there is no USB-DMX512 PRO.
+    ///
+    pub fn find() -> io::Result<EuroliteProOutput> {
+        let port = serialport::available_ports()?.into_iter()
+            .find(|info| matches!(&info.port_type, SerialPortType::UsbPort(usb) if usb.vid == VENDOR_ID && usb.pid == PRODUCT_ID))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no Eurolite USB-DMX512 PRO found"))?;
+        EuroliteProOutput::open(&port.port_name)
+    }
+
+    /// Opens the USB-DMX512 PRO at the given serial port *(e.g. `/dev/ttyACM0` or `COM3`)*.
+    ///
+    pub fn open(port: &str) -> io::Result<EuroliteProOutput> {
+        let port = serialport::new(crate::port_path::normalize(std::path::Path::new(port))?, BAUD_RATE)
+            .flow_control(serialport::FlowControl::None)
+            .open()?;
+        Ok(EuroliteProOutput::from_port(port))
+    }
+
+    /// Uses an already opened serial port.
+    ///
+    pub fn from_port(port: Box<dyn SerialPort>) -> EuroliteProOutput {
+        EuroliteProOutput { port }
+    }
+}
+
+// Message of a frame (start code + channels), filled up to all channels. `None` if it isn't a valid frame
+fn encode_frame(frame: &[u8]) -> Option<Vec<u8>> {
+    if frame.is_empty() || frame.len() > DMX_CHANNELS + 1 {
+        return None;
+    }
+    let mut full = [0; DMX_CHANNELS + 1];
+    full[..frame.len()].copy_from_slice(frame);
+    Some(widget::encode_message(LABEL_SEND_DMX, &full))
+}
+
+impl io::Write for EuroliteProOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The agent writes every frame at once
+        if let Some(message) = encode_frame(buf) {
+            self.port.write_all(&message)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Transport for EuroliteProOutput {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for EuroliteProOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EuroliteProOutput")
+            .field("port", &self.port.name())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_frames_are_filled_up() {
+        let message = encode_frame(&[0, 10, 20]).unwrap();
+        // Start delimiter, label 6, length 513 (LSB first), the frame and the end delimiter
+        assert_eq!(message.len(), DMX_CHANNELS + 6);
+        assert_eq!(message[..7], [0x7E, 6, 0x01, 0x02, 0, 10, 20]);
+        assert!(message[7..DMX_CHANNELS + 5].iter().all(|value| *value == 0));
+        assert_eq!(message[DMX_CHANNELS + 5], 0xE7);
+
+        // Other start codes are kept
+        assert_eq!(encode_frame(&[0xCC, 1]).unwrap()[4..6], [0xCC, 1]);
+        assert!(encode_frame(&[]).is_none());
+        assert!(encode_frame(&[0; DMX_CHANNELS + 2]).is_none());
+    }
+}
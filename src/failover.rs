@@ -0,0 +1,263 @@
+use crate::error::DMXChannelValidityError;
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::time;
+
+/// The interface of a [DMXFailover] which is currently sending.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverOutput {
+    Primary,
+    Backup,
+}
+
+/// Events reported by [`DMXFailover::events`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverEvent {
+    /// The primary interface got disconnected and the backup took over.
+    SwitchedToBackup,
+    /// The primary interface could be reopened and took over again.
+    SwitchedToPrimary,
+    /// The backup interface got disconnected.
+    BackupDisconnected,
+    /// The backup interface could be reopened and is ready again.
+    BackupReconnected,
+}
+
+/// Sends one universe over a primary [DMXSerial] and switches to a backup [DMXSerial] if the primary gets disconnected.
+///
+/// The channel values are kept on both interfaces, but only the active one is sending. The backup is held in **sync mode** while it's idle.
+///
+/// Disconnects are detected with [`DMXSerial::check_agent`] on every call to [`DMXFailover::poll`],
+/// which also tries to reopen the primary interface every [retry interval] and switches back once it succeeds.
+/// The idle backup is probed without sending anything, so a lost backup is noticed before it's needed, and reopened the same way.
+/// Both interfaces must be able to [reopen](DMXSerial::can_reopen), custom transports are opened with [`DMXSerialBuilder::open_with_transport_factory`].
+///
+/// [`DMXSerialBuilder::open_with_transport_factory`]: crate::DMXSerialBuilder::open_with_transport_factory
+///
+/// [retry interval]: DMXFailover::set_retry_interval
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::DMXFailover;
+///
+/// fn main() {
+///     let mut dmx = DMXFailover::open("/dev/ttyUSB0", "/dev/ttyUSB1").unwrap();
+///     let events = dmx.events();
+///
+///     dmx.set_channels([255; 512]);
+///     loop {
+///         dmx.poll();
+///         while let Ok(event) = events.try_recv() {
+///             println!("{:?}", event);
+///         }
+///         std::thread::sleep(std::time::Duration::from_millis(100));
+///     }
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct DMXFailover {
+    primary: DMXSerial,
+    backup: DMXSerial,
+    active: FailoverOutput,
+    retry_interval: time::Duration,
+    last_retry: Option<time::Instant>,
+    last_backup_retry: Option<time::Instant>,
+    backup_connected: bool,
+    events: Vec<mpsc::Sender<FailoverEvent>>,
+}
+
+impl DMXFailover {
+    /// Opens the `primary` interface and the `backup` interface in **sync mode** and starts sending on the `primary` one.
+    ///
+    /// The backup doesn't send a single packet before it takes over.
+    ///
+    pub fn open<P: AsRef<Path>, Q: AsRef<Path>>(primary: P, backup: Q) -> Result<DMXFailover, serialport::Error> {
+        let primary = DMXSerial::open(primary)?;
+        let backup = DMXSerial::open_sync(backup)?;
+        DMXFailover::new(primary, backup)
+    }
+
+    /// Creates a new [DMXFailover] which starts sending on the `primary` interface.
+    ///
+    /// The channel values of the `backup` are replaced with the ones of the `primary`.
+    /// The `backup` should already be opened in **sync mode** (e.g. with [`DMXSerial::open_sync`]), otherwise it sends until it's handed over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of the kind [`InvalidInput`](serialport::ErrorKind::InvalidInput) if one of the interfaces [can't be reopened](DMXSerial::can_reopen),
+    /// since the failover would be stuck after its first disconnect.
+    ///
+    pub fn new(primary: DMXSerial, mut backup: DMXSerial) -> Result<DMXFailover, serialport::Error> {
+        if let Some(dmx) = [&primary, &backup].into_iter().find(|dmx| !dmx.can_reopen()) {
+            return Err(serialport::Error::new(
+                serialport::ErrorKind::InvalidInput,
+                format!("\"{}\" can't be reopened, open it with a transport factory", dmx.name()),
+            ));
+        }
+        backup.set_sync();
+        backup.set_channels(primary.get_channels());
+        Ok(DMXFailover {
+            primary,
+            backup,
+            active: FailoverOutput::Primary,
+            retry_interval: time::Duration::from_secs(1),
+            last_retry: None,
+            last_backup_retry: None,
+            backup_connected: true,
+            events: Vec::new(),
+        })
+    }
+
+    /// Returns a [`Receiver`] for the [`FailoverEvent`]s of this [DMXFailover].
+    ///
+    /// [`Receiver`]: std::sync::mpsc::Receiver
+    ///
+    pub fn events(&mut self) -> mpsc::Receiver<FailoverEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.events.push(tx);
+        rx
+    }
+
+    /// Returns which interface is currently sending.
+    ///
+    pub fn active(&self) -> FailoverOutput {
+        self.active
+    }
+
+    /// Returns the primary [DMXSerial].
+    ///
+    pub fn primary(&self) -> &DMXSerial {
+        &self.primary
+    }
+
+    /// Returns the backup [DMXSerial].
+    ///
+    pub fn backup(&self) -> &DMXSerial {
+        &self.backup
+    }
+
+    /// Sets the minimum [`Duration`] between two attempts of reopening a disconnected interface.
+    ///
+    /// [`Duration`]: time::Duration
+    ///
+    /// # Default
+    ///
+    /// - 1 s
+    ///
+    pub fn set_retry_interval(&mut self, interval: time::Duration) {
+        self.retry_interval = interval;
+    }
+
+    /// Sets the specified [`channel`] to the given [`value`] on both interfaces. See [`DMXSerial::set_channel`].
+    ///
+    /// [`channel`]: usize
+    /// [`value`]: u8
+    ///
+    pub fn set_channel(&mut self, channel: usize, value: u8) -> Result<(), DMXChannelValidityError> {
+        self.primary.set_channel(channel, value)?;
+        self.backup.set_channel(channel, value)
+    }
+
+    /// Sets all channels on both interfaces. See [`DMXSerial::set_channels`].
+    ///
+    pub fn set_channels(&mut self, channels: [u8; DMX_CHANNELS]) {
+        self.primary.set_channels(channels);
+        self.backup.set_channels(channels);
+    }
+
+    /// Returns the [`value`] of all channels. See [`DMXSerial::get_channels`].
+    ///
+    /// [`value`]: u8
+    ///
+    pub fn get_channels(&self) -> [u8; DMX_CHANNELS] {
+        self.primary.get_channels()
+    }
+
+    /// Checks the interfaces and switches between them if needed.
+    ///
+    /// Should be called regularly. Returns the interface which is sending afterwards.
+    ///
+    pub fn poll(&mut self) -> FailoverOutput {
+        // The idle backup doesn't write anything, so it wouldn't notice a disconnect by itself
+        if self.backup_connected && self.active == FailoverOutput::Primary {
+            let _ = self.backup.probe();
+        }
+        if self.backup_connected && self.backup.check_agent().is_err() {
+            self.backup_connected = false;
+            self.notify(FailoverEvent::BackupDisconnected);
+        }
+        if !self.backup_connected && self.retry_backup() {
+            self.backup_connected = true;
+            if self.active == FailoverOutput::Backup {
+                // The backup is still the one sending
+                self.backup.set_async();
+                let _ = self.backup.update_async();
+            } else {
+                self.backup.set_sync();
+            }
+            self.notify(FailoverEvent::BackupReconnected);
+        }
+
+        match self.active {
+            FailoverOutput::Primary => {
+                if self.primary.check_agent().is_err() {
+                    if self.backup_connected {
+                        self.backup.set_async();
+                        // The idle agent waits for an update before it notices the mode change
+                        let _ = self.backup.update_async();
+                        self.active = FailoverOutput::Backup;
+                        self.notify(FailoverEvent::SwitchedToBackup);
+                    } else {
+                        // Nothing to switch to, so the primary is reopened in place
+                        self.retry_primary();
+                    }
+                }
+            },
+            FailoverOutput::Backup => {
+                if self.retry_primary() {
+                    self.backup.set_sync();
+                    self.active = FailoverOutput::Primary;
+                    self.notify(FailoverEvent::SwitchedToPrimary);
+                }
+            },
+        }
+        self.active
+    }
+
+    // Tries to reopen the primary interface, if the retry interval has passed
+    fn retry_primary(&mut self) -> bool {
+        if !Self::retry_due(&mut self.last_retry, self.retry_interval) {
+            return false;
+        }
+        self.primary.reopen().is_ok()
+    }
+
+    // Tries to reopen the backup interface, if the retry interval has passed
+    fn retry_backup(&mut self) -> bool {
+        if !Self::retry_due(&mut self.last_backup_retry, self.retry_interval) {
+            return false;
+        }
+        self.backup.reopen().is_ok()
+    }
+
+    fn retry_due(last_retry: &mut Option<time::Instant>, interval: time::Duration) -> bool {
+        if let Some(last) = *last_retry {
+            if last.elapsed() < interval {
+                return false;
+            }
+        }
+        *last_retry = Some(time::Instant::now());
+        true
+    }
+
+    fn notify(&mut self, event: FailoverEvent) {
+        // Receivers which got dropped are removed
+        self.events.retain(|tx| tx.send(event).is_ok());
+    }
+}
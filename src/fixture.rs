@@ -0,0 +1,273 @@
+use crate::check_valid_channel;
+use crate::color::Color;
+use crate::error::DMXChannelValidityError;
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::ops::Range;
+
+/// What a channel of a [`Fixture`] controls.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    Dimmer,
+    Red,
+    Green,
+    Blue,
+    White,
+    Amber,
+    Pan,
+    PanFine,
+    Tilt,
+    TiltFine,
+    Strobe,
+    /// Any other function *(e.g. gobos, macros or the fan speed)*.
+    Other,
+}
+
+impl Attribute {
+    /// Returns `true` if the attribute fades smoothly in a theatrical crossfade *(intensity, color and position)*, `false` if it snaps to its new value *(strobe and other functions)*.
+    ///
+    pub fn fades(&self) -> bool {
+        !matches!(self, Attribute::Strobe | Attribute::Other)
+    }
+
+    // Attributes which are driven to full by a highlight
+    fn is_highlighted(&self) -> bool {
+        matches!(self, Attribute::Dimmer | Attribute::Red | Attribute::Green | Attribute::Blue | Attribute::White)
+    }
+}
+
+/// A fixture which uses consecutive channels, starting at its address.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, DMXSerial, Fixture};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let par = Fixture::new("Par 1", 10, vec![Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+///     par.set(&mut dmx, Attribute::Red, 255).unwrap();
+/// }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    name: String,
+    // Name of the fixture type or mode, e.g. from an imported patch list
+    mode: Option<String>,
+    address: usize,
+    attributes: Vec<Attribute>,
+    white_balance: Color,
+}
+
+impl Fixture {
+    /// Creates a new [Fixture] at the given `address` with one channel per [`Attribute`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DMXChannelValidityError`] if the address isn't valid or the channels don't fit in the universe.
+    ///
+    pub fn new(name: &str, address: usize, attributes: Vec<Attribute>) -> Result<Fixture, DMXChannelValidityError> {
+        check_valid_channel(address)?;
+        check_valid_channel(address + attributes.len().max(1) - 1)?;
+        Ok(Fixture {
+            name: name.to_string(),
+            mode: None,
+            address,
+            attributes,
+            white_balance: Color::WHITE,
+        })
+    }
+
+    /// Returns the name of the [Fixture].
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name of the fixture type or mode *(e.g. `"RGBW 8ch"`)*, if it was set.
+    ///
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_deref()
+    }
+
+    /// Sets the name of the fixture type or mode. It's only used as a label, e.g. in exported patch lists.
+    ///
+    pub fn set_mode(&mut self, mode: &str) {
+        self.mode = Some(mode.to_string());
+    }
+
+    /// Returns the first channel of the [Fixture].
+    ///
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Moves the [Fixture] to another address.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DMXChannelValidityError`] if the address isn't valid or the channels don't fit in the universe. The address is kept in that case.
+    ///
+    pub fn set_address(&mut self, address: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(address)?;
+        check_valid_channel(address + self.attributes.len().max(1) - 1)?;
+        self.address = address;
+        Ok(())
+    }
+
+    /// Returns the [`Attribute`]s of the channels, starting at the address.
+    ///
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    /// Returns the number of channels the [Fixture] uses.
+    ///
+    pub fn footprint(&self) -> usize {
+        self.attributes.len()
+    }
+
+    /// Returns the channels the [Fixture] uses.
+    ///
+    pub fn channels(&self) -> Range<usize> {
+        self.address..self.address + self.footprint()
+    }
+
+    /// Returns the channel of the first [`Attribute`] of the given kind, or `None` if the [Fixture] doesn't have it.
+    ///
+    pub fn channel(&self, attribute: Attribute) -> Option<usize> {
+        self.attributes.iter().position(|a| *a == attribute).map(|offset| self.address + offset)
+    }
+
+    /// Returns the channels which control the intensity: the dimmer, or the color channels of fixtures without a dimmer.
+    ///
+    pub fn intensity_channels(&self) -> Vec<usize> {
+        if let Some(dimmer) = self.channel(Attribute::Dimmer) {
+            return vec![dimmer];
+        }
+        self.attributes.iter().enumerate()
+            .filter(|(_, attribute)| attribute.is_highlighted())
+            .map(|(offset, _)| self.address + offset)
+            .collect()
+    }
+
+    /// Sets the channel of the given [`Attribute`]. Does nothing if the [Fixture] doesn't have it.
+    ///
+    pub fn set(&self, dmx: &mut DMXSerial, attribute: Attribute, value: u8) -> Result<(), DMXChannelValidityError> {
+        match self.channel(attribute) {
+            Some(channel) => dmx.set_channel(channel, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the white balance of the [Fixture]. See [`Fixture::set_white_balance`].
+    ///
+    pub fn white_balance(&self) -> Color {
+        self.white_balance
+    }
+
+    /// Sets the gains of the red, green and blue channels *(`255` = unchanged)*, which calibrate the white point of the [Fixture].
+    ///
+    /// The gains are applied to every color set through [`Fixture::set_color`] and the pixel effects, so fixtures of different types show the same white *(e.g. a fixture with a strong blue emitter gets a lower blue gain)*.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use open_dmx::{Attribute, Color, DMXSerial, Fixture};
+    ///
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let mut wash = Fixture::new("Wash", 1, vec![Attribute::Red, Attribute::Green, Attribute::Blue, Attribute::White]).unwrap();
+    ///     wash.set_white_balance(Color::new(255, 240, 210));
+    ///     // A tungsten look which matches the other fixtures of the rig
+    ///     wash.set_color(&mut dmx, Color::from_temperature(3200)).unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn set_white_balance(&mut self, gains: Color) {
+        self.white_balance = gains;
+    }
+
+    /// Sets the color channels of the [Fixture], calibrated with its [white balance](Fixture::set_white_balance).
+    ///
+    /// Fixtures with a white channel show the part the red, green and blue have in common with it *(see [`Color::to_rgbw`])*. Channels the [Fixture] doesn't have are skipped.
+    ///
+    pub fn set_color(&self, dmx: &mut DMXSerial, color: Color) -> Result<(), DMXChannelValidityError> {
+        for (attribute, value) in self.color_values(color) {
+            self.set(dmx, attribute, value)?;
+        }
+        Ok(())
+    }
+
+    // Writes the color into a frame, e.g. from an effect
+    pub(crate) fn write_color(&self, channels: &mut [u8; DMX_CHANNELS], color: Color) {
+        for (attribute, value) in self.color_values(color) {
+            if let Some(channel) = self.channel(attribute) {
+                channels[channel - 1] = value;
+            }
+        }
+    }
+
+    fn color_values(&self, color: Color) -> Vec<(Attribute, u8)> {
+        let color = color.scale(self.white_balance);
+        let (color, white) = if self.channel(Attribute::White).is_some() {
+            color.to_rgbw()
+        } else {
+            (color, 0)
+        };
+        vec![(Attribute::Red, color.red), (Attribute::Green, color.green), (Attribute::Blue, color.blue), (Attribute::White, white)]
+    }
+
+    /// Drives the dimmer and the color channels of the [Fixture] to full, so it can be found in the rig *(e.g. for checking the address)*.
+    ///
+    /// The previous values are restored with [`Highlight::restore`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use open_dmx::{Attribute, DMXSerial, Fixture};
+    /// use std::time::Duration;
+    ///
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let par = Fixture::new("Par 1", 10, vec![Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+    ///     let highlight = par.highlight(&mut dmx);
+    ///     std::thread::sleep(Duration::from_secs(3));
+    ///     highlight.restore(&mut dmx);
+    /// }
+    /// ```
+    ///
+    pub fn highlight(&self, dmx: &mut DMXSerial) -> Highlight {
+        let channels = dmx.get_channels();
+        let saved: Vec<(usize, u8)> = self.attributes.iter().enumerate()
+            .filter(|(_, attribute)| attribute.is_highlighted())
+            .map(|(offset, _)| (self.address + offset, channels[self.address + offset - 1]))
+            .collect();
+        for (channel, _) in &saved {
+            // The channels were checked when the fixture was created
+            let _ = dmx.set_channel_tagged(*channel, 255, "highlight");
+        }
+        Highlight { saved }
+    }
+}
+
+/// The values of a highlighted [`Fixture`] before [`Fixture::highlight`] was called.
+///
+#[must_use = "the fixture stays highlighted until the highlight is restored"]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    // Channel and previous value of every highlighted channel
+    saved: Vec<(usize, u8)>,
+}
+
+impl Highlight {
+    /// Restores the values of the highlighted channels.
+    ///
+    pub fn restore(self, dmx: &mut DMXSerial) {
+        for (channel, value) in self.saved {
+            let _ = dmx.set_channel_tagged(channel, value, "highlight");
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::DMXSerial;
+use crate::DMX_CHANNELS;
+
+use std::io;
+
+// 1 start code + 512 channels
+const FRAME_SIZE: usize = DMX_CHANNELS + 1;
+
+/// An [`io::Write`] adapter which forwards raw **DMX frames** to a [DMXSerial].
+///
+/// Every `513` bytes written *(start code + 512 channels)* are sent as one frame by the agent, using the same break and packet timing as regular updates.
+/// Incomplete frames are buffered until the remaining bytes are written.
+///
+/// Each call to [`write`] returns once all completed frames have been sent, so the writer is paced by the [packet time].
+///
+/// [`io::Write`]: std::io::Write
+/// [`write`]: DmxFrameWriter::write
+/// [packet time]: DMXSerial::set_packet_time
+///
+/// # Example
+///
+/// Basic usage:
+///
+/// ```no_run
+/// # use open_dmx::{DMXSerial, DmxFrameWriter};
+/// # use std::io::Write;
+/// # fn main() {
+/// let dmx = DMXSerial::open("COM3").unwrap();
+/// let mut writer = DmxFrameWriter::new(&dmx);
+///
+/// let mut frame = [255; 513];
+/// frame[0] = 0; // null start code
+/// writer.write_all(&frame).unwrap();
+/// # }
+/// ```
+///
+#[derive(Debug)]
+pub struct DmxFrameWriter<'a> {
+    dmx: &'a DMXSerial,
+    buffer: Vec<u8>,
+}
+
+impl<'a> DmxFrameWriter<'a> {
+    /// Creates a new [DmxFrameWriter] which sends its frames over the given [DMXSerial].
+    ///
+    pub fn new(dmx: &'a DMXSerial) -> DmxFrameWriter<'a> {
+        DmxFrameWriter {
+            dmx,
+            buffer: Vec::with_capacity(FRAME_SIZE),
+        }
+    }
+
+    /// Returns the number of bytes of the current, incomplete frame.
+    ///
+    pub fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+impl io::Write for DmxFrameWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut remaining = buf;
+        while !remaining.is_empty() {
+            let missing = FRAME_SIZE - self.buffer.len();
+            let (head, tail) = remaining.split_at(missing.min(remaining.len()));
+            self.buffer.extend_from_slice(head);
+            remaining = tail;
+
+            if self.buffer.len() == FRAME_SIZE {
+                let frame = std::mem::replace(&mut self.buffer, Vec::with_capacity(FRAME_SIZE));
+                self.dmx.send_frame(frame).map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    /// Does nothing, since incomplete frames can't be sent.
+    ///
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+//! GPIO output pins on Linux via sysfs
+//!
+//! Used for driving the DE/RE pin of RS-485 transceivers on boards like the Raspberry Pi,
+//! where the native UART has no hardware direction control.
+
+use std::fs;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time;
+
+const GPIO_ROOT: &str = "/sys/class/gpio";
+
+/// An exported GPIO pin which is configured as an output.
+///
+#[derive(Debug)]
+pub struct GpioPin {
+    value: fs::File,
+}
+
+impl GpioPin {
+    /// Exports the given pin *(if it isn't already)* and sets it to a low output.
+    ///
+    pub fn open(pin: u32) -> io::Result<GpioPin> {
+        let dir = PathBuf::from(format!("{}/gpio{}", GPIO_ROOT, pin));
+        if !dir.exists() {
+            fs::write(format!("{}/export", GPIO_ROOT), pin.to_string())?;
+        }
+
+        // udev needs a moment to fix the permissions of a freshly exported pin
+        let mut tries = 0;
+        loop {
+            match fs::write(dir.join("direction"), "low") {
+                Ok(()) => break,
+                Err(e) if e.kind() == io::ErrorKind::PermissionDenied && tries < 10 => {
+                    tries += 1;
+                    thread::sleep(time::Duration::from_millis(10));
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        let value = fs::OpenOptions::new().write(true).open(dir.join("value"))?;
+        Ok(GpioPin { value })
+    }
+
+    /// Drives the pin `high` or low.
+    ///
+    pub fn set(&mut self, high: bool) -> io::Result<()> {
+        self.value.seek(SeekFrom::Start(0))?;
+        self.value.write_all(if high { b"1" } else { b"0" })
+    }
+}
+
+impl Drop for GpioPin {
+    fn drop(&mut self) {
+        // Leave the transceiver in receive mode
+        let _ = self.set(false);
+    }
+}
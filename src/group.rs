@@ -0,0 +1,168 @@
+use crate::fixture::{Attribute, Fixture};
+use crate::patch::Patch;
+
+use std::collections::BTreeMap;
+
+/// A selection of fixtures together with their universes, e.g. for targeting [effects](crate::Effect), [submasters](crate::Submaster) or cues symbolically instead of with hard-coded lists.
+///
+/// Groups are narrowed down with query methods, which keep the order of the fixtures.
+///
+/// # Example
+///
+/// ```
+/// use open_dmx::{Attribute, FixtureGroup, Patch};
+///
+/// let csv = "Name,Mode,Universe,Address\nPar 1,LED Par,1,1\nSpot 1,Spot,1,5\nPar 2,LED Par,2,1\n";
+/// let patches = Patch::import_csv(csv, |mode| match mode {
+///     "LED Par" => Some(vec![Attribute::Red, Attribute::Green, Attribute::Blue]),
+///     _ => Some(vec![Attribute::Dimmer, Attribute::Pan, Attribute::Tilt]),
+/// }).unwrap();
+/// let pars = FixtureGroup::from_patches(&patches).of_type("par").in_universe(1);
+/// assert_eq!(pars.names(), ["Par 1"]);
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixtureGroup {
+    // Same length, the universe of every fixture
+    universes: Vec<u16>,
+    fixtures: Vec<Fixture>,
+}
+
+impl FixtureGroup {
+    /// Creates an empty [FixtureGroup].
+    ///
+    pub fn new() -> FixtureGroup {
+        FixtureGroup::default()
+    }
+
+    /// Creates a [FixtureGroup] with all fixtures of the patches, ordered by universe.
+    ///
+    pub fn from_patches(patches: &BTreeMap<u16, Patch>) -> FixtureGroup {
+        let mut group = FixtureGroup::new();
+        for (universe, patch) in patches {
+            for fixture in patch.fixtures() {
+                group.push(*universe, fixture.clone());
+            }
+        }
+        group
+    }
+
+    /// Adds a fixture of the given universe at the end.
+    ///
+    pub fn push(&mut self, universe: u16, fixture: Fixture) {
+        self.universes.push(universe);
+        self.fixtures.push(fixture);
+    }
+
+    /// Keeps the fixtures whose type or mode *(see [`Fixture::mode`])* contains `kind`, ignoring the case.
+    ///
+    pub fn of_type(self, kind: &str) -> FixtureGroup {
+        let kind = kind.to_lowercase();
+        self.filter(|_, fixture| fixture.mode().is_some_and(|mode| mode.to_lowercase().contains(&kind)))
+    }
+
+    /// Keeps the fixtures whose name contains `name`, ignoring the case.
+    ///
+    pub fn named(self, name: &str) -> FixtureGroup {
+        let name = name.to_lowercase();
+        self.filter(|_, fixture| fixture.name().to_lowercase().contains(&name))
+    }
+
+    /// Keeps the fixtures of the universe.
+    ///
+    pub fn in_universe(self, universe: u16) -> FixtureGroup {
+        self.filter(|fixture_universe, _| fixture_universe == universe)
+    }
+
+    /// Keeps the fixtures which have the [`Attribute`].
+    ///
+    pub fn with_attribute(self, attribute: Attribute) -> FixtureGroup {
+        self.filter(|_, fixture| fixture.channel(attribute).is_some())
+    }
+
+    /// Keeps the fixtures for which `keep` returns `true`. It gets the universe and the fixture.
+    ///
+    pub fn filter<F: Fn(u16, &Fixture) -> bool>(self, keep: F) -> FixtureGroup {
+        let mut group = FixtureGroup::new();
+        for (universe, fixture) in self.universes.into_iter().zip(self.fixtures) {
+            if keep(universe, &fixture) {
+                group.push(universe, fixture);
+            }
+        }
+        group
+    }
+
+    /// Returns the fixtures, e.g. for creating an effect.
+    ///
+    pub fn fixtures(&self) -> &[Fixture] {
+        &self.fixtures
+    }
+
+    /// Returns the names of the fixtures.
+    ///
+    pub fn names(&self) -> Vec<&str> {
+        self.fixtures.iter().map(Fixture::name).collect()
+    }
+
+    /// Returns the universe and the fixture of every member.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &Fixture)> {
+        self.universes.iter().copied().zip(self.fixtures.iter())
+    }
+
+    /// Returns the number of fixtures.
+    ///
+    pub fn len(&self) -> usize {
+        self.fixtures.len()
+    }
+
+    /// Returns `true` if the group has no fixtures.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.fixtures.is_empty()
+    }
+}
+
+impl Patch {
+    /// Returns a [`FixtureGroup`] with all fixtures of the [Patch], which is patched to the given universe.
+    ///
+    pub fn group(&self, universe: u16) -> FixtureGroup {
+        let mut group = FixtureGroup::new();
+        for fixture in self.fixtures() {
+            group.push(universe, fixture.clone());
+        }
+        group
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Attribute, Fixture, Patch};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn fixture_groups_are_queried_from_the_patch() {
+        let fixture = |name: &str, mode: &str, address: usize, attributes: Vec<Attribute>| {
+            let mut fixture = Fixture::new(name, address, attributes).unwrap();
+            fixture.set_mode(mode);
+            fixture
+        };
+        let mut first = Patch::new();
+        first.add(fixture("Par 1", "LED PAR RGB", 1, vec![Attribute::Red, Attribute::Green, Attribute::Blue])).unwrap();
+        first.add(fixture("Spot 1", "Spot 575", 4, vec![Attribute::Dimmer, Attribute::Pan, Attribute::Tilt])).unwrap();
+        first.add(fixture("Par 2", "LED Par RGB", 7, vec![Attribute::Red, Attribute::Green, Attribute::Blue])).unwrap();
+        let mut second = Patch::new();
+        second.add(fixture("Par 3", "LED Par RGB", 1, vec![Attribute::Red, Attribute::Green, Attribute::Blue])).unwrap();
+        let patches = BTreeMap::from([(1, first.clone()), (2, second)]);
+
+        let all = FixtureGroup::from_patches(&patches);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all.clone().of_type("par").names(), ["Par 1", "Par 2", "Par 3"]);
+        assert_eq!(all.clone().of_type("par").in_universe(1).names(), ["Par 1", "Par 2"]);
+        assert_eq!(all.clone().with_attribute(Attribute::Pan).names(), ["Spot 1"]);
+        assert_eq!(all.clone().named("2").iter().map(|(universe, fixture)| (universe, fixture.address())).collect::<Vec<_>>(), [(1, 7)]);
+        assert!(all.clone().of_type("strobe").is_empty());
+        assert_eq!(first.group(5).filter(|universe, fixture| universe == 5 && fixture.address() > 1).fixtures().len(), 2);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::DMX_CHANNELS;
+
+use std::time;
+
+/// What happens when the channel values haven't been changed for a while. See [`DMXSerial::set_idle_policy`].
+///
+/// After the `timeout`, the output fades to the `look` over the `fade` time.
+/// The next change restores the output immediately.
+///
+/// [`DMXSerial::set_idle_policy`]: crate::DMXSerial::set_idle_policy
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlePolicy {
+    pub timeout: time::Duration,
+    pub fade: time::Duration,
+    pub look: [u8; DMX_CHANNELS],
+}
+
+impl IdlePolicy {
+    /// Fades to blackout after the `timeout`.
+    ///
+    pub fn blackout(timeout: time::Duration, fade: time::Duration) -> IdlePolicy {
+        IdlePolicy::look(timeout, fade, [0; DMX_CHANNELS])
+    }
+
+    /// Fades to the given `look` *(e.g. a house look)* after the `timeout`.
+    ///
+    pub fn look(timeout: time::Duration, fade: time::Duration, look: [u8; DMX_CHANNELS]) -> IdlePolicy {
+        IdlePolicy { timeout, fade, look }
+    }
+}
+
+// Tracks the last change of the set values in the agent
+#[derive(Debug)]
+pub(crate) struct IdleTracker {
+    last_values: [u8; DMX_CHANNELS],
+    last_change: time::Instant,
+}
+
+impl IdleTracker {
+    pub fn new(now: time::Instant) -> IdleTracker {
+        IdleTracker {
+            last_values: [0; DMX_CHANNELS],
+            last_change: now,
+        }
+    }
+
+    // Fades the output towards the look of the policy. Returns true if the output is idle
+    pub fn apply(&mut self, policy: Option<&IdlePolicy>, values: &[u8; DMX_CHANNELS], output: &mut [u8; DMX_CHANNELS], now: time::Instant) -> bool {
+        if *values != self.last_values {
+            self.last_values = *values;
+            self.last_change = now;
+        }
+        let Some(policy) = policy else {
+            return false;
+        };
+        let Some(idle_time) = now.saturating_duration_since(self.last_change).checked_sub(policy.timeout) else {
+            return false;
+        };
+
+        let progress = if policy.fade.is_zero() {
+            1.0
+        } else {
+            (idle_time.as_secs_f32() / policy.fade.as_secs_f32()).min(1.0)
+        };
+        output.iter_mut().zip(policy.look.iter()).for_each(|(value, target)| {
+            *value = (*value as f32 + (*target as f32 - *value as f32) * progress).round() as u8;
+        });
+        true
+    }
+}
@@ -0,0 +1,124 @@
+use crate::merge::DMXWriter;
+use crate::DMX_CHANNELS;
+
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time;
+
+// How often the listener checks if it should stop
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(200);
+
+// Receives universes from other processes and merges them with a writer. Stops when it's dropped
+#[derive(Debug)]
+pub(crate) struct SocketListener {
+    stop: Arc<AtomicBool>,
+}
+
+impl SocketListener {
+    pub fn bind(path: &Path, writer: DMXWriter) -> io::Result<SocketListener> {
+        // A socket file left over from a crashed process would make binding fail
+        if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            fs::remove_file(path)?;
+        }
+        let socket = UnixDatagram::bind(path)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let path = path.to_path_buf();
+        let _ = thread::spawn(move || {
+            let mut buffer = [0; DMX_CHANNELS + 1];
+            while !stopped.load(Ordering::Relaxed) {
+                match socket.recv(&mut buffer) {
+                    Ok(0) => writer.release_all(),
+                    // Longer datagrams are truncated by the buffer and ignored
+                    Ok(len) if len <= DMX_CHANNELS => writer.set_channel_range(1, &buffer[..len]),
+                    Ok(_) => {},
+                    Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted) => {},
+                    Err(e) => {
+                        eprintln!("Stopped receiving DMX data on \"{}\": \"{}\"", path.display(), e);
+                        break;
+                    },
+                }
+            }
+            // The sender of another process could still be connected, but there's no one left to read
+            writer.release_all();
+            let _ = fs::remove_file(&path);
+        });
+        Ok(SocketListener { stop })
+    }
+}
+
+impl Drop for SocketListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Sends channel values from another process to a [DMXSerial] listening with [`DMXSerial::listen_on_socket`]. *(Unix only)*
+///
+/// Every [`UniverseSender::send`] is a single datagram, so a crashing sender can't leave a half written universe behind.
+///
+/// [DMXSerial]: crate::DMXSerial
+/// [`DMXSerial::listen_on_socket`]: crate::DMXSerial::listen_on_socket
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::UniverseSender;
+///
+/// fn main() {
+///     let sender = UniverseSender::connect("/run/open_dmx.sock").unwrap();
+///     sender.send(&[255; 512]).unwrap();
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct UniverseSender {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl UniverseSender {
+    /// Connects to the socket of a listening [DMXSerial].
+    ///
+    /// [DMXSerial]: crate::DMXSerial
+    ///
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<UniverseSender> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&path)?;
+        Ok(UniverseSender {
+            socket,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Sets the channels from `1` up to the length of the given values. Longer slices than [`DMX_CHANNELS`] are rejected.
+    ///
+    pub fn send(&self, channels: &[u8]) -> io::Result<()> {
+        if channels.is_empty() || channels.len() > DMX_CHANNELS {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("expected 1 to {} channel values", DMX_CHANNELS)));
+        }
+        self.socket.send(channels)?;
+        Ok(())
+    }
+
+    /// Releases all channels, so only the values of the other writers are sent.
+    ///
+    pub fn release(&self) -> io::Result<()> {
+        self.socket.send(&[])?;
+        Ok(())
+    }
+
+    /// Returns the path of the socket.
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
@@ -0,0 +1,49 @@
+use crate::DMX_CHANNELS;
+
+use std::collections::VecDeque;
+use std::time;
+
+// Timestamped universes, which the agent interpolates between
+#[derive(Debug, Default)]
+pub(crate) struct Keyframes {
+    // Sorted by time
+    frames: VecDeque<(time::Instant, [u8; DMX_CHANNELS])>,
+}
+
+impl Keyframes {
+    pub fn insert(&mut self, universe: [u8; DMX_CHANNELS], at: time::Instant) {
+        // Keyframes usually arrive in order, so the search starts at the back
+        let index = self.frames.iter().rposition(|(time, _)| *time <= at).map_or(0, |index| index + 1);
+        self.frames.insert(index, (at, universe));
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Replaces the channels with the interpolated keyframes. Before the first keyframe, the channels are kept
+    pub fn apply(&mut self, now: time::Instant, channels: &mut [u8; DMX_CHANNELS]) {
+        // Keyframes are only needed until the next one is due
+        while self.frames.len() >= 2 && self.frames[1].0 <= now {
+            self.frames.pop_front();
+        }
+        let Some((from_time, from)) = self.frames.front() else {
+            return;
+        };
+        if *from_time > now {
+            return;
+        }
+        let Some((to_time, to)) = self.frames.get(1) else {
+            *channels = *from;
+            return;
+        };
+        let progress = now.duration_since(*from_time).as_secs_f32() / to_time.duration_since(*from_time).as_secs_f32();
+        channels.iter_mut().zip(from.iter().zip(to.iter())).for_each(|(value, (from, to))| {
+            *value = (*from as f32 + (*to as f32 - *from as f32) * progress).round() as u8;
+        });
+    }
+}
@@ -0,0 +1,119 @@
+use crate::builder::LineSettings;
+use crate::transport::Transport;
+use crate::DMX_CHANNELS;
+
+use std::io;
+use std::net::{Ipv4Addr, ToSocketAddrs, UdpSocket};
+
+// Header of a KiNET v1 DMXOUT packet: magic number, version, type, sequence, port, flags, timer and universe
+const DMXOUT_HEADER: [u8; 20] = [
+    0x04, 0x01, 0xDC, 0x4A,
+    0x01, 0x00,
+    0x01, 0x01,
+    0x00, 0x00, 0x00, 0x00,
+    0x00,
+    0x00,
+    0x00, 0x00,
+    0xFF, 0xFF, 0xFF, 0xFF,
+];
+
+/// A [`Transport`] which sends the **DMX frames** to a Philips Color Kinetics power supply using **KiNET** *(v1 DMXOUT)* instead of writing them to a [SerialPort].
+///
+/// Every frame the agent sends becomes one UDP packet, so the [packet time] sets the refresh rate of the power supply.
+///
+/// [SerialPort]: serialport::SerialPort
+/// [packet time]: crate::DMXSerial::set_packet_time
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, KinetOutput};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::builder("kinet")
+///         .open_with_transport(KinetOutput::new("10.0.0.20").unwrap())
+///         .unwrap();
+///     dmx.set_channels([255; 512]);
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct KinetOutput {
+    socket: UdpSocket,
+    frames: u64,
+}
+
+impl KinetOutput {
+    /// UDP port of KiNET power supplies.
+    ///
+    pub const PORT: u16 = 6038;
+
+    /// Creates a [KinetOutput] which sends to the power supply at the given host. [`KinetOutput::PORT`] is used if the address doesn't contain a port.
+    ///
+    pub fn new(host: &str) -> io::Result<KinetOutput> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        match (host, KinetOutput::PORT).to_socket_addrs() {
+            Ok(mut addrs) => socket.connect(addrs.next().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address for the host"))?)?,
+            Err(_) => socket.connect(host)?,
+        }
+        Ok(KinetOutput {
+            socket,
+            frames: 0,
+        })
+    }
+
+    /// Returns the number of frames sent so far.
+    ///
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+// KiNET v1 DMXOUT packet of a frame (start code + channels)
+fn encode_dmxout(frame: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(DMXOUT_HEADER.len() + frame.len());
+    packet.extend_from_slice(&DMXOUT_HEADER);
+    packet.extend_from_slice(frame);
+    packet
+}
+
+impl io::Write for KinetOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The agent writes every frame at once
+        if !buf.is_empty() && buf.len() <= DMX_CHANNELS + 1 {
+            self.socket.send(&encode_dmxout(buf))?;
+            self.frames += 1;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for KinetOutput {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        true
+    }
+}
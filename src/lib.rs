@@ -6,7 +6,7 @@
 //! 
 //! ## Usage
 //! 
-//! ```rust	
+//! ```rust,no_run
 //! use open_dmx::DMXSerial;
 //! 
 //! fn main() {
@@ -20,7 +20,20 @@
 //!
 //! ## Feature flags
 //! 
-//! - `thread_priority` *(enabled by default)*- Tries to set the [thread] priority of the [SerialPort] to *`MAX`*
+//! - `thread_priority` *(enabled by default)*- Tries to set the [thread] priority of the [SerialPort] to *`MAX`*. The priority and the handling of failures can be configured with the [`DMXSerialBuilder`]
+//! - `cpu_affinity` - Allows pinning the [thread] to a CPU core with [`DMXSerialBuilder::cpu_affinity`]
+//! - `config` - Loads the interface settings, a patch, curves and scenes from a TOML or JSON file and reloads them live with the [`ConfigWatcher`]
+//! - `cli` - Builds the `open-dmx-cli` binary for setting channels and replaying recordings from the command line *(e.g. `open-dmx-cli --port /dev/ttyUSB0 set 1 255`)*
+//! - `tui` - Adds the `edit` command to the `open-dmx-cli` binary, which shows all channels live and edits them with the arrow keys
+//! - `daemon` - Shares one interface with several applications over a Unix socket with the [`DMXDaemon`] and the [`DaemonClient`]
+//! - `tracing` - Wraps every sent frame in a [`tracing`] span with its sequence number, size, break and frame time *(in µs)*
+//! - `metrics` - Reports sent frames and bytes, the frame interval, channel changes, errors and reconnects to the [`metrics`] facade, labeled with the port *(e.g. for a Prometheus exporter)*
+//! - `image` - Renders a [`Universe`] as a heatmap image with [`Universe::to_image`], e.g. for saving it as PNG
+//! - `kinet` - Sends the frames to Philips Color Kinetics power supplies over the network with the [`KinetOutput`] transport
+//! - `eurolite` - Drives Eurolite USB-DMX512 PRO interfaces with the [`EuroliteProOutput`] transport
+//! - `dmxking` - Sends one universe per output port of DMXKing widgets *(e.g. the ultraDMX Pro)* with the [`DmxKingWidget`]
+//! - `udmx` - Drives uDMX *(Anyma)* USB dongles with the [`UdmxOutput`] transport *(needs libusb)*
+//! - `zstd` - Compresses recordings with [`RecordingWriter::compressed`](recording::RecordingWriter::compressed)
 //! 
 //! [**serial**]: https://dcuddeback.github.io/serial-rs/serial/
 //! [SerialPort]: https://dcuddeback.github.io/serial-rs/serial_core/trait.SerialPort
@@ -31,8 +44,190 @@ pub mod error;
 mod dmx_serial;
 pub use dmx_serial::*;
 
+mod builder;
+pub use builder::{BreakMode, DirectionControl, DirectionTiming, DMXSerialBuilder, LineSettings};
+pub use serialport::{DataBits, Parity, StopBits};
+
+mod adapter;
+pub use adapter::{AdapterChip, AdapterInfo};
+
+pub mod codec;
+
+mod universe;
+pub use universe::Universe;
+
+pub mod recording;
+
+mod player;
+pub use player::Player;
+
+mod multi_player;
+pub use multi_player::MultiPlayer;
+
+pub mod artnet;
+
+pub mod sacn;
+
+pub mod sim;
+
+mod port_path;
+
+mod transport;
+pub use transport::Transport;
+
+mod rfc2217;
+pub use rfc2217::Rfc2217Port;
+
+mod clock;
+pub use clock::{Clock, ManualClock, SystemClock};
+
+mod offline;
+pub use offline::OfflineRenderer;
+
+mod driver;
+pub use driver::{Action, DMXDriver};
+
+mod transform;
+pub use transform::{Blackout, Curve, FrameTransform, Limit, MasterDimmer, Solo, Submaster, TransformId};
+
+mod merge;
+pub use merge::{DMXWriter, MergePolicy};
+
+mod fixture;
+pub use fixture::{Attribute, Fixture, Highlight};
+
+mod color;
+pub use color::{Color, ColorPalette, ColorSpace};
+
+mod position;
+pub use position::{MotionLimiter, MoverRange, Position};
+
+mod patch;
+pub use patch::{AutoAddress, Patch};
+
+mod patch_csv;
+
+mod group;
+pub use group::FixtureGroup;
+
+#[cfg(unix)]
+mod ipc;
+#[cfg(unix)]
+pub use ipc::UniverseSender;
+
+#[cfg(all(unix, feature = "daemon"))]
+mod daemon;
+#[cfg(all(unix, feature = "daemon"))]
+pub use daemon::{DaemonClient, DMXDaemon};
+
+mod coalesce;
+
+mod idle;
+pub use idle::IdlePolicy;
+
+mod audit;
+pub use audit::ChannelWrite;
+
+mod changes;
+pub use changes::ChannelChange;
+
+mod failover;
+pub use failover::{DMXFailover, FailoverEvent, FailoverOutput};
+
+#[cfg(feature = "metrics")]
+mod telemetry;
+
+mod retry;
+pub use retry::{port_holders, PortHolder, RetryPolicy};
+
+mod once;
+pub use once::send_once;
+
+mod persist;
+
+mod keyframe;
+
+mod crossfade;
+pub use crossfade::{Crossfade, Crossfader, Easing};
+
+mod cue_stack;
+pub use cue_stack::{Cue, CueEvent, CueStack};
+
+mod preset;
+pub use preset::{Preset, PresetPool};
+
+mod ramp;
+
+mod safety;
+pub use safety::{BlockedOutput, SafetyInterlock, SlewLimit, StrobeGuard};
+
+mod render;
+pub use render::FrameContext;
+
+mod effect;
+pub use effect::{Direction, Effect, Oscillator, PixelEffect, PixelPattern, RandomLevels, Sparkle, Waveform};
+
+mod selftest;
+pub use selftest::{SelfTestIssue, SelfTestReport};
+
+mod shutdown;
+pub use shutdown::ShutdownSequence;
+#[cfg(target_os = "linux")]
+pub use shutdown::TerminationSignal;
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "config")]
+pub use config::{Config, ConfigWatcher, CurveConfig, InterfaceConfig};
+
+mod schedule;
+pub use schedule::{Location, LocationProvider, ScheduleId, Scheduler, TimeOfDay, Trigger};
+
+mod timecode;
+pub use timecode::{CueId, FrameRate, MtcDecoder, Timecode, TimecodeCueList, TimecodeSource};
+
+mod simulator;
+pub use simulator::SimulatorOutput;
+
+#[cfg(any(feature = "eurolite", feature = "dmxking"))]
+mod widget;
+
+#[cfg(feature = "eurolite")]
+mod eurolite;
+#[cfg(feature = "eurolite")]
+pub use eurolite::EuroliteProOutput;
+
+#[cfg(feature = "dmxking")]
+mod dmxking;
+#[cfg(feature = "dmxking")]
+pub use dmxking::{DmxKingOutput, DmxKingWidget};
+
+#[cfg(feature = "kinet")]
+mod kinet;
+#[cfg(feature = "kinet")]
+pub use kinet::KinetOutput;
+
+#[cfg(feature = "udmx")]
+mod udmx;
+#[cfg(feature = "udmx")]
+pub use udmx::UdmxOutput;
+
+mod frame_writer;
+pub use frame_writer::DmxFrameWriter;
+
 mod thread;
 
+#[cfg(feature = "thread_priority")]
+mod priority;
+#[cfg(feature = "thread_priority")]
+pub use priority::{AgentPriority, PriorityFailure};
+
+#[cfg(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64"))))]
+mod termios2;
+
+#[cfg(target_os = "linux")]
+mod gpio;
+
 
 
 
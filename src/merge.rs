@@ -0,0 +1,212 @@
+use crate::check_valid_channel;
+use crate::error::DMXChannelValidityError;
+use crate::DMX_CHANNELS;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+
+// Orders all writes for LTP merging. 0 means never written
+static WRITE_SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+fn next_sequence() -> u64 {
+    WRITE_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+}
+
+/// How the values of several [`DMXWriter`]s are combined into one packet. See [`DMXSerial::set_merge_policy`].
+///
+/// The values set directly on the [DMXSerial] take part as a writer with priority `0`, which has set all channels.
+///
+/// [DMXSerial]: crate::DMXSerial
+/// [`DMXSerial::set_merge_policy`]: crate::DMXSerial::set_merge_policy
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Highest takes precedence, the highest value of all writers is sent. *(default)*
+    #[default]
+    Htp,
+    /// Latest takes precedence, the most recently written value is sent.
+    ///
+    /// Changes of the values on the [DMXSerial] count from the first packet they're sent with.
+    Ltp,
+    /// The writer with the highest priority which has set the channel wins. Equal priorities are merged with HTP.
+    Priority,
+}
+
+#[derive(Debug)]
+struct WriterState {
+    priority: u8,
+    values: [u8; DMX_CHANNELS],
+    // Sequence number of the last write of every channel, 0 if the channel is released
+    written: [u64; DMX_CHANNELS],
+}
+
+/// An additional source of channel values for a [DMXSerial], created with [`DMXSerial::writer`].
+///
+/// The values of all writers are merged with the [`MergePolicy`] right before every packet is sent,
+/// so independent parts of an application can share one interface without coordinating.
+///
+/// Clones share the same values. The writer is removed once all clones are dropped.
+///
+/// [DMXSerial]: crate::DMXSerial
+/// [`DMXSerial::writer`]: crate::DMXSerial::writer
+///
+#[derive(Debug, Clone)]
+pub struct DMXWriter {
+    state: Arc<RwLock<WriterState>>,
+}
+
+impl DMXWriter {
+    pub(crate) fn new(priority: u8) -> DMXWriter {
+        DMXWriter {
+            state: Arc::new(RwLock::new(WriterState {
+                priority,
+                values: [0; DMX_CHANNELS],
+                written: [0; DMX_CHANNELS],
+            })),
+        }
+    }
+
+    /// Sets the specified [`channel`] to the given [`value`].
+    ///
+    /// [`channel`]: usize
+    /// [`value`]: u8
+    ///
+    pub fn set_channel(&self, channel: usize, value: u8) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        // RwLock can be unwrapped here
+        let mut state = self.state.write().unwrap();
+        state.values[channel - 1] = value;
+        state.written[channel - 1] = next_sequence();
+        Ok(())
+    }
+
+    /// Sets all channels to the given values.
+    ///
+    pub fn set_channels(&self, channels: [u8; DMX_CHANNELS]) {
+        // RwLock can be unwrapped here
+        let mut state = self.state.write().unwrap();
+        state.values = channels;
+        state.written = [next_sequence(); DMX_CHANNELS];
+    }
+
+    // Sets the channels starting at the first one at once. The range has to be checked before
+    pub(crate) fn set_channel_range(&self, first: usize, values: &[u8]) {
+        let range = first - 1..first - 1 + values.len();
+        let sequence = next_sequence();
+        // RwLock can be unwrapped here
+        let mut state = self.state.write().unwrap();
+        state.values[range.clone()].copy_from_slice(values);
+        state.written[range].fill(sequence);
+    }
+
+    /// Returns the value of the specified [`channel`], or `None` if the writer hasn't set it.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn get_channel(&self, channel: usize) -> Result<Option<u8>, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        // RwLock can be unwrapped here
+        let state = self.state.read().unwrap();
+        Ok((state.written[channel - 1] != 0).then_some(state.values[channel - 1]))
+    }
+
+    /// Releases the specified [`channel`], so the writer doesn't take part in merging it anymore.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn release_channel(&self, channel: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        // RwLock can be unwrapped here
+        self.state.write().unwrap().written[channel - 1] = 0;
+        Ok(())
+    }
+
+    /// Releases all channels.
+    ///
+    pub fn release_all(&self) {
+        // RwLock can be unwrapped here
+        self.state.write().unwrap().written = [0; DMX_CHANNELS];
+    }
+
+    /// Sets the priority used with [`MergePolicy::Priority`].
+    ///
+    pub fn set_priority(&self, priority: u8) {
+        // RwLock can be unwrapped here
+        self.state.write().unwrap().priority = priority;
+    }
+
+    /// Returns the priority used with [`MergePolicy::Priority`].
+    ///
+    pub fn priority(&self) -> u8 {
+        // RwLock can be unwrapped here
+        self.state.read().unwrap().priority
+    }
+
+    pub(crate) fn downgrade(&self) -> WriterRef {
+        WriterRef(Arc::downgrade(&self.state))
+    }
+}
+
+// Reference to a writer which doesn't keep it alive
+#[derive(Debug, Clone)]
+pub(crate) struct WriterRef(Weak<RwLock<WriterState>>);
+
+impl WriterRef {
+    pub fn is_alive(&self) -> bool {
+        self.0.strong_count() > 0
+    }
+}
+
+// Merges the writers into the values of the interface in the agent
+#[derive(Debug)]
+pub(crate) struct Merger {
+    // The interface values of the last packet and when they changed
+    values: [u8; DMX_CHANNELS],
+    written: [u64; DMX_CHANNELS],
+}
+
+impl Merger {
+    pub fn new() -> Merger {
+        Merger {
+            values: [0; DMX_CHANNELS],
+            written: [0; DMX_CHANNELS],
+        }
+    }
+
+    pub fn apply(&mut self, policy: MergePolicy, writers: &[WriterRef], channels: &mut [u8; DMX_CHANNELS]) {
+        // Changes of the interface values are only noticed here, so they're ordered by packet
+        for ((value, last), written) in channels.iter().zip(self.values.iter_mut()).zip(self.written.iter_mut()) {
+            if value != last {
+                *last = *value;
+                *written = next_sequence();
+            }
+        }
+        let writers: Vec<Arc<RwLock<WriterState>>> = writers.iter().filter_map(|writer| writer.0.upgrade()).collect();
+        if writers.is_empty() {
+            return;
+        }
+        // RwLock can be unwrapped here
+        let states: Vec<_> = writers.iter().map(|writer| writer.read().unwrap()).collect();
+
+        for (index, value) in channels.iter_mut().enumerate() {
+            let mut priority = 0;
+            let mut written = self.written[index];
+            for state in states.iter().filter(|state| state.written[index] != 0) {
+                let candidate = state.values[index];
+                match policy {
+                    MergePolicy::Htp => *value = (*value).max(candidate),
+                    MergePolicy::Ltp => if state.written[index] > written {
+                        written = state.written[index];
+                        *value = candidate;
+                    },
+                    MergePolicy::Priority => if state.priority > priority {
+                        priority = state.priority;
+                        *value = candidate;
+                    } else if state.priority == priority {
+                        *value = (*value).max(candidate);
+                    },
+                }
+            }
+        }
+    }
+}
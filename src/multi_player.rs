@@ -0,0 +1,218 @@
+use crate::clock::Clock;
+use crate::error::DMXDisconnectionError;
+use crate::universe::Universe;
+use crate::DMXSerial;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time;
+
+/// Plays **pre-rendered frame sequences** of several universes *(e.g. from the [`OfflineRenderer`](crate::OfflineRenderer) or external tools)* in sync.
+///
+/// Each universe is routed to its own [DMXSerial], while playing, pausing, seeking and looping apply to all of them.
+/// Like the [`Player`](crate::Player), it doesn't run on its own: [`MultiPlayer::poll`] sends the frames which are due and should be called at least once per frame time.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, MultiPlayer, OfflineRenderer};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let frame_time = Duration::from_millis(40);
+///     let mut renderer = OfflineRenderer::new().unwrap();
+///     renderer.dmx().set_channels([255; 512]);
+///     let frames = renderer.render(Duration::from_secs(60), frame_time).unwrap();
+///
+///     let mut player = MultiPlayer::new();
+///     player.add_universe(1, frame_time, frames);
+///     player.route(1, DMXSerial::open("/dev/ttyUSB0").unwrap());
+///     player.play();
+///     while player.poll().unwrap() {
+///         std::thread::sleep(frame_time / 2);
+///     }
+/// }
+/// ```
+///
+pub struct MultiPlayer {
+    sequences: BTreeMap<u16, Sequence>,
+    routes: BTreeMap<u16, DMXSerial>,
+    // Position when paused or when playing was started
+    position: time::Duration,
+    playing_since: Option<time::Instant>,
+    looping: bool,
+    clock: Arc<dyn Clock>,
+}
+
+struct Sequence {
+    frame_time: time::Duration,
+    frames: Vec<Universe>,
+    // Index of the frame which was sent last
+    sent: Option<usize>,
+}
+
+impl Sequence {
+    fn duration(&self) -> time::Duration {
+        self.frame_time * self.frames.len() as u32
+    }
+}
+
+impl MultiPlayer {
+    /// Creates a new, paused [MultiPlayer] without universes.
+    ///
+    pub fn new() -> MultiPlayer {
+        MultiPlayer::with_clock(crate::SystemClock)
+    }
+
+    /// Creates a new, paused [MultiPlayer] which follows the given [`Clock`].
+    ///
+    pub fn with_clock<C: Clock + 'static>(clock: C) -> MultiPlayer {
+        MultiPlayer {
+            sequences: BTreeMap::new(),
+            routes: BTreeMap::new(),
+            position: time::Duration::ZERO,
+            playing_since: None,
+            looping: false,
+            clock: Arc::new(clock),
+        }
+    }
+
+    /// Adds the frames of a universe, one every `frame_time` from the start. Replaces the frames the universe had before.
+    ///
+    /// After its last frame, a universe keeps showing it until the longest sequence has ended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `frame_time` is `0`.
+    ///
+    pub fn add_universe(&mut self, universe: u16, frame_time: time::Duration, frames: Vec<Universe>) {
+        assert!(!frame_time.is_zero(), "the frame time must not be 0");
+        self.sequences.insert(universe, Sequence { frame_time, frames, sent: None });
+    }
+
+    /// Removes the frames of a universe. Returns `false` if it had none.
+    ///
+    pub fn remove_universe(&mut self, universe: u16) -> bool {
+        self.sequences.remove(&universe).is_some()
+    }
+
+    /// Routes a universe to the given [DMXSerial] *(e.g. a [clone](DMXSerial::try_clone))*. Returns the [DMXSerial] it was routed to before.
+    ///
+    /// Universes without a route are played silently.
+    ///
+    pub fn route(&mut self, universe: u16, dmx: DMXSerial) -> Option<DMXSerial> {
+        if let Some(sequence) = self.sequences.get_mut(&universe) {
+            // The new output gets the current frame with the next poll
+            sequence.sent = None;
+        }
+        self.routes.insert(universe, dmx)
+    }
+
+    /// Removes the route of a universe and returns its [DMXSerial].
+    ///
+    pub fn unroute(&mut self, universe: u16) -> Option<DMXSerial> {
+        self.routes.remove(&universe)
+    }
+
+    /// Returns the universes with frames, in ascending order.
+    ///
+    pub fn universes(&self) -> Vec<u16> {
+        self.sequences.keys().copied().collect()
+    }
+
+    /// Returns the length of the longest sequence.
+    ///
+    pub fn duration(&self) -> time::Duration {
+        self.sequences.values().map(Sequence::duration).max().unwrap_or_default()
+    }
+
+    /// Starts or resumes playing at the current position.
+    ///
+    pub fn play(&mut self) {
+        if self.playing_since.is_none() {
+            self.playing_since = Some(self.clock.now());
+        }
+    }
+
+    /// Pauses playing. The last frames stay on the outputs.
+    ///
+    pub fn pause(&mut self) {
+        self.position = self.position();
+        self.playing_since = None;
+    }
+
+    /// Returns `true` if the player is playing.
+    ///
+    pub fn is_playing(&self) -> bool {
+        self.playing_since.is_some()
+    }
+
+    /// Starts over from the beginning when the end is reached, if `looping` is `true`. Disabled by default.
+    ///
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Returns the current position, within the [duration](MultiPlayer::duration) while looping.
+    ///
+    pub fn position(&self) -> time::Duration {
+        let position = self.position + self.playing_since.map_or(time::Duration::ZERO, |since| self.clock.now().saturating_duration_since(since));
+        let duration = self.duration();
+        if self.looping && !duration.is_zero() {
+            time::Duration::from_nanos((position.as_nanos() % duration.as_nanos()) as u64)
+        } else {
+            position
+        }
+    }
+
+    /// Jumps to the given position in all universes. Works while playing and while paused, the frames at the position are sent with the next poll.
+    ///
+    pub fn seek(&mut self, position: time::Duration) {
+        self.position = position;
+        if self.playing_since.is_some() {
+            self.playing_since = Some(self.clock.now());
+        }
+    }
+
+    /// Sends the frame which is due at the current position to every routed universe, if it wasn't sent yet.
+    ///
+    /// Returns `false` once the end of the longest sequence was reached *(never while looping)*.
+    ///
+    pub fn poll(&mut self) -> Result<bool, DMXDisconnectionError> {
+        let position = self.position();
+        for (universe, sequence) in self.sequences.iter_mut() {
+            let Some(dmx) = self.routes.get_mut(universe) else {
+                continue;
+            };
+            if sequence.frames.is_empty() {
+                continue;
+            }
+            let index = ((position.as_nanos() / sequence.frame_time.as_nanos()) as usize).min(sequence.frames.len() - 1);
+            if sequence.sent != Some(index) {
+                dmx.check_agent()?;
+                dmx.set_channels_tagged(*sequence.frames[index].channels(), "player");
+                sequence.sent = Some(index);
+            }
+        }
+        Ok(self.looping || position < self.duration())
+    }
+}
+
+impl Default for MultiPlayer {
+    fn default() -> MultiPlayer {
+        MultiPlayer::new()
+    }
+}
+
+impl fmt::Debug for MultiPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MultiPlayer")
+            .field("universes", &self.universes())
+            .field("routes", &self.routes.keys().collect::<Vec<_>>())
+            .field("position", &self.position())
+            .field("playing", &self.is_playing())
+            .field("looping", &self.looping)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,164 @@
+use crate::builder::{DMXSerialBuilder, LineSettings};
+use crate::clock::{Clock, ManualClock};
+use crate::codec::START_CODE_DMX;
+use crate::error::DMXDisconnectionError;
+use crate::transport::Transport;
+use crate::universe::Universe;
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+/// Renders the output of a [DMXSerial] frame by frame at given times, without an output device and without waiting in real time.
+///
+/// The whole engine takes part in the rendering: keyframes, the render callback, writers, transforms, the idle policy, the output ramp and parked channels.
+/// Useful for pre-rendering shows *(e.g. into a [recording](crate::recording))* or for generating previews.
+///
+/// # Example
+///
+/// ```
+/// use open_dmx::OfflineRenderer;
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let mut renderer = OfflineRenderer::new().unwrap();
+///     let start = renderer.start();
+///     renderer.dmx().submit_keyframe([0; 512], start);
+///     renderer.dmx().submit_keyframe([255; 512], start + Duration::from_secs(2));
+///     let universe = renderer.render_frame(Duration::from_secs(1)).unwrap();
+///     assert_eq!(universe.channels()[0], 128);
+/// }
+/// ```
+///
+pub struct OfflineRenderer {
+    dmx: DMXSerial,
+    clock: ManualClock,
+    start: time::Instant,
+    time: time::Duration,
+    output: Arc<Mutex<[u8; DMX_CHANNELS]>>,
+}
+
+impl OfflineRenderer {
+    /// Creates a new [OfflineRenderer] at the time `0`, with all channels at `0`.
+    ///
+    pub fn new() -> Result<OfflineRenderer, serialport::Error> {
+        let clock = ManualClock::new();
+        let output = Arc::new(Mutex::new([0; DMX_CHANNELS]));
+        let mut builder = DMXSerialBuilder::new("offline").sync().clock(clock.clone());
+        builder.paced = false;
+        let dmx = builder.open_with_transport(CaptureOutput {
+            output: output.clone(),
+        })?;
+        Ok(OfflineRenderer {
+            dmx,
+            start: clock.now(),
+            clock,
+            time: time::Duration::ZERO,
+            output,
+        })
+    }
+
+    /// Returns the [DMXSerial] which is rendered, for setting the channels, keyframes, transforms or the render callback.
+    ///
+    /// The channels and the render callback take effect with the next rendered frame.
+    ///
+    pub fn dmx(&mut self) -> &mut DMXSerial {
+        &mut self.dmx
+    }
+
+    /// Returns the [`Instant`](time::Instant) of the time `0`, e.g. for [keyframes](DMXSerial::submit_keyframe).
+    ///
+    pub fn start(&self) -> time::Instant {
+        self.start
+    }
+
+    /// Returns the time of the last rendered frame.
+    ///
+    pub fn time(&self) -> time::Duration {
+        self.time
+    }
+
+    /// Renders the frame at the given time since the start.
+    ///
+    /// The engine can't go back in time, so times before the last rendered frame are rendered at the time of that frame.
+    ///
+    pub fn render_frame(&mut self, time: time::Duration) -> Result<Universe, DMXDisconnectionError> {
+        if time > self.time {
+            self.clock.advance(time - self.time);
+            self.time = time;
+        }
+        self.dmx.update()?;
+        let channels = *self.output.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(Universe::new(channels))
+    }
+
+    /// Renders the frames from the current time up to *(but not including)* the time `end`, one every `frame_time`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `frame_time` is `0`.
+    ///
+    pub fn render(&mut self, end: time::Duration, frame_time: time::Duration) -> Result<Vec<Universe>, DMXDisconnectionError> {
+        assert!(!frame_time.is_zero(), "the frame time must not be 0");
+        let mut frames = Vec::new();
+        let mut time = self.time;
+        while time < end {
+            frames.push(self.render_frame(time)?);
+            time += frame_time;
+        }
+        Ok(frames)
+    }
+}
+
+impl fmt::Debug for OfflineRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OfflineRenderer").field("time", &self.time).finish_non_exhaustive()
+    }
+}
+
+// Keeps the last frame the agent sent
+struct CaptureOutput {
+    output: Arc<Mutex<[u8; DMX_CHANNELS]>>,
+}
+
+impl io::Write for CaptureOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The agent writes every frame at once, truncated frames only replace their channels
+        if buf.len() > 1 && buf.len() <= DMX_CHANNELS + 1 && buf[0] == START_CODE_DMX {
+            self.output.lock().unwrap_or_else(|poisoned| poisoned.into_inner())[..buf.len() - 1].copy_from_slice(&buf[1..]);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for CaptureOutput {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::path::Path;
+
+// Some fixtures ignore the first packet after a pause, so a few are sent
+const FRAMES: usize = 5;
+
+/// Opens the port on the given [`path`], sends the channel values a few times and closes it again.
+///
+/// The values start at channel `1`, all other channels are sent as `0`. Meant for scripts which don't want an interface running in the background.
+///
+/// [`path`]: std::path::Path
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::send_once;
+///
+/// fn main() {
+///     send_once("/dev/ttyUSB0", &[255, 0, 128]).unwrap();
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns a [`serialport::Error`] if there are more than [`DMX_CHANNELS`] values or if the port could not be opened or written to.
+///
+pub fn send_once<P: AsRef<Path>>(port: P, channels: &[u8]) -> Result<(), serialport::Error> {
+    if channels.len() > DMX_CHANNELS {
+        return Err(serialport::Error::new(serialport::ErrorKind::InvalidInput, format!("expected up to {} channel values", DMX_CHANNELS)));
+    }
+    let mut values = [0; DMX_CHANNELS];
+    values[..channels.len()].copy_from_slice(channels);
+
+    let mut dmx = DMXSerial::builder(port).sync().open()?;
+    dmx.set_channels(values);
+    dmx.send_burst(FRAMES).map_err(|e| serialport::Error::new(serialport::ErrorKind::Io(std::io::ErrorKind::BrokenPipe), e.to_string()))
+}
@@ -0,0 +1,196 @@
+use crate::error::PatchError;
+use crate::fixture::Fixture;
+
+/// Where [`Patch::auto_address`] places the fixtures.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoAddress {
+    /// The first address which is tried.
+    pub start: usize,
+    /// Free channels between two placed fixtures.
+    pub gap: usize,
+}
+
+impl Default for AutoAddress {
+    /// Starts at channel `1` without gaps.
+    ///
+    fn default() -> Self {
+        AutoAddress {
+            start: 1,
+            gap: 0,
+        }
+    }
+}
+
+/// The [`Fixture`]s of a universe.
+///
+/// Fixtures with overlapping channels are rejected, since they are usually a patching mistake.
+/// Overlaps can be allowed with [`Patch::set_allow_overlaps`] *(e.g. for fixtures which should always show the same look)*.
+///
+/// # Example
+///
+/// ```
+/// use open_dmx::{Attribute, Fixture, Patch};
+/// use open_dmx::error::PatchError;
+///
+/// let mut patch = Patch::new();
+/// patch.add(Fixture::new("Par 1", 1, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap()).unwrap();
+/// let result = patch.add(Fixture::new("Par 2", 3, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap());
+/// assert_eq!(result, Err(PatchError::AddressConflict { fixture: "Par 2".to_string(), conflicts: vec!["Par 1".to_string()] }));
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Patch {
+    fixtures: Vec<Fixture>,
+    allow_overlaps: bool,
+}
+
+impl Patch {
+    /// Creates an empty [Patch].
+    ///
+    pub fn new() -> Patch {
+        Patch::default()
+    }
+
+    /// Sets if fixtures with overlapping channels can be added. Disabled by default.
+    ///
+    pub fn set_allow_overlaps(&mut self, allow: bool) {
+        self.allow_overlaps = allow;
+    }
+
+    /// Returns `true` if fixtures with overlapping channels can be added.
+    ///
+    pub fn allows_overlaps(&self) -> bool {
+        self.allow_overlaps
+    }
+
+    /// Adds a [`Fixture`] to the [Patch].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::AddressConflict`] with all conflicting fixtures if the channels overlap and overlaps aren't allowed.
+    ///
+    pub fn add(&mut self, fixture: Fixture) -> Result<(), PatchError> {
+        if !self.allow_overlaps {
+            let conflicts: Vec<String> = self.conflicts(&fixture).iter().map(|other| other.name().to_string()).collect();
+            if !conflicts.is_empty() {
+                return Err(PatchError::AddressConflict { fixture: fixture.name().to_string(), conflicts });
+            }
+        }
+        self.fixtures.push(fixture);
+        Ok(())
+    }
+
+    /// Assigns sequential addresses to the fixtures and adds them, e.g. for generated rigs like pixel bars.
+    ///
+    /// Each fixture is placed at the first address after the previous one *(plus the gap)*, where its whole footprint is free.
+    /// Returns the name and the assigned address of every fixture, in the given order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::NoSpace`] if a fixture doesn't fit in the universe anymore. No fixture is added in that case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use open_dmx::{Attribute, AutoAddress, Fixture, Patch};
+    ///
+    /// let bars = (1..=4).map(|i| Fixture::new(&format!("Bar {}", i), 1, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap());
+    /// let mut patch = Patch::new();
+    /// let addresses = patch.auto_address(bars.collect(), AutoAddress { start: 1, gap: 1 }).unwrap();
+    /// assert_eq!(addresses[3], ("Bar 4".to_string(), 13));
+    /// ```
+    ///
+    pub fn auto_address(&mut self, fixtures: Vec<Fixture>, options: AutoAddress) -> Result<Vec<(String, usize)>, PatchError> {
+        let mut placed = self.clone();
+        // Overlaps are never created on purpose here
+        placed.allow_overlaps = false;
+        let mut next = options.start.max(1);
+        let mut addresses = Vec::with_capacity(fixtures.len());
+        for mut fixture in fixtures {
+            loop {
+                if fixture.set_address(next).is_err() {
+                    return Err(PatchError::NoSpace { fixture: fixture.name().to_string() });
+                }
+                // Skips over the fixtures which are in the way
+                match placed.conflicts(&fixture).iter().map(|other| other.channels().end).max() {
+                    Some(end) => next = end,
+                    None => break,
+                }
+            }
+            next = fixture.channels().end + options.gap;
+            addresses.push((fixture.name().to_string(), fixture.address()));
+            placed.fixtures.push(fixture);
+        }
+        self.fixtures = placed.fixtures;
+        Ok(addresses)
+    }
+
+    /// Removes the first [`Fixture`] with the given name and returns it.
+    ///
+    pub fn remove(&mut self, name: &str) -> Option<Fixture> {
+        let index = self.fixtures.iter().position(|fixture| fixture.name() == name)?;
+        Some(self.fixtures.remove(index))
+    }
+
+    /// Returns the first [`Fixture`] with the given name.
+    ///
+    pub fn get(&self, name: &str) -> Option<&Fixture> {
+        self.fixtures.iter().find(|fixture| fixture.name() == name)
+    }
+
+    /// Returns all fixtures in the order they were added.
+    ///
+    pub fn fixtures(&self) -> &[Fixture] {
+        &self.fixtures
+    }
+
+    /// Returns the fixtures whose channels overlap with the channels of the given [`Fixture`].
+    ///
+    pub fn conflicts(&self, fixture: &Fixture) -> Vec<&Fixture> {
+        let channels = fixture.channels();
+        self.fixtures.iter()
+            .filter(|other| {
+                let other = other.channels();
+                other.start < channels.end && channels.start < other.end
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PatchError;
+    use crate::{Attribute, Fixture};
+
+    #[test]
+    fn overlapping_fixtures_are_rejected() {
+        let rgb = |name: &str, address: usize| Fixture::new(name, address, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+        let mut patch = Patch::new();
+        patch.add(rgb("Par 1", 1)).unwrap();
+        patch.add(rgb("Par 2", 4)).unwrap();
+        let error = patch.add(rgb("Par 3", 3)).unwrap_err();
+        assert_eq!(error, PatchError::AddressConflict { fixture: "Par 3".to_string(), conflicts: vec!["Par 1".to_string(), "Par 2".to_string()] });
+        assert_eq!(patch.fixtures().len(), 2);
+
+        patch.set_allow_overlaps(true);
+        patch.add(rgb("Par 3", 3)).unwrap();
+        assert_eq!(patch.conflicts(patch.get("Par 3").unwrap()).len(), 3);
+    }
+
+    #[test]
+    fn fixtures_are_auto_addressed_around_the_patch() {
+        let bar = |i: usize| Fixture::new(&format!("Bar {}", i), 1, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+        let mut patch = Patch::new();
+        patch.add(Fixture::new("Hazer", 8, vec![Attribute::Other, Attribute::Other]).unwrap()).unwrap();
+
+        let addresses = patch.auto_address((1..=3).map(bar).collect(), AutoAddress { start: 1, gap: 1 }).unwrap();
+        assert_eq!(addresses, [("Bar 1".to_string(), 1), ("Bar 2".to_string(), 5), ("Bar 3".to_string(), 10)]);
+        assert_eq!(patch.fixtures().len(), 4);
+
+        let error = patch.auto_address((4..=200).map(bar).collect(), AutoAddress::default()).unwrap_err();
+        assert!(matches!(error, PatchError::NoSpace { .. }));
+        assert_eq!(patch.fixtures().len(), 4);
+    }
+}
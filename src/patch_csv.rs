@@ -0,0 +1,170 @@
+use crate::error::PatchError;
+use crate::fixture::{Attribute, Fixture};
+use crate::patch::Patch;
+
+use std::collections::BTreeMap;
+
+// Column names used by common console exports, compared without case
+const NAME_COLUMNS: [&str; 4] = ["name", "label", "fixture name", "fixture"];
+const MODE_COLUMNS: [&str; 5] = ["mode", "dmx mode", "fixture type", "type", "personality"];
+const UNIVERSE_COLUMNS: [&str; 3] = ["universe", "univ", "dmx universe"];
+const ADDRESS_COLUMNS: [&str; 5] = ["address", "dmx address", "addr", "start address", "patch"];
+
+impl Patch {
+    /// Writes the patches of several universes as a CSV patch list with the columns `Name`, `Mode`, `Universe` and `Address`.
+    ///
+    /// The list can be imported again with [`Patch::import_csv`] or by consoles which read these columns.
+    ///
+    pub fn export_csv(patches: &BTreeMap<u16, Patch>) -> String {
+        let mut csv = String::from("Name,Mode,Universe,Address\n");
+        for (universe, patch) in patches {
+            for fixture in patch.fixtures() {
+                csv.push_str(&format!("{},{},{},{}\n", quote(fixture.name()), quote(fixture.mode().unwrap_or_default()), universe, fixture.address()));
+            }
+        }
+        csv
+    }
+
+    /// Reads a CSV patch list, e.g. exported by a console, and returns the [Patch] of every universe.
+    ///
+    /// The columns are found by their names in the first line *(e.g. `Name` or `Label`, `Mode` or `Fixture Type`, `Universe` and `Address` or `Patch`)*.
+    /// Addresses can also contain the universe *(e.g. `2/101` or `2.101`)*, then the universe column isn't needed. Without both, everything is patched to universe `1`.
+    /// Fields can be separated with `,` or `;` and quoted with `"`.
+    ///
+    /// The patch list doesn't contain the channels of a mode, so they are looked up with `modes`.
+    ///
+    /// # Errors
+    ///
+    /// - [`PatchError::InvalidCsv`] if the name or address column is missing or a line can't be read.
+    ///
+    /// - [`PatchError::UnknownMode`] if `modes` returns `None` for a mode.
+    ///
+    /// - [`PatchError::AddressConflict`] if fixtures overlap in a universe.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use open_dmx::{Attribute, Patch};
+    ///
+    /// let csv = "Name;Fixture Type;Patch\nPar 1;RGB;1.1\nPar 2;RGB;1.4\n";
+    /// let patches = Patch::import_csv(csv, |mode| (mode == "RGB").then(|| vec![Attribute::Red, Attribute::Green, Attribute::Blue])).unwrap();
+    /// assert_eq!(patches[&1].get("Par 2").unwrap().address(), 4);
+    /// ```
+    ///
+    pub fn import_csv<F>(csv: &str, modes: F) -> Result<BTreeMap<u16, Patch>, PatchError>
+    where
+        F: Fn(&str) -> Option<Vec<Attribute>>,
+    {
+        let mut lines = csv.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let Some((_, header)) = lines.next() else {
+            return Ok(BTreeMap::new());
+        };
+        let separator = if header.contains(';') && !header.contains(',') { ';' } else { ',' };
+        let header = split(header, separator).ok_or_else(|| invalid(1, "unterminated quote"))?;
+        let column = |names: &[&str]| header.iter().position(|field| names.contains(&field.trim().to_lowercase().as_str()));
+        let name_column = column(&NAME_COLUMNS).ok_or_else(|| invalid(1, "missing name column"))?;
+        let address_column = column(&ADDRESS_COLUMNS).ok_or_else(|| invalid(1, "missing address column"))?;
+        let mode_column = column(&MODE_COLUMNS);
+        let universe_column = column(&UNIVERSE_COLUMNS);
+
+        let mut patches: BTreeMap<u16, Patch> = BTreeMap::new();
+        for (index, line) in lines {
+            let line_number = index + 1;
+            let fields = split(line, separator).ok_or_else(|| invalid(line_number, "unterminated quote"))?;
+            let field = |column: usize| fields.get(column).map_or("", |field| field.trim());
+
+            let (mut universe, address) = parse_address(field(address_column)).ok_or_else(|| invalid(line_number, "invalid address"))?;
+            if let Some(column) = universe_column {
+                universe = Some(field(column).parse().map_err(|_| invalid(line_number, "invalid universe"))?);
+            }
+            let mode = mode_column.map_or("", field);
+            let attributes = modes(mode).ok_or_else(|| PatchError::UnknownMode { line: line_number, mode: mode.to_string() })?;
+            let mut fixture = Fixture::new(field(name_column), address, attributes).map_err(|e| invalid(line_number, &e.to_string()))?;
+            if !mode.is_empty() {
+                fixture.set_mode(mode);
+            }
+            patches.entry(universe.unwrap_or(1)).or_default().add(fixture)?;
+        }
+        Ok(patches)
+    }
+}
+
+fn invalid(line: usize, message: &str) -> PatchError {
+    PatchError::InvalidCsv { line, message: message.to_string() }
+}
+
+// Parses `101`, `2/101` or `2.101`
+fn parse_address(field: &str) -> Option<(Option<u16>, usize)> {
+    match field.split_once(['/', '.']) {
+        Some((universe, address)) => Some((Some(universe.trim().parse().ok()?), address.trim().parse().ok()?)),
+        None => Some((None, field.parse().ok()?)),
+    }
+}
+
+// Splits a line into its fields. Returns `None` if a quote isn't closed
+fn split(line: &str, separator: char) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', ';', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PatchError;
+    use crate::{Attribute, Fixture, Patch};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn patches_round_trip_through_csv() {
+        let modes = |mode: &str| match mode {
+            "RGB" => Some(vec![Attribute::Red, Attribute::Green, Attribute::Blue]),
+            "Dim" => Some(vec![Attribute::Dimmer]),
+            _ => None,
+        };
+        let mut patches = BTreeMap::new();
+        let mut par = Fixture::new("Par, left", 1, modes("RGB").unwrap()).unwrap();
+        par.set_mode("RGB");
+        patches.entry(1).or_insert_with(Patch::new).add(par).unwrap();
+        let mut dimmer = Fixture::new("Dimmer \"A\"", 20, modes("Dim").unwrap()).unwrap();
+        dimmer.set_mode("Dim");
+        patches.entry(2).or_insert_with(Patch::new).add(dimmer).unwrap();
+
+        let csv = Patch::export_csv(&patches);
+        assert_eq!(csv, "Name,Mode,Universe,Address\n\"Par, left\",RGB,1,1\n\"Dimmer \"\"A\"\"\",Dim,2,20\n");
+        assert_eq!(Patch::import_csv(&csv, modes).unwrap(), patches);
+
+        let imported = Patch::import_csv("Label;Fixture Type;Patch\n\"Par; right\";RGB;2/101\n", modes).unwrap();
+        let par = imported[&2].get("Par; right").unwrap();
+        assert_eq!((par.address(), par.mode()), (101, Some("RGB")));
+
+        let error = Patch::import_csv("Name,Mode,Address\nPar,RGB,1\n\nSpot,Spot 16ch,10\n", modes).unwrap_err();
+        assert_eq!(error, PatchError::UnknownMode { line: 4, mode: "Spot 16ch".to_string() });
+        let error = Patch::import_csv("Name,Mode,Address\nPar,RGB,x\n", modes).unwrap_err();
+        assert!(matches!(error, PatchError::InvalidCsv { line: 2, .. }));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::thread::ChannelBuffer;
+use crate::DMX_CHANNELS;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Weak;
+use std::thread;
+use std::time;
+
+// Reads the channel values saved with `save`
+pub(crate) fn load(path: &Path) -> io::Result<[u8; DMX_CHANNELS]> {
+    fs::read(path)?.try_into().map_err(|data: Vec<u8>| io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("expected {} channel values, found {} bytes", DMX_CHANNELS, data.len()),
+    ))
+}
+
+// Writes the channel values to a temporary file first, so a power cut can't leave a truncated file behind
+pub(crate) fn save(path: &Path, channels: &[u8; DMX_CHANNELS]) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, channels)?;
+    fs::rename(&temp, path)
+}
+
+// Saves the channels in the given interval until the buffer is dropped. Unchanged values aren't written again
+pub(crate) fn spawn(path: PathBuf, interval: time::Duration, channels: Weak<ChannelBuffer>) {
+    let _ = thread::spawn(move || {
+        let mut saved = load(&path).ok();
+        let mut failed = false;
+        loop {
+            thread::sleep(interval);
+            let Some(values) = channels.upgrade().map(|channels| channels.load()) else {
+                break;
+            };
+            if saved == Some(values) {
+                continue;
+            }
+            match save(&path, &values) {
+                Ok(()) => {
+                    saved = Some(values);
+                    failed = false;
+                },
+                // Only logged once, until saving works again
+                Err(e) if !failed => {
+                    eprintln!("Failed to save the DMX state to \"{}\": \"{}\"", path.display(), e);
+                    failed = true;
+                },
+                Err(_) => {},
+            }
+        }
+    });
+}
@@ -0,0 +1,150 @@
+use crate::clock::Clock;
+use crate::error::RecordingError;
+use crate::recording::{RecordedFrame, RecordingReader};
+use crate::DMXSerial;
+
+use std::fmt;
+use std::io::{Read, Seek};
+use std::sync::Arc;
+use std::time;
+
+/// Plays a recording in the [`.dmxrec`](crate::recording) format on a [DMXSerial].
+///
+/// The player doesn't run on its own: [`Player::poll`] sends the frame which is due at the current position and should be called at least once per packet time.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, Player};
+/// use open_dmx::recording::RecordingReader;
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let file = BufReader::new(File::open("show.dmxrec").unwrap());
+///     let mut player = Player::new(RecordingReader::new(file).unwrap());
+///     player.seek(Duration::from_secs(90)).unwrap();
+///     player.play();
+///     while player.poll(&mut dmx).unwrap() {
+///         std::thread::sleep(dmx.get_packet_time());
+///     }
+/// }
+/// ```
+///
+pub struct Player<R: Read + Seek> {
+    reader: RecordingReader<R>,
+    // Next frame of the recording, once it was read
+    next: Option<RecordedFrame>,
+    // Frame to send with the next poll
+    pending: Option<RecordedFrame>,
+    // Position when paused or when playing was started
+    position: time::Duration,
+    playing_since: Option<time::Instant>,
+    ended: bool,
+    clock: Arc<dyn Clock>,
+}
+
+impl<R: Read + Seek> Player<R> {
+    /// Creates a new, paused [Player] at the start of the recording.
+    ///
+    pub fn new(reader: RecordingReader<R>) -> Player<R> {
+        Player::with_clock(reader, crate::SystemClock)
+    }
+
+    /// Creates a new, paused [Player] which follows the given [`Clock`] *(e.g. a [`ManualClock`](crate::ManualClock) in tests)*.
+    ///
+    pub fn with_clock<C: Clock + 'static>(reader: RecordingReader<R>, clock: C) -> Player<R> {
+        Player {
+            reader,
+            next: None,
+            pending: None,
+            position: time::Duration::ZERO,
+            playing_since: None,
+            ended: false,
+            clock: Arc::new(clock),
+        }
+    }
+
+    /// Returns the reader of the recording, e.g. for its [`info`](RecordingReader::info).
+    ///
+    pub fn reader(&self) -> &RecordingReader<R> {
+        &self.reader
+    }
+
+    /// Starts or resumes playing at the current position.
+    ///
+    pub fn play(&mut self) {
+        if self.playing_since.is_none() {
+            self.playing_since = Some(self.clock.now());
+        }
+    }
+
+    /// Pauses playing. The last frame stays on the output.
+    ///
+    pub fn pause(&mut self) {
+        self.position = self.position();
+        self.playing_since = None;
+    }
+
+    /// Returns `true` if the player is playing.
+    ///
+    pub fn is_playing(&self) -> bool {
+        self.playing_since.is_some()
+    }
+
+    /// Returns the current position in the recording.
+    ///
+    pub fn position(&self) -> time::Duration {
+        self.position + self.playing_since.map_or(time::Duration::ZERO, |since| self.clock.now().saturating_duration_since(since))
+    }
+
+    /// Jumps to the given position in the recording. Works while playing and while paused.
+    ///
+    /// The full frame at the position is rebuilt from the closest keyframe *(see [`RecordingReader::seek`])* and sent with the next [`Player::poll`], before playing continues from there.
+    ///
+    pub fn seek(&mut self, position: time::Duration) -> Result<(), RecordingError> {
+        let frame = self.reader.seek(position)?;
+        self.pending = frame;
+        self.next = None;
+        self.ended = false;
+        self.position = position;
+        if self.playing_since.is_some() {
+            self.playing_since = Some(self.clock.now());
+        }
+        Ok(())
+    }
+
+    /// Sends the latest frame which is due at the current position, if it wasn't sent yet.
+    ///
+    /// Returns `false` once the end of the recording was reached.
+    ///
+    pub fn poll(&mut self, dmx: &mut DMXSerial) -> Result<bool, RecordingError> {
+        let position = self.position();
+        while !self.ended {
+            if self.next.is_none() {
+                self.next = self.reader.read_frame()?;
+                self.ended = self.next.is_none();
+            }
+            match self.next {
+                Some(frame) if frame.time <= position => self.pending = self.next.take(),
+                _ => break,
+            }
+        }
+        if let Some(frame) = self.pending.take() {
+            dmx.set_channels_tagged(frame.channels, "player");
+        }
+        Ok(!self.ended)
+    }
+}
+
+impl<R: Read + Seek> fmt::Debug for Player<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Player")
+            .field("position", &self.position())
+            .field("playing", &self.is_playing())
+            .field("ended", &self.ended)
+            .finish_non_exhaustive()
+    }
+}
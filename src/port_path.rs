@@ -0,0 +1,49 @@
+use std::path::Path;
+
+// Checks the path of a port and brings it into the form expected by the serialport library
+pub(crate) fn normalize(path: &Path) -> serialport::Result<String> {
+    let Some(name) = path.to_str() else {
+        return Err(serialport::Error::new(serialport::ErrorKind::InvalidInput, format!("\"{}\" is not a valid unicode port path", path.display())));
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(serialport::Error::new(serialport::ErrorKind::InvalidInput, "The port path is empty"));
+    }
+    if cfg!(windows) {
+        // COM10 and above can only be opened with the device namespace prefix
+        if let Some(number) = com_port_number(name) {
+            return Ok(format!(r"\\.\COM{}", number));
+        }
+    }
+    Ok(name.to_string())
+}
+
+// Checks if the system lists the port, so missing ports can be told apart from busy ones. Assumed to be listed if the ports can't be read
+#[cfg(windows)]
+pub(crate) fn is_listed(path: &Path) -> bool {
+    let Some(name) = path.to_str().map(str::trim) else {
+        return false;
+    };
+    let number = com_port_number(name);
+    match serialport::available_ports() {
+        Ok(ports) => ports.iter().any(|info| match number {
+            Some(number) => com_port_number(&info.port_name) == Some(number),
+            None => info.port_name.eq_ignore_ascii_case(name),
+        }),
+        Err(_) => true,
+    }
+}
+
+// Parses names like `COM10`, `com3` or `COM4:`
+fn com_port_number(name: &str) -> Option<u32> {
+    let name = name.strip_suffix(':').unwrap_or(name);
+    let prefix = name.get(..3)?;
+    if !prefix.eq_ignore_ascii_case("COM") {
+        return None;
+    }
+    let number = &name[3..];
+    if number.is_empty() || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    number.parse().ok()
+}
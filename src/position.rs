@@ -0,0 +1,242 @@
+use crate::error::DMXChannelValidityError;
+use crate::fixture::{Attribute, Fixture};
+use crate::DMXSerial;
+
+use std::time;
+
+/// The pan and tilt of a moving head in degrees, counted from the position at the DMX value `0`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub pan: f32,
+    pub tilt: f32,
+}
+
+impl Position {
+    /// Creates a [Position] from the pan and tilt in degrees.
+    ///
+    pub fn new(pan: f32, tilt: f32) -> Position {
+        Position { pan, tilt }
+    }
+}
+
+/// The pan and tilt ranges of a moving head in degrees *(e.g. `540` and `270`)*, which the DMX values from `0` to full are spread over.
+///
+/// Converts between degrees and 16-bit *(coarse and fine channel)* or 8-bit DMX values. Degrees outside the range are clamped.
+///
+/// # Example
+///
+/// ```
+/// use open_dmx::{MoverRange, Position};
+///
+/// let range = MoverRange::new(540.0, 270.0);
+/// let (pan, tilt) = range.to_dmx16(Position::new(270.0, 135.0));
+/// assert_eq!((pan, tilt), (32768, 32768));
+/// assert_eq!(range.from_dmx16(pan, tilt).pan.round(), 270.0);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoverRange {
+    pub pan: f32,
+    pub tilt: f32,
+}
+
+impl MoverRange {
+    /// Creates a [MoverRange] from the pan and tilt range in degrees.
+    ///
+    pub fn new(pan: f32, tilt: f32) -> MoverRange {
+        MoverRange { pan, tilt }
+    }
+
+    /// Converts the [`Position`] to 16-bit pan and tilt values. The coarse channel gets the high byte *(see [`u16::to_be_bytes`])*.
+    ///
+    pub fn to_dmx16(&self, position: Position) -> (u16, u16) {
+        (to_dmx(position.pan, self.pan), to_dmx(position.tilt, self.tilt))
+    }
+
+    /// Converts the [`Position`] to 8-bit pan and tilt values, for fixtures without fine channels.
+    ///
+    pub fn to_dmx8(&self, position: Position) -> (u8, u8) {
+        let (pan, tilt) = self.to_dmx16(position);
+        (coarse(pan), coarse(tilt))
+    }
+
+    /// Converts 16-bit pan and tilt values to a [`Position`].
+    ///
+    pub fn from_dmx16(&self, pan: u16, tilt: u16) -> Position {
+        Position::new(to_degrees(pan, self.pan), to_degrees(tilt, self.tilt))
+    }
+
+    /// Converts 8-bit pan and tilt values to a [`Position`].
+    ///
+    pub fn from_dmx8(&self, pan: u8, tilt: u8) -> Position {
+        // 255 stands for full like 65535
+        self.from_dmx16(pan as u16 * 257, tilt as u16 * 257)
+    }
+}
+
+fn to_dmx(degrees: f32, range: f32) -> u16 {
+    if range <= 0.0 {
+        return 0;
+    }
+    ((degrees / range).clamp(0.0, 1.0) * u16::MAX as f32).round() as u16
+}
+
+fn to_degrees(value: u16, range: f32) -> f32 {
+    value as f32 / u16::MAX as f32 * range
+}
+
+fn coarse(value: u16) -> u8 {
+    ((value as u32 + 128) / 257).min(255) as u8
+}
+
+impl Fixture {
+    /// Moves the [Fixture] to the [`Position`], using the fine channels if it has them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use open_dmx::{Attribute, DMXSerial, Fixture, MoverRange, Position};
+    ///
+    /// fn main() {
+    ///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    ///     let spot = Fixture::new("Spot", 1, vec![Attribute::Pan, Attribute::PanFine, Attribute::Tilt, Attribute::TiltFine]).unwrap();
+    ///     spot.set_position(&mut dmx, &MoverRange::new(540.0, 270.0), Position::new(90.0, 45.0)).unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn set_position(&self, dmx: &mut DMXSerial, range: &MoverRange, position: Position) -> Result<(), DMXChannelValidityError> {
+        let (pan, tilt) = range.to_dmx16(position);
+        for (value, axis, fine) in [(pan, Attribute::Pan, Attribute::PanFine), (tilt, Attribute::Tilt, Attribute::TiltFine)] {
+            if self.channel(fine).is_some() {
+                let [high, low] = value.to_be_bytes();
+                self.set(dmx, axis, high)?;
+                self.set(dmx, fine, low)?;
+            } else {
+                self.set(dmx, axis, coarse(value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the [`Position`] the set values of the [Fixture] point to, or `None` if it has no pan and tilt channels.
+    ///
+    pub fn position(&self, dmx: &DMXSerial, range: &MoverRange) -> Option<Position> {
+        let channels = dmx.get_channels();
+        let value = |axis: Attribute, fine: Attribute| -> Option<u16> {
+            let high = channels[self.channel(axis)? - 1];
+            Some(match self.channel(fine) {
+                Some(fine) => u16::from_be_bytes([high, channels[fine - 1]]),
+                None => high as u16 * 257,
+            })
+        };
+        Some(range.from_dmx16(value(Attribute::Pan, Attribute::PanFine)?, value(Attribute::Tilt, Attribute::TiltFine)?))
+    }
+}
+
+/// Smooths the movement to a target [`Position`] and limits its speed, to protect the motors and avoid jerky moves *(e.g. with positions from a tracking system)*.
+///
+/// [`MotionLimiter::update`] is called once per frame with the target and the time since the previous frame.
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MotionLimiter {
+    /// Maximum speed of each axis in degrees per second, `None` for no limit.
+    pub max_speed: Option<f32>,
+    /// Time constant of the smoothing *(the time for covering about two thirds of the way)*, `0` for no smoothing.
+    pub smoothing: time::Duration,
+    current: Option<Position>,
+}
+
+impl MotionLimiter {
+    /// Creates a [MotionLimiter] without smoothing and speed limit.
+    ///
+    pub fn new() -> MotionLimiter {
+        MotionLimiter::default()
+    }
+
+    /// Sets the maximum speed. See [`MotionLimiter::max_speed`].
+    ///
+    pub fn max_speed(mut self, degrees_per_second: f32) -> MotionLimiter {
+        self.max_speed = Some(degrees_per_second);
+        self
+    }
+
+    /// Sets the smoothing. See [`MotionLimiter::smoothing`].
+    ///
+    pub fn smoothing(mut self, smoothing: time::Duration) -> MotionLimiter {
+        self.smoothing = smoothing;
+        self
+    }
+
+    /// Moves towards the `target` for the time `delta` and returns the new position. The first update jumps to the target.
+    ///
+    pub fn update(&mut self, target: Position, delta: time::Duration) -> Position {
+        let Some(current) = self.current else {
+            self.current = Some(target);
+            return target;
+        };
+        let share = if self.smoothing.is_zero() {
+            1.0
+        } else {
+            1.0 - (-delta.as_secs_f32() / self.smoothing.as_secs_f32()).exp()
+        };
+        let step = self.max_speed.map(|speed| speed.max(0.0) * delta.as_secs_f32());
+        let axis = |from: f32, to: f32| {
+            let change = (to - from) * share;
+            from + step.map_or(change, |step| change.clamp(-step, step))
+        };
+        let next = Position::new(axis(current.pan, target.pan), axis(current.tilt, target.tilt));
+        self.current = Some(next);
+        next
+    }
+
+    /// Returns the last position, or `None` before the first update.
+    ///
+    pub fn current(&self) -> Option<Position> {
+        self.current
+    }
+
+    /// Forgets the last position, so the next update jumps to its target.
+    ///
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OfflineRenderer;
+    use std::time::Duration;
+
+    #[test]
+    fn mover_positions_convert_between_degrees_and_dmx() {
+        let range = MoverRange::new(540.0, 270.0);
+        assert_eq!(range.to_dmx16(Position::new(0.0, 270.0)), (0, 65535));
+        assert_eq!(range.to_dmx8(Position::new(270.0, 900.0)), (128, 255));
+        assert_eq!(range.from_dmx8(255, 0), Position::new(540.0, 0.0));
+
+        let mut renderer = OfflineRenderer::new().unwrap();
+        let dmx = renderer.dmx();
+        let spot = Fixture::new("Spot", 1, vec![Attribute::Pan, Attribute::PanFine, Attribute::Tilt, Attribute::TiltFine]).unwrap();
+        let scanner = Fixture::new("Scanner", 5, vec![Attribute::Pan, Attribute::Tilt]).unwrap();
+        spot.set_position(dmx, &range, Position::new(135.0, 90.0)).unwrap();
+        scanner.set_position(dmx, &MoverRange::new(180.0, 90.0), Position::new(90.0, 45.0)).unwrap();
+        assert_eq!(dmx.get_channels()[..6], [64, 0, 85, 85, 128, 128]);
+        let position = spot.position(dmx, &range).unwrap();
+        assert!((position.pan - 135.0).abs() < 0.01 && (position.tilt - 90.0).abs() < 0.01);
+        assert!(Fixture::new("Par", 7, vec![Attribute::Dimmer]).unwrap().position(dmx, &range).is_none());
+
+        let frame = Duration::from_millis(100);
+        let mut limiter = MotionLimiter::new().max_speed(90.0);
+        assert_eq!(limiter.update(Position::new(0.0, 0.0), frame), Position::new(0.0, 0.0));
+        assert_eq!(limiter.update(Position::new(180.0, 5.0), frame), Position::new(9.0, 5.0));
+        assert_eq!(limiter.update(Position::new(180.0, 5.0), frame), Position::new(18.0, 5.0));
+        let mut smooth = MotionLimiter::new().smoothing(Duration::from_secs(1));
+        smooth.update(Position::default(), frame);
+        let first = smooth.update(Position::new(100.0, 0.0), Duration::from_secs(1));
+        assert!((first.pan - 63.2).abs() < 0.1);
+        smooth.reset();
+        assert_eq!(smooth.current(), None);
+    }
+}
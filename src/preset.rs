@@ -0,0 +1,178 @@
+use crate::fixture::{Attribute, Fixture};
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Values of some attributes of some fixtures, e.g. the pan and tilt of the movers for a focus position, or a color or a beam look.
+///
+/// [Cue](crate::Cue)s refer to presets by name, see [`PresetPool`].
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preset {
+    values: BTreeMap<usize, u8>,
+}
+
+impl Preset {
+    /// Creates an empty [Preset].
+    ///
+    pub fn new() -> Preset {
+        Preset::default()
+    }
+
+    /// Records the current values of the attributes of the fixtures, e.g. after focusing the movers by hand. Attributes a fixture doesn't have are skipped.
+    ///
+    pub fn capture(fixtures: &[Fixture], attributes: &[Attribute], dmx: &DMXSerial) -> Preset {
+        let channels = dmx.get_channels();
+        let mut preset = Preset::new();
+        for fixture in fixtures {
+            for channel in attributes.iter().filter_map(|attribute| fixture.channel(*attribute)) {
+                preset.values.insert(channel, channels[channel - 1]);
+            }
+        }
+        preset
+    }
+
+    /// Sets the value of an attribute of the fixture. Does nothing if the fixture doesn't have it.
+    ///
+    pub fn set(&mut self, fixture: &Fixture, attribute: Attribute, value: u8) {
+        if let Some(channel) = fixture.channel(attribute) {
+            self.values.insert(channel, value);
+        }
+    }
+
+    /// Returns the channels and values of the [Preset].
+    ///
+    pub fn values(&self) -> Vec<(usize, u8)> {
+        self.values.iter().map(|(channel, value)| (*channel, *value)).collect()
+    }
+
+    /// Writes the values into the channels. The other channels are kept.
+    ///
+    pub fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        for (channel, value) in &self.values {
+            channels[channel - 1] = *value;
+        }
+    }
+}
+
+/// Named [`Preset`]s *(palettes)*, which [Cue](crate::Cue)s refer to.
+///
+/// Changing a preset changes every cue which uses it from its next start on, like the referential palettes of lighting consoles *(e.g. re-focusing a position for a new venue)*.
+/// Clones share the same presets.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, Crossfade, Cue, CueStack, DMXSerial, Easing, Fixture, Preset};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let mover = Fixture::new("Mover", 1, vec![Attribute::Pan, Attribute::Tilt, Attribute::Dimmer]).unwrap();
+///     let mut stack = CueStack::new();
+///     let mut singer = Preset::new();
+///     singer.set(&mover, Attribute::Pan, 100);
+///     singer.set(&mover, Attribute::Tilt, 60);
+///     stack.presets().set("Singer", singer);
+///
+///     let mut look = [0; 512];
+///     look[2] = 255;
+///     stack.add(Cue::new("Song", look, Crossfade::new(Duration::from_secs(2), Easing::Linear)).preset("Singer"));
+///     // Re-focused in the next venue, the cue follows
+///     stack.presets().set("Singer", Preset::capture(&[mover], &[Attribute::Pan, Attribute::Tilt], &dmx));
+///     stack.go(&mut dmx);
+/// }
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct PresetPool {
+    presets: Arc<RwLock<BTreeMap<String, Preset>>>,
+}
+
+impl PresetPool {
+    /// Creates an empty [PresetPool].
+    ///
+    pub fn new() -> PresetPool {
+        PresetPool::default()
+    }
+
+    /// Stores a [`Preset`] under the name. A preset with the same name is replaced.
+    ///
+    pub fn set(&self, name: &str, preset: Preset) {
+        // RwLock can be unwrapped here
+        self.presets.write().unwrap().insert(name.to_string(), preset);
+    }
+
+    /// Returns the [`Preset`] with the name.
+    ///
+    pub fn get(&self, name: &str) -> Option<Preset> {
+        // RwLock can be unwrapped here
+        self.presets.read().unwrap().get(name).cloned()
+    }
+
+    /// Removes the [`Preset`] with the name and returns it. Cues which refer to it keep their own values for its channels.
+    ///
+    pub fn remove(&self, name: &str) -> Option<Preset> {
+        // RwLock can be unwrapped here
+        self.presets.write().unwrap().remove(name)
+    }
+
+    /// Returns the names of the presets, in alphabetical order.
+    ///
+    pub fn names(&self) -> Vec<String> {
+        // RwLock can be unwrapped here
+        self.presets.read().unwrap().keys().cloned().collect()
+    }
+
+    // Writes the presets with the names over the channels, unknown names are skipped
+    pub(crate) fn apply(&self, names: &[String], channels: &mut [u8; DMX_CHANNELS]) {
+        // RwLock can be unwrapped here
+        let presets = self.presets.read().unwrap();
+        for preset in names.iter().filter_map(|name| presets.get(name)) {
+            preset.apply(channels);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Crossfade, Cue, CueStack, Easing, OfflineRenderer};
+    use std::time::Duration;
+
+    #[test]
+    fn presets_update_every_cue_using_them() {
+        let mut renderer = OfflineRenderer::new().unwrap();
+        let dmx = renderer.dmx();
+        let mover = Fixture::new("Mover", 1, vec![Attribute::Pan, Attribute::Tilt, Attribute::Dimmer]).unwrap();
+        let snap = Crossfade::new(Duration::ZERO, Easing::Linear);
+        let mut stack = CueStack::new();
+        let mut look = [0; DMX_CHANNELS];
+        look[2] = 255;
+        stack.add(Cue::new("Intro", look, snap.clone()).preset("Center"));
+        stack.add(Cue::new("Verse", look, snap.clone()).preset("Center").preset("Missing"));
+        stack.add(Cue::new("Dark", [0; DMX_CHANNELS], snap).preset("Center"));
+
+        let mut center = Preset::new();
+        center.set(&mover, Attribute::Pan, 128);
+        center.set(&mover, Attribute::Tilt, 64);
+        center.set(&mover, Attribute::Red, 255);
+        assert_eq!(center.values(), [(1, 128), (2, 64)]);
+        stack.presets().set("Center", center);
+        stack.go(dmx);
+        assert_eq!(dmx.get_channels()[..3], [128, 64, 255]);
+
+        // Re-focus by hand and record the new position
+        dmx.set_channel(1, 100).unwrap();
+        dmx.set_channel(2, 90).unwrap();
+        let pool = stack.presets().clone();
+        pool.set("Center", Preset::capture(&[mover], &[Attribute::Pan, Attribute::Tilt], dmx));
+        assert_eq!(stack.look(2).unwrap()[..3], [100, 90, 0]);
+        stack.go(dmx);
+        assert_eq!(dmx.get_channels()[..3], [100, 90, 255]);
+        assert_eq!(pool.names(), ["Center"]);
+        assert!(pool.remove("Center").is_some());
+        assert_eq!(stack.look(1).unwrap()[..3], [0, 0, 255]);
+    }
+}
@@ -0,0 +1,68 @@
+//! Priority of the agent thread
+
+use std::fmt;
+use std::sync::Arc;
+
+/// The priority the agent thread tries to get.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AgentPriority {
+    /// The highest crossplatform priority. *(default)*
+    #[default]
+    Max,
+    /// A crossplatform priority from `0` *(lowest)* to `99` *(highest)*.
+    Level(u8),
+    /// Real-time `SCHED_FIFO` scheduling with a priority from `1` to `99`. *(Linux only)*
+    ///
+    /// Requires `CAP_SYS_NICE` or a matching `rtprio` limit.
+    #[cfg(target_os = "linux")]
+    Fifo(u8),
+}
+
+/// What happens if the priority of the agent thread can't be set.
+///
+#[derive(Clone, Default)]
+pub enum PriorityFailure {
+    /// Continues silently.
+    Ignore,
+    /// Prints the error to `stderr` and continues. *(default)*
+    #[default]
+    Log,
+    /// Fails opening the [DMXSerial].
+    ///
+    /// [DMXSerial]: crate::DMXSerial
+    Error,
+    /// Calls the given function with the error message and continues.
+    Callback(Arc<dyn Fn(&str) + Send + Sync>),
+}
+
+impl fmt::Debug for PriorityFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PriorityFailure::Ignore => write!(f, "Ignore"),
+            PriorityFailure::Log => write!(f, "Log"),
+            PriorityFailure::Error => write!(f, "Error"),
+            PriorityFailure::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
+
+// Applies the priority to the current thread
+pub(crate) fn apply(priority: AgentPriority) -> Result<(), String> {
+    use thread_priority::{ThreadPriority, ThreadPriorityValue};
+
+    let value = |level: u8| ThreadPriorityValue::try_from(level.min(99)).map_err(|e| format!("{:?}", e));
+    match priority {
+        AgentPriority::Max => thread_priority::set_current_thread_priority(ThreadPriority::Max),
+        AgentPriority::Level(level) => thread_priority::set_current_thread_priority(ThreadPriority::Crossplatform(value(level)?)),
+        #[cfg(target_os = "linux")]
+        AgentPriority::Fifo(level) => {
+            use thread_priority::unix::{RealtimeThreadSchedulePolicy, ThreadSchedulePolicy};
+            thread_priority::unix::set_thread_priority_and_policy(
+                thread_priority::unix::thread_native_id(),
+                ThreadPriority::Crossplatform(value(level.max(1))?),
+                ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+            )
+        },
+    }.map_err(|e| format!("{:?}", e))
+}
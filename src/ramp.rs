@@ -0,0 +1,51 @@
+use crate::DMX_CHANNELS;
+
+use std::time;
+
+// Ramps the whole output between off (0.0) and on (1.0), see DMXSerial::enable_output
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OutputRamp {
+    from: f32,
+    to: f32,
+    start: time::Instant,
+    fade: time::Duration,
+}
+
+impl OutputRamp {
+    pub fn new(now: time::Instant) -> OutputRamp {
+        OutputRamp {
+            from: 1.0,
+            to: 1.0,
+            start: now,
+            fade: time::Duration::ZERO,
+        }
+    }
+
+    // Starts a new ramp from the current level, so an unfinished ramp is reversed smoothly
+    pub fn start(&mut self, to: f32, fade: time::Duration, now: time::Instant) {
+        self.from = self.level(now);
+        self.to = to;
+        self.start = now;
+        self.fade = fade;
+    }
+
+    pub fn target(&self) -> f32 {
+        self.to
+    }
+
+    pub fn level(&self, now: time::Instant) -> f32 {
+        if self.fade.is_zero() {
+            return self.to;
+        }
+        let progress = (now.saturating_duration_since(self.start).as_secs_f32() / self.fade.as_secs_f32()).min(1.0);
+        self.from + (self.to - self.from) * progress
+    }
+
+    pub fn apply(&self, output: &mut [u8; DMX_CHANNELS], now: time::Instant) {
+        let level = self.level(now);
+        if level >= 1.0 {
+            return;
+        }
+        output.iter_mut().for_each(|value| *value = (*value as f32 * level).round() as u8);
+    }
+}
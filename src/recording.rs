@@ -0,0 +1,866 @@
+//! The **`.dmxrec`** format for recorded DMX frames, so recordings can be exchanged between tools.
+//!
+//! A recording is written with a [`RecordingWriter`] and read with a [`RecordingReader`]. Both can also convert recordings from and to CSV.
+//! A [`Recorder`] writes the frames a [`DMXSerial`] transmits.
+//!
+//! Frames are stored as deltas to the previous frame, so recordings of mostly static universes stay small.
+//! With the `zstd` feature, recordings can also be compressed with [`RecordingWriter::compressed`].
+//!
+//! An index of keyframes allows jumping to any time of a recording with [`RecordingReader::seek`] or the [`Player`](crate::Player).
+//!
+//! # Format
+//!
+//! All numbers are little-endian. A recording starts with a header:
+//!
+//! | Size | Field |
+//! |------|-------|
+//! | 6 | Magic bytes [`MAGIC`] *(`DMXREC`)* |
+//! | 2 | Format version, currently [`FORMAT_VERSION`] |
+//! | 2 | Flags. Bit `0` is set if everything after the header is compressed with [zstd]. Readers reject unknown flags |
+//! | 2 | Universe number |
+//! | 8 | Start of the recording in milliseconds since the Unix epoch |
+//! | 4 | Packet time of the recorded interface in microseconds |
+//! | 4 | Length of the description in bytes |
+//! | n | Description as UTF-8 |
+//!
+//! It is followed by the frames:
+//!
+//! | Size | Field |
+//! |------|-------|
+//! | 8 | Time since the start of the recording in microseconds, never decreasing |
+//! | 1 | Kind of the frame, `0` for a full frame and `1` for a delta frame |
+//! | 2 | Full frame: Number of channels `n` *(`1` to `512`)*. Missing channels are `0` <br> Delta frame: Number of runs |
+//! | n | Full frame: Channel values, starting at channel `1` <br> Delta frame: The runs |
+//!
+//! A delta frame only contains the channels which changed since the previous frame, all others keep their values. The first frame is always a full frame.
+//! The changed channels are grouped in runs:
+//!
+//! | Size | Field |
+//! |------|-------|
+//! | 2 | Index of the first channel of the run, starting at `0` |
+//! | 2 | Number of channels `n` in the run |
+//! | n | Channel values |
+//!
+//! Every second of the recording, a full frame is written as a keyframe. In compressed recordings, every keyframe also starts a new zstd frame, so decompression can start there.
+//!
+//! The frames are ended by the index of the keyframes:
+//!
+//! | Size | Field |
+//! |------|-------|
+//! | 8 | `0xFFFFFFFFFFFFFFFF` instead of the time of a frame |
+//! | 4 | Number of keyframes `n` |
+//! | n × 16 | Time of the keyframe in microseconds and its offset from the start of the file, both 8 bytes |
+//!
+//! The last 16 bytes of the file contain the offset of the index and the magic bytes `DMXINDEX`.
+//! In compressed recordings, the index starts a new zstd frame and these 16 bytes are wrapped in a [skippable frame].
+//! Recordings which weren't finished have no index, but can still be read up to their last complete frame.
+//!
+//! [skippable frame]: https://github.com/facebook/zstd/blob/dev/doc/zstd_compression_format.md#skippable-frames
+//! [zstd]: https://facebook.github.io/zstd/
+//!
+//! # Example
+//!
+//! ```
+//! use open_dmx::recording::{RecordedFrame, RecordingInfo, RecordingReader, RecordingWriter};
+//! use std::time::Duration;
+//!
+//! let mut writer = RecordingWriter::new(Vec::new(), &RecordingInfo::default()).unwrap();
+//! writer.write_frame(&RecordedFrame { time: Duration::ZERO, channels: [255; 512] }).unwrap();
+//! let file = writer.finish().unwrap();
+//!
+//! let reader = RecordingReader::new(&file[..]).unwrap();
+//! let frames: Vec<RecordedFrame> = reader.collect::<Result<_, _>>().unwrap();
+//! assert_eq!(frames[0].channels, [255; 512]);
+//! ```
+//!
+
+use crate::codec::DEFAULT_PACKET_TIME;
+use crate::error::RecordingError;
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::fmt;
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::sync::mpsc;
+use std::time;
+
+/// Magic bytes at the start of every recording.
+///
+pub const MAGIC: [u8; 6] = *b"DMXREC";
+
+/// Version of the format written by this crate.
+///
+pub const FORMAT_VERSION: u16 = 1;
+
+const FLAG_ZSTD: u16 = 1;
+
+// Size of the header without the description
+const HEADER_SIZE: u64 = 28;
+
+const KEYFRAME_INTERVAL: time::Duration = time::Duration::from_secs(1);
+
+// Written instead of the time of a frame after the last frame
+const INDEX_MARKER: u64 = u64::MAX;
+const INDEX_MAGIC: [u8; 8] = *b"DMXINDEX";
+const TRAILER_SIZE: i64 = 16;
+
+#[cfg(feature = "zstd")]
+const ZSTD_SKIPPABLE_MAGIC: u32 = 0x184D2A50;
+
+const FRAME_FULL: u8 = 0;
+const FRAME_DELTA: u8 = 1;
+
+// Index and length of a run in a delta frame
+const RUN_HEADER_SIZE: usize = 4;
+
+/// Metadata stored in the header of a recording.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingInfo {
+    /// Number of the recorded universe.
+    pub universe: u16,
+    /// Packet time of the recorded interface. Stored with microsecond precision.
+    pub packet_time: time::Duration,
+    /// Start of the recording. Stored with millisecond precision.
+    pub started: time::SystemTime,
+    /// Free text, e.g. the name of the show.
+    pub description: String,
+}
+
+impl Default for RecordingInfo {
+    /// Returns the info of a recording of universe `1`, started now, with the [default packet time] and no description.
+    ///
+    /// [default packet time]: crate::codec::DEFAULT_PACKET_TIME
+    ///
+    fn default() -> RecordingInfo {
+        RecordingInfo {
+            universe: 1,
+            packet_time: DEFAULT_PACKET_TIME,
+            started: time::SystemTime::now(),
+            description: String::new(),
+        }
+    }
+}
+
+/// A recorded frame with all channels.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedFrame {
+    /// Time since the start of the recording. Stored with microsecond precision.
+    pub time: time::Duration,
+    pub channels: [u8; DMX_CHANNELS],
+}
+
+/// Writes frames in the [`.dmxrec`](self) format.
+///
+/// Only the channels which changed since the previous frame are stored, unless a full frame is smaller.
+///
+/// The index is written by [`RecordingWriter::finish`], so recordings should always be finished.
+///
+pub struct RecordingWriter<W: Write> {
+    output: Output<W>,
+    last_time: time::Duration,
+    previous: Option<[u8; DMX_CHANNELS]>,
+    // Time and offset of every keyframe
+    index: Vec<(time::Duration, u64)>,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// Creates a new [RecordingWriter] and writes the header with the given info.
+    ///
+    pub fn new(writer: W, info: &RecordingInfo) -> io::Result<RecordingWriter<W>> {
+        let mut writer = Counter::new(writer);
+        write_header(&mut writer, info, 0)?;
+        Ok(RecordingWriter::with_output(Output::Plain(writer)))
+    }
+
+    /// Creates a new [RecordingWriter] which compresses the frames with [zstd] at the given `level` *(`1` to `22`, `0` for the default of `3`)*.
+    ///
+    /// Long recordings of mostly static universes compress to a fraction of their size, since the deltas are very similar.
+    /// The compressed data is only complete after [`RecordingWriter::finish`].
+    ///
+    /// [zstd]: https://facebook.github.io/zstd/
+    ///
+    #[cfg(feature = "zstd")]
+    pub fn compressed(writer: W, info: &RecordingInfo, level: i32) -> io::Result<RecordingWriter<W>> {
+        let mut writer = Counter::new(writer);
+        write_header(&mut writer, info, FLAG_ZSTD)?;
+        Ok(RecordingWriter::with_output(Output::Zstd { encoder: Some(zstd::Encoder::new(writer, level)?), level }))
+    }
+
+    fn with_output(output: Output<W>) -> RecordingWriter<W> {
+        RecordingWriter {
+            output,
+            last_time: time::Duration::ZERO,
+            previous: None,
+            index: Vec::new(),
+        }
+    }
+
+    /// Appends a frame to the recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of the kind [`io::ErrorKind::InvalidInput`] if the frame is older than the previous one.
+    ///
+    pub fn write_frame(&mut self, frame: &RecordedFrame) -> io::Result<()> {
+        if frame.time < self.last_time {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frames must be written in order"));
+        }
+        self.last_time = frame.time;
+        let keyframe = self.index.last().is_none_or(|(time, _)| frame.time >= *time + KEYFRAME_INTERVAL);
+        if keyframe {
+            self.index.push((frame.time, self.output.start_block()?));
+        }
+        let runs = self.previous.filter(|_| !keyframe).map(|previous| delta_runs(&previous, &frame.channels));
+        self.previous = Some(frame.channels);
+
+        self.output.write_all(&(frame.time.as_micros() as u64).to_le_bytes())?;
+        match runs {
+            Some(runs) if runs.iter().map(|run| RUN_HEADER_SIZE + run.len()).sum::<usize>() < DMX_CHANNELS => {
+                self.output.write_all(&[FRAME_DELTA])?;
+                self.output.write_all(&(runs.len() as u16).to_le_bytes())?;
+                for run in runs {
+                    self.output.write_all(&(run.start as u16).to_le_bytes())?;
+                    self.output.write_all(&(run.len() as u16).to_le_bytes())?;
+                    self.output.write_all(&frame.channels[run])?;
+                }
+                Ok(())
+            },
+            _ => {
+                self.output.write_all(&[FRAME_FULL])?;
+                self.output.write_all(&(DMX_CHANNELS as u16).to_le_bytes())?;
+                self.output.write_all(&frame.channels)
+            },
+        }
+    }
+
+    /// Writes the index, flushes the recording and returns the underlying writer.
+    ///
+    pub fn finish(mut self) -> io::Result<W> {
+        let index_offset = self.output.start_block()?;
+        self.output.write_all(&INDEX_MARKER.to_le_bytes())?;
+        self.output.write_all(&(self.index.len() as u32).to_le_bytes())?;
+        for (time, offset) in &self.index {
+            self.output.write_all(&(time.as_micros() as u64).to_le_bytes())?;
+            self.output.write_all(&offset.to_le_bytes())?;
+        }
+        let (mut writer, compressed) = self.output.finish()?;
+        if compressed {
+            // zstd decoders skip the trailer
+            #[cfg(feature = "zstd")]
+            {
+                writer.write_all(&ZSTD_SKIPPABLE_MAGIC.to_le_bytes())?;
+                writer.write_all(&(TRAILER_SIZE as u32).to_le_bytes())?;
+            }
+        }
+        writer.write_all(&index_offset.to_le_bytes())?;
+        writer.write_all(&INDEX_MAGIC)?;
+        writer.writer.flush()?;
+        Ok(writer.writer)
+    }
+
+    /// Converts frames from CSV into a recording.
+    ///
+    /// The first line is a header, which is skipped. Every other line contains the time in seconds *(e.g. `1.250`)*, followed by the channel values, starting at channel `1`.
+    /// Missing channels are `0`. This is the format written by [`RecordingReader::to_csv`].
+    ///
+    /// # Errors
+    ///
+    /// - [`RecordingError::InvalidCsv`] if a line can't be read *(counted from `1`)*.
+    ///
+    /// - [`RecordingError::Io`] if reading or writing fails.
+    ///
+    pub fn from_csv<R: BufRead>(csv: R, writer: W, info: &RecordingInfo) -> Result<W, RecordingError> {
+        let mut recording = RecordingWriter::new(writer, info)?;
+        for (index, line) in csv.lines().enumerate().skip(1) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = |message: &str| RecordingError::InvalidCsv { line: index + 1, message: message.to_string() };
+            let mut fields = line.split(',').map(str::trim);
+            // Rounded to microseconds, so times written by `to_csv` are read back exactly
+            let time = fields.next().and_then(|time| time.parse::<f64>().ok())
+                .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                .map(|seconds| time::Duration::from_micros((seconds * 1_000_000.0).round() as u64))
+                .ok_or_else(|| invalid("invalid time"))?;
+            let mut frame = RecordedFrame { time, channels: [0; DMX_CHANNELS] };
+            for (channel, value) in fields.enumerate() {
+                let slot = frame.channels.get_mut(channel).ok_or_else(|| invalid("too many channels"))?;
+                *slot = value.parse().map_err(|_| invalid("invalid channel value"))?;
+            }
+            recording.write_frame(&frame).map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidInput => invalid("frames are not in order"),
+                _ => RecordingError::Io(e),
+            })?;
+        }
+        Ok(recording.finish()?)
+    }
+}
+
+/// Records the frames a [`DMXSerial`] transmits into a [`RecordingWriter`].
+///
+/// The frames are collected in the background while the interface is sending and written by [`Recorder::write_pending`], which should be called regularly.
+/// They contain the values as they were sent, after all stages of the interface. The time of the first frame is the start of the recording.
+///
+/// ```no_run
+/// use open_dmx::DMXSerial;
+/// use open_dmx::recording::{Recorder, RecordingInfo, RecordingWriter};
+/// use std::fs::File;
+///
+/// let dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+/// let file = File::create("show.dmxrec").unwrap();
+/// let mut recorder = Recorder::start(&dmx, RecordingWriter::new(file, &RecordingInfo::default()).unwrap());
+/// for _ in 0..100 {
+///     std::thread::sleep(std::time::Duration::from_millis(100));
+///     recorder.write_pending().unwrap();
+/// }
+/// recorder.finish().unwrap();
+/// ```
+///
+pub struct Recorder<W: Write> {
+    writer: RecordingWriter<W>,
+    frames: mpsc::Receiver<(time::Instant, [u8; DMX_CHANNELS])>,
+    start: Option<time::Instant>,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts recording every frame the interface sends from now on.
+    ///
+    pub fn start(dmx: &DMXSerial, writer: RecordingWriter<W>) -> Recorder<W> {
+        Recorder {
+            writer,
+            frames: dmx.subscribe_frames(),
+            start: None,
+        }
+    }
+
+    /// Writes the frames which were sent since the last call and returns their number.
+    ///
+    pub fn write_pending(&mut self) -> io::Result<usize> {
+        let mut written = 0;
+        while let Ok((sent, channels)) = self.frames.try_recv() {
+            let start = *self.start.get_or_insert(sent);
+            self.writer.write_frame(&RecordedFrame { time: sent.saturating_duration_since(start), channels })?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Writes the remaining frames and [finishes](RecordingWriter::finish) the recording.
+    ///
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_pending()?;
+        self.writer.finish()
+    }
+}
+
+
+/// Reads a recording in the [`.dmxrec`](self) format frame by frame.
+///
+/// Iterating over the reader returns the frames until the end of the recording.
+///
+pub struct RecordingReader<R: Read> {
+    input: Input<R>,
+    info: RecordingInfo,
+    compressed: bool,
+    // Offset of the first frame
+    data_start: u64,
+    previous: Option<[u8; DMX_CHANNELS]>,
+    // Read while seeking, but not returned yet
+    peeked: Option<RecordedFrame>,
+    ended: bool,
+    // Time and offset of every keyframe, loaded by the first seek
+    index: Option<Vec<(time::Duration, u64)>>,
+}
+
+impl<R: Read> RecordingReader<R> {
+    /// Creates a new [RecordingReader] and reads the header.
+    ///
+    /// # Errors
+    ///
+    /// - [`RecordingError::NotARecording`] if the data doesn't start with the [`MAGIC`] bytes.
+    ///
+    /// - [`RecordingError::UnsupportedVersion`] if the recording was written with another version or unknown flags.
+    ///
+    /// - [`RecordingError::Compressed`] if the recording is compressed, but the `zstd` feature isn't enabled.
+    ///
+    /// - [`RecordingError::Corrupt`] if the header is incomplete.
+    ///
+    pub fn new(mut reader: R) -> Result<RecordingReader<R>, RecordingError> {
+        let mut magic = [0; 6];
+        read_exact(&mut reader, &mut magic, "truncated header")?;
+        if magic != MAGIC {
+            return Err(RecordingError::NotARecording);
+        }
+        let version = u16::from_le_bytes(read_array(&mut reader, "truncated header")?);
+        let flags = u16::from_le_bytes(read_array(&mut reader, "truncated header")?);
+        if version != FORMAT_VERSION || flags & !FLAG_ZSTD != 0 {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+        let universe = u16::from_le_bytes(read_array(&mut reader, "truncated header")?);
+        let started = u64::from_le_bytes(read_array(&mut reader, "truncated header")?);
+        let packet_time = u32::from_le_bytes(read_array(&mut reader, "truncated header")?);
+        let description_len = u32::from_le_bytes(read_array(&mut reader, "truncated header")?);
+        let mut description = Vec::new();
+        (&mut reader).take(description_len as u64).read_to_end(&mut description)?;
+        if description.len() != description_len as usize {
+            return Err(RecordingError::Corrupt("truncated description"));
+        }
+        let compressed = flags & FLAG_ZSTD != 0;
+        if compressed && cfg!(not(feature = "zstd")) {
+            return Err(RecordingError::Compressed);
+        }
+        Ok(RecordingReader {
+            input: Input::new(reader, compressed)?,
+            compressed,
+            data_start: HEADER_SIZE + description_len as u64,
+            previous: None,
+            peeked: None,
+            ended: false,
+            index: None,
+            info: RecordingInfo {
+                universe,
+                packet_time: time::Duration::from_micros(packet_time as u64),
+                started: time::UNIX_EPOCH + time::Duration::from_millis(started),
+                description: String::from_utf8(description).map_err(|_| RecordingError::Corrupt("description is not UTF-8"))?,
+            },
+        })
+    }
+
+    /// Returns the metadata from the header.
+    ///
+    pub fn info(&self) -> &RecordingInfo {
+        &self.info
+    }
+
+    /// Reads the next frame, or returns `None` at the end of the recording.
+    ///
+    pub fn read_frame(&mut self) -> Result<Option<RecordedFrame>, RecordingError> {
+        if let Some(frame) = self.peeked.take() {
+            return Ok(Some(frame));
+        }
+        if self.ended {
+            return Ok(None);
+        }
+        let mut time = [0; 8];
+        // Unfinished recordings end after any complete frame
+        let read = read_full(&mut self.input, &mut time)?;
+        if read == 0 || u64::from_le_bytes(time) == INDEX_MARKER {
+            self.ended = true;
+            return Ok(None);
+        }
+        if read < time.len() {
+            return Err(RecordingError::Corrupt("truncated frame"));
+        }
+        let [kind] = read_array(&mut self.input, "truncated frame")?;
+        let len = u16::from_le_bytes(read_array(&mut self.input, "truncated frame")?) as usize;
+        let mut frame = RecordedFrame {
+            time: time::Duration::from_micros(u64::from_le_bytes(time)),
+            channels: [0; DMX_CHANNELS],
+        };
+        match kind {
+            FRAME_FULL => {
+                if !(1..=DMX_CHANNELS).contains(&len) {
+                    return Err(RecordingError::Corrupt("invalid number of channels"));
+                }
+                read_exact(&mut self.input, &mut frame.channels[..len], "truncated frame")?;
+            },
+            FRAME_DELTA => {
+                frame.channels = self.previous.ok_or(RecordingError::Corrupt("delta frame before the first full frame"))?;
+                for _ in 0..len {
+                    let start = u16::from_le_bytes(read_array(&mut self.input, "truncated frame")?) as usize;
+                    let run = u16::from_le_bytes(read_array(&mut self.input, "truncated frame")?) as usize;
+                    let values = frame.channels.get_mut(start..start + run).ok_or(RecordingError::Corrupt("invalid delta run"))?;
+                    read_exact(&mut self.input, values, "truncated frame")?;
+                }
+            },
+            _ => return Err(RecordingError::Corrupt("unknown frame kind")),
+        }
+        self.previous = Some(frame.channels);
+        Ok(Some(frame))
+    }
+
+    /// Writes all remaining frames as CSV *(see [`RecordingWriter::from_csv`])*.
+    ///
+    pub fn to_csv<W: Write>(self, mut csv: W) -> Result<(), RecordingError> {
+        write!(csv, "Time")?;
+        for channel in 1..=DMX_CHANNELS {
+            write!(csv, ",{}", channel)?;
+        }
+        writeln!(csv)?;
+        for frame in self {
+            let frame = frame?;
+            write!(csv, "{:.6}", frame.time.as_secs_f64())?;
+            for value in frame.channels {
+                write!(csv, ",{}", value)?;
+            }
+            writeln!(csv)?;
+        }
+        Ok(csv.flush()?)
+    }
+}
+
+impl<R: Read + Seek> RecordingReader<R> {
+    /// Moves to the given time of the recording and returns the frame which is shown at that time, or `None` if it's before the first frame.
+    ///
+    /// The frame is rebuilt from the closest keyframe before the time, which is found in the index of the recording.
+    /// Recordings without an index *(e.g. unfinished ones)* are read from the start instead.
+    /// Reading continues with the first frame after the time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RecordingReader::read_frame`]. The reader can't be used anymore if seeking fails, except for seeking again.
+    ///
+    pub fn seek(&mut self, time: time::Duration) -> Result<Option<RecordedFrame>, RecordingError> {
+        if self.index.is_none() {
+            let index = self.read_index()?;
+            self.index = Some(index.unwrap_or_default());
+        }
+        let index = self.index.as_deref().unwrap_or_default();
+        let keyframe = index.partition_point(|(keyframe, _)| *keyframe <= time);
+        let offset = keyframe.checked_sub(1).map_or(self.data_start, |keyframe| index[keyframe].1);
+        self.move_to(offset)?;
+
+        let mut shown = None;
+        while let Some(frame) = self.read_frame()? {
+            if frame.time > time {
+                self.peeked = Some(frame);
+                break;
+            }
+            shown = Some(frame);
+        }
+        Ok(shown)
+    }
+
+    // Continues reading at the given offset of the file, where a keyframe or the index starts
+    fn move_to(&mut self, offset: u64) -> Result<(), RecordingError> {
+        let mut reader = std::mem::replace(&mut self.input, Input::Detached).into_inner()?;
+        reader.seek(SeekFrom::Start(offset))?;
+        self.input = Input::new(reader, self.compressed)?;
+        self.previous = None;
+        self.peeked = None;
+        self.ended = false;
+        Ok(())
+    }
+
+    // Returns `None` if the recording has no index
+    fn read_index(&mut self) -> Result<Option<Vec<(time::Duration, u64)>>, RecordingError> {
+        let mut reader = std::mem::replace(&mut self.input, Input::Detached).into_inner()?;
+        let mut trailer = [0; TRAILER_SIZE as usize];
+        let found = reader.seek(SeekFrom::End(-TRAILER_SIZE)).is_ok() && read_full(&mut reader, &mut trailer)? == trailer.len() && trailer[8..] == INDEX_MAGIC;
+        let offset = u64::from_le_bytes(trailer[..8].try_into().unwrap_or_default());
+        self.input = Input::Plain(reader);
+        if !found || offset < self.data_start {
+            return Ok(None);
+        }
+
+        self.move_to(offset)?;
+        if u64::from_le_bytes(read_array(&mut self.input, "truncated index")?) != INDEX_MARKER {
+            return Err(RecordingError::Corrupt("invalid index"));
+        }
+        let len = u32::from_le_bytes(read_array(&mut self.input, "truncated index")?);
+        let mut index = Vec::with_capacity(len.min(u16::MAX as u32) as usize);
+        for _ in 0..len {
+            let time = u64::from_le_bytes(read_array(&mut self.input, "truncated index")?);
+            let offset = u64::from_le_bytes(read_array(&mut self.input, "truncated index")?);
+            index.push((time::Duration::from_micros(time), offset));
+        }
+        Ok(Some(index))
+    }
+}
+
+impl<W: Write> fmt::Debug for RecordingWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RecordingWriter").field("last_time", &self.last_time).finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> fmt::Debug for Recorder<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Recorder").field("writer", &self.writer).field("start", &self.start).finish_non_exhaustive()
+    }
+}
+
+impl<R: Read> fmt::Debug for RecordingReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RecordingReader").field("info", &self.info).finish_non_exhaustive()
+    }
+}
+
+impl<R: Read> Iterator for RecordingReader<R> {
+    type Item = Result<RecordedFrame, RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+enum Output<W: Write> {
+    Plain(Counter<W>),
+    // The encoder is only taken while a new zstd frame is started
+    #[cfg(feature = "zstd")]
+    Zstd { encoder: Option<zstd::Encoder<'static, Counter<W>>>, level: i32 },
+}
+
+impl<W: Write> Output<W> {
+    // Returns the offset in the file where the following data can be decoded on its own
+    fn start_block(&mut self) -> io::Result<u64> {
+        match self {
+            Output::Plain(writer) => Ok(writer.written),
+            #[cfg(feature = "zstd")]
+            Output::Zstd { encoder, level } => {
+                let writer = encoder.take().ok_or_else(|| io::Error::other("recording failed before"))?.finish()?;
+                let offset = writer.written;
+                *encoder = Some(zstd::Encoder::new(writer, *level)?);
+                Ok(offset)
+            },
+        }
+    }
+
+    // Returns the writer and if the data was compressed
+    fn finish(self) -> io::Result<(Counter<W>, bool)> {
+        match self {
+            Output::Plain(writer) => Ok((writer, false)),
+            #[cfg(feature = "zstd")]
+            Output::Zstd { encoder, .. } => Ok((encoder.ok_or_else(|| io::Error::other("recording failed before"))?.finish()?, true)),
+        }
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "zstd")]
+            Output::Zstd { encoder, .. } => encoder.as_mut().ok_or_else(|| io::Error::other("recording failed before"))?.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Plain(writer) => writer.flush(),
+            #[cfg(feature = "zstd")]
+            Output::Zstd { encoder, .. } => encoder.as_mut().map_or(Ok(()), |encoder| encoder.flush()),
+        }
+    }
+}
+
+// Counts the written bytes for the offsets in the index
+struct Counter<W: Write> {
+    writer: W,
+    written: u64,
+}
+
+impl<W: Write> Counter<W> {
+    fn new(writer: W) -> Counter<W> {
+        Counter { writer, written: 0 }
+    }
+}
+
+impl<W: Write> Write for Counter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+enum Input<R: Read> {
+    Plain(R),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Decoder<'static, io::BufReader<R>>),
+    // Only while seeking. Left behind if seeking fails
+    Detached,
+}
+
+impl<R: Read> Input<R> {
+    fn new(reader: R, compressed: bool) -> io::Result<Input<R>> {
+        match compressed {
+            #[cfg(feature = "zstd")]
+            true => Ok(Input::Zstd(zstd::Decoder::new(reader)?)),
+            _ => Ok(Input::Plain(reader)),
+        }
+    }
+
+    fn into_inner(self) -> io::Result<R> {
+        match self {
+            Input::Plain(reader) => Ok(reader),
+            #[cfg(feature = "zstd")]
+            Input::Zstd(decoder) => Ok(decoder.finish().into_inner()),
+            Input::Detached => Err(detached()),
+        }
+    }
+}
+
+impl<R: Read> Read for Input<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "zstd")]
+            Input::Zstd(decoder) => decoder.read(buf),
+            Input::Detached => Err(detached()),
+        }
+    }
+}
+
+fn detached() -> io::Error {
+    io::Error::other("seeking in the recording failed before")
+}
+
+fn write_header<W: Write>(writer: &mut W, info: &RecordingInfo, flags: u16) -> io::Result<()> {
+    let started = info.started.duration_since(time::UNIX_EPOCH).unwrap_or_default();
+    let description_len = u32::try_from(info.description.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "description is too long"))?;
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&flags.to_le_bytes())?;
+    writer.write_all(&info.universe.to_le_bytes())?;
+    writer.write_all(&(started.as_millis() as u64).to_le_bytes())?;
+    writer.write_all(&(info.packet_time.as_micros().min(u32::MAX as u128) as u32).to_le_bytes())?;
+    writer.write_all(&description_len.to_le_bytes())?;
+    writer.write_all(info.description.as_bytes())
+}
+
+// Groups the changed channels into runs. Unchanged channels between two changes are included if that's smaller than starting a new run
+fn delta_runs(previous: &[u8; DMX_CHANNELS], channels: &[u8; DMX_CHANNELS]) -> Vec<Range<usize>> {
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    for index in (0..DMX_CHANNELS).filter(|index| previous[*index] != channels[*index]) {
+        match runs.last_mut() {
+            Some(run) if index - run.end < RUN_HEADER_SIZE => run.end = index + 1,
+            _ => runs.push(index..index + 1),
+        }
+    }
+    runs
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8], part: &'static str) -> Result<(), RecordingError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => RecordingError::Corrupt(part),
+        _ => RecordingError::Io(e),
+    })
+}
+
+fn read_array<const N: usize, R: Read>(reader: &mut R, part: &'static str) -> Result<[u8; N], RecordingError> {
+    let mut buf = [0; N];
+    read_exact(reader, &mut buf, part)?;
+    Ok(buf)
+}
+
+// Like `read_exact`, but returns how many bytes were read before the end of the data
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RecordingError;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn recordings_round_trip_through_dmxrec_and_csv() {
+        let info = RecordingInfo {
+            universe: 3,
+            packet_time: Duration::from_millis(25),
+            started: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+            description: "Act 1".to_string(),
+        };
+        let frames: Vec<RecordedFrame> = (0..5u8).map(|i| {
+            let mut channels = [0; DMX_CHANNELS];
+            channels[i as usize] = 255;
+            channels[511] = i;
+            RecordedFrame { time: Duration::from_micros(i as u64 * 25_000 + 7), channels }
+        }).collect();
+
+        let mut writer = RecordingWriter::new(Vec::new(), &info).unwrap();
+        frames.iter().for_each(|frame| writer.write_frame(frame).unwrap());
+        assert!(writer.write_frame(&frames[0]).is_err());
+        let file = writer.finish().unwrap();
+        assert_eq!(file[..6], MAGIC);
+
+        let reader = RecordingReader::new(&file[..]).unwrap();
+        assert_eq!(reader.info(), &info);
+        let mut csv = Vec::new();
+        reader.to_csv(&mut csv).unwrap();
+        assert!(csv.starts_with(b"Time,1,2,3,"));
+
+        let converted = RecordingWriter::from_csv(&csv[..], Vec::new(), &info).unwrap();
+        assert_eq!(converted, file);
+        let read: Vec<RecordedFrame> = RecordingReader::new(&converted[..]).unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(read, frames);
+
+        assert!(matches!(RecordingReader::new(&b"not a recording"[..]), Err(RecordingError::NotARecording)));
+        let mut other = file.clone();
+        assert_eq!(other[6..8], [1, 0]);
+        for version in [0, 2, 99] {
+            other[6] = version;
+            assert!(matches!(RecordingReader::new(&other[..]), Err(RecordingError::UnsupportedVersion(v)) if v == version as u16));
+        }
+        // The index with one keyframe and the trailer follow the frames
+        let truncated = &file[..file.len() - 44 - 10];
+        assert_eq!(RecordingReader::new(truncated).unwrap().filter(Result::is_ok).count(), 4);
+        assert!(matches!(RecordingReader::new(truncated).unwrap().last(), Some(Err(RecordingError::Corrupt(_)))));
+        assert!(matches!(RecordingWriter::from_csv(&b"Time,1\n0.5,256\n"[..], Vec::new(), &info), Err(RecordingError::InvalidCsv { line: 2, .. })));
+    }
+
+    #[test]
+    fn recordings_store_deltas_and_compress() {
+        // One minute of a slow fade on one channel
+        let frames: Vec<RecordedFrame> = (0..2640u32).map(|i| {
+            let mut channels = [100; DMX_CHANNELS];
+            channels[0] = (i / 11) as u8;
+            RecordedFrame { time: Duration::from_micros(i as u64 * 22_700), channels }
+        }).collect();
+        let record = |mut writer: RecordingWriter<Vec<u8>>| {
+            frames.iter().for_each(|frame| writer.write_frame(frame).unwrap());
+            writer.finish().unwrap()
+        };
+        let read = |file: &[u8]| RecordingReader::new(file).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+
+        let file = record(RecordingWriter::new(Vec::new(), &RecordingInfo::default()).unwrap());
+        // Deltas of up to 16 bytes and a keyframe every second
+        assert!(file.len() < 2640 * 16 + 60 * DMX_CHANNELS + 2048);
+        assert_eq!(read(&file), frames);
+
+        #[cfg(feature = "zstd")]
+        {
+            let compressed = record(RecordingWriter::compressed(Vec::new(), &RecordingInfo::default(), 0).unwrap());
+            assert!(compressed.len() < file.len() / 3);
+            assert_eq!(read(&compressed), frames);
+        }
+    }
+
+    #[test]
+    fn recorders_capture_the_transmitted_frames() {
+        let mut renderer = crate::OfflineRenderer::new().unwrap();
+        let mut recorder = Recorder::start(renderer.dmx(), RecordingWriter::new(Vec::new(), &RecordingInfo::default()).unwrap());
+        renderer.dmx().set_channel(1, 200).unwrap();
+        renderer.dmx().park_channel(2, 50).unwrap();
+        renderer.render_frame(Duration::from_millis(100)).unwrap();
+        renderer.dmx().set_channel(1, 100).unwrap();
+        renderer.render_frame(Duration::from_millis(150)).unwrap();
+        assert_eq!(recorder.write_pending().unwrap(), 2);
+        renderer.render_frame(Duration::from_millis(175)).unwrap();
+
+        let file = recorder.finish().unwrap();
+        let frames = RecordingReader::new(&file[..]).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(frames.iter().map(|frame| frame.time).collect::<Vec<_>>(), [Duration::ZERO, Duration::from_millis(50), Duration::from_millis(75)]);
+        assert_eq!(frames.iter().map(|frame| frame.channels[0]).collect::<Vec<_>>(), [200, 100, 100]);
+        // Parked channels are recorded as they were sent
+        assert!(frames.iter().all(|frame| frame.channels[1] == 50));
+    }
+}
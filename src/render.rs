@@ -0,0 +1,63 @@
+use crate::DMX_CHANNELS;
+
+use std::fmt;
+use std::time;
+
+/// Timing of the frame which is being rendered. See [`DMXSerial::set_render_callback`].
+///
+/// [`DMXSerial::set_render_callback`]: crate::DMXSerial::set_render_callback
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameContext {
+    /// Number of the frame, starting at `0` with the first frame after the callback was set.
+    pub sequence: u64,
+    /// When the frame is rendered, right before it is sent.
+    pub time: time::Instant,
+    /// Time since the first frame.
+    pub elapsed: time::Duration,
+    /// Time since the previous frame, `0` for the first one.
+    pub delta: time::Duration,
+    /// The packet time the frames are sent with.
+    pub packet_time: time::Duration,
+}
+
+pub(crate) type RenderCallback = Box<dyn FnMut(&FrameContext, &mut [u8; DMX_CHANNELS]) + Send>;
+
+// Calls the render callback once for every frame the agent sends
+pub(crate) struct Renderer {
+    callback: RenderCallback,
+    sequence: u64,
+    first: Option<time::Instant>,
+    last: Option<time::Instant>,
+}
+
+impl Renderer {
+    pub fn new(callback: RenderCallback) -> Renderer {
+        Renderer {
+            callback,
+            sequence: 0,
+            first: None,
+            last: None,
+        }
+    }
+
+    pub fn render(&mut self, packet_time: time::Duration, channels: &mut [u8; DMX_CHANNELS], now: time::Instant) {
+        let first = *self.first.get_or_insert(now);
+        let context = FrameContext {
+            sequence: self.sequence,
+            time: now,
+            elapsed: now.saturating_duration_since(first),
+            delta: self.last.map_or(time::Duration::ZERO, |last| now.saturating_duration_since(last)),
+            packet_time,
+        };
+        (self.callback)(&context, channels);
+        self.sequence += 1;
+        self.last = Some(now);
+    }
+}
+
+impl fmt::Debug for Renderer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Renderer").field("sequence", &self.sequence).finish_non_exhaustive()
+    }
+}
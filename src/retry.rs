@@ -0,0 +1,115 @@
+use crate::error::DMXOpenError;
+use crate::DMXSerial;
+
+use std::path::Path;
+use std::thread;
+use std::time;
+
+/// How [`DMXSerial::open_with_retry`] waits for a port which is held by another process.
+///
+/// The delay starts at `initial_delay` and doubles after every attempt, up to `max_delay`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts, including the first one.
+    pub attempts: usize,
+    pub initial_delay: time::Duration,
+    pub max_delay: time::Duration,
+    /// Logs the processes holding the port after a failed attempt. *(Linux only)*
+    pub log_holders: bool,
+}
+
+impl Default for RetryPolicy {
+    /// Tries 5 times, starting with a delay of 100 ms up to 2 seconds, and logs the holders.
+    ///
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 5,
+            initial_delay: time::Duration::from_millis(100),
+            max_delay: time::Duration::from_secs(2),
+            log_holders: true,
+        }
+    }
+}
+
+/// A process which has a port open, returned by [`port_holders`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortHolder {
+    pub pid: u32,
+    /// The command name of the process, if it could be read.
+    pub name: Option<String>,
+}
+
+/// Looks up the processes which have the port on the given [`path`] open. *(Linux only, empty elsewhere)*
+///
+/// Only processes of the same user can be found without elevated rights.
+///
+/// [`path`]: std::path::Path
+///
+#[cfg(target_os = "linux")]
+pub fn port_holders<P: AsRef<Path>>(port: P) -> Vec<PortHolder> {
+    use std::fs;
+
+    let wanted = crate::adapter::canonical(port);
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    processes.filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok().map(|pid| (pid, entry.path())))
+        .filter(|(_, path)| {
+            // Processes of other users can't be read and are skipped
+            fs::read_dir(path.join("fd")).is_ok_and(|fds| fds.filter_map(|fd| fd.ok())
+                .any(|fd| fs::read_link(fd.path()).is_ok_and(|target| target == Path::new(&wanted))))
+        })
+        .map(|(pid, path)| PortHolder {
+            pid,
+            name: fs::read_to_string(path.join("comm")).ok().map(|name| name.trim_end().to_string()),
+        })
+        .collect()
+}
+
+/// Looks up the processes which have the port on the given [`path`] open. *(Linux only, empty elsewhere)*
+///
+/// [`path`]: std::path::Path
+///
+#[cfg(not(target_os = "linux"))]
+pub fn port_holders<P: AsRef<Path>>(_port: P) -> Vec<PortHolder> {
+    Vec::new()
+}
+
+impl DMXSerial {
+    /// Does the same as [`DMXSerial::open`], but retries with the [`RetryPolicy`] while another process holds the port.
+    ///
+    /// Other errors are returned right away.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use open_dmx::{DMXSerial, RetryPolicy};
+    /// # fn main() {
+    /// let dmx = DMXSerial::open_with_retry("/dev/ttyUSB0", RetryPolicy::default()).unwrap();
+    /// # }
+    /// ```
+    ///
+    pub fn open_with_retry<P: AsRef<Path>>(port: P, policy: RetryPolicy) -> Result<DMXSerial, DMXOpenError> {
+        let port = port.as_ref();
+        let mut delay = policy.initial_delay;
+        let mut attempt = 1;
+        loop {
+            match DMXSerial::open(port).map_err(DMXOpenError::from) {
+                Err(DMXOpenError::Busy(_)) if attempt < policy.attempts => {
+                    if policy.log_holders {
+                        for holder in port_holders(port) {
+                            eprintln!("\"{}\" is held by process {} ({}). Retrying in {:?}...", port.display(), holder.pid, holder.name.as_deref().unwrap_or("unknown"), delay);
+                        }
+                    }
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2).min(policy.max_delay);
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::builder::LineSettings;
+use crate::transport::Transport;
+
+use serialport::{DataBits, Parity, StopBits};
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+
+const SCHEME: &str = "rfc2217://";
+
+// Telnet commands and options
+const IAC: u8 = 255;
+const WILL: u8 = 251;
+const DO: u8 = 253;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const BINARY: u8 = 0;
+const COM_PORT_OPTION: u8 = 44;
+
+// Commands of the COM-PORT-OPTION (RFC 2217)
+const SET_BAUDRATE: u8 = 1;
+const SET_DATASIZE: u8 = 2;
+const SET_PARITY: u8 = 3;
+const SET_STOPSIZE: u8 = 4;
+const SET_CONTROL: u8 = 5;
+
+// Values of SET-CONTROL
+const NO_FLOW_CONTROL: u8 = 1;
+const BREAK_ON: u8 = 5;
+const BREAK_OFF: u8 = 6;
+const DTR_ON: u8 = 8;
+const DTR_OFF: u8 = 9;
+const RTS_ON: u8 = 11;
+const RTS_OFF: u8 = 12;
+
+/// A [`Transport`] for a **remote serial port**, shared over the network with **RFC 2217** *(Telnet Com Port Control, e.g. by `ser2net`)*.
+///
+/// The break, the line settings and the direction lines are sent as commands to the server, so the adapter can stay near the rig while the controller runs elsewhere.
+/// The network adds its own latency and jitter to the packets, so the timing is less exact than with a local port.
+///
+/// [`DMXSerial::open`] connects to the server on its own if the port is an address like `rfc2217://host:port`.
+///
+/// [`DMXSerial::open`]: crate::DMXSerial::open
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::DMXSerial;
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("rfc2217://10.0.0.5:7000").unwrap();
+///     dmx.set_channels([255; 512]);
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct Rfc2217Port {
+    stream: TcpStream,
+}
+
+impl Rfc2217Port {
+    /// Connects to the RFC 2217 server at the given address *(`host:port`)* and switches the remote port to the given [`LineSettings`].
+    ///
+    pub fn connect(address: &str, settings: LineSettings) -> io::Result<Rfc2217Port> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        let mut port = Rfc2217Port { stream };
+        port.stream.write_all(&[IAC, WILL, COM_PORT_OPTION, IAC, WILL, BINARY, IAC, DO, BINARY])?;
+        port.command(SET_CONTROL, &[NO_FLOW_CONTROL])?;
+        port.set_line_settings(settings)?;
+        Ok(port)
+    }
+
+    fn command(&mut self, command: u8, value: &[u8]) -> io::Result<()> {
+        let mut message = vec![IAC, SB, COM_PORT_OPTION, command];
+        escape(value, &mut message);
+        message.extend_from_slice(&[IAC, SE]);
+        self.stream.write_all(&message)
+    }
+
+    fn set_line_settings(&mut self, settings: LineSettings) -> io::Result<()> {
+        self.command(SET_BAUDRATE, &settings.baud_rate.to_be_bytes())?;
+        let data_size = match settings.data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        };
+        self.command(SET_DATASIZE, &[data_size])?;
+        let parity = match settings.parity {
+            Parity::None => 1,
+            Parity::Odd => 2,
+            Parity::Even => 3,
+        };
+        self.command(SET_PARITY, &[parity])?;
+        let stop_size = match settings.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        self.command(SET_STOPSIZE, &[stop_size])
+    }
+
+    // Discards the answers of the server and the data received by the remote port, so they don't fill up the connection
+    fn drain(&mut self) -> io::Result<()> {
+        self.stream.set_nonblocking(true)?;
+        let mut buf = [0; 1024];
+        let result = loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => break Err(io::Error::new(io::ErrorKind::ConnectionAborted, "the RFC 2217 server closed the connection")),
+                Ok(_) => continue,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => break Err(e),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        result
+    }
+}
+
+// Returns the `host:port` of an `rfc2217://` port
+pub(crate) fn remote_address(port: &Path) -> Option<&str> {
+    port.to_str()?.strip_prefix(SCHEME)
+}
+
+// Data bytes equal to IAC are doubled
+fn escape(data: &[u8], out: &mut Vec<u8>) {
+    for &byte in data {
+        out.push(byte);
+        if byte == IAC {
+            out.push(IAC);
+        }
+    }
+}
+
+impl io::Write for Rfc2217Port {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.drain()?;
+        let mut data = Vec::with_capacity(buf.len() + 8);
+        escape(buf, &mut data);
+        self.stream.write_all(&data)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Transport for Rfc2217Port {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(self.command(SET_CONTROL, &[BREAK_ON])?)
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(self.command(SET_CONTROL, &[BREAK_OFF])?)
+    }
+
+    fn apply_line_settings(&mut self, settings: LineSettings) -> serialport::Result<()> {
+        Ok(self.set_line_settings(settings)?)
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        Ok(self.command(SET_CONTROL, &[if level { RTS_ON } else { RTS_OFF }])?)
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        Ok(self.command(SET_CONTROL, &[if level { DTR_ON } else { DTR_OFF }])?)
+    }
+}
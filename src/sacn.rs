@@ -0,0 +1,321 @@
+//! **sACN** *(E1.31)* universe discovery, so sources can advertise their universes and receivers can list what's on the network.
+//!
+//! Sources send the list of their universes every [`DISCOVERY_INTERVAL`] with a [`UniverseAnnouncer`]. [`discover`] collects these lists.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use open_dmx::sacn;
+//! use std::time::Duration;
+//!
+//! fn main() {
+//!     for source in sacn::discover(sacn::DISCOVERY_INTERVAL + Duration::from_secs(1)).unwrap() {
+//!         println!("{}: {:?}", source.name, source.universes);
+//!     }
+//! }
+//! ```
+//!
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time;
+
+/// UDP port of sACN.
+///
+pub const PORT: u16 = 5568;
+
+/// Multicast address of the universe discovery packets.
+///
+pub const DISCOVERY_ADDRESS: Ipv4Addr = Ipv4Addr::new(239, 255, 250, 214);
+
+/// Time between two universe discovery packets of a source.
+///
+pub const DISCOVERY_INTERVAL: time::Duration = time::Duration::from_secs(10);
+
+/// Most universes in one discovery packet. Longer lists are split into pages.
+///
+pub const UNIVERSES_PER_PAGE: usize = 512;
+
+const ACN_PACKET_IDENTIFIER: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_E131_EXTENDED: u32 = 0x0000_0008;
+const VECTOR_E131_EXTENDED_DISCOVERY: u32 = 0x0000_0002;
+const VECTOR_UNIVERSE_DISCOVERY_UNIVERSE_LIST: u32 = 0x0000_0001;
+
+// Offsets of the layers, which start with their flags and length
+const FRAMING_LAYER: usize = 38;
+const DISCOVERY_LAYER: usize = 112;
+const HEADER_SIZE: usize = 120;
+
+const SOURCE_NAME_SIZE: usize = 64;
+
+/// One page of the universe list of a source, as sent in a universe discovery packet.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryPage {
+    /// Unique identifier of the source *(CID)*.
+    pub cid: [u8; 16],
+    pub source_name: String,
+    /// Number of this page, starting at `0`.
+    pub page: u8,
+    /// Number of the last page of the list.
+    pub last_page: u8,
+    /// Universes on this page, sorted.
+    pub universes: Vec<u16>,
+}
+
+/// A source found by [`discover`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredSource {
+    /// Unique identifier of the source *(CID)*.
+    pub cid: [u8; 16],
+    pub name: String,
+    /// Universes the source sends, sorted.
+    pub universes: Vec<u16>,
+    /// `false` if some pages of the universe list weren't received.
+    pub complete: bool,
+}
+
+/// Returns the universe discovery packets of a source, one per page of [`UNIVERSES_PER_PAGE`] universes.
+///
+/// The universes are sorted and duplicates are removed. A source without universes sends one empty page.
+///
+pub fn encode_universe_discovery(cid: &[u8; 16], source_name: &str, universes: &[u16]) -> Vec<Vec<u8>> {
+    let mut universes = universes.to_vec();
+    universes.sort_unstable();
+    universes.dedup();
+    let pages: Vec<&[u16]> = match universes.is_empty() {
+        true => vec![&[]],
+        false => universes.chunks(UNIVERSES_PER_PAGE).collect(),
+    };
+    let last_page = (pages.len() - 1).min(u8::MAX as usize) as u8;
+
+    let mut name = [0; SOURCE_NAME_SIZE];
+    // The name is null-terminated
+    let name_len = (0..=source_name.len().min(SOURCE_NAME_SIZE - 1)).rev().find(|len| source_name.is_char_boundary(*len)).unwrap_or(0);
+    name[..name_len].copy_from_slice(&source_name.as_bytes()[..name_len]);
+
+    pages.iter().take(u8::MAX as usize + 1).enumerate().map(|(page, universes)| {
+        let len = HEADER_SIZE + universes.len() * 2;
+        let mut packet = Vec::with_capacity(len);
+        // Root layer
+        packet.extend_from_slice(&0x0010u16.to_be_bytes());
+        packet.extend_from_slice(&0x0000u16.to_be_bytes());
+        packet.extend_from_slice(ACN_PACKET_IDENTIFIER);
+        packet.extend_from_slice(&flags_and_length(len - 16));
+        packet.extend_from_slice(&VECTOR_ROOT_E131_EXTENDED.to_be_bytes());
+        packet.extend_from_slice(cid);
+        // Framing layer
+        packet.extend_from_slice(&flags_and_length(len - FRAMING_LAYER));
+        packet.extend_from_slice(&VECTOR_E131_EXTENDED_DISCOVERY.to_be_bytes());
+        packet.extend_from_slice(&name);
+        packet.extend_from_slice(&[0; 4]);
+        // Universe discovery layer
+        packet.extend_from_slice(&flags_and_length(len - DISCOVERY_LAYER));
+        packet.extend_from_slice(&VECTOR_UNIVERSE_DISCOVERY_UNIVERSE_LIST.to_be_bytes());
+        packet.extend_from_slice(&[page as u8, last_page]);
+        universes.iter().for_each(|universe| packet.extend_from_slice(&universe.to_be_bytes()));
+        packet
+    }).collect()
+}
+
+/// Reads a universe discovery packet. Returns `None` if the packet isn't one.
+///
+pub fn decode_universe_discovery(packet: &[u8]) -> Option<DiscoveryPage> {
+    let u32_at = |offset: usize| u32::from_be_bytes([packet[offset], packet[offset + 1], packet[offset + 2], packet[offset + 3]]);
+    if packet.len() < HEADER_SIZE
+        || &packet[4..16] != ACN_PACKET_IDENTIFIER
+        || u32_at(18) != VECTOR_ROOT_E131_EXTENDED
+        || u32_at(FRAMING_LAYER + 2) != VECTOR_E131_EXTENDED_DISCOVERY
+        || u32_at(DISCOVERY_LAYER + 2) != VECTOR_UNIVERSE_DISCOVERY_UNIVERSE_LIST {
+        return None;
+    }
+    // The length of the last layer tells how many universes follow
+    let layer_len = (u16::from_be_bytes([packet[DISCOVERY_LAYER], packet[DISCOVERY_LAYER + 1]]) & 0x0FFF) as usize;
+    let end = (DISCOVERY_LAYER + layer_len).min(packet.len());
+    let name = &packet[44..44 + SOURCE_NAME_SIZE];
+    let name_len = name.iter().position(|c| *c == 0).unwrap_or(SOURCE_NAME_SIZE);
+    Some(DiscoveryPage {
+        cid: packet[22..38].try_into().ok()?,
+        source_name: String::from_utf8_lossy(&name[..name_len]).into_owned(),
+        page: packet[118],
+        last_page: packet[119],
+        universes: packet[HEADER_SIZE..end.max(HEADER_SIZE)].chunks_exact(2).map(|universe| u16::from_be_bytes([universe[0], universe[1]])).collect(),
+    })
+}
+
+/// Lists the sACN sources on the local network and their universes, by receiving universe discovery packets for `timeout`.
+///
+/// Sources only announce their universes every [`DISCOVERY_INTERVAL`], so the timeout should be a bit longer.
+/// The packets are received on port [`PORT`], which fails if another application uses the port.
+///
+pub fn discover(timeout: time::Duration) -> io::Result<Vec<DiscoveredSource>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, PORT))?;
+    socket.join_multicast_v4(&DISCOVERY_ADDRESS, &Ipv4Addr::UNSPECIFIED)?;
+    discover_on(&socket, timeout)
+}
+
+/// Does the same as [`discover`], but receives the packets on the given socket *(e.g. bound to a specific interface)*.
+///
+pub fn discover_on(socket: &UdpSocket, timeout: time::Duration) -> io::Result<Vec<DiscoveredSource>> {
+    let deadline = time::Instant::now() + timeout;
+    // Sources and the universes of every page of their list
+    let mut found: Vec<(DiscoveredSource, Vec<Option<Vec<u16>>>)> = Vec::new();
+    let mut buf = [0; HEADER_SIZE + UNIVERSES_PER_PAGE * 2];
+    loop {
+        let remaining = deadline.saturating_duration_since(time::Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))?;
+        let len = match socket.recv(&mut buf) {
+            Ok(len) => len,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => break,
+            Err(e) => return Err(e),
+        };
+        let Some(page) = decode_universe_discovery(&buf[..len]) else {
+            continue;
+        };
+        let index = match found.iter().position(|(source, _)| source.cid == page.cid) {
+            Some(index) => index,
+            None => {
+                let source = DiscoveredSource { cid: page.cid, name: String::new(), universes: Vec::new(), complete: false };
+                found.push((source, Vec::new()));
+                found.len() - 1
+            },
+        };
+        let (source, pages) = &mut found[index];
+        source.name = page.source_name;
+        // The list changed its length since the last packets
+        if pages.len() != page.last_page as usize + 1 {
+            *pages = vec![None; page.last_page as usize + 1];
+        }
+        if let Some(slot) = pages.get_mut(page.page as usize) {
+            *slot = Some(page.universes);
+        }
+    }
+
+    Ok(found.into_iter().map(|(mut source, pages)| {
+        source.complete = pages.iter().all(Option::is_some);
+        source.universes = pages.into_iter().flatten().flatten().collect();
+        source
+    }).collect())
+}
+
+/// Announces the universes of a source with universe discovery packets every [`DISCOVERY_INTERVAL`].
+///
+/// The announcer doesn't run on its own: [`UniverseAnnouncer::poll`] sends the packets when they are due.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::sacn::UniverseAnnouncer;
+///
+/// fn main() {
+///     let mut announcer = UniverseAnnouncer::new([7; 16], "open_dmx").unwrap();
+///     announcer.set_universes(&[1, 2, 3]);
+///     loop {
+///         announcer.poll().unwrap();
+///         std::thread::sleep(std::time::Duration::from_secs(1));
+///     }
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct UniverseAnnouncer {
+    socket: UdpSocket,
+    target: SocketAddr,
+    cid: [u8; 16],
+    name: String,
+    universes: Vec<u16>,
+    last_sent: Option<time::Instant>,
+}
+
+impl UniverseAnnouncer {
+    /// Creates a new [UniverseAnnouncer] which sends to the [`DISCOVERY_ADDRESS`]. `cid` has to be unique for the source and should stay the same between restarts.
+    ///
+    pub fn new(cid: [u8; 16], name: &str) -> io::Result<UniverseAnnouncer> {
+        UniverseAnnouncer::with_target(cid, name, SocketAddr::from((DISCOVERY_ADDRESS, PORT)))
+    }
+
+    /// Does the same as [`UniverseAnnouncer::new`], but sends the packets to the given address.
+    ///
+    pub fn with_target(cid: [u8; 16], name: &str, target: SocketAddr) -> io::Result<UniverseAnnouncer> {
+        Ok(UniverseAnnouncer {
+            socket: UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?,
+            target,
+            cid,
+            name: name.to_string(),
+            universes: Vec::new(),
+            last_sent: None,
+        })
+    }
+
+    /// Sets the universes the source sends. They are announced with the next [`UniverseAnnouncer::poll`].
+    ///
+    pub fn set_universes(&mut self, universes: &[u16]) {
+        self.universes = universes.to_vec();
+        self.universes.sort_unstable();
+        self.universes.dedup();
+        self.last_sent = None;
+    }
+
+    /// Returns the announced universes, sorted.
+    ///
+    pub fn universes(&self) -> &[u16] {
+        &self.universes
+    }
+
+    /// Sends the universe list if it changed or the [`DISCOVERY_INTERVAL`] passed. Returns `true` if it was sent.
+    ///
+    /// Nothing is sent while there are no universes.
+    ///
+    pub fn poll(&mut self) -> io::Result<bool> {
+        if self.universes.is_empty() || self.last_sent.is_some_and(|sent| sent.elapsed() < DISCOVERY_INTERVAL) {
+            return Ok(false);
+        }
+        for packet in encode_universe_discovery(&self.cid, &self.name, &self.universes) {
+            self.socket.send_to(&packet, self.target)?;
+        }
+        self.last_sent = Some(time::Instant::now());
+        Ok(true)
+    }
+}
+
+fn flags_and_length(len: usize) -> [u8; 2] {
+    (0x7000 | (len as u16 & 0x0FFF)).to_be_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn sacn_universes_are_announced_and_discovered() {
+        let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = receiver.local_addr().unwrap();
+        let universes: Vec<u16> = (1..=600).rev().collect();
+
+        let packets = encode_universe_discovery(&[1; 16], "Console", &universes);
+        assert_eq!(packets.len(), 2);
+        let page = decode_universe_discovery(&packets[1]).unwrap();
+        assert_eq!((page.page, page.last_page, page.source_name.as_str()), (1, 1, "Console"));
+        assert_eq!(page.universes, (513..=600).collect::<Vec<u16>>());
+
+        let mut console = UniverseAnnouncer::with_target([1; 16], "Console", target).unwrap();
+        assert!(!console.poll().unwrap());
+        console.set_universes(&universes);
+        assert!(console.poll().unwrap());
+        assert!(!console.poll().unwrap());
+        let mut node = UniverseAnnouncer::with_target([2; 16], "Media server", target).unwrap();
+        node.set_universes(&[7]);
+        assert!(node.poll().unwrap());
+
+        let sources = discover_on(&receiver, Duration::from_millis(200)).unwrap();
+        assert_eq!(sources.len(), 2);
+        assert_eq!((sources[0].name.as_str(), sources[0].complete), ("Console", true));
+        assert_eq!(sources[0].universes, (1..=600).collect::<Vec<u16>>());
+        assert_eq!((sources[1].cid, sources[1].universes.as_slice()), ([2; 16], &[7][..]));
+    }
+}
@@ -0,0 +1,521 @@
+use crate::check_valid_channel;
+use crate::error::DMXChannelValidityError;
+use crate::fixture::Fixture;
+use crate::DMX_CHANNELS;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+// Blocked outputs which are kept for SafetyInterlock::blocked
+const BLOCKED_LOG_DEPTH: usize = 256;
+
+// The window the flashes of StrobeGuard are counted in
+const FLASH_WINDOW: time::Duration = time::Duration::from_secs(1);
+
+/// A non-zero value on a protected channel which was forced to `0`. See [`SafetyInterlock`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedOutput {
+    pub channel: usize,
+    /// The value which would have been sent.
+    pub value: u8,
+    /// When the value was blocked.
+    pub time: time::SystemTime,
+}
+
+/// Protects channels which must only output when they're explicitly **armed**, e.g. pyro effects or strobes in photosensitive-safe venues.
+///
+/// The interlock is applied by the agent right before every packet is sent, after the [transforms](crate::DMXSerial::add_transform) and the [parked channels](crate::DMXSerial::park_channel).
+/// Raw frames with the null start code are checked as well, so they can't bypass it.
+/// A protected channel which isn't armed is always sent as `0`. Every attempt to output a non-zero value on it is logged to `stderr` and kept in [`SafetyInterlock::blocked`].
+///
+/// Clones share the same state, so channels can be armed after the interlock was set.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, SafetyInterlock};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let interlock = SafetyInterlock::new();
+///     interlock.protect(100).unwrap(); // pyro
+///     dmx.set_interlock(interlock.clone());
+///     dmx.set_channel(100, 255).unwrap(); // blocked
+///     interlock.arm(100).unwrap();
+///     // ... fire
+///     interlock.disarm_all();
+/// }
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct SafetyInterlock {
+    state: Arc<Mutex<InterlockState>>,
+}
+
+struct InterlockState {
+    protected: [bool; DMX_CHANNELS],
+    armed: [bool; DMX_CHANNELS],
+    // Channels which are blocked right now, so an attempt is only logged once
+    blocking: [bool; DMX_CHANNELS],
+    blocked: VecDeque<BlockedOutput>,
+}
+
+impl Default for InterlockState {
+    fn default() -> InterlockState {
+        InterlockState {
+            protected: [false; DMX_CHANNELS],
+            armed: [false; DMX_CHANNELS],
+            blocking: [false; DMX_CHANNELS],
+            blocked: VecDeque::new(),
+        }
+    }
+}
+
+impl SafetyInterlock {
+    /// Creates a [SafetyInterlock] without protected channels.
+    ///
+    pub fn new() -> SafetyInterlock {
+        SafetyInterlock::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, InterlockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Protects the [`channel`], it starts disarmed.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn protect(&self, channel: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        let mut state = self.state();
+        state.protected[channel - 1] = true;
+        state.armed[channel - 1] = false;
+        Ok(())
+    }
+
+    /// Removes the protection of the [`channel`].
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn unprotect(&self, channel: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        self.state().protected[channel - 1] = false;
+        Ok(())
+    }
+
+    /// Returns `true` if the [`channel`] is protected.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn is_protected(&self, channel: usize) -> Result<bool, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        Ok(self.state().protected[channel - 1])
+    }
+
+    /// Arms the protected [`channel`], so it outputs its value.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn arm(&self, channel: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        self.state().armed[channel - 1] = true;
+        Ok(())
+    }
+
+    /// Disarms the [`channel`], so it's forced to `0` again if it's protected.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn disarm(&self, channel: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        self.state().armed[channel - 1] = false;
+        Ok(())
+    }
+
+    /// Disarms all channels.
+    ///
+    pub fn disarm_all(&self) {
+        self.state().armed.fill(false);
+    }
+
+    /// Returns `true` if the [`channel`] is armed.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn is_armed(&self, channel: usize) -> Result<bool, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        Ok(self.state().armed[channel - 1])
+    }
+
+    /// Returns the last blocked outputs *(up to 256)*, oldest first.
+    ///
+    /// A value which stays on a channel is only recorded once, until the channel returns to `0` or is armed.
+    ///
+    pub fn blocked(&self) -> Vec<BlockedOutput> {
+        self.state().blocked.iter().cloned().collect()
+    }
+
+    /// Clears the blocked outputs.
+    ///
+    pub fn clear_blocked(&self) {
+        self.state().blocked.clear();
+    }
+
+    // Forces the protected channels which aren't armed to 0
+    pub(crate) fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        let mut state = self.state();
+        let state = &mut *state;
+        for (index, value) in channels.iter_mut().enumerate() {
+            let blocked = state.protected[index] && !state.armed[index] && *value != 0;
+            if blocked && !state.blocking[index] {
+                eprintln!("Blocked the value {} on the protected channel {}, it isn't armed", value, index + 1);
+                if state.blocked.len() == BLOCKED_LOG_DEPTH {
+                    state.blocked.pop_front();
+                }
+                state.blocked.push_back(BlockedOutput {
+                    channel: index + 1,
+                    value: *value,
+                    time: time::SystemTime::now(),
+                });
+            }
+            state.blocking[index] = blocked;
+            if blocked {
+                *value = 0;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SafetyInterlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state();
+        f.debug_struct("SafetyInterlock")
+            .field("protected", &state.protected.iter().filter(|protected| **protected).count())
+            .field("armed", &state.armed.iter().zip(state.protected.iter()).filter(|(armed, protected)| **armed && **protected).count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Limits how fast channels may change, in values per second, e.g. to protect mechanical dimmers and motors or to prevent flicker from buggy content.
+///
+/// The limit is applied by the agent right before every packet is sent, after the parked channels and before the [`SafetyInterlock`]. A limited channel moves towards its target at the given rate.
+/// Clones share the same limits, so they can be changed after the limit was set.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, DMXSerial, Fixture, SlewLimit};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let scroller = Fixture::new("Scroller", 10, vec![Attribute::Other]).unwrap();
+///     let limit = SlewLimit::new();
+///     // At most from 0 to full in 2 seconds
+///     limit.set_fixture_rate(&scroller, 127.5);
+///     dmx.set_slew_limit(limit);
+/// }
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct SlewLimit {
+    state: Arc<Mutex<SlewState>>,
+}
+
+struct SlewState {
+    rates: [Option<f32>; DMX_CHANNELS],
+    // Output of the last packet, with fractions so slow rates don't get stuck at rounding
+    last: Option<([f32; DMX_CHANNELS], time::Instant)>,
+}
+
+impl Default for SlewState {
+    fn default() -> SlewState {
+        SlewState {
+            rates: [None; DMX_CHANNELS],
+            last: None,
+        }
+    }
+}
+
+impl SlewLimit {
+    /// Creates a [SlewLimit] without limited channels.
+    ///
+    pub fn new() -> SlewLimit {
+        SlewLimit::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, SlewState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Limits the [`channel`] to the given change per second.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn set_rate(&self, channel: usize, per_second: f32) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        self.state().rates[channel - 1] = Some(per_second.max(0.0));
+        Ok(())
+    }
+
+    /// Limits all channels of the [`Fixture`] to the given change per second.
+    ///
+    pub fn set_fixture_rate(&self, fixture: &Fixture, per_second: f32) {
+        let mut state = self.state();
+        // The channels were checked when the fixture was created
+        fixture.channels().for_each(|channel| state.rates[channel - 1] = Some(per_second.max(0.0)));
+    }
+
+    /// Removes the limit of the [`channel`].
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn clear_rate(&self, channel: usize) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        self.state().rates[channel - 1] = None;
+        Ok(())
+    }
+
+    /// Returns the limit of the [`channel`] in values per second, or `None` if it isn't limited.
+    ///
+    /// [`channel`]: usize
+    ///
+    pub fn rate(&self, channel: usize) -> Result<Option<f32>, DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        Ok(self.state().rates[channel - 1])
+    }
+
+    // Moves the limited channels towards their values, the first packet isn't limited
+    pub(crate) fn apply(&self, channels: &mut [u8; DMX_CHANNELS], now: time::Instant) {
+        let mut state = self.state();
+        let state = &mut *state;
+        let mut output = channels.map(|value| value as f32);
+        if let Some((last, since)) = &state.last {
+            let elapsed = now.saturating_duration_since(*since).as_secs_f32();
+            for ((value, last), rate) in output.iter_mut().zip(last.iter()).zip(state.rates.iter()) {
+                if let Some(rate) = rate {
+                    let step = rate * elapsed;
+                    *value = last + (*value - last).clamp(-step, step);
+                }
+            }
+        }
+        channels.iter_mut().zip(output.iter()).for_each(|(value, output)| *value = output.round() as u8);
+        state.last = Some((output, now));
+    }
+}
+
+impl fmt::Debug for SlewLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SlewLimit")
+            .field("limited", &self.state().rates.iter().filter(|rate| rate.is_some()).count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Suppresses flashing of the whole rig above a maximum rate, to guard e.g. public installations against accidental strobing from buggy content.
+///
+/// The guard follows the average level of the channels which were used since it was last configured. A **flash** is a rise of that level by at least the [depth](StrobeGuard::set_depth), followed by a fall by as much.
+/// When more flashes than the [maximum rate](StrobeGuard::set_max_rate) happen within a second, the output is held at the frame from right before the first of these flashes until it calms down.
+/// Strobing has to be [allowed](StrobeGuard::allow) explicitly, e.g. for a show which was checked for it.
+///
+/// It's applied by the agent right before every frame with the null start code is sent *(including raw frames)*, after the [`SlewLimit`] and before the [`SafetyInterlock`]. Clones share the same state.
+/// Changing the configuration starts over with the used channels and the counted flashes.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, StrobeGuard};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let guard = StrobeGuard::new();
+///     guard.set_max_rate(2.0);
+///     dmx.set_strobe_guard(guard.clone());
+///     // ... later, for the checked finale
+///     guard.allow();
+/// }
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct StrobeGuard {
+    state: Arc<Mutex<StrobeState>>,
+}
+
+struct StrobeState {
+    max_rate: f32,
+    depth: u8,
+    allowed: bool,
+    used: [bool; DMX_CHANNELS],
+    // Lowest level since the last fall, or highest level since the last flash
+    extreme: f32,
+    high: bool,
+    // The frame at the lowest level, where the next flash begins
+    valley: [u8; DMX_CHANNELS],
+    // The counted flashes with the frame from right before each of them
+    flashes: VecDeque<(time::Instant, [u8; DMX_CHANNELS])>,
+    held: Option<[u8; DMX_CHANNELS]>,
+}
+
+impl Default for StrobeState {
+    fn default() -> StrobeState {
+        StrobeState {
+            // The common limit for photosensitive epilepsy
+            max_rate: 3.0,
+            depth: 128,
+            allowed: false,
+            used: [false; DMX_CHANNELS],
+            // Nothing was measured yet, so the first frame is the lowest
+            extreme: f32::INFINITY,
+            high: false,
+            valley: [0; DMX_CHANNELS],
+            flashes: VecDeque::new(),
+            held: None,
+        }
+    }
+}
+
+impl StrobeState {
+    // Forgets everything which was measured, but keeps the configuration
+    fn reset(&mut self) {
+        *self = StrobeState {
+            max_rate: self.max_rate,
+            depth: self.depth,
+            allowed: self.allowed,
+            ..StrobeState::default()
+        };
+    }
+}
+
+impl StrobeGuard {
+    /// Creates a [StrobeGuard] which allows up to 3 flashes per second with a depth of `128`.
+    ///
+    pub fn new() -> StrobeGuard {
+        StrobeGuard::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, StrobeState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Sets the maximum number of flashes per second.
+    ///
+    pub fn set_max_rate(&self, flashes_per_second: f32) {
+        let mut state = self.state();
+        state.max_rate = flashes_per_second.max(0.0);
+        state.reset();
+    }
+
+    /// Returns the maximum number of flashes per second.
+    ///
+    pub fn max_rate(&self) -> f32 {
+        self.state().max_rate
+    }
+
+    /// Sets how much the average level has to change for a flash *(`1` to `255`)*.
+    ///
+    pub fn set_depth(&self, depth: u8) {
+        let mut state = self.state();
+        state.depth = depth.max(1);
+        state.reset();
+    }
+
+    /// Returns how much the average level has to change for a flash.
+    ///
+    pub fn depth(&self) -> u8 {
+        self.state().depth
+    }
+
+    /// Allows strobing, the output isn't held anymore.
+    ///
+    pub fn allow(&self) {
+        let mut state = self.state();
+        state.allowed = true;
+        state.reset();
+    }
+
+    /// Forbids strobing again.
+    ///
+    pub fn forbid(&self) {
+        let mut state = self.state();
+        state.allowed = false;
+        state.reset();
+    }
+
+    /// Returns `true` if strobing is allowed.
+    ///
+    pub fn is_allowed(&self) -> bool {
+        self.state().allowed
+    }
+
+    /// Returns `true` if the output is held because of flashing right now.
+    ///
+    pub fn is_suppressing(&self) -> bool {
+        self.state().held.is_some()
+    }
+
+    pub(crate) fn reset(&self) {
+        self.state().reset();
+    }
+
+    // Counts the flashes and holds the output while there are too many
+    pub(crate) fn apply(&self, channels: &mut [u8; DMX_CHANNELS], now: time::Instant) {
+        let mut state = self.state();
+        let state = &mut *state;
+        let mut used = 0;
+        let mut total = 0.0;
+        for (value, channel_used) in channels.iter().zip(state.used.iter_mut()) {
+            *channel_used |= *value != 0;
+            if *channel_used {
+                used += 1;
+                total += *value as f32;
+            }
+        }
+        let level = if used == 0 { 0.0 } else { total / used as f32 };
+
+        let depth = state.depth as f32;
+        if state.high {
+            state.extreme = state.extreme.max(level);
+            if state.extreme - level >= depth {
+                state.high = false;
+                state.extreme = level;
+                state.valley = *channels;
+            }
+        } else if level <= state.extreme {
+            state.extreme = level;
+            state.valley = *channels;
+        } else if level - state.extreme >= depth {
+            state.high = true;
+            state.extreme = level;
+            state.flashes.push_back((now, state.valley));
+        }
+        while state.flashes.front().is_some_and(|(flash, _)| now.saturating_duration_since(*flash) >= FLASH_WINDOW) {
+            state.flashes.pop_front();
+        }
+
+        let flashing = state.flashes.len() as f32 > state.max_rate;
+        if flashing && !state.allowed && state.held.is_none() {
+            eprintln!("Suppressed flashing with {} flashes per second, holding the output", state.flashes.len());
+            state.held = state.flashes.front().map(|(_, before)| *before);
+        } else if !flashing {
+            state.held = None;
+        }
+        if let Some(held) = state.held {
+            *channels = held;
+        }
+    }
+}
+
+impl fmt::Debug for StrobeGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state();
+        f.debug_struct("StrobeGuard")
+            .field("max_rate", &state.max_rate)
+            .field("depth", &state.depth)
+            .field("allowed", &state.allowed)
+            .field("suppressing", &state.held.is_some())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::fmt;
+use std::time;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A position on earth, used for calculating sunrise and sunset.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    /// Latitude in degrees, north is positive.
+    pub latitude: f64,
+    /// Longitude in degrees, east is positive.
+    pub longitude: f64,
+}
+
+impl Location {
+    /// Returns the sunrise and sunset on the UTC day of the given `date`.
+    ///
+    /// Returns `None` during polar day and polar night.
+    ///
+    pub fn sun_times(&self, date: time::SystemTime) -> Option<(time::SystemTime, time::SystemTime)> {
+        let (rise, set) = self.sun_times_on_day(unix_seconds(date).div_euclid(SECONDS_PER_DAY))?;
+        Some((from_unix_seconds(rise), from_unix_seconds(set)))
+    }
+
+    // Sunrise equation, see https://en.wikipedia.org/wiki/Sunrise_equation
+    fn sun_times_on_day(&self, day: i64) -> Option<(i64, i64)> {
+        // Days since 2000-01-01 12:00 UTC
+        let n = (day as f64 + 2_440_588.0 - 2_451_545.0 + 0.0008).round();
+        let mean_solar_time = n - self.longitude / 360.0;
+        let anomaly = (357.5291 + 0.985_600_28 * mean_solar_time).rem_euclid(360.0).to_radians();
+        let center = 1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+        let ecliptic_longitude = (anomaly.to_degrees() + center + 180.0 + 102.9372).rem_euclid(360.0).to_radians();
+        let transit = 2_451_545.0 + mean_solar_time + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+        let declination = (ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+        let latitude = self.latitude.to_radians();
+        let cos_hour_angle = ((-0.833_f64).to_radians().sin() - latitude.sin() * declination.sin()) / (latitude.cos() * declination.cos());
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return None;
+        }
+        let hour_angle = cos_hour_angle.acos().to_degrees();
+
+        let to_unix = |julian: f64| ((julian - 2_440_587.5) * SECONDS_PER_DAY as f64).round() as i64;
+        Some((to_unix(transit - hour_angle / 360.0), to_unix(transit + hour_angle / 360.0)))
+    }
+}
+
+/// Provides the [`Location`] for sunrise and sunset triggers *(e.g. from a config file or a GPS receiver)*.
+///
+pub trait LocationProvider: Send {
+    /// Returns the current [`Location`], or `None` if it's unknown.
+    ///
+    fn location(&self) -> Option<Location>;
+}
+
+impl LocationProvider for Location {
+    fn location(&self) -> Option<Location> {
+        Some(*self)
+    }
+}
+
+/// A time of the day in the local time of the [Scheduler].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl TimeOfDay {
+    /// Creates a new [TimeOfDay]. Returns `None` if the time isn't valid.
+    ///
+    pub fn new(hour: u8, minute: u8, second: u8) -> Option<TimeOfDay> {
+        (hour < 24 && minute < 60 && second < 60).then_some(TimeOfDay { hour, minute, second })
+    }
+
+    fn seconds(&self) -> i64 {
+        self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+}
+
+/// When a scheduled action is run. It's run once per day.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// At a wall-clock time.
+    At(TimeOfDay),
+    /// At sunrise, shifted by the given offset in seconds *(negative is earlier)*.
+    Sunrise(i64),
+    /// At sunset, shifted by the given offset in seconds *(negative is earlier)*.
+    Sunset(i64),
+}
+
+/// Identifies an action added to a [Scheduler].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScheduleId(u64);
+
+struct Entry {
+    id: ScheduleId,
+    trigger: Trigger,
+    action: Box<dyn FnMut(&mut DMXSerial) + Send>,
+}
+
+/// Runs actions on a [DMXSerial] at wall-clock times or relative to sunrise and sunset.
+///
+/// Meant for architectural installations, where the interface runs headless around the clock.
+/// The scheduler doesn't run by itself, [`Scheduler::poll`] has to be called regularly.
+///
+/// Wall-clock times use a fixed offset to UTC, daylight saving time isn't taken into account.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, Location, Scheduler, TimeOfDay, Trigger};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let mut scheduler = Scheduler::new(3600); // UTC+1
+///     scheduler.set_location_provider(Box::new(Location { latitude: 48.2, longitude: 16.4 }));
+///
+///     scheduler.add_scene(Trigger::Sunset(-15 * 60), [255; 512]);
+///     scheduler.add_scene(Trigger::At(TimeOfDay::new(23, 0, 0).unwrap()), [0; 512]);
+///     loop {
+///         scheduler.poll(&mut dmx);
+///         std::thread::sleep(std::time::Duration::from_secs(1));
+///     }
+/// }
+/// ```
+///
+pub struct Scheduler {
+    utc_offset: i64,
+    location: Option<Box<dyn LocationProvider>>,
+    entries: Vec<Entry>,
+    next_id: u64,
+    last_poll: Option<i64>,
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("utc_offset", &self.utc_offset)
+            .field("location", &self.location.as_ref().and_then(|provider| provider.location()))
+            .field("triggers", &self.entries.iter().map(|entry| entry.trigger).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Scheduler {
+    /// Creates a new [Scheduler] for the local time with the given offset to UTC in seconds.
+    ///
+    pub fn new(utc_offset: i64) -> Scheduler {
+        Scheduler {
+            utc_offset,
+            location: None,
+            entries: Vec::new(),
+            next_id: 0,
+            last_poll: None,
+        }
+    }
+
+    /// Sets the [`LocationProvider`] used for sunrise and sunset triggers.
+    ///
+    /// Without a location, sunrise and sunset triggers never run.
+    ///
+    pub fn set_location_provider(&mut self, provider: Box<dyn LocationProvider>) {
+        self.location = Some(provider);
+    }
+
+    /// Adds an action which is run on the [DMXSerial] whenever the [`Trigger`] is due.
+    ///
+    pub fn add<F: FnMut(&mut DMXSerial) + Send + 'static>(&mut self, trigger: Trigger, action: F) -> ScheduleId {
+        let id = ScheduleId(self.next_id);
+        self.next_id += 1;
+        self.entries.push(Entry {
+            id,
+            trigger,
+            action: Box::new(action),
+        });
+        id
+    }
+
+    /// Adds a scene which is set on the [DMXSerial] whenever the [`Trigger`] is due.
+    ///
+    pub fn add_scene(&mut self, trigger: Trigger, channels: [u8; DMX_CHANNELS]) -> ScheduleId {
+        self.add(trigger, move |dmx| dmx.set_channels(channels))
+    }
+
+    /// Removes an action. Returns `false` if there was no action with the given [`ScheduleId`].
+    ///
+    pub fn remove(&mut self, id: ScheduleId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.len() != len
+    }
+
+    /// Runs all actions which got due since the last poll. Returns the number of actions run.
+    ///
+    /// The first poll only remembers the time, so no missed actions are run after starting.
+    ///
+    pub fn poll(&mut self, dmx: &mut DMXSerial) -> usize {
+        self.poll_at(dmx, time::SystemTime::now())
+    }
+
+    /// Does the same as [`Scheduler::poll`], but at the given time instead of now.
+    ///
+    pub fn poll_at(&mut self, dmx: &mut DMXSerial, now: time::SystemTime) -> usize {
+        let now = unix_seconds(now);
+        let Some(last) = self.last_poll.replace(now) else {
+            return 0;
+        };
+        if now <= last {
+            return 0;
+        }
+
+        let location = self.location.as_ref().and_then(|provider| provider.location());
+        let first_day = (last + self.utc_offset).div_euclid(SECONDS_PER_DAY);
+        let last_day = (now + self.utc_offset).div_euclid(SECONDS_PER_DAY);
+        let mut count = 0;
+        for entry in &mut self.entries {
+            // Only the last occurrence is run, if several got due
+            let due = (first_day..=last_day).rev()
+                .filter_map(|day| trigger_time(entry.trigger, day, self.utc_offset, location))
+                .any(|time| last < time && time <= now);
+            if due {
+                (entry.action)(dmx);
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+// Returns the UTC time of the trigger on the given local day
+fn trigger_time(trigger: Trigger, day: i64, utc_offset: i64, location: Option<Location>) -> Option<i64> {
+    match trigger {
+        Trigger::At(time) => Some(day * SECONDS_PER_DAY + time.seconds() - utc_offset),
+        Trigger::Sunrise(offset) => Some(location?.sun_times_on_day(day)?.0 + offset),
+        Trigger::Sunset(offset) => Some(location?.sun_times_on_day(day)?.1 + offset),
+    }
+}
+
+fn unix_seconds(time: time::SystemTime) -> i64 {
+    match time.duration_since(time::UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+fn from_unix_seconds(seconds: i64) -> time::SystemTime {
+    if seconds >= 0 {
+        time::UNIX_EPOCH + time::Duration::from_secs(seconds as u64)
+    } else {
+        time::UNIX_EPOCH - time::Duration::from_secs(seconds.unsigned_abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn sun_times_match_known_values() {
+        // London on 2024-06-21: sunrise at 03:43 UTC, sunset at 20:21 UTC
+        let london = Location { latitude: 51.5074, longitude: -0.1278 };
+        let (sunrise, sunset) = london.sun_times(UNIX_EPOCH + Duration::from_secs(1_718_971_200)).unwrap();
+        let minutes = |time: std::time::SystemTime| time.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 % 86_400 / 60;
+        assert!((minutes(sunrise) - (3 * 60 + 43)).abs() <= 3);
+        assert!((minutes(sunset) - (20 * 60 + 21)).abs() <= 3);
+
+        // Polar day in Longyearbyen
+        let svalbard = Location { latitude: 78.22, longitude: 15.65 };
+        assert!(svalbard.sun_times(UNIX_EPOCH + Duration::from_secs(1_718_971_200)).is_none());
+    }
+}
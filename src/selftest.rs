@@ -0,0 +1,213 @@
+use crate::builder::{BreakMode, LineSettings};
+use crate::codec::{MIN_BREAK_TIME, MIN_PACKET_TIME};
+use crate::error::DMXDisconnectionError;
+use crate::DMXSerial;
+
+use std::thread;
+use std::time;
+
+// Number of packets which are timed by the self-test
+const TEST_PACKETS: u64 = 10;
+// Number of packets which are timed for the calibration
+const CALIBRATION_PACKETS: u64 = 50;
+// Higher latency timers delay the end of every packet on FTDI adapters
+const MAX_LATENCY_TIMER: time::Duration = time::Duration::from_millis(2);
+
+// Timing of the sent packets, measured by the agent
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PacketTiming {
+    pub shortest_break: Option<time::Duration>,
+    pub longest_interval: Option<time::Duration>,
+    pub intervals: u64,
+    last_start: Option<time::Instant>,
+    // Kept by `reset`, since they're used by the health report
+    pub frames: u64,
+    pub last_frame: Option<time::Instant>,
+}
+
+impl PacketTiming {
+    // Starts a new measurement
+    pub fn reset(&mut self) {
+        *self = PacketTiming {
+            frames: self.frames,
+            last_frame: self.last_frame,
+            ..PacketTiming::default()
+        };
+    }
+
+    // Returns the time since the start of the last packet
+    pub fn record_start(&mut self, start: time::Instant) -> Option<time::Duration> {
+        self.frames += 1;
+        self.last_frame = Some(start);
+        let interval = self.last_start.replace(start).map(|last_start| start.duration_since(last_start));
+        if let Some(interval) = interval {
+            self.record_interval(interval);
+        }
+        interval
+    }
+
+    pub fn record_break(&mut self, duration: time::Duration) {
+        self.shortest_break = Some(self.shortest_break.map_or(duration, |shortest| shortest.min(duration)));
+    }
+
+    fn record_interval(&mut self, duration: time::Duration) {
+        self.longest_interval = Some(self.longest_interval.map_or(duration, |longest| longest.max(duration)));
+        self.intervals += 1;
+    }
+}
+
+/// A setup problem found by [`DMXSerial::self_test`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfTestIssue {
+    /// The interface stopped, see [`DMXSerial::port_diagnostics`] for the reason.
+    Disconnected,
+    /// The driver didn't report the applied [`LineSettings`].
+    LineSettingsUnreadable(String),
+    /// The driver accepted the requested [`LineSettings`], but applied others *(e.g. an unsupported baud rate)*.
+    LineSettingsNotApplied { requested: LineSettings, applied: LineSettings },
+    /// The break signal was shorter than the 92 µs required by the standard.
+    BreakTooShort(time::Duration),
+    /// Packets were sent less often than the [packet time] allows.
+    ///
+    /// [packet time]: DMXSerial::set_packet_time
+    PacketTimeExceeded { requested: time::Duration, achieved: time::Duration },
+    /// The latency timer of the FTDI driver is higher than 2 ms, which delays every packet. *(Linux only)*
+    LatencyTimerTooHigh(time::Duration),
+}
+
+/// The result of [`DMXSerial::self_test`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub issues: Vec<SelfTestIssue>,
+    /// Shortest break signal, measured on the host. `None` with [`BreakMode::Baud`], where the break is a byte.
+    pub shortest_break: Option<time::Duration>,
+    /// Longest time between the start of two packets.
+    pub longest_packet_time: Option<time::Duration>,
+    /// The latency timer of the FTDI driver, if there is one.
+    pub latency_timer: Option<time::Duration>,
+}
+
+impl SelfTestReport {
+    /// Returns `true` if no issues were found.
+    ///
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl DMXSerial {
+    /// Sends a few packets and checks the setup for common problems.
+    ///
+    /// The applied line settings are read back from the driver and the break and packet timing is measured.
+    /// The timing is measured on the host, so delays inside the adapter aren't noticed.
+    ///
+    /// In **sync mode** the packets are sent by the test, otherwise it waits for the regular packets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    /// for issue in dmx.self_test().issues {
+    ///     eprintln!("{:?}", issue);
+    /// }
+    /// # }
+    /// ```
+    ///
+    pub fn self_test(&mut self) -> SelfTestReport {
+        let mut issues = Vec::new();
+
+        let diagnostics = self.port_diagnostics();
+        match diagnostics.applied {
+            Ok(applied) if applied != diagnostics.requested => issues.push(SelfTestIssue::LineSettingsNotApplied { requested: diagnostics.requested, applied }),
+            Ok(_) => {},
+            Err(e) => issues.push(SelfTestIssue::LineSettingsUnreadable(e)),
+        }
+
+        if self.time_packets(TEST_PACKETS).is_err() {
+            issues.push(SelfTestIssue::Disconnected);
+        }
+
+        let timing = self.packet_timing();
+        let shortest_break = match self.break_mode() {
+            BreakMode::Signal => timing.shortest_break,
+            BreakMode::Baud(_) => None,
+        };
+        if let Some(duration) = shortest_break.filter(|duration| *duration < MIN_BREAK_TIME) {
+            issues.push(SelfTestIssue::BreakTooShort(duration));
+        }
+        // Sleeping always overshoots a bit, so only clear misses are reported
+        let requested = self.get_packet_time();
+        if let Some(achieved) = timing.longest_interval.filter(|achieved| *achieved > requested + requested / 4 + time::Duration::from_millis(1)) {
+            issues.push(SelfTestIssue::PacketTimeExceeded { requested, achieved });
+        }
+
+        let latency_timer = crate::adapter::latency_timer(self.name());
+        if let Some(timer) = latency_timer.filter(|timer| *timer > MAX_LATENCY_TIMER) {
+            issues.push(SelfTestIssue::LatencyTimerTooHigh(timer));
+        }
+
+        SelfTestReport {
+            issues,
+            shortest_break,
+            longest_packet_time: timing.longest_interval,
+            latency_timer,
+        }
+    }
+
+    /// Measures how long sending a packet takes on the adapter and sets the shortest stable [packet time].
+    ///
+    /// Packets are sent as fast as possible for a moment. The slowest packet plus a margin of 10 % is used,
+    /// but never less than the 1204 µs allowed by the standard. Returns the new packet time.
+    ///
+    /// The previous packet time is kept if the interface gets disconnected.
+    ///
+    /// [packet time]: DMXSerial::set_packet_time
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use open_dmx::DMXSerial;
+    /// # fn main() {
+    /// let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    /// let packet_time = dmx.calibrate_packet_time().unwrap();
+    /// println!("Refreshing at {:.1} Hz", 1.0 / packet_time.as_secs_f64());
+    /// # }
+    /// ```
+    ///
+    pub fn calibrate_packet_time(&mut self) -> Result<time::Duration, DMXDisconnectionError> {
+        let previous = self.get_packet_time();
+        self.set_packet_time(time::Duration::ZERO);
+        let measured = self.time_packets(CALIBRATION_PACKETS).map(|_| self.packet_timing().longest_interval);
+        let packet_time = match measured {
+            Ok(Some(longest)) => (longest + longest / 10).max(MIN_PACKET_TIME),
+            Ok(None) => MIN_PACKET_TIME.max(previous),
+            Err(e) => {
+                self.set_packet_time(previous);
+                return Err(e);
+            },
+        };
+        self.set_packet_time(packet_time);
+        Ok(packet_time)
+    }
+
+    // Waits until the given number of packet intervals has been timed, sending the packets itself in sync mode
+    fn time_packets(&mut self, count: u64) -> Result<(), DMXDisconnectionError> {
+        self.reset_packet_timing();
+        // The first interval starts with the first measured packet
+        if self.is_sync() {
+            for _ in 0..=count {
+                self.update()?;
+            }
+        } else {
+            while self.packet_timing().intervals < count {
+                self.check_agent()?;
+                thread::sleep(time::Duration::from_millis(1));
+            }
+        }
+        Ok(())
+    }
+}
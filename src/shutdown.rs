@@ -0,0 +1,134 @@
+use crate::error::DMXDisconnectionError;
+use crate::transform::MasterDimmer;
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::thread;
+use std::time;
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// What happens when a [DMXSerial] is shut down with [`DMXSerial::shutdown`].
+///
+/// The output fades to `0` over the `fade` time, then `blackout_frames` empty frames are sent before the port is closed.
+/// Parked channels keep their value during the fade, but are included in the blackout.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownSequence {
+    pub fade: time::Duration,
+    pub blackout_frames: usize,
+}
+
+impl Default for ShutdownSequence {
+    /// Fades out over 2 seconds and sends 3 blackout frames.
+    ///
+    fn default() -> Self {
+        ShutdownSequence {
+            fade: time::Duration::from_secs(2),
+            blackout_frames: 3,
+        }
+    }
+}
+
+impl DMXSerial {
+    /// Runs the [`ShutdownSequence`] and closes the port.
+    ///
+    /// Meant for services, which should leave the rig dark when they're stopped. See [`TerminationSignal`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use open_dmx::{DMXSerial, ShutdownSequence};
+    /// # fn main() {
+    /// let dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+    /// dmx.shutdown(ShutdownSequence::default()).unwrap();
+    /// # }
+    /// ```
+    ///
+    pub fn shutdown(mut self, sequence: ShutdownSequence) -> Result<(), DMXDisconnectionError> {
+        self.set_async();
+        // Wakes up the agent, if it was waiting for an update in sync mode
+        self.update_async()?;
+        let master = MasterDimmer::all_channels(255);
+        // Added last, so it dims the result of all other transforms
+        self.add_transform(Box::new(master.clone()));
+
+        let start = time::Instant::now();
+        let step = self.get_packet_time().max(time::Duration::from_millis(1));
+        while start.elapsed() < sequence.fade {
+            let progress = start.elapsed().as_secs_f32() / sequence.fade.as_secs_f32();
+            master.set_level((255.0 * (1.0 - progress)).round() as u8);
+            self.check_agent()?;
+            thread::sleep(step);
+        }
+        master.set_level(0);
+
+        // No regular packets are sent between the blackout frames in sync mode
+        self.set_sync();
+        for _ in 0..sequence.blackout_frames {
+            self.send_raw_frame(&[0; DMX_CHANNELS + 1]).map_err(|_| DMXDisconnectionError)?;
+        }
+        Ok(())
+    }
+}
+
+/// Catches `SIGTERM` and `SIGINT`, so a service can run its [`ShutdownSequence`] before exiting. *(Linux only)*
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, ShutdownSequence, TerminationSignal};
+///
+/// fn main() {
+///     let signal = TerminationSignal::install().unwrap();
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     dmx.set_channels([255; 512]);
+///     signal.wait();
+///     dmx.shutdown(ShutdownSequence::default()).unwrap();
+/// }
+/// ```
+///
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy)]
+pub struct TerminationSignal {
+    // Can only be created by installing the handler
+    _private: (),
+}
+
+#[cfg(target_os = "linux")]
+static TERMINATION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(target_os = "linux")]
+extern "C" fn on_termination(_signal: libc::c_int) {
+    // Only async-signal-safe operations are allowed here
+    TERMINATION_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+#[cfg(target_os = "linux")]
+impl TerminationSignal {
+    /// Installs the signal handlers. Afterwards, the process isn't stopped by the signals anymore.
+    ///
+    pub fn install() -> std::io::Result<TerminationSignal> {
+        let handler = on_termination as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        for signal in [libc::SIGTERM, libc::SIGINT] {
+            // SAFETY: The handler only stores to an atomic
+            if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(TerminationSignal { _private: () })
+    }
+
+    /// Returns `true` once a signal has been received.
+    ///
+    pub fn is_requested(&self) -> bool {
+        TERMINATION_REQUESTED.load(Ordering::Relaxed)
+    }
+
+    /// Blocks until a signal is received.
+    ///
+    pub fn wait(&self) {
+        while !self.is_requested() {
+            thread::sleep(time::Duration::from_millis(50));
+        }
+    }
+}
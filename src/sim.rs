@@ -0,0 +1,261 @@
+//! A **rack of simulated fixtures** for testing scenes, fades and effects end to end without hardware.
+//!
+//! The fixtures of a [`Rack`] receive the frames of a [DMXSerial] through [`Rack::output`] and interpret their channels like real fixtures, so tests can assert on the resulting light instead of raw channel values.
+//!
+//! [DMXSerial]: crate::DMXSerial
+//!
+//! # Example
+//!
+//! ```
+//! use open_dmx::{Attribute, DMXSerial};
+//! use open_dmx::sim::{FixtureModel, Rack};
+//!
+//! fn main() {
+//!     let rack = Rack::new();
+//!     let par = rack.add("Par 1", FixtureModel::RgbPar, 1).unwrap();
+//!     let mut dmx = DMXSerial::builder("rack").sync().open_with_transport(rack.output()).unwrap();
+//!     par.set(&mut dmx, Attribute::Dimmer, 255).unwrap();
+//!     par.set(&mut dmx, Attribute::Red, 255).unwrap();
+//!     dmx.update().unwrap();
+//!     assert_eq!(rack.state("Par 1").unwrap().color, [255, 0, 0]);
+//! }
+//! ```
+//!
+
+use crate::builder::LineSettings;
+use crate::codec::START_CODE_DMX;
+use crate::error::DMXChannelValidityError;
+use crate::transport::Transport;
+use crate::{Attribute, Fixture, DMX_CHANNELS};
+
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The type of a simulated fixture and its channel layout.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureModel {
+    /// A single dimmer channel with white light.
+    Dimmer,
+    /// Dimmer, red, green and blue.
+    RgbPar,
+    /// Pan, pan fine, tilt, tilt fine, dimmer, red, green and blue. Pans over [`MOVER_PAN_RANGE`] and tilts over [`MOVER_TILT_RANGE`].
+    Mover,
+}
+
+/// Pan range of the [`FixtureModel::Mover`] in degrees.
+///
+pub const MOVER_PAN_RANGE: f32 = 540.0;
+
+/// Tilt range of the [`FixtureModel::Mover`] in degrees.
+///
+pub const MOVER_TILT_RANGE: f32 = 270.0;
+
+impl FixtureModel {
+    /// Returns the [`Attribute`]s of the channels.
+    ///
+    pub fn attributes(&self) -> Vec<Attribute> {
+        match self {
+            FixtureModel::Dimmer => vec![Attribute::Dimmer],
+            FixtureModel::RgbPar => vec![Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue],
+            FixtureModel::Mover => vec![
+                Attribute::Pan, Attribute::PanFine, Attribute::Tilt, Attribute::TiltFine,
+                Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue,
+            ],
+        }
+    }
+
+    // Interprets the channels of the fixture, starting at its address
+    fn interpret(&self, slots: &[u8]) -> FixtureState {
+        let intensity = |value: u8| value as f32 / 255.0;
+        let angle = |coarse: u8, fine: u8, range: f32| u16::from_be_bytes([coarse, fine]) as f32 / u16::MAX as f32 * range;
+        match self {
+            FixtureModel::Dimmer => FixtureState {
+                intensity: intensity(slots[0]),
+                color: [255; 3],
+                ..FixtureState::default()
+            },
+            FixtureModel::RgbPar => FixtureState {
+                intensity: intensity(slots[0]),
+                color: [slots[1], slots[2], slots[3]],
+                ..FixtureState::default()
+            },
+            FixtureModel::Mover => FixtureState {
+                intensity: intensity(slots[4]),
+                color: [slots[5], slots[6], slots[7]],
+                pan: angle(slots[0], slots[1], MOVER_PAN_RANGE),
+                tilt: angle(slots[2], slots[3], MOVER_TILT_RANGE),
+            },
+        }
+    }
+}
+
+/// The light of a simulated fixture, as interpreted from the last received frame.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FixtureState {
+    /// Output of the dimmer from `0.0` to `1.0`.
+    pub intensity: f32,
+    /// Color of the light, always white for fixtures without color channels.
+    pub color: [u8; 3],
+    /// Pan in degrees, `0.0` for fixtures which don't move.
+    pub pan: f32,
+    /// Tilt in degrees, `0.0` for fixtures which don't move.
+    pub tilt: f32,
+}
+
+/// A rack of simulated fixtures which share one universe.
+///
+/// Clones share the same fixtures, so the rack can be queried while its [`RackOutput`] belongs to a [DMXSerial].
+///
+/// [DMXSerial]: crate::DMXSerial
+///
+#[derive(Clone, Default)]
+pub struct Rack {
+    inner: Arc<Mutex<RackState>>,
+}
+
+struct RackState {
+    fixtures: Vec<(Fixture, FixtureModel, FixtureState)>,
+    channels: [u8; DMX_CHANNELS],
+    frames: u64,
+}
+
+impl Default for RackState {
+    fn default() -> RackState {
+        RackState {
+            fixtures: Vec::new(),
+            channels: [0; DMX_CHANNELS],
+            frames: 0,
+        }
+    }
+}
+
+impl Rack {
+    /// Creates an empty [Rack].
+    ///
+    pub fn new() -> Rack {
+        Rack::default()
+    }
+
+    /// Adds a fixture of the given model at the given address. Returns the matching [`Fixture`], e.g. for setting its channels.
+    ///
+    /// The fixture starts with the channels received so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DMXChannelValidityError`] if the address isn't valid or the channels don't fit in the universe.
+    ///
+    pub fn add(&self, name: &str, model: FixtureModel, address: usize) -> Result<Fixture, DMXChannelValidityError> {
+        let fixture = Fixture::new(name, address, model.attributes())?;
+        let mut inner = self.lock();
+        let state = model.interpret(&inner.channels[fixture.address() - 1..]);
+        inner.fixtures.push((fixture.clone(), model, state));
+        Ok(fixture)
+    }
+
+    /// Returns the state of the fixture with the given name, or `None` if there is none.
+    ///
+    pub fn state(&self, name: &str) -> Option<FixtureState> {
+        self.lock().fixtures.iter().find(|(fixture, ..)| fixture.name() == name).map(|(.., state)| *state)
+    }
+
+    /// Returns the names and states of all fixtures, in the order they were added.
+    ///
+    pub fn states(&self) -> Vec<(String, FixtureState)> {
+        self.lock().fixtures.iter().map(|(fixture, _, state)| (fixture.name().to_string(), *state)).collect()
+    }
+
+    /// Returns the channels of the universe as received so far.
+    ///
+    pub fn channels(&self) -> [u8; DMX_CHANNELS] {
+        self.lock().channels
+    }
+
+    /// Returns the number of DMX frames received so far. Frames with other start codes aren't counted.
+    ///
+    pub fn frames(&self) -> u64 {
+        self.lock().frames
+    }
+
+    /// Returns a [`Transport`] which feeds the sent frames to the fixtures of the rack.
+    ///
+    pub fn output(&self) -> RackOutput {
+        RackOutput {
+            rack: self.clone(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RackState> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn receive(&self, slots: &[u8]) {
+        let mut inner = self.lock();
+        // Like real fixtures, channels which aren't part of a shorter frame keep their values
+        inner.channels[..slots.len()].copy_from_slice(slots);
+        inner.frames += 1;
+        let RackState { fixtures, channels, .. } = &mut *inner;
+        for (fixture, model, state) in fixtures.iter_mut() {
+            *state = model.interpret(&channels[fixture.address() - 1..]);
+        }
+    }
+}
+
+impl fmt::Debug for Rack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inner = self.lock();
+        f.debug_struct("Rack")
+            .field("fixtures", &inner.fixtures.len())
+            .field("frames", &inner.frames)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The [`Transport`] of a [`Rack`], see [`Rack::output`].
+///
+#[derive(Debug)]
+pub struct RackOutput {
+    rack: Rack,
+}
+
+impl io::Write for RackOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The agent writes every frame at once
+        if buf.len() > 1 && buf.len() <= DMX_CHANNELS + 1 && buf[0] == START_CODE_DMX {
+            self.rack.receive(&buf[1..]);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for RackOutput {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,140 @@
+use crate::builder::LineSettings;
+use crate::transport::Transport;
+use crate::DMX_CHANNELS;
+
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+// Channels per row of the terminal grid
+const GRID_COLUMNS: usize = 32;
+
+/// A [`Transport`] for developing without hardware, which shows the sent **DMX frames** instead of writing them to a [SerialPort].
+///
+/// - [`SimulatorOutput::terminal`] renders the universe as a live grid in the terminal.
+/// - [`SimulatorOutput::tcp`] forwards every frame to a viewer listening on a TCP socket.
+///   Each frame is prefixed with its length as a big-endian `u16`.
+///
+/// [SerialPort]: serialport::SerialPort
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, SimulatorOutput};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::builder("simulator")
+///         .open_with_transport(SimulatorOutput::terminal())
+///         .unwrap();
+///     dmx.set_channels([255; 512]);
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct SimulatorOutput {
+    target: Target,
+    frames: u64,
+}
+
+#[derive(Debug)]
+enum Target {
+    Terminal(io::Stdout),
+    Tcp(TcpStream),
+}
+
+impl SimulatorOutput {
+    /// Creates a [SimulatorOutput] which renders the universe to `stdout`.
+    ///
+    pub fn terminal() -> SimulatorOutput {
+        SimulatorOutput::new(Target::Terminal(io::stdout()))
+    }
+
+    /// Creates a [SimulatorOutput] which sends the frames to the viewer at the given address.
+    ///
+    pub fn tcp<A: ToSocketAddrs>(addr: A) -> io::Result<SimulatorOutput> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(SimulatorOutput::new(Target::Tcp(stream)))
+    }
+
+    fn new(target: Target) -> SimulatorOutput {
+        SimulatorOutput {
+            target,
+            frames: 0,
+        }
+    }
+
+    /// Returns the number of frames shown so far.
+    ///
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    fn show(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.frames += 1;
+        match &mut self.target {
+            Target::Terminal(stdout) => {
+                let mut out = stdout.lock();
+                // Only clear the screen once and then redraw in place, to avoid flickering
+                if self.frames == 1 {
+                    write!(out, "\x1b[2J")?;
+                }
+                write!(out, "\x1b[H")?;
+                writeln!(out, "frame {:>8} | start code 0x{:02X} | {} slots", self.frames, frame[0], frame.len() - 1)?;
+                for (row, values) in frame[1..].chunks(GRID_COLUMNS).enumerate() {
+                    write!(out, "{:>3} |", row * GRID_COLUMNS + 1)?;
+                    for value in values {
+                        write!(out, " {:02X}", value)?;
+                    }
+                    writeln!(out)?;
+                }
+                // Clear the rest of a previously longer frame
+                write!(out, "\x1b[J")?;
+                out.flush()
+            },
+            Target::Tcp(stream) => {
+                stream.write_all(&(frame.len() as u16).to_be_bytes())?;
+                stream.write_all(frame)
+            },
+        }
+    }
+}
+
+impl io::Write for SimulatorOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The agent writes every frame at once
+        if !buf.is_empty() && buf.len() <= DMX_CHANNELS + 1 {
+            self.show(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for SimulatorOutput {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        true
+    }
+}
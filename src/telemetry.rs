@@ -0,0 +1,23 @@
+use std::time;
+
+// Metrics reported to the `metrics` facade, all labeled with the port
+
+pub(crate) fn frame_sent(port: &str, bytes: usize, interval: Option<time::Duration>) {
+    metrics::counter!("open_dmx_frames_total", "port" => port.to_string()).increment(1);
+    metrics::counter!("open_dmx_bytes_total", "port" => port.to_string()).increment(bytes as u64);
+    if let Some(interval) = interval {
+        metrics::histogram!("open_dmx_frame_interval_seconds", "port" => port.to_string()).record(interval.as_secs_f64());
+    }
+}
+
+pub(crate) fn channels_changed(port: &str, count: usize) {
+    metrics::counter!("open_dmx_channel_changes_total", "port" => port.to_string()).increment(count as u64);
+}
+
+pub(crate) fn port_error(port: &str) {
+    metrics::counter!("open_dmx_errors_total", "port" => port.to_string()).increment(1);
+}
+
+pub(crate) fn reconnected(port: &str) {
+    metrics::counter!("open_dmx_reconnects_total", "port" => port.to_string()).increment(1);
+}
@@ -0,0 +1,45 @@
+//! Custom baud rates on Linux via `termios2` and `BOTHER`
+//!
+//! Some drivers don't accept `250000` baud through the standard `Bxxx` constants.
+//! Setting the rate directly with `TCSETS2` works on most of them *(e.g. Raspberry Pi UARTs, CH340 adapters)*.
+
+use std::io;
+use std::os::unix::io::RawFd;
+
+fn get(fd: RawFd) -> io::Result<libc::termios2> {
+    // termios2 is a plain C struct, so zeroed memory is a valid value
+    let mut tio: libc::termios2 = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(fd, libc::TCGETS2, &mut tio) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(tio)
+}
+
+/// Returns the output baud rate which is currently applied by the driver.
+///
+pub fn baud_rate(fd: RawFd) -> io::Result<u32> {
+    Ok(get(fd)?.c_ospeed)
+}
+
+/// Sets an arbitrary baud rate and checks if the driver applied it.
+///
+pub fn set_baud_rate(fd: RawFd, baud_rate: u32) -> io::Result<()> {
+    let mut tio = get(fd)?;
+    tio.c_cflag &= !(libc::CBAUD | (libc::CBAUD << libc::IBSHIFT));
+    tio.c_cflag |= libc::BOTHER | (libc::BOTHER << libc::IBSHIFT);
+    tio.c_ispeed = baud_rate;
+    tio.c_ospeed = baud_rate;
+    if unsafe { libc::ioctl(fd, libc::TCSETS2, &tio) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Some drivers accept the request but silently keep another rate
+    let applied = self::baud_rate(fd)?;
+    if applied != baud_rate {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("driver applied {} baud instead of {}", applied, baud_rate),
+        ));
+    }
+    Ok(())
+}
@@ -1,4 +1,24 @@
+use crate::DMX_CHANNELS;
+
+use std::hint;
+use std::sync::atomic::{fence, AtomicU8, AtomicUsize, Ordering};
 use std::sync::{Arc, LockResult, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
+use std::time;
+
+// Below this, sleeping would overshoot too much, so the rest is spun
+const SPIN_THRESHOLD: time::Duration = time::Duration::from_millis(1);
+
+// Sleeps for the given duration, spinning for the last part, so it doesn't depend on the timer slack of the scheduler
+pub fn precise_sleep(duration: time::Duration) {
+    let deadline = time::Instant::now() + duration;
+    if duration > SPIN_THRESHOLD {
+        thread::sleep(duration - SPIN_THRESHOLD);
+    }
+    while time::Instant::now() < deadline {
+        hint::spin_loop();
+    }
+}
 
 #[derive(Debug)]
 pub struct ArcRwLock<T> {
@@ -27,6 +47,15 @@ impl<T> ArcRwLock<T> {
     }
 }
 
+// Shares the value instead of copying it
+impl<T> Clone for ArcRwLock<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 pub struct ReadOnly<T> {
     inner: Arc<RwLock<T>>,
 }
@@ -35,4 +64,116 @@ impl<T> ReadOnly<T> {
     pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
         self.inner.read()
     }
-}
\ No newline at end of file
+}
+
+// A seqlock protected buffer of channel values.
+// Writers only wait for other writers and readers retry instead of blocking the writers.
+#[derive(Debug)]
+pub struct ChannelBuffer {
+    // Odd while a write is in progress
+    seq: AtomicUsize,
+    slots: [AtomicU8; DMX_CHANNELS],
+}
+
+impl ChannelBuffer {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            seq: AtomicUsize::new(0),
+            slots: std::array::from_fn(|_| AtomicU8::new(0)),
+        })
+    }
+
+    // Returns a consistent copy, which never contains a half finished write
+    pub fn load(&self) -> [u8; DMX_CHANNELS] {
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            let values = std::array::from_fn(|i| self.slots[i].load(Ordering::Relaxed));
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == before {
+                return values;
+            }
+        }
+    }
+
+    pub fn write(&self) -> ChannelWriteGuard<'_> {
+        loop {
+            let seq = self.seq.load(Ordering::Relaxed);
+            if seq & 1 == 0 && self.seq.compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                // Keeps the slot stores from being reordered before the sequence update
+                fence(Ordering::Release);
+                return ChannelWriteGuard { buffer: self, seq };
+            }
+            hint::spin_loop();
+        }
+    }
+}
+
+pub struct ChannelWriteGuard<'a> {
+    buffer: &'a ChannelBuffer,
+    seq: usize,
+}
+
+impl ChannelWriteGuard<'_> {
+    pub fn set(&self, index: usize, value: u8) {
+        self.buffer.slots[index].store(value, Ordering::Relaxed);
+    }
+
+    pub fn set_all(&self, values: &[u8; DMX_CHANNELS]) {
+        self.buffer.slots.iter().zip(values.iter()).for_each(|(slot, value)| slot.store(*value, Ordering::Relaxed));
+    }
+}
+
+impl Drop for ChannelWriteGuard<'_> {
+    fn drop(&mut self) {
+        self.buffer.seq.store(self.seq + 2, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn channel_buffer_loads_are_never_torn() {
+        let buffer = ChannelBuffer::new();
+        let done = Arc::new(AtomicBool::new(false));
+
+        // Each writer fills all slots with one generation, slot by slot, to leave as much room for tearing as possible
+        let writers: Vec<_> = (0..2).map(|writer| {
+            let buffer = buffer.clone();
+            thread::spawn(move || {
+                for generation in 0..2000usize {
+                    let value = (generation * 2 + writer) as u8;
+                    let guard = buffer.write();
+                    if generation % 2 == 0 {
+                        (0..DMX_CHANNELS).for_each(|i| guard.set(i, value));
+                    } else {
+                        guard.set_all(&[value; DMX_CHANNELS]);
+                    }
+                }
+            })
+        }).collect();
+        let readers: Vec<_> = (0..2).map(|_| {
+            let buffer = buffer.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut loads = 0;
+                while !done.load(Ordering::Relaxed) || loads == 0 {
+                    let values = buffer.load();
+                    assert!(values.iter().all(|value| *value == values[0]), "torn frame: {:?}", values);
+                    loads += 1;
+                }
+            })
+        }).collect();
+
+        writers.into_iter().for_each(|writer| writer.join().unwrap());
+        done.store(true, Ordering::Relaxed);
+        readers.into_iter().for_each(|reader| reader.join().unwrap());
+    }
+}
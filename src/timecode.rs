@@ -0,0 +1,386 @@
+use crate::{DMXSerial, DMX_CHANNELS};
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+// Frames of 10 minutes and of a single minute in 29.97 fps drop-frame timecode
+const DROP_FRAMES_PER_10_MINUTES: u64 = 17_982;
+const DROP_FRAMES_PER_MINUTE: u64 = 1_798;
+
+/// The frame rate of a [`Timecode`], as defined for SMPTE and MIDI timecode.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameRate {
+    /// 24 fps *(film)*
+    Fps24,
+    /// 25 fps *(PAL)*
+    Fps25,
+    /// 29.97 fps drop-frame *(NTSC)*, which skips the frame numbers `0` and `1` every minute, except every tenth.
+    Fps2997Drop,
+    /// 30 fps
+    Fps30,
+}
+
+impl FrameRate {
+    /// Returns the number of frame labels per second.
+    ///
+    pub fn frames_per_second(&self) -> u8 {
+        match self {
+            FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps2997Drop | FrameRate::Fps30 => 30,
+        }
+    }
+
+    // Rate bits of MIDI timecode
+    fn from_mtc(bits: u8) -> FrameRate {
+        match bits & 0b11 {
+            0 => FrameRate::Fps24,
+            1 => FrameRate::Fps25,
+            2 => FrameRate::Fps2997Drop,
+            _ => FrameRate::Fps30,
+        }
+    }
+
+    fn frames_per_day(&self) -> u64 {
+        match self {
+            FrameRate::Fps2997Drop => DROP_FRAMES_PER_10_MINUTES * 6 * 24,
+            _ => self.frames_per_second() as u64 * 86_400,
+        }
+    }
+}
+
+/// A SMPTE timecode *(hours:minutes:seconds:frames)*.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub rate: FrameRate,
+}
+
+impl Timecode {
+    /// Creates a new [Timecode]. Returns `None` if the time or the frame isn't valid for the [`FrameRate`].
+    ///
+    /// In drop-frame timecode, the skipped labels *(frames `0` and `1` of every minute which isn't divisible by 10)* aren't valid.
+    ///
+    pub fn new(hours: u8, minutes: u8, seconds: u8, frames: u8, rate: FrameRate) -> Option<Timecode> {
+        let dropped = rate == FrameRate::Fps2997Drop && seconds == 0 && frames < 2 && !minutes.is_multiple_of(10);
+        (hours < 24 && minutes < 60 && seconds < 60 && frames < rate.frames_per_second() && !dropped)
+            .then_some(Timecode { hours, minutes, seconds, frames, rate })
+    }
+
+    /// Returns the number of frames since `00:00:00:00`.
+    ///
+    pub fn frame_number(&self) -> u64 {
+        self.frame_number_at(self.rate)
+    }
+
+    /// Creates the [Timecode] of the given number of frames since `00:00:00:00`. It wraps around after 24 hours.
+    ///
+    pub fn from_frame_number(frame_number: u64, rate: FrameRate) -> Timecode {
+        let mut frame_number = frame_number % rate.frames_per_day();
+        if rate == FrameRate::Fps2997Drop {
+            // Adds the skipped frame numbers back, so the labels can be calculated like at 30 fps
+            let tens = frame_number / DROP_FRAMES_PER_10_MINUTES;
+            let remainder = frame_number % DROP_FRAMES_PER_10_MINUTES;
+            frame_number += 18 * tens + if remainder < 2 { 0 } else { 2 * ((remainder - 2) / DROP_FRAMES_PER_MINUTE) };
+        }
+        let fps = rate.frames_per_second() as u64;
+        let seconds = frame_number / fps;
+        Timecode {
+            hours: (seconds / 3600) as u8,
+            minutes: (seconds / 60 % 60) as u8,
+            seconds: (seconds % 60) as u8,
+            frames: (frame_number % fps) as u8,
+            rate,
+        }
+    }
+
+    // Frame number of the label at another rate, so cues can be compared with the incoming timecode
+    fn frame_number_at(&self, rate: FrameRate) -> u64 {
+        let seconds = self.hours as u64 * 3600 + self.minutes as u64 * 60 + self.seconds as u64;
+        let frames = seconds * rate.frames_per_second() as u64 + self.frames as u64;
+        if rate == FrameRate::Fps2997Drop {
+            let minutes = self.hours as u64 * 60 + self.minutes as u64;
+            frames - 2 * (minutes - minutes / 10)
+        } else {
+            frames
+        }
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = if self.rate == FrameRate::Fps2997Drop { ';' } else { ':' };
+        write!(f, "{:02}:{:02}:{:02}{}{:02}", self.hours, self.minutes, self.seconds, separator, self.frames)
+    }
+}
+
+/// Provides the current [`Timecode`] for a [`TimecodeCueList`] *(e.g. a [`MtcDecoder`], LTC reader or media player)*.
+///
+pub trait TimecodeSource: Send {
+    /// Returns the current [`Timecode`], or `None` if no timecode is received.
+    ///
+    fn timecode(&self) -> Option<Timecode>;
+}
+
+/// Decodes **MIDI timecode** from the bytes of a MIDI input.
+///
+/// Quarter frame messages and full frame messages are supported. The MIDI input itself isn't part of this crate, the received bytes are passed to [`MtcDecoder::push`].
+/// Clones share the same state, so one can be fed by the MIDI thread while another one is the source of a [`TimecodeCueList`].
+///
+#[derive(Debug, Clone, Default)]
+pub struct MtcDecoder {
+    state: Arc<Mutex<MtcState>>,
+}
+
+#[derive(Debug, Default)]
+struct MtcState {
+    // Nibbles of the quarter frame messages
+    pieces: [u8; 8],
+    // Set for every piece received since the last complete timecode
+    received: u8,
+    // Status byte of the running message and its data
+    status: Option<u8>,
+    data: Vec<u8>,
+    timecode: Option<Timecode>,
+}
+
+impl MtcDecoder {
+    /// Creates a new [MtcDecoder] without a timecode.
+    ///
+    pub fn new() -> MtcDecoder {
+        MtcDecoder::default()
+    }
+
+    /// Decodes the received MIDI bytes. Other messages are ignored.
+    ///
+    pub fn push(&self, bytes: &[u8]) {
+        // Mutex can be unwrapped here
+        let mut state = self.state.lock().unwrap();
+        bytes.iter().for_each(|byte| state.push(*byte));
+    }
+}
+
+impl TimecodeSource for MtcDecoder {
+    fn timecode(&self) -> Option<Timecode> {
+        // Mutex can be unwrapped here
+        self.state.lock().unwrap().timecode
+    }
+}
+
+impl MtcState {
+    fn push(&mut self, byte: u8) {
+        match byte {
+            // Real-time messages can appear anywhere
+            0xF8..=0xFF => {},
+            0xF0 | 0xF1 => {
+                self.status = Some(byte);
+                self.data.clear();
+            },
+            0xF7 => {
+                if self.status == Some(0xF0) {
+                    self.full_frame();
+                }
+                self.status = None;
+            },
+            0x80..=0xEF | 0xF2..=0xF6 => self.status = None,
+            _ => match self.status {
+                Some(0xF1) => {
+                    self.quarter_frame(byte);
+                    self.status = None;
+                },
+                // Full frame messages are short, longer system exclusive messages are not needed
+                Some(0xF0) if self.data.len() < 16 => self.data.push(byte),
+                _ => {},
+            },
+        }
+    }
+
+    fn quarter_frame(&mut self, data: u8) {
+        let piece = (data >> 4) as usize & 0b111;
+        self.pieces[piece] = data & 0x0F;
+        self.received |= 1 << piece;
+        if piece != 7 || self.received != 0xFF {
+            return;
+        }
+        self.received = 0;
+        let pieces = self.pieces;
+        let rate = FrameRate::from_mtc(pieces[7] >> 1);
+        let Some(timecode) = Timecode::new(
+            pieces[6] | (pieces[7] & 0b1) << 4,
+            pieces[4] | pieces[5] << 4,
+            pieces[2] | pieces[3] << 4,
+            pieces[0] | pieces[1] << 4,
+            rate,
+        ) else {
+            return;
+        };
+        // The pieces describe the frame at which the first one was sent, two frames ago
+        self.timecode = Some(Timecode::from_frame_number(timecode.frame_number() + 2, rate));
+    }
+
+    fn full_frame(&mut self) {
+        // Universal real-time message: 7F, device, 01 (timecode), 01 (full frame), hours, minutes, seconds, frames
+        if let [0x7F, _, 0x01, 0x01, hours, minutes, seconds, frames] = self.data[..] {
+            let rate = FrameRate::from_mtc(hours >> 5);
+            if let Some(timecode) = Timecode::new(hours & 0x1F, minutes, seconds, frames, rate) {
+                self.timecode = Some(timecode);
+                self.received = 0;
+            }
+        }
+    }
+}
+
+/// Identifies a cue added to a [`TimecodeCueList`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CueId(u64);
+
+struct Cue {
+    id: CueId,
+    at: Timecode,
+    action: Box<dyn FnMut(&mut DMXSerial) + Send>,
+}
+
+/// Runs cues on a [DMXSerial] when the timecode of a [`TimecodeSource`] passes them, for playback in sync with a show.
+///
+/// The cue list doesn't run by itself, [`TimecodeCueList::poll`] has to be called regularly *(at least once per frame for exact timing)*.
+/// If several cues got due since the last poll, they are run in the order of their timecodes.
+/// Jumps backwards *(e.g. when the show is rewound)* don't run any cues.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, FrameRate, MtcDecoder, Timecode, TimecodeCueList};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let decoder = MtcDecoder::new();
+///     // Feed the decoder from the MIDI input, e.g. decoder.push(&message)
+///     let mut cues = TimecodeCueList::new(Box::new(decoder.clone()));
+///     cues.add_scene(Timecode::new(1, 0, 0, 0, FrameRate::Fps25).unwrap(), [255; 512]);
+///     cues.add_scene(Timecode::new(1, 0, 30, 12, FrameRate::Fps25).unwrap(), [0; 512]);
+///     loop {
+///         cues.poll(&mut dmx);
+///         std::thread::sleep(std::time::Duration::from_millis(10));
+///     }
+/// }
+/// ```
+///
+pub struct TimecodeCueList {
+    source: Box<dyn TimecodeSource>,
+    cues: Vec<Cue>,
+    next_id: u64,
+    last: Option<Timecode>,
+}
+
+impl fmt::Debug for TimecodeCueList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TimecodeCueList")
+            .field("cues", &self.cues.iter().map(|cue| cue.at).collect::<Vec<_>>())
+            .field("last", &self.last)
+            .finish()
+    }
+}
+
+impl TimecodeCueList {
+    /// Creates a new [TimecodeCueList], which follows the given [`TimecodeSource`].
+    ///
+    pub fn new(source: Box<dyn TimecodeSource>) -> TimecodeCueList {
+        TimecodeCueList {
+            source,
+            cues: Vec::new(),
+            next_id: 0,
+            last: None,
+        }
+    }
+
+    /// Adds a cue which runs the action on the [DMXSerial] when the timecode passes `at`.
+    ///
+    /// The cue is compared by its label, so it also matches a timecode with another [`FrameRate`].
+    ///
+    pub fn add<F: FnMut(&mut DMXSerial) + Send + 'static>(&mut self, at: Timecode, action: F) -> CueId {
+        let id = CueId(self.next_id);
+        self.next_id += 1;
+        self.cues.push(Cue {
+            id,
+            at,
+            action: Box::new(action),
+        });
+        id
+    }
+
+    /// Adds a cue which sets a scene on the [DMXSerial] when the timecode passes `at`.
+    ///
+    pub fn add_scene(&mut self, at: Timecode, channels: [u8; DMX_CHANNELS]) -> CueId {
+        self.add(at, move |dmx| dmx.set_channels(channels))
+    }
+
+    /// Removes a cue. Returns `false` if there was no cue with the given [`CueId`].
+    ///
+    pub fn remove(&mut self, id: CueId) -> bool {
+        let len = self.cues.len();
+        self.cues.retain(|cue| cue.id != id);
+        self.cues.len() != len
+    }
+
+    /// Returns the timecode of the last poll.
+    ///
+    pub fn timecode(&self) -> Option<Timecode> {
+        self.last
+    }
+
+    /// Runs all cues which the timecode passed since the last poll. Returns the number of cues run.
+    ///
+    /// The first timecode only sets the position, so cues before it aren't run after starting or when the timecode returns.
+    ///
+    pub fn poll(&mut self, dmx: &mut DMXSerial) -> usize {
+        let Some(now) = self.source.timecode() else {
+            self.last = None;
+            return 0;
+        };
+        let Some(last) = self.last.replace(now) else {
+            return 0;
+        };
+        let (from, to) = (last.frame_number_at(now.rate), now.frame_number());
+        if to <= from {
+            return 0;
+        }
+
+        let mut due: Vec<&mut Cue> = self.cues.iter_mut()
+            .filter(|cue| (from + 1..=to).contains(&cue.at.frame_number_at(now.rate)))
+            .collect();
+        due.sort_by_key(|cue| cue.at.frame_number_at(now.rate));
+        for cue in &mut due {
+            (cue.action)(dmx);
+        }
+        due.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timecode_labels_round_trip() {
+        let timecode = Timecode::new(1, 10, 0, 2, FrameRate::Fps2997Drop).unwrap();
+        assert_eq!(timecode.to_string(), "01:10:00;02");
+        assert_eq!(Timecode::from_frame_number(timecode.frame_number(), FrameRate::Fps2997Drop), timecode);
+        // 00:00:59;29 is followed by 00:01:00;02
+        let before = Timecode::new(0, 0, 59, 29, FrameRate::Fps2997Drop).unwrap();
+        assert_eq!(Timecode::from_frame_number(before.frame_number() + 1, FrameRate::Fps2997Drop).to_string(), "00:01:00;02");
+        assert!(Timecode::new(0, 0, 0, 25, FrameRate::Fps25).is_none());
+
+        // The skipped labels don't exist, except every tenth minute
+        assert!(Timecode::new(0, 1, 0, 0, FrameRate::Fps2997Drop).is_none());
+        assert!(Timecode::new(0, 1, 0, 1, FrameRate::Fps2997Drop).is_none());
+        assert!(Timecode::new(0, 10, 0, 0, FrameRate::Fps2997Drop).is_some());
+        assert!(Timecode::new(0, 1, 1, 0, FrameRate::Fps2997Drop).is_some());
+        assert!(Timecode::new(0, 1, 0, 0, FrameRate::Fps30).is_some());
+    }
+}
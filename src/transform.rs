@@ -0,0 +1,493 @@
+use crate::check_valid_channel;
+use crate::error::DMXChannelValidityError;
+use crate::fixture::Fixture;
+use crate::DMX_CHANNELS;
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// A stage of the transmit pipeline, which modifies the channel values right before they are sent.
+///
+/// Transforms are added with [`DMXSerial::add_transform`] and applied in the order they were added.
+/// They only change the sent values, [`DMXSerial::get_channels`] still returns the values which were set.
+///
+/// It's implemented for closures, so simple stages don't need their own type.
+///
+/// [`DMXSerial::add_transform`]: crate::DMXSerial::add_transform
+/// [`DMXSerial::get_channels`]: crate::DMXSerial::get_channels
+///
+/// # Example
+///
+/// Swapping the first two channels:
+///
+/// ```no_run
+/// # use open_dmx::{DMXSerial, DMX_CHANNELS};
+/// # fn main() {
+/// # let mut dmx = DMXSerial::open("COM3").unwrap();
+/// dmx.add_transform(Box::new(|channels: &mut [u8; DMX_CHANNELS]| channels.swap(0, 1)));
+/// # }
+/// ```
+///
+pub trait FrameTransform: Send + Sync {
+    /// Modifies the channel values of the next **DMX packet**.
+    ///
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]);
+}
+
+impl<F: Fn(&mut [u8; DMX_CHANNELS]) + Send + Sync> FrameTransform for F {
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        self(channels)
+    }
+}
+
+/// Identifies a transform added with [`DMXSerial::add_transform()`].
+///
+/// [`DMXSerial::add_transform()`]: crate::DMXSerial::add_transform
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransformId(pub(crate) u64);
+
+pub(crate) struct TransformStage {
+    pub id: TransformId,
+    pub transform: Box<dyn FrameTransform>,
+}
+
+impl fmt::Debug for TransformStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TransformStage").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+/// A **grand master**, which scales the intensity of the rig by a master level.
+///
+/// Only the [intensity channels](Fixture::intensity_channels) of the fixtures are scaled, so the color and the position of the rig are kept.
+/// Without a patch, [`MasterDimmer::all_channels`] scales every channel instead.
+/// Clones share the same level, so it can be changed after the transform was added.
+///
+#[derive(Debug, Clone)]
+pub struct MasterDimmer {
+    // `None` scales all channels
+    channels: Option<Arc<[bool; DMX_CHANNELS]>>,
+    level: Arc<AtomicU8>,
+}
+
+impl MasterDimmer {
+    /// Creates a new [MasterDimmer] for the fixtures of the rig with the given `level` *(`255` = full)*.
+    ///
+    pub fn new(fixtures: &[Fixture], level: u8) -> MasterDimmer {
+        MasterDimmer {
+            channels: Some(Arc::new(intensity_mask(fixtures))),
+            level: Arc::new(AtomicU8::new(level)),
+        }
+    }
+
+    /// Creates a new [MasterDimmer] which scales every channel with the given `level` *(`255` = full)*.
+    ///
+    pub fn all_channels(level: u8) -> MasterDimmer {
+        MasterDimmer {
+            channels: None,
+            level: Arc::new(AtomicU8::new(level)),
+        }
+    }
+
+    /// Sets the master `level` *(`255` = full)*.
+    ///
+    pub fn set_level(&self, level: u8) {
+        self.level.store(level, Ordering::Relaxed);
+    }
+
+    /// Returns the master level.
+    ///
+    pub fn level(&self) -> u8 {
+        self.level.load(Ordering::Relaxed)
+    }
+}
+
+impl FrameTransform for MasterDimmer {
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        let level = self.level();
+        match self.channels.as_ref() {
+            Some(mask) => channels.iter_mut().zip(mask.iter())
+                .filter(|(_, masked)| **masked)
+                .for_each(|(value, _)| *value = scale(*value, level)),
+            None => channels.iter_mut().for_each(|value| *value = scale(*value, level)),
+        }
+    }
+}
+
+fn scale(value: u8, level: u8) -> u8 {
+    (value as u16 * level as u16 / 255) as u8
+}
+
+// Marks the intensity channels of the fixtures
+fn intensity_mask(fixtures: &[Fixture]) -> [bool; DMX_CHANNELS] {
+    let mut mask = [false; DMX_CHANNELS];
+    fixtures.iter()
+        .flat_map(Fixture::intensity_channels)
+        .for_each(|channel| mask[channel - 1] = true);
+    mask
+}
+
+/// An **inhibitive submaster**, which scales the maximum intensity of a group of fixtures.
+///
+/// Only the [intensity channels](Fixture::intensity_channels) of the fixtures are scaled, so the color and the position of the group are kept.
+/// Clones share the same level, so it can be changed after the transform was added *(e.g. from a fader)*.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, DMXSerial, Fixture, Submaster};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let front = [
+///         Fixture::new("Front 1", 1, vec![Attribute::Dimmer]).unwrap(),
+///         Fixture::new("Front 2", 2, vec![Attribute::Dimmer]).unwrap(),
+///     ];
+///     let submaster = Submaster::new(&front, 255);
+///     dmx.add_transform(Box::new(submaster.clone()));
+///     submaster.set_level(128);
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Submaster {
+    channels: Arc<[bool; DMX_CHANNELS]>,
+    level: Arc<AtomicU8>,
+}
+
+impl Submaster {
+    /// Creates a new [Submaster] for the fixtures with the given `level` *(`255` = full)*.
+    ///
+    pub fn new(fixtures: &[Fixture], level: u8) -> Submaster {
+        Submaster {
+            channels: Arc::new(intensity_mask(fixtures)),
+            level: Arc::new(AtomicU8::new(level)),
+        }
+    }
+
+    /// Sets the `level` of the submaster *(`255` = full)*.
+    ///
+    pub fn set_level(&self, level: u8) {
+        self.level.store(level, Ordering::Relaxed);
+    }
+
+    /// Returns the level of the submaster.
+    ///
+    pub fn level(&self) -> u8 {
+        self.level.load(Ordering::Relaxed)
+    }
+}
+
+impl FrameTransform for Submaster {
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        let level = self.level();
+        channels.iter_mut().zip(self.channels.iter())
+            .filter(|(_, grouped)| **grouped)
+            .for_each(|(value, _)| *value = scale(*value, level));
+    }
+}
+
+/// A **solo** *(rem dim)* mode, which dims all fixtures except the selected group, e.g. for focusing or for a quick look while programming.
+///
+/// Only the [intensity channels](Fixture::intensity_channels) of the other fixtures are dimmed, to the remainder level *(`0` by default)*.
+/// Clones share the same state, so the group can be selected after the transform was added.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, DMXSerial, Fixture, Solo};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let rig = vec![
+///         Fixture::new("Front", 1, vec![Attribute::Dimmer]).unwrap(),
+///         Fixture::new("Back", 2, vec![Attribute::Dimmer]).unwrap(),
+///     ];
+///     let solo = Solo::new(&rig);
+///     dmx.add_transform(Box::new(solo.clone()));
+///     solo.set_remainder(25);
+///     solo.select(&rig[..1]);
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Solo {
+    state: Arc<RwLock<SoloState>>,
+}
+
+#[derive(Debug)]
+struct SoloState {
+    // Intensity channels of all fixtures and of the selected group
+    fixtures: [bool; DMX_CHANNELS],
+    selected: Option<[bool; DMX_CHANNELS]>,
+    remainder: u8,
+}
+
+impl Solo {
+    /// Creates a new, inactive [Solo] for the fixtures of the rig *(e.g. [`Patch::fixtures`](crate::Patch::fixtures))*.
+    ///
+    pub fn new(fixtures: &[Fixture]) -> Solo {
+        Solo {
+            state: Arc::new(RwLock::new(SoloState {
+                fixtures: intensity_mask(fixtures),
+                selected: None,
+                remainder: 0,
+            })),
+        }
+    }
+
+    /// Selects the group of fixtures which keeps its intensity and activates the solo.
+    ///
+    pub fn select(&self, group: &[Fixture]) {
+        // RwLock can be unwrapped here
+        self.state.write().unwrap().selected = Some(intensity_mask(group));
+    }
+
+    /// Deactivates the solo.
+    ///
+    pub fn release(&self) {
+        // RwLock can be unwrapped here
+        self.state.write().unwrap().selected = None;
+    }
+
+    /// Returns `true` while a group is selected.
+    ///
+    pub fn is_active(&self) -> bool {
+        // RwLock can be unwrapped here
+        self.state.read().unwrap().selected.is_some()
+    }
+
+    /// Sets the level the other fixtures are scaled to *(`0` = off, `255` = not dimmed)*.
+    ///
+    pub fn set_remainder(&self, level: u8) {
+        // RwLock can be unwrapped here
+        self.state.write().unwrap().remainder = level;
+    }
+
+    /// Returns the level the other fixtures are scaled to.
+    ///
+    pub fn remainder(&self) -> u8 {
+        // RwLock can be unwrapped here
+        self.state.read().unwrap().remainder
+    }
+}
+
+impl FrameTransform for Solo {
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        // RwLock can be unwrapped here
+        let state = self.state.read().unwrap();
+        let Some(selected) = state.selected.as_ref() else {
+            return;
+        };
+        channels.iter_mut().zip(state.fixtures.iter().zip(selected.iter()))
+            .filter(|(_, (fixture, selected))| **fixture && !**selected)
+            .for_each(|(value, _)| *value = scale(*value, state.remainder));
+    }
+}
+
+/// Maps the channel values through a lookup table *(e.g. for dimmer curves)*.
+///
+/// A new curve maps every channel. With [`Curve::fixtures`] it only maps the [intensity channels](Fixture::intensity_channels) of the rig,
+/// so the position, color mixing and mode channels pass through unchanged.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{Attribute, Curve, DMXSerial, Fixture};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let rig = vec![Fixture::new("Spot", 1, vec![Attribute::Dimmer, Attribute::Pan]).unwrap()];
+///     dmx.add_transform(Box::new(Curve::gamma(2.2).unwrap().fixtures(&rig)));
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Curve {
+    table: [u8; 256],
+    // `None` maps all channels
+    channels: Option<Arc<[bool; DMX_CHANNELS]>>,
+}
+
+impl Curve {
+    /// Creates a new [Curve] from a lookup table, where `table[value]` is sent instead of `value`.
+    ///
+    pub fn new(table: [u8; 256]) -> Curve {
+        Curve { table, channels: None }
+    }
+
+    /// Creates a gamma correction [Curve]. A `gamma` of `1.0` doesn't change the values.
+    ///
+    /// Returns `None` if `gamma` isn't a finite number above `0`.
+    ///
+    pub fn gamma(gamma: f32) -> Option<Curve> {
+        if !gamma.is_finite() || gamma <= 0.0 {
+            return None;
+        }
+        let mut table = [0; 256];
+        for (value, mapped) in table.iter_mut().enumerate() {
+            *mapped = ((value as f32 / 255.0).powf(gamma) * 255.0).round() as u8;
+        }
+        Some(Curve::new(table))
+    }
+
+    /// Limits the curve to the intensity channels of the given fixtures.
+    ///
+    pub fn fixtures(mut self, fixtures: &[Fixture]) -> Curve {
+        self.channels = Some(Arc::new(intensity_mask(fixtures)));
+        self
+    }
+
+    #[cfg(any(test, feature = "config"))]
+    pub(crate) fn table(&self) -> &[u8; 256] {
+        &self.table
+    }
+}
+
+impl FrameTransform for Curve {
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        match self.channels.as_ref() {
+            Some(mask) => channels.iter_mut().zip(mask.iter())
+                .filter(|(_, masked)| **masked)
+                .for_each(|(value, _)| *value = self.table[*value as usize]),
+            None => channels.iter_mut().for_each(|value| *value = self.table[*value as usize]),
+        }
+    }
+}
+
+/// Limits channels to a maximum value.
+///
+/// Clones share the same limits, so they can be changed after the transform was added.
+///
+#[derive(Debug, Clone)]
+pub struct Limit {
+    max: Arc<RwLock<[u8; DMX_CHANNELS]>>,
+}
+
+impl Limit {
+    /// Creates a new [Limit] without any limited channels.
+    ///
+    pub fn new() -> Limit {
+        Limit {
+            max: Arc::new(RwLock::new([255; DMX_CHANNELS])),
+        }
+    }
+
+    /// Sets the maximum [`value`] of the specified [`channel`].
+    ///
+    /// [`channel`]: usize
+    /// [`value`]: u8
+    ///
+    pub fn set_limit(&self, channel: usize, max: u8) -> Result<(), DMXChannelValidityError> {
+        check_valid_channel(channel)?;
+        // RwLock can be unwrapped here
+        self.max.write().unwrap()[channel - 1] = max;
+        Ok(())
+    }
+}
+
+impl Default for Limit {
+    fn default() -> Self {
+        Limit::new()
+    }
+}
+
+impl FrameTransform for Limit {
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        // RwLock can be unwrapped here
+        let max = self.max.read().unwrap();
+        channels.iter_mut().zip(max.iter()).for_each(|(value, max)| *value = (*value).min(*max));
+    }
+}
+
+/// Sets the intensity of the rig to `0` while it's active.
+///
+/// Only the [intensity channels](Fixture::intensity_channels) of the fixtures are cleared, so the color and the position of the rig are kept.
+/// Without a patch, [`Blackout::all_channels`] clears every channel instead.
+/// Clones share the same state, so it can be toggled after the transform was added.
+///
+#[derive(Debug, Clone)]
+pub struct Blackout {
+    // `None` clears all channels
+    channels: Option<Arc<[bool; DMX_CHANNELS]>>,
+    active: Arc<AtomicBool>,
+}
+
+impl Blackout {
+    /// Creates a new, inactive [Blackout] for the fixtures of the rig.
+    ///
+    pub fn new(fixtures: &[Fixture]) -> Blackout {
+        Blackout {
+            channels: Some(Arc::new(intensity_mask(fixtures))),
+            active: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Creates a new, inactive [Blackout] which clears every channel.
+    ///
+    pub fn all_channels() -> Blackout {
+        Blackout {
+            channels: None,
+            active: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Activates or deactivates the blackout.
+    ///
+    pub fn set_active(&self, active: bool) {
+        self.active.store(active, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the blackout is active.
+    ///
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+}
+
+impl FrameTransform for Blackout {
+    fn apply(&self, channels: &mut [u8; DMX_CHANNELS]) {
+        if !self.is_active() {
+            return;
+        }
+        match self.channels.as_ref() {
+            Some(mask) => channels.iter_mut().zip(mask.iter())
+                .filter(|(_, masked)| **masked)
+                .for_each(|(value, _)| *value = 0),
+            None => channels.fill(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Attribute;
+
+    #[test]
+    fn gamma_curves_reject_invalid_exponents() {
+        for gamma in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(Curve::gamma(gamma).is_none(), "gamma {} was accepted", gamma);
+        }
+        let linear = Curve::gamma(1.0).unwrap();
+        assert!(linear.table().iter().enumerate().all(|(value, mapped)| *mapped as usize == value));
+        let curve = Curve::gamma(2.0).unwrap();
+        assert_eq!([curve.table()[0], curve.table()[128], curve.table()[255]], [0, 64, 255]);
+    }
+
+    #[test]
+    fn curves_only_map_the_intensity_of_the_fixtures() {
+        let spot = Fixture::new("Spot", 1, vec![Attribute::Dimmer, Attribute::Pan, Attribute::Red]).unwrap();
+        let mut channels = [128; DMX_CHANNELS];
+        Curve::gamma(2.0).unwrap().fixtures(&[spot]).apply(&mut channels);
+        // Pan, the color and the unpatched channels pass through unchanged
+        assert_eq!(channels[..4], [64, 128, 128, 128]);
+        assert!(channels[3..].iter().all(|value| *value == 128));
+
+        let mut channels = [128; DMX_CHANNELS];
+        Curve::gamma(2.0).unwrap().apply(&mut channels);
+        assert_eq!(channels, [64; DMX_CHANNELS]);
+    }
+}
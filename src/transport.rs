@@ -0,0 +1,203 @@
+use crate::builder::{DMXSerialBuilder, LineSettings};
+
+use serialport::SerialPort;
+
+use std::io;
+
+/// The connection the agent thread writes the **DMX packets** to.
+///
+/// It's implemented for the [SerialPort] used by [`DMXSerial::open`], but can also be implemented for other connections *(e.g. a mock for tests)*.
+/// Use [`DMXSerialBuilder::open_with_transport`] to open a [DMXSerial] on a custom transport.
+///
+/// [SerialPort]: serialport::SerialPort
+/// [DMXSerial]: crate::DMXSerial
+/// [`DMXSerial::open`]: crate::DMXSerial::open
+///
+pub trait Transport: io::Write + Send {
+    /// Starts the break signal. Only used with [`BreakMode::Signal`].
+    ///
+    /// [`BreakMode::Signal`]: crate::BreakMode::Signal
+    fn set_break(&mut self) -> serialport::Result<()>;
+
+    /// Stops the break signal. Only used with [`BreakMode::Signal`].
+    ///
+    /// [`BreakMode::Signal`]: crate::BreakMode::Signal
+    fn clear_break(&mut self) -> serialport::Result<()>;
+
+    /// Switches the line to the given [`LineSettings`]. Only used with [`BreakMode::Baud`].
+    ///
+    /// [`BreakMode::Baud`]: crate::BreakMode::Baud
+    fn apply_line_settings(&mut self, settings: LineSettings) -> serialport::Result<()>;
+
+    /// Sets the level of the RTS line. Only used with [`DirectionControl::Rts`].
+    ///
+    /// [`DirectionControl::Rts`]: crate::DirectionControl::Rts
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()>;
+
+    /// Sets the level of the DTR line. Only used with [`DirectionControl::Dtr`].
+    ///
+    /// [`DirectionControl::Dtr`]: crate::DirectionControl::Dtr
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()>;
+
+    /// Reads back the [`LineSettings`] which are actually applied by the driver. See [`DMXSerial::port_diagnostics`].
+    ///
+    /// Returns an error by default, for transports which can't read back their settings.
+    ///
+    /// [`DMXSerial::port_diagnostics`]: crate::DMXSerial::port_diagnostics
+    fn read_line_settings(&mut self) -> serialport::Result<LineSettings> {
+        Err(serialport::Error::new(serialport::ErrorKind::Unknown, "The transport can't read back its line settings"))
+    }
+
+    /// Returns `true` if the transport receives whole frames instead of generating a DMX signal *(e.g. USB interfaces, network protocols or simulators)*.
+    ///
+    /// The agent then skips the break and the direction control and writes every frame *(start code + channels)* with a single call to [`write_all`](io::Write::write_all).
+    /// Returns `false` by default.
+    ///
+    fn is_framed(&self) -> bool {
+        false
+    }
+
+    /// Checks that the connection is still there, without sending anything. An error stops the agent like a failed write.
+    ///
+    /// Used for interfaces which are idle, like the backup of a [`DMXFailover`](crate::DMXFailover). Returns `Ok` by default.
+    ///
+    fn probe(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
+
+// The SerialPort used by default
+pub(crate) struct SerialTransport {
+    port: Box<dyn SerialPort>,
+    // Needed for setting custom baud rates via termios2, not available for ports opened by the user
+    #[cfg(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64"))))]
+    fd: Option<std::os::unix::io::RawFd>,
+}
+
+impl SerialTransport {
+    pub fn open(options: &DMXSerialBuilder) -> serialport::Result<SerialTransport> {
+        let builder = serialport::new(crate::port_path::normalize(&options.port)?, options.line_settings.baud_rate)
+        .flow_control(serialport::FlowControl::None);
+        #[cfg(unix)]
+        let builder = builder.exclusive(options.exclusive);
+        let transport = SerialTransport::from_builder(builder, options.line_settings);
+        #[cfg(windows)]
+        let transport = transport.map_err(|e| crate::error::missing_port(e, crate::port_path::is_listed(&options.port)));
+        transport
+    }
+
+    // Opens a port configured by the user, only the line settings are replaced
+    pub fn from_builder(builder: serialport::SerialPortBuilder, settings: LineSettings) -> serialport::Result<SerialTransport> {
+        let builder = builder
+        .baud_rate(settings.baud_rate)
+        .data_bits(settings.data_bits)
+        .stop_bits(settings.stop_bits)
+        .parity(settings.parity);
+
+        #[cfg(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64"))))]
+        {
+            use std::os::unix::io::AsRawFd;
+
+            let port = match builder.clone().open_native() {
+                Ok(port) => port,
+                // The driver rejected the baud rate, so it is set via termios2 instead
+                Err(e) if e.kind() == serialport::ErrorKind::InvalidInput => builder.baud_rate(9600).open_native()?,
+                Err(e) => return Err(e),
+            };
+            let fd = port.as_raw_fd();
+            crate::termios2::set_baud_rate(fd, settings.baud_rate).map_err(|e| unsupported_baud(settings.baud_rate, e))?;
+            Ok(SerialTransport { port: Box::new(port), fd: Some(fd) })
+        }
+        #[cfg(not(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64")))))]
+        Ok(SerialTransport { port: builder.open()? })
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.port.name()
+    }
+
+    // Takes over a port opened by the user and switches it to the given settings
+    pub fn from_port(port: Box<dyn SerialPort>, settings: LineSettings) -> serialport::Result<SerialTransport> {
+        let mut transport = SerialTransport {
+            port,
+            #[cfg(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64"))))]
+            fd: None,
+        };
+        transport.apply_line_settings(settings)?;
+        Ok(transport)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        #[cfg(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64"))))]
+        if let Some(fd) = self.fd {
+            return crate::termios2::set_baud_rate(fd, baud_rate).map_err(|e| unsupported_baud(baud_rate, e));
+        }
+        self.port.set_baud_rate(baud_rate)
+    }
+}
+
+impl io::Write for SerialTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Transport for SerialTransport {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        self.port.set_break()
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        self.port.clear_break()
+    }
+
+    fn apply_line_settings(&mut self, settings: LineSettings) -> serialport::Result<()> {
+        self.set_baud_rate(settings.baud_rate)?;
+        self.port.set_data_bits(settings.data_bits)?;
+        self.port.set_stop_bits(settings.stop_bits)?;
+        self.port.set_parity(settings.parity)?;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        self.port.write_request_to_send(level)
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        self.port.write_data_terminal_ready(level)
+    }
+
+    fn read_line_settings(&mut self) -> serialport::Result<LineSettings> {
+        // The standard read back can't represent custom rates set via termios2
+        #[cfg(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64"))))]
+        let baud_rate = match self.fd {
+            Some(fd) => crate::termios2::baud_rate(fd)?,
+            None => self.port.baud_rate()?,
+        };
+        #[cfg(not(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64")))))]
+        let baud_rate = self.port.baud_rate()?;
+        Ok(LineSettings {
+            baud_rate,
+            data_bits: self.port.data_bits()?,
+            stop_bits: self.port.stop_bits()?,
+            parity: self.port.parity()?,
+        })
+    }
+
+    fn probe(&mut self) -> serialport::Result<()> {
+        // Fails once the adapter was unplugged
+        self.port.bytes_to_write().map(|_| ())
+    }
+}
+
+#[cfg(all(target_os = "linux", not(any(target_arch = "powerpc", target_arch = "powerpc64"))))]
+fn unsupported_baud(baud_rate: u32, e: std::io::Error) -> serialport::Error {
+    serialport::Error::new(
+        serialport::ErrorKind::InvalidInput,
+        format!("{} baud is not supported by the serial driver: {}", baud_rate, e),
+    )
+}
@@ -0,0 +1,172 @@
+use crate::builder::LineSettings;
+use crate::transport::Transport;
+use crate::codec::START_CODE_DMX;
+use crate::DMX_CHANNELS;
+
+use rusb::{Direction, GlobalContext, Recipient, RequestType};
+
+use std::fmt;
+use std::io;
+use std::time;
+
+// Shared USB IDs of the V-USB based devices, told apart by their names
+const VENDOR_ID: u16 = 0x16C0;
+const PRODUCT_ID: u16 = 0x05DC;
+const PRODUCT_NAME: &str = "uDMX";
+
+// Sets a range of channels: value = number of channels, index = first channel (from 0)
+const SET_CHANNEL_RANGE: u8 = 2;
+
+const USB_TIMEOUT: time::Duration = time::Duration::from_millis(500);
+
+/// A [`Transport`] for **uDMX** *(Anyma)* USB dongles, which aren't serial ports, but receive the channels with USB control transfers.
+///
+/// The dongle generates the DMX signal on its own, so the break settings are ignored and the [packet time] only sets how often the channels are transferred.
+/// It can only send the null start code, raw frames with other start codes are dropped.
+///
+/// [packet time]: crate::DMXSerial::set_packet_time
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, UdmxOutput};
+///
+/// fn main() {
+///     let mut dmx = DMXSerial::builder("uDMX")
+///         .open_with_transport(UdmxOutput::open().unwrap())
+///         .unwrap();
+///     dmx.set_channels([255; 512]);
+/// }
+/// ```
+///
+pub struct UdmxOutput {
+    handle: rusb::DeviceHandle<GlobalContext>,
+}
+
+impl UdmxOutput {
+    /// Opens the first connected uDMX dongle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of the kind [`io::ErrorKind::NotFound`] if there is no uDMX, or the error of the USB library if it can't be opened *(e.g. missing permissions)*.
+    ///
+    pub fn open() -> io::Result<UdmxOutput> {
+        for device in rusb::devices().map_err(usb_error)?.iter() {
+            let Ok(descriptor) = device.device_descriptor() else {
+                continue;
+            };
+            if descriptor.vendor_id() != VENDOR_ID || descriptor.product_id() != PRODUCT_ID {
+                continue;
+            }
+            let handle = device.open().map_err(usb_error)?;
+            // Other devices use the same IDs
+            if handle.read_product_string_ascii(&descriptor).is_ok_and(|name| name == PRODUCT_NAME) {
+                return Ok(UdmxOutput { handle });
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound, "no uDMX found"))
+    }
+}
+
+// A vendor control transfer to the dongle
+#[derive(Debug, PartialEq, Eq)]
+struct ControlRequest<'a> {
+    request: u8,
+    value: u16,
+    index: u16,
+    data: &'a [u8],
+}
+
+// Control transfer of a frame (start code + channels), `None` for frames the dongle can't send.
+// The dongle always sends the null start code on its own, so only the channels are transferred
+fn encode_channel_range(frame: &[u8]) -> Option<ControlRequest<'_>> {
+    if frame.len() < 2 || frame.len() > DMX_CHANNELS + 1 || frame[0] != START_CODE_DMX {
+        return None;
+    }
+    let channels = &frame[1..];
+    Some(ControlRequest {
+        request: SET_CHANNEL_RANGE,
+        value: channels.len() as u16,
+        index: 0,
+        data: channels,
+    })
+}
+
+impl io::Write for UdmxOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The agent writes every frame at once
+        if let Some(request) = encode_channel_range(buf) {
+            let request_type = rusb::request_type(Direction::Out, RequestType::Vendor, Recipient::Device);
+            self.handle.write_control(request_type, request.request, request.value, request.index, request.data, USB_TIMEOUT).map_err(usb_error)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for UdmxOutput {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for UdmxOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UdmxOutput").finish_non_exhaustive()
+    }
+}
+
+fn usb_error(e: rusb::Error) -> io::Error {
+    let kind = match e {
+        rusb::Error::NoDevice | rusb::Error::NotFound => io::ErrorKind::NotFound,
+        rusb::Error::Access => io::ErrorKind::PermissionDenied,
+        rusb::Error::Busy => io::ErrorKind::ResourceBusy,
+        rusb::Error::Timeout => io::ErrorKind::TimedOut,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_sent_as_channel_ranges() {
+        let request = encode_channel_range(&[0, 10, 20, 30]).unwrap();
+        assert_eq!(request, ControlRequest { request: 2, value: 3, index: 0, data: &[10, 20, 30] });
+
+        let frame = [0; DMX_CHANNELS + 1];
+        let request = encode_channel_range(&frame).unwrap();
+        assert_eq!((request.value, request.data.len()), (512, 512));
+
+        // Nothing to send, too long or another start code
+        assert!(encode_channel_range(&[]).is_none());
+        assert!(encode_channel_range(&[0]).is_none());
+        assert!(encode_channel_range(&[0; DMX_CHANNELS + 2]).is_none());
+        assert!(encode_channel_range(&[0xCC, 1, 2]).is_none());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::DMX_CHANNELS;
+
+use std::fmt::Write;
+
+// Characters of the ASCII grid, from off to full
+const LEVELS: &[u8] = b" .:-=+*#%@";
+
+/// A snapshot of the channel levels of a universe, which can be shown for debugging or documentation.
+///
+/// # Example
+///
+/// ```no_run
+/// use open_dmx::{DMXSerial, Universe};
+///
+/// fn main() {
+///     let dmx = DMXSerial::open("/dev/ttyUSB0").unwrap();
+///     let universe = Universe::from(dmx.get_channels());
+///     println!("{}", universe.to_ascii_grid(32));
+/// }
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Universe {
+    channels: [u8; DMX_CHANNELS],
+}
+
+impl Universe {
+    /// Creates a new [Universe] with the given channel values.
+    ///
+    pub fn new(channels: [u8; DMX_CHANNELS]) -> Universe {
+        Universe { channels }
+    }
+
+    /// Returns the channel values.
+    ///
+    pub fn channels(&self) -> &[u8; DMX_CHANNELS] {
+        &self.channels
+    }
+
+    /// Renders the levels as a text grid with `columns` channels per row *(at least `1`)*.
+    ///
+    /// Every row starts with the number of its first channel, followed by one character per channel, from `' '` for off to `'@'` for full.
+    ///
+    pub fn to_ascii_grid(&self, columns: usize) -> String {
+        let columns = columns.max(1);
+        let mut grid = String::new();
+        for (row, values) in self.channels.chunks(columns).enumerate() {
+            let _ = write!(grid, "{:>3} |", row * columns + 1);
+            grid.extend(values.iter().map(|value| LEVELS[*value as usize * (LEVELS.len() - 1) / 255] as char));
+            grid.extend(std::iter::repeat_n(' ', columns - values.len()));
+            grid.push_str("|\n");
+        }
+        grid
+    }
+
+    /// Renders the levels as a heatmap with `columns` channels per row *(at least `1`)*, drawing each channel as a square of `cell_size` pixels.
+    ///
+    /// The colors go from black over red and yellow to white at full. Use [`image::RgbImage::save`] to store it *(e.g. as PNG)*.
+    ///
+    #[cfg(feature = "image")]
+    pub fn to_image(&self, columns: usize, cell_size: u32) -> image::RgbImage {
+        let columns = columns.max(1);
+        let cell_size = cell_size.max(1);
+        let rows = DMX_CHANNELS.div_ceil(columns);
+        image::RgbImage::from_fn(columns as u32 * cell_size, rows as u32 * cell_size, |x, y| {
+            let channel = (y / cell_size) as usize * columns + (x / cell_size) as usize;
+            image::Rgb(self.channels.get(channel).map_or([0, 0, 0], |value| heat(*value)))
+        })
+    }
+}
+
+impl Default for Universe {
+    /// Returns a [Universe] with all channels at `0`.
+    ///
+    fn default() -> Universe {
+        Universe::new([0; DMX_CHANNELS])
+    }
+}
+
+impl From<[u8; DMX_CHANNELS]> for Universe {
+    fn from(channels: [u8; DMX_CHANNELS]) -> Universe {
+        Universe::new(channels)
+    }
+}
+
+// Black -> red -> yellow -> white
+#[cfg(feature = "image")]
+fn heat(value: u8) -> [u8; 3] {
+    let level = value as u32 * 3;
+    let part = |offset: u32| level.saturating_sub(offset).min(255) as u8;
+    [part(0), part(255), part(510)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DMX_CHANNELS;
+
+    #[test]
+    fn universe_snapshots_show_the_levels() {
+        let mut channels = [0; DMX_CHANNELS];
+        channels[0] = 255;
+        channels[1] = 128;
+        channels[511] = 255;
+        let grid = Universe::from(channels).to_ascii_grid(100);
+        let rows: Vec<&str> = grid.lines().collect();
+        assert_eq!(rows.len(), 6);
+        assert!(rows[0].starts_with("  1 |@=  "));
+        assert_eq!(rows[5], format!("501 |{}@{}|", " ".repeat(11), " ".repeat(88)));
+
+        #[cfg(feature = "image")]
+        {
+            let image = Universe::from(channels).to_image(32, 4);
+            assert_eq!(image.dimensions(), (128, 64));
+            assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255]);
+            assert_eq!(image.get_pixel(4, 0).0, [255, 129, 0]);
+            assert_eq!(image.get_pixel(8, 0).0, [0, 0, 0]);
+        }
+    }
+}
@@ -0,0 +1,18 @@
+// Messages of the Enttec DMX USB Pro API, which other widgets have adopted with their own labels
+
+const START_OF_MESSAGE: u8 = 0x7E;
+const END_OF_MESSAGE: u8 = 0xE7;
+
+// Sends a frame (start code + channels)
+pub(crate) const LABEL_SEND_DMX: u8 = 6;
+
+// Start delimiter, label, length (LSB first), data and end delimiter
+pub(crate) fn encode_message(label: u8, data: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(data.len() + 5);
+    message.push(START_OF_MESSAGE);
+    message.push(label);
+    message.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    message.extend_from_slice(data);
+    message.push(END_OF_MESSAGE);
+    message
+}
@@ -0,0 +1,1619 @@
+use open_dmx::{Action, Attribute, Blackout, BreakMode, ChannelChange, Clock, Crossfade, DMXDriver, DMXFailover, DMXSerial, DmxFrameWriter, Easing, FailoverEvent, FailoverOutput, Fixture, FrameRate, IdlePolicy, LineSettings, ManualClock, MasterDimmer, MergePolicy, MtcDecoder, MultiPlayer, OfflineRenderer, Player, RetryPolicy, SafetyInterlock, Scheduler, SelfTestIssue, ShutdownSequence, SlewLimit, Solo, StrobeGuard, Submaster, TimeOfDay, Timecode, TimecodeCueList, TimecodeSource, Transport, Trigger, Truncation, Universe, DMX_CHANNELS};
+use open_dmx::sim::{FixtureModel, FixtureState, Rack};
+use open_dmx::recording::{RecordedFrame, RecordingInfo, RecordingReader, RecordingWriter};
+use open_dmx::error::DMXOpenError;
+#[cfg(feature = "config")]
+use open_dmx::error::ConfigError;
+
+use proptest::prelude::*;
+
+use std::io::{self, Cursor, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    BreakOn,
+    BreakOff,
+    LineSettings(LineSettings),
+    Data(Vec<u8>),
+}
+
+// Records everything the agent does with a timestamp
+#[derive(Debug, Clone, Default)]
+struct MockTransport {
+    events: Arc<Mutex<Vec<(Instant, Event)>>>,
+    unplugged: Arc<AtomicBool>,
+    framed: bool,
+}
+
+impl MockTransport {
+    fn push(&self, event: Event) {
+        self.events.lock().unwrap().push((Instant::now(), event));
+    }
+
+    // Lets every following write fail like a removed adapter
+    fn unplug(&self) {
+        self.unplugged.store(true, Ordering::SeqCst);
+    }
+
+    fn replug(&self) {
+        self.unplugged.store(false, Ordering::SeqCst);
+    }
+
+    // Hands out the mock for every reopen while it's plugged in
+    fn factory(&self) -> impl Fn() -> serialport::Result<MockTransport> + Send + Sync + 'static {
+        let mock = self.clone();
+        move || match mock.unplugged.load(Ordering::SeqCst) {
+            true => Err(serialport::Error::new(serialport::ErrorKind::NoDevice, "adapter unplugged")),
+            false => Ok(mock.clone()),
+        }
+    }
+
+    // Splits the recorded events into frames, checking that every frame starts with a complete break
+    fn frames(&self) -> Vec<(Instant, Vec<u8>)> {
+        let mut frames: Vec<(Instant, Vec<u8>)> = Vec::new();
+        let mut in_break = false;
+        for (time, event) in self.events.lock().unwrap().iter() {
+            match event {
+                Event::BreakOn => {
+                    assert!(!in_break, "break started twice");
+                    in_break = true;
+                    frames.push((*time, Vec::new()));
+                },
+                Event::BreakOff => {
+                    assert!(in_break, "break stopped without being started");
+                    in_break = false;
+                },
+                Event::LineSettings(_) => {},
+                Event::Data(data) => {
+                    assert!(!in_break, "data sent during the break");
+                    frames.last_mut().expect("data sent before the first break").1.extend_from_slice(data);
+                },
+            }
+        }
+        frames
+    }
+}
+
+impl io::Write for MockTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.unplugged.load(Ordering::SeqCst) {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "adapter unplugged"));
+        }
+        self.push(Event::Data(buf.to_vec()));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for MockTransport {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        self.push(Event::BreakOn);
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        self.push(Event::BreakOff);
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, settings: LineSettings) -> serialport::Result<()> {
+        self.push(Event::LineSettings(settings));
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn is_framed(&self) -> bool {
+        self.framed
+    }
+
+    fn probe(&mut self) -> serialport::Result<()> {
+        match self.unplugged.load(Ordering::SeqCst) {
+            true => Err(serialport::Error::new(serialport::ErrorKind::NoDevice, "adapter unplugged")),
+            false => Ok(()),
+        }
+    }
+}
+
+// Opens the mock in sync mode, optionally following a clock which only moves when the test advances it
+fn open_sync(packet_time: Duration, clock: Option<&ManualClock>) -> (DMXSerial, MockTransport) {
+    let mock = MockTransport::default();
+    let mut builder = DMXSerial::builder("mock").sync().packet_time(packet_time);
+    if let Some(clock) = clock {
+        builder = builder.clock(clock.clone());
+    }
+    let dmx = builder.open_with_transport(mock.clone()).unwrap();
+    (dmx, mock)
+}
+
+fn channels() -> impl Strategy<Value = [u8; DMX_CHANNELS]> {
+    prop::collection::vec(any::<u8>(), DMX_CHANNELS).prop_map(|values| values.try_into().unwrap())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn packets_contain_start_code_and_channels(values in prop::collection::vec(channels(), 1..4)) {
+        let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+        for channels in &values {
+            dmx.set_channels(*channels);
+            dmx.update().unwrap();
+        }
+
+        let frames = mock.frames();
+        prop_assert_eq!(frames.len(), values.len());
+        for ((_, frame), channels) in frames.iter().zip(&values) {
+            prop_assert_eq!(frame.len(), DMX_CHANNELS + 1);
+            prop_assert_eq!(frame[0], 0);
+            prop_assert_eq!(&frame[1..], &channels[..]);
+        }
+    }
+
+    #[test]
+    fn raw_frames_are_sent_unchanged(frame in prop::collection::vec(any::<u8>(), 1..=DMX_CHANNELS + 1)) {
+        let (dmx, mock) = open_sync(Duration::from_millis(2), None);
+        dmx.send_raw_frame(&frame).unwrap();
+
+        let frames = mock.frames();
+        prop_assert_eq!(frames.len(), 1);
+        prop_assert_eq!(&frames[0].1, &frame);
+    }
+
+    #[test]
+    fn packets_keep_the_packet_time(millis in 2u64..6, count in 2usize..5) {
+        let packet_time = Duration::from_millis(millis);
+        let (mut dmx, mock) = open_sync(packet_time, None);
+        for _ in 0..count {
+            dmx.update().unwrap();
+        }
+
+        let frames = mock.frames();
+        prop_assert_eq!(frames.len(), count);
+        for pair in frames.windows(2) {
+            prop_assert!(pair[1].0 - pair[0].0 >= packet_time);
+        }
+    }
+}
+
+#[test]
+fn oversized_raw_frames_are_rejected() {
+    let (dmx, mock) = open_sync(Duration::from_millis(2), None);
+    assert!(dmx.send_raw_frame(&[]).is_err());
+    assert!(dmx.send_raw_frame(&[0; DMX_CHANNELS + 2]).is_err());
+    assert!(mock.frames().is_empty());
+}
+
+#[test]
+fn transforms_are_applied_in_order() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let rig = [
+        Fixture::new("Dimmer", 1, vec![Attribute::Dimmer]).unwrap(),
+        Fixture::new("Mover", 2, vec![Attribute::Pan, Attribute::Dimmer]).unwrap(),
+    ];
+    let master = MasterDimmer::new(&rig, 255);
+    let blackout = Blackout::new(&rig);
+    dmx.add_transform(Box::new(master.clone()));
+    let blackout_id = dmx.add_transform(Box::new(blackout.clone()));
+    dmx.set_channels([200; DMX_CHANNELS]);
+
+    master.set_level(127);
+    dmx.update().unwrap();
+    blackout.set_active(true);
+    dmx.update().unwrap();
+    assert!(dmx.remove_transform(blackout_id));
+    dmx.update().unwrap();
+
+    // Only the intensity channels are dimmed, the pan and the unpatched channels are kept
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..5], [99, 200, 99, 200]);
+    assert_eq!(frames[1].1[1..5], [0, 200, 0, 200]);
+    assert_eq!(frames[2].1[1..5], [99, 200, 99, 200]);
+    assert_eq!(dmx.get_channels(), [200; DMX_CHANNELS]);
+
+    // Without a patch all channels are affected
+    let master = MasterDimmer::all_channels(127);
+    let blackout = Blackout::all_channels();
+    dmx.add_transform(Box::new(master));
+    dmx.update().unwrap();
+    dmx.add_transform(Box::new(blackout.clone()));
+    blackout.set_active(true);
+    dmx.update().unwrap();
+    let frames = mock.frames();
+    assert_eq!(frames[3].1[1..5], [49, 99, 49, 99]);
+    assert_eq!(frames[4].1[1..], [0; DMX_CHANNELS]);
+}
+
+#[test]
+fn parked_channels_override_transforms() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let blackout = Blackout::all_channels();
+    blackout.set_active(true);
+    dmx.add_transform(Box::new(blackout));
+    dmx.park_channel(512, 255).unwrap();
+    dmx.update().unwrap();
+    dmx.unpark_channel(512).unwrap();
+    dmx.update().unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[512], 255);
+    assert_eq!(frames[1].1[512], 0);
+    assert_eq!(dmx.parked_value(512).unwrap(), None);
+}
+
+#[test]
+fn peaks_hold_the_highest_sent_values() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_channel(1, 200).unwrap();
+    dmx.update().unwrap();
+    assert_eq!(dmx.peaks()[0], 0);
+
+    dmx.set_peak_hold(true);
+    dmx.update().unwrap();
+    dmx.set_channel(1, 100).unwrap();
+    dmx.set_channel(2, 50).unwrap();
+    dmx.update().unwrap();
+    assert_eq!(dmx.peaks()[..3], [200, 50, 0]);
+
+    dmx.reset_peaks();
+    assert_eq!(dmx.peaks(), [0; DMX_CHANNELS]);
+}
+
+#[test]
+fn audit_trail_keeps_the_last_writes() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_channel(37, 1).unwrap();
+    assert!(dmx.channel_history(37).unwrap().is_empty());
+
+    dmx.set_audit_depth(2);
+    dmx.set_channel(37, 10).unwrap();
+    dmx.set_channel_tagged(37, 20, "ui").unwrap();
+    dmx.set_channel_tagged(37, 255, "effect").unwrap();
+
+    let history: Vec<_> = dmx.channel_history(37).unwrap().into_iter().map(|write| (write.value, write.tag)).collect();
+    assert_eq!(history, [(20, Some("ui")), (255, Some("effect"))]);
+}
+
+#[test]
+fn coalesced_writes_are_sent_with_the_next_packet() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_coalescing(true);
+    for value in 0..=200 {
+        dmx.set_channel(1, value).unwrap();
+    }
+    dmx.set_channel(2, 50).unwrap();
+    assert_eq!(dmx.get_channel(1).unwrap(), 200);
+    dmx.update().unwrap();
+
+    dmx.set_channels([7; DMX_CHANNELS]);
+    dmx.set_coalescing(false);
+    assert_eq!(dmx.get_channels(), [7; DMX_CHANNELS]);
+    dmx.update().unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..3], [200, 50]);
+    assert_eq!(frames[1].1[1..], [7; DMX_CHANNELS]);
+}
+
+#[test]
+fn unchanged_frames_are_counted() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_channel(1, 255).unwrap();
+    assert!(dmx.is_dirty());
+    dmx.update().unwrap();
+    assert!(!dmx.is_dirty());
+    assert_eq!(dmx.frames_since_last_change(), 0);
+
+    dmx.update().unwrap();
+    dmx.update().unwrap();
+    assert_eq!(dmx.frames_since_last_change(), 2);
+
+    dmx.set_channel(1, 0).unwrap();
+    dmx.update().unwrap();
+    assert_eq!(dmx.frames_since_last_change(), 0);
+}
+
+#[test]
+fn idle_output_fades_to_the_look_and_restores() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    dmx.set_channels([100; DMX_CHANNELS]);
+    dmx.set_idle_policy(Some(IdlePolicy::blackout(Duration::from_millis(20), Duration::ZERO)));
+    dmx.update().unwrap();
+    assert!(!dmx.is_idle());
+
+    clock.advance(Duration::from_millis(30));
+    dmx.update().unwrap();
+    assert!(dmx.is_idle());
+
+    dmx.set_channel(1, 200).unwrap();
+    dmx.update().unwrap();
+    assert!(!dmx.is_idle());
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1], 100);
+    assert_eq!(frames[1].1[1..], [0; DMX_CHANNELS]);
+    assert_eq!(frames[2].1[1..3], [200, 100]);
+}
+
+#[test]
+fn scheduled_scenes_run_once_when_due() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    let mut scheduler = Scheduler::new(3600);
+    scheduler.add_scene(Trigger::At(TimeOfDay::new(1, 0, 0).unwrap()), [255; DMX_CHANNELS]);
+
+    // 2024-06-21 00:00:00 UTC, one hour before the trigger in local time
+    let midnight = UNIX_EPOCH + Duration::from_secs(1_718_928_000);
+    assert_eq!(scheduler.poll_at(&mut dmx, midnight - Duration::from_secs(3599)), 0);
+    assert_eq!(scheduler.poll_at(&mut dmx, midnight - Duration::from_secs(1)), 0);
+    assert_eq!(scheduler.poll_at(&mut dmx, midnight), 1);
+    assert_eq!(scheduler.poll_at(&mut dmx, midnight + Duration::from_secs(60)), 0);
+    assert_eq!(dmx.get_channels(), [255; DMX_CHANNELS]);
+}
+
+#[test]
+fn persisted_state_is_restored() {
+    let path = std::env::temp_dir().join(format!("open_dmx_state_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut dmx = DMXSerial::builder("mock")
+        .sync()
+        .restore_state(&path)
+        .persist_state(&path, Duration::from_millis(5))
+        .open_with_transport(MockTransport::default())
+        .unwrap();
+    assert_eq!(dmx.get_channels(), [0; DMX_CHANNELS]);
+    dmx.set_channels([42; DMX_CHANNELS]);
+    // The transmitted output is saved, not the set values
+    dmx.park_channel(2, 99).unwrap();
+    dmx.update().unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    drop(dmx);
+    let mut saved = [42; DMX_CHANNELS];
+    saved[1] = 99;
+
+    let mock = MockTransport::default();
+    let mut dmx = DMXSerial::builder("mock")
+        .sync()
+        .restore_state(&path)
+        .open_with_transport(mock.clone())
+        .unwrap();
+    dmx.update().unwrap();
+    assert_eq!(dmx.get_channels(), saved);
+    assert_eq!(mock.frames()[0].1[1..], saved);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn config_changes_are_applied_live() {
+    let path = std::env::temp_dir().join(format!("open_dmx_config_{}.toml", std::process::id()));
+    std::fs::write(&path, "[patch]\nfront = 1\n\n[scenes.full]\nfront = 255\n2 = 128\n").unwrap();
+
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let mut watcher = open_dmx::ConfigWatcher::new(&path).unwrap();
+    watcher.apply(&mut dmx);
+    assert!(matches!(watcher.set_scene(&mut dmx, "empty"), Err(ConfigError::UnknownScene(_))));
+    watcher.set_scene(&mut dmx, "full").unwrap();
+    dmx.update().unwrap();
+
+    // Make sure the modification time differs
+    std::thread::sleep(Duration::from_millis(20));
+    std::fs::write(&path, "[interface]\npacket_time_us = 3000\n\n[[curves]]\ngamma = 2.0\nchannels = [\"2\"]\n").unwrap();
+    assert!(watcher.poll(&mut dmx).unwrap());
+    assert!(!watcher.poll(&mut dmx).unwrap());
+    dmx.update().unwrap();
+
+    std::thread::sleep(Duration::from_millis(20));
+    std::fs::write(&path, "[scenes.broken]\nmissing = 1\n").unwrap();
+    assert!(watcher.poll(&mut dmx).is_err());
+    assert_eq!(watcher.config().interface.packet_time_us, Some(3000));
+    let _ = std::fs::remove_file(&path);
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..4], [255, 128, 0]);
+    assert_eq!(frames[1].1[1..4], [255, 64, 0]);
+    for gamma in ["0.0", "-1.0", "nan", "inf"] {
+        let result = open_dmx::Config::from_toml(&format!("[[curves]]\ngamma = {}\n", gamma));
+        assert!(matches!(result, Err(ConfigError::InvalidGamma(_))), "gamma {} was accepted", gamma);
+    }
+    assert_eq!(dmx.get_packet_time(), Duration::from_millis(3));
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn config_reloads_update_the_scene_and_reset_removed_settings() {
+    let path = std::env::temp_dir().join(format!("open_dmx_config_reset_{}.toml", std::process::id()));
+    std::fs::write(&path, "[interface]\nsync = true\npacket_time_us = 3000\n\n[scenes.full]\n1 = 255\n").unwrap();
+
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    let mut watcher = open_dmx::ConfigWatcher::new(&path).unwrap();
+    watcher.apply(&mut dmx);
+    watcher.set_scene(&mut dmx, "full").unwrap();
+    dmx.set_channel(2, 10).unwrap();
+
+    // An unchanged scene keeps the live changes
+    std::thread::sleep(Duration::from_millis(20));
+    std::fs::write(&path, "[interface]\nsync = true\n\n[scenes.full]\n1 = 255\n").unwrap();
+    assert!(watcher.poll(&mut dmx).unwrap());
+    assert_eq!(dmx.get_packet_time(), open_dmx::codec::DEFAULT_PACKET_TIME);
+    assert_eq!(dmx.get_channel(2).unwrap(), 10);
+
+    std::thread::sleep(Duration::from_millis(20));
+    std::fs::write(&path, "[scenes.full]\n1 = 100\n").unwrap();
+    assert!(watcher.poll(&mut dmx).unwrap());
+    assert!(dmx.is_async());
+    assert_eq!(dmx.get_channel(1).unwrap(), 100);
+    assert_eq!(dmx.get_channel(2).unwrap(), 0);
+
+    // A removed scene stops being active and leaves the channels
+    std::thread::sleep(Duration::from_millis(20));
+    std::fs::write(&path, "").unwrap();
+    assert!(watcher.poll(&mut dmx).unwrap());
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(watcher.scene(), None);
+    assert_eq!(dmx.get_channel(1).unwrap(), 100);
+}
+
+#[test]
+fn truncated_packets_are_interleaved_with_full_frames() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    assert!(dmx.set_truncation(Some(Truncation { last_channel: 0, full_frame_interval: 3 })).is_err());
+    dmx.set_truncation(Some(Truncation { last_channel: 40, full_frame_interval: 3 })).unwrap();
+    for _ in 0..6 {
+        dmx.update().unwrap();
+    }
+    dmx.set_truncation(None).unwrap();
+    dmx.update().unwrap();
+
+    let lengths: Vec<usize> = mock.frames().iter().map(|(_, frame)| frame.len()).collect();
+    assert_eq!(lengths, [41, 41, 513, 41, 41, 513, 513]);
+}
+
+#[test]
+fn writers_are_merged_with_the_policy() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_channel(1, 100).unwrap();
+    let low = dmx.writer(10);
+    let high = dmx.writer(20);
+    low.set_channels([50; DMX_CHANNELS]);
+    high.set_channel(1, 20).unwrap();
+    high.set_channel(2, 10).unwrap();
+    dmx.update().unwrap();
+
+    dmx.set_merge_policy(MergePolicy::Ltp);
+    low.set_channel(2, 70).unwrap();
+    dmx.update().unwrap();
+
+    dmx.set_merge_policy(MergePolicy::Priority);
+    dmx.update().unwrap();
+    high.release_all();
+    drop(low);
+    dmx.update().unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..4], [100, 50, 50]);
+    assert_eq!(frames[1].1[1..4], [100, 70, 50]);
+    assert_eq!(frames[2].1[1..4], [20, 10, 50]);
+    assert_eq!(frames[3].1[1..4], [100, 0, 0]);
+    assert_eq!(dmx.get_channel(1).unwrap(), 100);
+}
+
+#[cfg(unix)]
+#[test]
+fn universes_from_other_processes_are_merged() {
+    let path = std::env::temp_dir().join(format!("open_dmx_socket_{}", std::process::id()));
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    dmx.listen_on_socket(&path, 0).unwrap();
+
+    let sender = open_dmx::UniverseSender::connect(&path).unwrap();
+    assert!(sender.send(&[0; DMX_CHANNELS + 1]).is_err());
+    sender.send(&[10, 20, 30]).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    dmx.update().unwrap();
+    sender.release().unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    dmx.update().unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..5], [10, 20, 30, 0]);
+    assert_eq!(frames[1].1[1..5], [0; 4]);
+
+    drop(dmx);
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(!path.exists());
+}
+
+#[cfg(all(unix, feature = "daemon"))]
+#[test]
+fn daemon_clients_share_the_interface() {
+    let path = std::env::temp_dir().join(format!("open_dmx_daemon_{}", std::process::id()));
+    let (dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let daemon = Arc::new(open_dmx::DMXDaemon::bind(dmx, &path).unwrap());
+    let server = Arc::clone(&daemon);
+    std::thread::spawn(move || server.run());
+
+    let mut first = open_dmx::DaemonClient::connect(&path).unwrap();
+    let mut second = open_dmx::DaemonClient::connect(&path).unwrap();
+    assert!(first.set_channel(513, 1).is_err());
+    first.set_channel(1, 100).unwrap();
+    second.set_channel_range(1, &[50, 60]).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    daemon.interface().update().unwrap();
+
+    drop(first);
+    std::thread::sleep(Duration::from_millis(50));
+    daemon.interface().update().unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..4], [100, 60, 0]);
+    assert_eq!(frames[1].1[1..4], [50, 60, 0]);
+}
+
+#[test]
+fn shutdown_fades_out_and_sends_blackout_frames() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_channels([200; DMX_CHANNELS]);
+    dmx.park_channel(1, 255).unwrap();
+    dmx.shutdown(ShutdownSequence { fade: Duration::from_millis(50), blackout_frames: 3 }).unwrap();
+
+    let frames = mock.frames();
+    let (fade, blackout) = frames.split_at(frames.len() - 3);
+    assert!(fade.iter().any(|(_, frame)| frame[2] > 0 && frame[2] < 200));
+    assert_eq!(fade.last().unwrap().1[1], 255);
+    assert!(blackout.iter().all(|(_, frame)| frame[..] == [0; DMX_CHANNELS + 1]));
+}
+
+// Rejects all data, like an unplugged adapter
+struct FailingTransport;
+
+impl io::Write for FailingTransport {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "adapter unplugged"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for FailingTransport {
+    fn set_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&mut self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn apply_line_settings(&mut self, _settings: LineSettings) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_line_settings(&mut self) -> serialport::Result<LineSettings> {
+        Ok(LineSettings { baud_rate: 230_400, ..LineSettings::DMX })
+    }
+}
+
+#[test]
+fn port_diagnostics_report_settings_and_errors() {
+    let (dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    let diagnostics = dmx.port_diagnostics();
+    assert_eq!(diagnostics.requested, LineSettings::DMX);
+    assert!(diagnostics.applied.is_err());
+    assert!(!diagnostics.is_applied());
+
+    let mut dmx = DMXSerial::builder("failing").sync().open_with_transport(FailingTransport).unwrap();
+    assert_eq!(dmx.port_diagnostics().applied.unwrap().baud_rate, 230_400);
+    assert!(dmx.update().is_err());
+    let diagnostics = dmx.port_diagnostics();
+    assert!(diagnostics.applied.is_err());
+    assert_eq!(diagnostics.errors.len(), 1);
+    assert!(diagnostics.errors[0].contains("adapter unplugged"));
+}
+
+#[test]
+fn self_test_reports_setup_issues() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(10), None);
+    let report = dmx.self_test();
+    assert!(matches!(report.issues.as_slice(), [SelfTestIssue::LineSettingsUnreadable(_)]));
+    assert!(report.shortest_break.unwrap() >= Duration::from_micros(136));
+    assert!(report.longest_packet_time.unwrap() >= Duration::from_millis(10));
+
+    let mut dmx = DMXSerial::builder("failing").sync().open_with_transport(FailingTransport).unwrap();
+    let report = dmx.self_test();
+    assert!(!report.is_ok());
+    assert!(report.issues.contains(&SelfTestIssue::Disconnected));
+    assert!(report.issues.iter().any(|issue| matches!(issue, SelfTestIssue::LineSettingsNotApplied { applied, .. } if applied.baud_rate == 230_400)));
+}
+
+#[test]
+fn calibration_sets_the_shortest_stable_packet_time() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(20), None);
+    let packet_time = dmx.calibrate_packet_time().unwrap();
+    assert!(packet_time >= Duration::from_micros(1204));
+    assert!(packet_time < Duration::from_millis(20));
+    assert_eq!(dmx.get_packet_time(), packet_time);
+
+    let mut dmx = DMXSerial::builder("failing").sync().packet_time(Duration::from_millis(20)).open_with_transport(FailingTransport).unwrap();
+    assert!(dmx.calibrate_packet_time().is_err());
+    assert_eq!(dmx.get_packet_time(), Duration::from_millis(20));
+}
+
+#[test]
+fn health_reports_frames_and_errors() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    let health = dmx.health();
+    assert!(health.is_alive);
+    assert_eq!(health.last_frame_age, None);
+    assert!(!health.is_healthy(Duration::from_secs(1)));
+    dmx.update().unwrap();
+    dmx.update().unwrap();
+    let health = dmx.health();
+    assert_eq!(health.frames_sent, 2);
+    assert!(health.is_healthy(Duration::from_secs(1)));
+
+    let mut dmx = DMXSerial::builder("failing").sync().open_with_transport(FailingTransport).unwrap();
+    assert!(dmx.update().is_err());
+    let health = dmx.health();
+    assert!(!health.is_alive);
+    assert_eq!(health.errors, 1);
+    assert_eq!(health.reconnects, 0);
+}
+
+#[test]
+fn missing_ports_are_not_retried() {
+    let start = Instant::now();
+    let result = DMXSerial::open_with_retry("/dev/open_dmx_missing", RetryPolicy { initial_delay: Duration::from_secs(1), ..RetryPolicy::default() });
+    assert!(matches!(result, Err(DMXOpenError::NotFound(_))));
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn port_holders_include_this_process() {
+    let path = std::env::temp_dir().join(format!("open_dmx_holder_{}", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    let holders = open_dmx::port_holders(path.to_str().unwrap());
+    assert!(holders.iter().any(|holder| holder.pid == std::process::id()));
+    drop(file);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn opened_ports_are_taken_over() {
+    use serialport::SerialPort;
+    use std::io::Read;
+
+    let (primary, mut secondary) = serialport::TTYPort::pair().unwrap();
+    let mut dmx = DMXSerial::builder("pty").sync().open_with_port(Box::new(primary)).unwrap();
+    dmx.set_channel(1, 255).unwrap();
+    dmx.update().unwrap();
+    let mut frame = [0; DMX_CHANNELS + 1];
+    secondary.set_timeout(Duration::from_secs(1)).unwrap();
+    secondary.read_exact(&mut frame).unwrap();
+    assert_eq!(&frame[..3], &[0, 255, 0]);
+}
+
+#[cfg(unix)]
+#[test]
+fn configured_port_builders_are_opened() {
+    use serialport::SerialPort;
+    use std::io::Read;
+
+    let (mut primary, secondary) = serialport::TTYPort::pair().unwrap();
+    let path = secondary.name().unwrap();
+    let mut dmx = DMXSerial::builder("ignored").sync().open_with(serialport::new(&path, 9600).exclusive(false)).unwrap();
+    assert_eq!(dmx.name(), path);
+    dmx.set_channel(2, 128).unwrap();
+    dmx.update().unwrap();
+    let mut frame = [0; DMX_CHANNELS + 1];
+    primary.set_timeout(Duration::from_secs(1)).unwrap();
+    primary.read_exact(&mut frame).unwrap();
+    assert_eq!(&frame[..3], &[0, 0, 128]);
+}
+
+#[cfg(unix)]
+#[test]
+fn non_exclusive_ports_can_be_shared() {
+    use serialport::SerialPort;
+
+    let (_primary, secondary) = serialport::TTYPort::pair().unwrap();
+    let path = secondary.name().unwrap();
+    drop(secondary);
+    let _dmx = DMXSerial::builder(&path).exclusive(false).open().unwrap();
+    assert!(serialport::new(&path, 9600).exclusive(false).open().is_ok());
+    // Shared holders keep out exclusive openers
+    assert!(DMXSerial::builder(&path).open().is_err());
+}
+
+#[test]
+fn break_is_held_for_the_break_time() {
+    let mut dmx = DMXSerial::builder("mock")
+        .sync()
+        .packet_time(Duration::from_millis(2))
+        .break_time(Duration::from_micros(400))
+        .open_with_transport(MockTransport::default())
+        .unwrap();
+    let report = dmx.self_test();
+    let shortest = report.shortest_break.unwrap();
+    assert!(shortest >= Duration::from_micros(400));
+    assert!(shortest < Duration::from_millis(5));
+}
+
+#[test]
+fn packets_are_sent_without_drift() {
+    let dmx = DMXSerial::builder("mock")
+        .packet_time(Duration::from_millis(10))
+        .open_with_transport(MockTransport::default())
+        .unwrap();
+    std::thread::sleep(Duration::from_secs(1));
+    let frames = dmx.health().frames_sent;
+    assert!((95..=101).contains(&frames), "{} frames", frames);
+}
+
+#[test]
+fn bursts_send_the_given_number_of_frames() {
+    let mock = MockTransport::default();
+    let mut dmx = DMXSerial::builder("mock")
+        .packet_time(Duration::from_millis(2))
+        .open_with_transport(mock.clone())
+        .unwrap();
+    dmx.set_channel(1, 200).unwrap();
+    std::thread::sleep(Duration::from_millis(20));
+    let before = mock.frames().len();
+    dmx.send_burst(5).unwrap();
+    assert!(dmx.is_sync());
+    // The packet in progress while switching to sync mode can still be sent
+    std::thread::sleep(Duration::from_millis(20));
+    let frames = mock.frames();
+    assert!((before + 5..=before + 6).contains(&frames.len()));
+    assert!(frames[frames.len() - 5..].iter().all(|(_, frame)| frame[1] == 200));
+}
+
+#[cfg(unix)]
+#[test]
+fn values_can_be_sent_once() {
+    use serialport::SerialPort;
+    use std::io::Read;
+
+    let (mut primary, secondary) = serialport::TTYPort::pair().unwrap();
+    let path = secondary.name().unwrap();
+    drop(secondary);
+    open_dmx::send_once(&path, &[255, 0, 128]).unwrap();
+    let mut frames = [0; (DMX_CHANNELS + 1) * 5];
+    primary.set_timeout(Duration::from_secs(1)).unwrap();
+    primary.read_exact(&mut frames).unwrap();
+    assert!(frames.chunks(DMX_CHANNELS + 1).all(|frame| frame[..4] == [0, 255, 0, 128]));
+    assert!(open_dmx::send_once(&path, &[0; DMX_CHANNELS + 1]).is_err());
+}
+
+#[test]
+fn cloned_handles_share_the_interface() {
+    let (dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let mut clone = dmx.try_clone().unwrap();
+    clone.set_channel(1, 42).unwrap();
+    assert_eq!(dmx.get_channel(1).unwrap(), 42);
+    clone.set_packet_time(Duration::from_millis(3));
+    assert_eq!(dmx.get_packet_time(), Duration::from_millis(3));
+
+    // Coalesced writes are shared as well
+    let mut dmx = dmx;
+    dmx.set_coalescing(true);
+    assert!(clone.is_coalescing());
+    clone.set_channel(2, 7).unwrap();
+    assert_eq!(dmx.get_channel(2).unwrap(), 7);
+    dmx.set_channel(3, 9).unwrap();
+    assert_eq!(clone.get_channels()[..3], [42, 7, 9]);
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1..4], [42, 7, 9]);
+    clone.set_coalescing(false);
+    assert!(!dmx.is_coalescing());
+
+    // The agent keeps running for the remaining handle
+    drop(dmx);
+    clone.update().unwrap();
+    assert!(clone.check_agent().is_ok());
+    assert_eq!(mock.frames().last().unwrap().1[1], 42);
+}
+
+#[test]
+fn updates_are_confirmed_by_their_own_packet() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(1), None);
+    // One handle can't take the confirmation of another one
+    let threads: Vec<_> = (0..4).map(|_| {
+        let mut handle = dmx.try_clone().unwrap();
+        std::thread::spawn(move || (0..25).for_each(|_| handle.update().unwrap()))
+    }).collect();
+    threads.into_iter().for_each(|thread| thread.join().unwrap());
+    assert_eq!(mock.frames().len(), 100);
+
+    // Keep-alive packets in between don't confirm an update
+    dmx.set_max_frame_interval(Some(Duration::from_millis(1)));
+    for value in 1..=20 {
+        dmx.set_channel(1, value).unwrap();
+        dmx.update().unwrap();
+        // A keep-alive packet may already have been started
+        let frames = mock.frames();
+        let (_, frame) = frames.iter().rev().find(|(_, frame)| !frame.is_empty()).unwrap();
+        assert_eq!(frame[1], value);
+    }
+}
+
+#[test]
+fn backup_takes_over_when_the_primary_is_lost() {
+    // Interfaces which can't be reopened would leave the failover stuck
+    let (fixed, _) = open_sync(Duration::from_millis(2), None);
+    let (other, _) = open_sync(Duration::from_millis(2), None);
+    assert_eq!(DMXFailover::new(fixed, other).unwrap_err().kind(), serialport::ErrorKind::InvalidInput);
+
+    let (primary_mock, backup_mock) = (MockTransport::default(), MockTransport::default());
+    let primary = DMXSerial::builder("mock-primary")
+        .packet_time(Duration::from_millis(2))
+        .open_with_transport_factory(primary_mock.factory())
+        .unwrap();
+    let backup = DMXSerial::builder("mock-backup")
+        .sync()
+        .packet_time(Duration::from_millis(2))
+        .open_with_transport_factory(backup_mock.factory())
+        .unwrap();
+    let mut dmx = DMXFailover::new(primary, backup).unwrap();
+    dmx.set_retry_interval(Duration::ZERO);
+    let events = dmx.events();
+    dmx.set_channel(1, 200).unwrap();
+    assert_eq!(dmx.poll(), FailoverOutput::Primary);
+
+    // The idle backup doesn't send anything
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(backup_mock.frames().is_empty());
+    assert!(primary_mock.frames().len() > 1);
+
+    // But a lost backup is noticed before it's needed, reported once and reopened once it's back
+    backup_mock.unplug();
+    dmx.poll();
+    dmx.poll();
+    assert_eq!(events.try_iter().collect::<Vec<_>>(), [FailoverEvent::BackupDisconnected]);
+    backup_mock.replug();
+    assert_eq!(dmx.poll(), FailoverOutput::Primary);
+    assert_eq!(events.try_recv(), Ok(FailoverEvent::BackupReconnected));
+    assert!(!dmx.backup().is_async());
+    assert!(backup_mock.frames().is_empty());
+
+    primary_mock.unplug();
+    let start = Instant::now();
+    while dmx.primary().check_agent().is_ok() {
+        assert!(start.elapsed() < Duration::from_secs(1));
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(dmx.poll(), FailoverOutput::Backup);
+    assert_eq!(events.try_recv(), Ok(FailoverEvent::SwitchedToBackup));
+    assert!(dmx.backup().is_async());
+    while backup_mock.frames().len() < 3 {
+        assert!(start.elapsed() < Duration::from_secs(1));
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(backup_mock.frames()[1].1[1], 200);
+
+    // The primary can't be reopened while it's unplugged, so the backup keeps sending
+    assert_eq!(dmx.poll(), FailoverOutput::Backup);
+    assert!(events.try_recv().is_err());
+
+    primary_mock.replug();
+    assert_eq!(dmx.poll(), FailoverOutput::Primary);
+    assert_eq!(events.try_recv(), Ok(FailoverEvent::SwitchedToPrimary));
+    assert!(!dmx.backup().is_async());
+    assert_eq!(dmx.primary().get_channel(1).unwrap(), 200);
+}
+
+#[test]
+fn weak_handles_do_not_keep_the_interface_alive() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_channel(3, 99).unwrap();
+    let weak = dmx.downgrade();
+    assert!(weak.is_alive());
+    assert_eq!(weak.get_channel(3).unwrap(), Some(99));
+    assert!(weak.get_channel(0).is_err());
+
+    drop(dmx);
+    let start = Instant::now();
+    while weak.is_alive() || weak.get_channels().is_some() {
+        assert!(start.elapsed() < Duration::from_secs(1));
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(weak.get_channel(3).unwrap(), None);
+}
+
+#[test]
+fn channel_changes_are_streamed() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_channel(1, 10).unwrap();
+    let changes = dmx.subscribe_changes();
+    dmx.set_channel(1, 10).unwrap();
+    dmx.set_channel_tagged(1, 20, "ui").unwrap();
+    let mut channels = dmx.get_channels();
+    channels[4] = 5;
+    dmx.try_clone().unwrap().set_channels(channels);
+
+    let changes: Vec<ChannelChange> = changes.try_iter().collect();
+    assert_eq!(changes, [
+        ChannelChange { sequence: 0, channel: 1, old: 10, new: 20, source: Some("ui") },
+        ChannelChange { sequence: 1, channel: 5, old: 0, new: 5, source: None },
+    ]);
+}
+
+#[test]
+fn keep_alive_packets_are_sent_in_sync_mode() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    let keep_alives = dmx.subscribe_keep_alives();
+    dmx.set_channel(1, 77).unwrap();
+    dmx.set_max_frame_interval(Some(Duration::from_millis(20)));
+    dmx.update().unwrap();
+    let before = mock.frames().len();
+    // The deadline follows the clock, not the real time
+    assert!(keep_alives.recv_timeout(Duration::from_millis(50)).is_err());
+    clock.advance(Duration::from_millis(25));
+    assert_eq!(keep_alives.recv_timeout(Duration::from_secs(1)).unwrap(), clock.now());
+    assert!(keep_alives.recv_timeout(Duration::from_millis(50)).is_err());
+    let frames = mock.frames();
+    assert_eq!(frames.len(), before + 1);
+    assert_eq!(frames[before].1[1], 77);
+
+    // Without a max frame interval, nothing is sent between the updates
+    dmx.set_max_frame_interval(None);
+    dmx.update().unwrap();
+    let before = mock.frames().len();
+    clock.advance(Duration::from_secs(1));
+    assert!(keep_alives.recv_timeout(Duration::from_millis(50)).is_err());
+    assert_eq!(mock.frames().len(), before);
+}
+
+#[test]
+fn output_is_ramped_on_and_off() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    dmx.set_channels([200; DMX_CHANNELS]);
+    dmx.park_channel(2, 50).unwrap();
+    dmx.disable_output(Duration::ZERO);
+    assert!(!dmx.is_output_enabled());
+    dmx.update().unwrap();
+    assert_eq!(&mock.frames().last().unwrap().1[1..3], [0, 50]);
+
+    dmx.enable_output(Duration::from_millis(100));
+    assert!(dmx.is_output_enabled());
+    clock.advance(Duration::from_millis(50));
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1], 100);
+
+    clock.advance(Duration::from_millis(50));
+    assert_eq!(dmx.output_level(), 1.0);
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1], 200);
+}
+
+#[cfg(unix)]
+#[test]
+fn port_paths_are_validated_and_kept() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let invalid = OsStr::from_bytes(b"/dev/ttyUSB\xff");
+    assert_eq!(DMXSerial::open(invalid).unwrap_err().kind(), serialport::ErrorKind::InvalidInput);
+    assert_eq!(DMXSerial::open(" ").unwrap_err().kind(), serialport::ErrorKind::InvalidInput);
+
+    let dmx = DMXSerial::builder(invalid).open_with_transport(MockTransport::default()).unwrap();
+    assert_eq!(dmx.path().as_os_str(), invalid);
+    assert_eq!(dmx.name(), "/dev/ttyUSB\u{FFFD}");
+}
+
+#[test]
+fn render_callback_runs_once_per_frame() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    dmx.set_render_callback(200, |frame, channels| {
+        assert_eq!(frame.packet_time, Duration::from_millis(5));
+        assert!(frame.sequence == 0 || !frame.delta.is_zero());
+        channels[0] = frame.sequence as u8;
+    });
+    assert_eq!(dmx.get_packet_time(), Duration::from_millis(5));
+    for _ in 0..3 {
+        dmx.update().unwrap();
+    }
+    let rendered: Vec<u8> = mock.frames().iter().map(|(_, frame)| frame[1]).collect();
+    assert_eq!(rendered, [0, 1, 2]);
+
+    dmx.clear_render_callback();
+    dmx.set_channel(1, 99).unwrap();
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1], 99);
+}
+
+#[test]
+fn keyframes_are_interpolated() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    dmx.set_channel(1, 7).unwrap();
+    let start = clock.now() + Duration::from_millis(20);
+    dmx.submit_keyframe([200; DMX_CHANNELS], start + Duration::from_millis(100));
+    dmx.submit_keyframe([0; DMX_CHANNELS], start);
+    assert_eq!(dmx.pending_keyframes(), 2);
+
+    // The set values are sent until the first keyframe is due
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1], 7);
+
+    clock.advance(Duration::from_millis(70));
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1], 100);
+
+    clock.advance(Duration::from_millis(50));
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1], 200);
+    assert_eq!(dmx.pending_keyframes(), 1);
+
+    dmx.clear_keyframes();
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1], 7);
+}
+
+#[test]
+fn timecode_cues_run_when_passed() {
+    let full_frame = |seconds: u8, frames: u8| [0xF0, 0x7F, 0x7F, 0x01, 0x01, 0x20 | 1, 0, seconds, frames, 0xF7];
+    let decoder = MtcDecoder::new();
+    let mut cues = TimecodeCueList::new(Box::new(decoder.clone()));
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    cues.add(Timecode::new(1, 0, 2, 0, FrameRate::Fps25).unwrap(), |dmx| dmx.set_channel(1, 1).unwrap());
+    cues.add(Timecode::new(1, 0, 1, 0, FrameRate::Fps25).unwrap(), |dmx| dmx.set_channel(1, 2).unwrap());
+
+    decoder.push(&full_frame(0, 0));
+    assert_eq!(cues.poll(&mut dmx), 0);
+    assert_eq!(cues.timecode(), Timecode::new(1, 0, 0, 0, FrameRate::Fps25));
+
+    // Quarter frames of 01:00:01:23, which is received two frames later
+    let pieces = [23 & 0xF, 23 >> 4, 1, 0, 0, 0, 1, 1 << 1];
+    decoder.push(&pieces.iter().enumerate().flat_map(|(piece, value)| [0xF1, (piece as u8) << 4 | value]).collect::<Vec<u8>>());
+    assert_eq!(decoder.timecode(), Timecode::new(1, 0, 2, 0, FrameRate::Fps25));
+    assert_eq!(cues.poll(&mut dmx), 2);
+    assert_eq!(dmx.get_channel(1).unwrap(), 1);
+
+    // Rewinding doesn't run cues
+    decoder.push(&full_frame(0, 0));
+    assert_eq!(cues.poll(&mut dmx), 0);
+}
+
+#[test]
+fn highlighted_fixtures_are_restored() {
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(2), None);
+    let fixture = Fixture::new("Spot", 10, vec![Attribute::Pan, Attribute::Tilt, Attribute::Dimmer, Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+    assert_eq!(fixture.channel(Attribute::Dimmer), Some(12));
+    assert!(Fixture::new("Too long", 510, vec![Attribute::Dimmer; 4]).is_err());
+
+    dmx.set_channels([10; DMX_CHANNELS]);
+    let highlight = fixture.highlight(&mut dmx);
+    assert_eq!(dmx.get_channels()[9..17], [10, 10, 255, 255, 255, 255, 10, 10]);
+    fixture.set(&mut dmx, Attribute::Pan, 128).unwrap();
+    highlight.restore(&mut dmx);
+    assert_eq!(dmx.get_channels()[9..17], [128, 10, 10, 10, 10, 10, 10, 10]);
+}
+
+#[test]
+fn recordings_can_be_seeked_and_played() {
+    let frames: Vec<RecordedFrame> = (0..200u32).map(|i| {
+        let mut channels = [0; DMX_CHANNELS];
+        channels[0] = i as u8;
+        channels[1] = (i / 40) as u8;
+        RecordedFrame { time: Duration::from_millis(i as u64 * 25), channels }
+    }).collect();
+    let record = |mut writer: RecordingWriter<Vec<u8>>| {
+        frames.iter().for_each(|frame| writer.write_frame(frame).unwrap());
+        writer.finish().unwrap()
+    };
+    let check_seeking = |file: Vec<u8>| {
+        let mut reader = RecordingReader::new(Cursor::new(file)).unwrap();
+        assert_eq!(reader.seek(Duration::from_millis(2510)).unwrap(), Some(frames[100]));
+        assert_eq!(reader.read_frame().unwrap(), Some(frames[101]));
+        assert_eq!(reader.seek(Duration::from_millis(990)).unwrap(), Some(frames[39]));
+        assert_eq!(reader.seek(Duration::from_secs(60)).unwrap(), Some(frames[199]));
+        assert_eq!(reader.read_frame().unwrap(), None);
+        assert_eq!(reader.seek(Duration::ZERO).unwrap(), Some(frames[0]));
+        assert_eq!(reader.count(), 199);
+    };
+
+    let file = record(RecordingWriter::new(Vec::new(), &RecordingInfo::default()).unwrap());
+    check_seeking(file.clone());
+    // Without the index with five keyframes and the trailer, the recording is read from the start
+    check_seeking(file[..file.len() - 92 - 16].to_vec());
+    #[cfg(feature = "zstd")]
+    check_seeking(record(RecordingWriter::compressed(Vec::new(), &RecordingInfo::default(), 0).unwrap()));
+
+    let (mut dmx, _mock) = open_sync(Duration::from_millis(5), None);
+    let clock = ManualClock::new();
+    let mut player = Player::with_clock(RecordingReader::new(Cursor::new(file)).unwrap(), clock.clone());
+    player.seek(Duration::from_millis(2510)).unwrap();
+    assert!(player.poll(&mut dmx).unwrap());
+    assert_eq!(dmx.get_channels(), frames[100].channels);
+    assert!(!player.is_playing());
+
+    player.play();
+    clock.advance(Duration::from_millis(60));
+    assert!(player.poll(&mut dmx).unwrap());
+    assert_eq!(dmx.get_channels(), frames[102].channels);
+    player.seek(Duration::from_secs(60)).unwrap();
+    assert!(!player.poll(&mut dmx).unwrap());
+    assert_eq!(dmx.get_channels(), frames[199].channels);
+}
+
+#[cfg(feature = "kinet")]
+#[test]
+fn kinet_output_sends_dmxout_packets() {
+    let psu = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    psu.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+    let output = open_dmx::KinetOutput::new(&psu.local_addr().unwrap().to_string()).unwrap();
+    let mut dmx = DMXSerial::builder("kinet").sync().open_with_transport(output).unwrap();
+    let mut channels = [0; DMX_CHANNELS];
+    channels[0] = 255;
+    channels[511] = 7;
+    dmx.set_channels(channels);
+    dmx.update().unwrap();
+
+    let mut packet = [0; 1024];
+    let len = psu.recv(&mut packet).unwrap();
+    assert_eq!(len, 20 + 1 + DMX_CHANNELS);
+    assert_eq!(packet[..8], [0x04, 0x01, 0xDC, 0x4A, 0x01, 0x00, 0x01, 0x01]);
+    assert_eq!(packet[16..20], [0xFF; 4]);
+    assert_eq!(packet[20], 0);
+    assert_eq!(packet[21..len], channels);
+}
+
+#[cfg(all(unix, feature = "eurolite"))]
+#[test]
+fn eurolite_output_sends_full_widget_messages() {
+    use io::{Read, Write};
+    use serialport::SerialPort;
+
+    let (primary, mut secondary) = serialport::TTYPort::pair().unwrap();
+    secondary.set_timeout(Duration::from_secs(1)).unwrap();
+    let mut output = open_dmx::EuroliteProOutput::from_port(Box::new(primary));
+    // Short frames are filled up
+    output.write_all(&[0, 255, 128]).unwrap();
+
+    let mut message = [0; 5 + 1 + DMX_CHANNELS];
+    secondary.read_exact(&mut message).unwrap();
+    assert_eq!(message[..4], [0x7E, 6, 0x01, 0x02]);
+    assert_eq!(message[4..7], [0, 255, 128]);
+    assert!(message[7..message.len() - 1].iter().all(|&value| value == 0));
+    assert_eq!(message[message.len() - 1], 0xE7);
+
+    let mut dmx = DMXSerial::builder("eurolite").sync().open_with_transport(output).unwrap();
+    dmx.set_channel(512, 9).unwrap();
+    dmx.update().unwrap();
+    secondary.read_exact(&mut message).unwrap();
+    assert_eq!(message[message.len() - 2..], [9, 0xE7]);
+}
+
+#[cfg(all(unix, feature = "dmxking"))]
+#[test]
+fn dmxking_ports_send_their_own_universes() {
+    use io::Read;
+    use serialport::SerialPort;
+
+    let (primary, mut secondary) = serialport::TTYPort::pair().unwrap();
+    secondary.set_timeout(Duration::from_secs(1)).unwrap();
+    let widget = open_dmx::DmxKingWidget::from_port(Box::new(primary));
+    assert!(widget.output(open_dmx::DmxKingWidget::MAX_PORTS).is_err());
+    let output = widget.output(1).unwrap();
+    assert_eq!(output.port(), Some(1));
+    assert_eq!(widget.all_outputs().port(), None);
+
+    let mut port_b = DMXSerial::builder("ultraDMX B").sync().open_with_transport(output).unwrap();
+    port_b.set_channel(1, 42).unwrap();
+    port_b.update().unwrap();
+    let mut message = [0; 5 + 1 + DMX_CHANNELS];
+    secondary.read_exact(&mut message).unwrap();
+    assert_eq!(message[..6], [0x7E, 101, 0x01, 0x02, 0, 42]);
+    assert_eq!(message[message.len() - 1], 0xE7);
+}
+
+#[test]
+fn remote_ports_are_driven_over_rfc2217() {
+    use io::Read;
+
+    let server = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = format!("rfc2217://{}", server.local_addr().unwrap());
+    let mut dmx = DMXSerial::builder(&port).sync().open().unwrap();
+    assert_eq!(dmx.name(), port);
+    dmx.set_channel(1, 255).unwrap();
+    dmx.update().unwrap();
+    drop(dmx);
+
+    let (mut client, _) = server.accept().unwrap();
+    let mut received = Vec::new();
+    client.read_to_end(&mut received).unwrap();
+    let contains = |bytes: &[u8]| received.windows(bytes.len()).any(|window| window == bytes);
+    // Com port option, 250000 baud and the break
+    assert!(contains(&[255, 251, 44]));
+    assert!(contains(&[255, 250, 44, 1, 0x00, 0x03, 0xD0, 0x90, 255, 240]));
+    assert!(contains(&[255, 250, 44, 5, 5, 255, 240]));
+    assert!(contains(&[255, 250, 44, 5, 6, 255, 240]));
+    // The channel value equal to IAC is doubled
+    assert!(contains(&[255, 240, 0, 255, 255, 0, 0]));
+}
+
+#[test]
+fn framed_transports_receive_whole_frames() {
+    for mode in [BreakMode::Signal, BreakMode::Baud(LineSettings::BREAK)] {
+        let mock = MockTransport { framed: true, ..MockTransport::default() };
+        let mut dmx = DMXSerial::builder("framed")
+            .sync()
+            .break_mode(mode)
+            .open_with_transport(mock.clone())
+            .unwrap();
+        mock.events.lock().unwrap().clear();
+        for value in 1..=3 {
+            dmx.set_channel(1, value).unwrap();
+            dmx.update().unwrap();
+        }
+        dmx.send_raw_frame(&[0xCC, 1, 2]).unwrap();
+
+        // Neither a break nor the break settings, every frame is written at once
+        let events: Vec<_> = mock.events.lock().unwrap().iter().map(|(_, event)| event.clone()).collect();
+        assert_eq!(events.len(), 4);
+        for (event, value) in events[..3].iter().zip(1..) {
+            assert!(matches!(event, Event::Data(data) if data.len() == DMX_CHANNELS + 1 && data[..2] == [0, value]));
+        }
+        assert_eq!(events[3], Event::Data(vec![0xCC, 1, 2]));
+    }
+}
+
+#[test]
+fn simulated_fixtures_interpret_the_frames() {
+    let rack = Rack::new();
+    let dimmer = rack.add("Dimmer", FixtureModel::Dimmer, 1).unwrap();
+    let par = rack.add("Par", FixtureModel::RgbPar, 2).unwrap();
+    let mover = rack.add("Mover", FixtureModel::Mover, 10).unwrap();
+    assert!(rack.add("Too far", FixtureModel::Mover, 510).is_err());
+    let mut dmx = DMXSerial::builder("rack").sync().open_with_transport(rack.output()).unwrap();
+
+    dimmer.set(&mut dmx, Attribute::Dimmer, 255).unwrap();
+    for (attribute, value) in [(Attribute::Dimmer, 255), (Attribute::Green, 200)] {
+        par.set(&mut dmx, attribute, value).unwrap();
+    }
+    for (attribute, value) in [(Attribute::Pan, 0x80), (Attribute::Tilt, 0xFF), (Attribute::TiltFine, 0xFF), (Attribute::Dimmer, 51)] {
+        mover.set(&mut dmx, attribute, value).unwrap();
+    }
+    dmx.update().unwrap();
+    assert_eq!(rack.state("Dimmer").unwrap(), FixtureState { intensity: 1.0, color: [255; 3], pan: 0.0, tilt: 0.0 });
+    assert_eq!(rack.state("Par").unwrap().color, [0, 200, 0]);
+    let moved = rack.state("Mover").unwrap();
+    assert!((moved.pan - 270.0).abs() < 0.1);
+    assert_eq!((moved.tilt, moved.intensity), (270.0, 0.2));
+
+    // Transforms are part of the light
+    let master = MasterDimmer::all_channels(127);
+    dmx.add_transform(Box::new(master));
+    dmx.update().unwrap();
+    assert!((rack.state("Par").unwrap().intensity - 0.5).abs() < 0.01);
+    assert_eq!(rack.frames(), 2);
+    assert!(rack.state("Missing").is_none());
+}
+
+#[test]
+fn manual_clocks_step_the_timing_logic() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    dmx.set_channel(1, 200).unwrap();
+    dmx.disable_output(Duration::from_secs(2));
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(dmx.output_level(), 0.5);
+    dmx.update().unwrap();
+    clock.advance(Duration::from_secs(5));
+    dmx.update().unwrap();
+    let frames = mock.frames();
+    assert_eq!(frames[frames.len() - 2].1[1], 100);
+    assert_eq!(frames[frames.len() - 1].1[1], 0);
+
+    let mut driver = DMXDriver::with_clock(clock.clone());
+    driver.set_packet_time(Duration::from_millis(5));
+    assert_eq!(driver.next_action(), Action::SendBreak);
+    assert!(matches!(driver.next_action(), Action::SendData(_)));
+    assert_eq!(driver.next_action(), Action::Sleep(Duration::from_millis(5)));
+    clock.advance(Duration::from_millis(3));
+    assert_eq!(driver.next_action(), Action::Sleep(Duration::from_millis(2)));
+    clock.advance(Duration::from_millis(2));
+    assert_eq!(driver.next_action(), Action::SendBreak);
+}
+
+#[test]
+fn offline_rendering_steps_the_engine_frame_by_frame() {
+    let mut renderer = OfflineRenderer::new().unwrap();
+    let start = renderer.start();
+    renderer.dmx().submit_keyframe([0; DMX_CHANNELS], start);
+    renderer.dmx().submit_keyframe([200; DMX_CHANNELS], start + Duration::from_secs(10));
+    renderer.dmx().set_render_callback(25, |context, channels| channels[511] = context.sequence as u8);
+
+    let began = Instant::now();
+    let frames = renderer.render(Duration::from_secs(10), Duration::from_millis(40)).unwrap();
+    // Rendered much faster than real time
+    assert!(began.elapsed() < Duration::from_secs(5));
+    assert_eq!(frames.len(), 250);
+    assert_eq!(frames[125].channels()[0], 100);
+    assert_eq!(frames[249].channels()[511], 249);
+    assert_eq!(renderer.time(), Duration::from_millis(9960));
+
+    // Earlier times can't be rendered again
+    let frame = renderer.render_frame(Duration::from_secs(1)).unwrap();
+    assert_eq!(frame.channels()[0], 199);
+}
+
+#[test]
+fn pre_rendered_universes_play_in_sync() {
+    let frame = |value: u8| Universe::new([value; DMX_CHANNELS]);
+    let (dmx_a, _) = open_sync(Duration::from_millis(2), None);
+    let (dmx_b, _) = open_sync(Duration::from_millis(2), None);
+    let clock = ManualClock::new();
+    let mut player = MultiPlayer::with_clock(clock.clone());
+    player.add_universe(1, Duration::from_millis(100), vec![frame(1), frame(2), frame(3)]);
+    player.add_universe(2, Duration::from_millis(100), vec![frame(9)]);
+    player.add_universe(3, Duration::from_millis(100), vec![frame(7)]);
+    assert!(player.route(1, dmx_a.try_clone().unwrap()).is_none());
+    player.route(2, dmx_b.try_clone().unwrap());
+    assert_eq!(player.duration(), Duration::from_millis(300));
+
+    assert!(player.poll().unwrap());
+    assert_eq!((dmx_a.get_channel(1).unwrap(), dmx_b.get_channel(1).unwrap()), (1, 9));
+    player.play();
+    clock.advance(Duration::from_millis(150));
+    player.poll().unwrap();
+    assert_eq!(dmx_a.get_channel(1).unwrap(), 2);
+
+    player.seek(Duration::from_millis(250));
+    player.poll().unwrap();
+    assert_eq!((dmx_a.get_channel(1).unwrap(), dmx_b.get_channel(1).unwrap()), (3, 9));
+    clock.advance(Duration::from_millis(100));
+    assert!(!player.poll().unwrap());
+
+    player.set_looping(true);
+    assert_eq!(player.position(), Duration::from_millis(50));
+    assert!(player.poll().unwrap());
+    assert_eq!(dmx_a.get_channel(1).unwrap(), 1);
+}
+
+#[test]
+fn crossfades_fade_and_snap_channels() {
+    assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    assert!(Easing::EaseIn.apply(0.5) < 0.5 && Easing::EaseOut.apply(0.5) > 0.5);
+
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    let spot = Fixture::new("Spot", 1, vec![Attribute::Dimmer, Attribute::Other]).unwrap();
+    let mut target = [0; DMX_CHANNELS];
+    target[..3].copy_from_slice(&[200, 100, 50]);
+    dmx.crossfade_with(target, &Crossfade::new(Duration::from_secs(2), Easing::Linear).snap_fixture(&spot));
+    assert_eq!(dmx.get_channels(), target);
+    assert!(dmx.is_crossfading());
+
+    clock.advance(Duration::from_secs(1));
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1..4], [100, 100, 25]);
+
+    // A new crossfade starts from the current level
+    dmx.crossfade_to([0; DMX_CHANNELS], Duration::from_secs(1), Easing::Linear);
+    clock.advance(Duration::from_millis(500));
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1..4], [50, 50, 13]);
+    clock.advance(Duration::from_millis(500));
+    assert!(!dmx.is_crossfading());
+    dmx.update().unwrap();
+    assert_eq!(mock.frames().last().unwrap().1[1..4], [0, 0, 0]);
+}
+
+#[test]
+fn crossfades_split_up_and_down_times() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    dmx.set_channel(2, 200).unwrap();
+    let mut target = [0; DMX_CHANNELS];
+    target[0] = 200;
+    // Up in 1 s, down in 4 s after a 2 s delay
+    let crossfade = Crossfade::split(Duration::from_secs(1), Duration::from_secs(4), Easing::Linear)
+        .delays(Duration::ZERO, Duration::from_secs(2));
+    assert_eq!(crossfade.total_time(), Duration::from_secs(6));
+    dmx.crossfade_with(target, &crossfade);
+
+    let mut output = |after: u64| {
+        clock.advance(Duration::from_secs(after));
+        dmx.update().unwrap();
+        let frame = mock.frames().last().unwrap().1.clone();
+        (frame[1], frame[2])
+    };
+    assert_eq!(output(0), (0, 200));
+    assert_eq!(output(1), (200, 200));
+    assert_eq!(output(3), (200, 100));
+    assert_eq!(output(2), (200, 0));
+}
+
+#[test]
+fn submasters_and_solo_dim_groups() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let front = Fixture::new("Front", 1, vec![Attribute::Dimmer, Attribute::Pan]).unwrap();
+    let wash = Fixture::new("Wash", 3, vec![Attribute::Red, Attribute::Green, Attribute::Blue]).unwrap();
+    let back = Fixture::new("Back", 6, vec![Attribute::Dimmer]).unwrap();
+    assert_eq!(front.intensity_channels(), [1]);
+    assert_eq!(wash.intensity_channels(), [3, 4, 5]);
+
+    let submaster = Submaster::new(&[front.clone(), wash.clone()], 255);
+    let solo = Solo::new(&[front.clone(), wash.clone(), back.clone()]);
+    dmx.add_transform(Box::new(submaster.clone()));
+    dmx.add_transform(Box::new(solo.clone()));
+    dmx.set_channels([200; DMX_CHANNELS]);
+
+    submaster.set_level(127);
+    dmx.update().unwrap();
+    solo.set_remainder(127);
+    solo.select(&[back]);
+    assert!(solo.is_active());
+    dmx.update().unwrap();
+    solo.release();
+    submaster.set_level(255);
+    dmx.update().unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..8], [99, 200, 99, 99, 99, 200, 200]);
+    assert_eq!(frames[1].1[1..8], [49, 200, 49, 49, 49, 200, 200]);
+    assert_eq!(frames[2].1[1..8], [200; 7]);
+}
+
+#[test]
+fn protected_channels_need_to_be_armed() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let interlock = SafetyInterlock::new();
+    interlock.protect(3).unwrap();
+    interlock.protect(4).unwrap();
+    dmx.set_interlock(interlock.clone());
+    dmx.set_channels([200; DMX_CHANNELS]);
+    dmx.park_channel(4, 255).unwrap();
+
+    dmx.update().unwrap();
+    dmx.update().unwrap();
+    interlock.arm(3).unwrap();
+    assert!(interlock.is_armed(3).unwrap());
+    dmx.update().unwrap();
+    interlock.disarm_all();
+    dmx.update().unwrap();
+    interlock.unprotect(3).unwrap();
+    dmx.update().unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1[1..6], [200, 200, 0, 0, 200]);
+    assert_eq!(frames[2].1[1..6], [200, 200, 200, 0, 200]);
+    assert_eq!(frames[3].1[1..6], [200, 200, 0, 0, 200]);
+    assert_eq!(frames[4].1[1..6], [200, 200, 200, 0, 200]);
+    // Every attempt is logged once, not every frame
+    let blocked: Vec<(usize, u8)> = interlock.blocked().iter().map(|blocked| (blocked.channel, blocked.value)).collect();
+    assert_eq!(blocked, [(3, 200), (4, 255), (3, 200)]);
+    assert!(dmx.interlock().unwrap().is_protected(4).unwrap());
+    assert!(interlock.arm(513).is_err());
+}
+
+#[test]
+fn raw_frames_cannot_bypass_the_interlock() {
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), None);
+    let interlock = SafetyInterlock::new();
+    interlock.protect(2).unwrap();
+    dmx.set_interlock(interlock.clone());
+
+    dmx.send_raw_frame(&[0, 255, 255, 255]).unwrap();
+    let mut frame = [100; DMX_CHANNELS + 1];
+    frame[0] = 0;
+    DmxFrameWriter::new(&dmx).write_all(&frame).unwrap();
+    dmx.add_periodic_frame(&[0, 50, 50], 1).unwrap();
+    dmx.update().unwrap();
+    // Other start codes aren't DMX data
+    dmx.send_raw_frame(&[0x17, 255, 255]).unwrap();
+
+    let frames = mock.frames();
+    assert_eq!(frames[0].1, [0, 255, 0, 255]);
+    assert_eq!(frames[1].1[..4], [0, 100, 0, 100]);
+    assert_eq!(frames[3].1, [0, 50, 0]);
+    assert_eq!(frames[4].1, [0x17, 255, 255]);
+    let blocked: Vec<(usize, u8)> = interlock.blocked().iter().map(|blocked| (blocked.channel, blocked.value)).collect();
+    // The frame writer kept the channel blocked, the regular packet released it
+    assert_eq!(blocked, [(2, 255), (2, 50)]);
+}
+
+#[test]
+fn slew_limits_slow_down_channel_changes() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    let limit = SlewLimit::new();
+    limit.set_rate(1, 100.0).unwrap();
+    limit.set_fixture_rate(&Fixture::new("Motor", 2, vec![Attribute::Pan, Attribute::Tilt]).unwrap(), 10.0);
+    assert_eq!(limit.rate(3).unwrap(), Some(10.0));
+    dmx.set_slew_limit(limit.clone());
+
+    dmx.update().unwrap();
+    dmx.set_channels([255; DMX_CHANNELS]);
+    let mut output = |after: Duration| {
+        clock.advance(after);
+        dmx.update().unwrap();
+        mock.frames().last().unwrap().1[1..5].to_vec()
+    };
+    assert_eq!(output(Duration::from_millis(500)), [50, 5, 5, 255]);
+    // Fractions add up at slow rates
+    for _ in 0..3 {
+        output(Duration::from_millis(25));
+    }
+    assert_eq!(output(Duration::from_millis(25)), [60, 6, 6, 255]);
+    assert_eq!(output(Duration::from_secs(10)), [255, 106, 106, 255]);
+    limit.clear_rate(2).unwrap();
+    assert_eq!(output(Duration::ZERO), [255, 255, 106, 255]);
+}
+
+#[test]
+fn strobe_guard_holds_fast_flashing() {
+    let clock = ManualClock::new();
+    let (mut dmx, mock) = open_sync(Duration::from_millis(2), Some(&clock));
+    let guard = StrobeGuard::new();
+    dmx.set_strobe_guard(guard.clone());
+
+    // Cycles through the levels on the first channels, a frame every 50 ms
+    let mut flash = |levels: &[u8], width: usize, frames: usize| {
+        let mut outputs = Vec::new();
+        for frame in 0..frames {
+            let mut channels = [0; DMX_CHANNELS];
+            channels[..width].fill(levels[frame % levels.len()]);
+            dmx.set_channels(channels);
+            dmx.update().unwrap();
+            clock.advance(Duration::from_millis(50));
+            outputs.push(mock.frames().last().unwrap().1[1]);
+        }
+        outputs
+    };
+    // About 7 flashes per second, the 4th one within a second is suppressed
+    let outputs = flash(&[20, 140, 255], DMX_CHANNELS, 30);
+    assert_eq!(outputs[..11], [20, 140, 255, 20, 140, 255, 20, 140, 255, 20, 140]);
+    assert!(guard.is_suppressing());
+    // Held at the frame before the first flash, not in the middle of one
+    assert!(outputs[11..].iter().all(|value| *value == 20));
+
+    guard.allow();
+    assert_eq!(flash(&[20, 140, 255], DMX_CHANNELS, 6), [20, 140, 255, 20, 140, 255]);
+    guard.forbid();
+    flash(&[0, 255], DMX_CHANNELS, 20);
+    assert!(guard.is_suppressing());
+
+    // Slow changes are fine
+    assert_eq!(flash(&[255], DMX_CHANNELS, 30).last(), Some(&255));
+    assert!(!guard.is_suppressing());
+
+    // All channels were used, so a few flashing ones hardly change the level, until the guard is reconfigured
+    flash(&[0, 255], 4, 30);
+    assert!(!guard.is_suppressing());
+    guard.set_depth(128);
+    flash(&[0, 255], 4, 30);
+    assert!(guard.is_suppressing());
+
+    // Raw frames are held as well
+    dmx.send_raw_frame(&[0, 255, 255]).unwrap();
+    assert_eq!(mock.frames().last().unwrap().1, [0, 0, 0]);
+}